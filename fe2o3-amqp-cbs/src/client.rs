@@ -52,6 +52,12 @@ impl CbsClient {
             entity_type,
             None,
         );
+        self.put_token_request(req).await
+    }
+
+    /// Send a custom [`PutTokenRequest`], which allows overriding the CBS node address, the
+    /// operation name and adding extra application properties
+    pub async fn put_token_request(&mut self, req: PutTokenRequest<'_>) -> Result<(), MgmtError> {
         let _res: PutTokenResponse = self.mgmt_client.call(req).await?;
         Ok(())
     }
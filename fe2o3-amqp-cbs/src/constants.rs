@@ -11,3 +11,6 @@ pub const DEFAULT_CBS_CLIENT_NODE: &str = "cbs-client-node";
 
 /// Name of the operation property
 pub const EXPIRATION: &str = "expiration";
+
+/// Token type of Azure Service Bus/Event Hubs shared access signature (SAS) tokens
+pub const SAS_TOKEN_TYPE: &str = "servicebus.windows.net:sastoken";
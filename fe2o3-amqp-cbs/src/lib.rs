@@ -14,6 +14,9 @@ pub mod constants;
 pub mod put_token;
 pub mod token;
 
+#[cfg(feature = "azure")]
+pub mod sas;
+
 /// A trait for providing CBS tokens
 pub trait CbsTokenProvider {
     /// The associated error type
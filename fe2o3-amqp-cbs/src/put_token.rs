@@ -1,13 +1,17 @@
 //! Defines PutTokenRequest and PutTokenResponse
 
 use fe2o3_amqp::types::{
-    messaging::{ApplicationProperties, Message},
-    primitives::{SimpleValue, Timestamp, Value},
+    messaging::{ApplicationProperties, Message, Properties},
+    primitives::{OrderedMap, SimpleValue, Timestamp, Value},
+};
+use fe2o3_amqp_management::{
+    constants::{NAME, OPERATION},
+    request::Request,
+    response::Response,
 };
-use fe2o3_amqp_management::{constants::NAME, request::Request, response::Response};
 use std::borrow::Cow;
 
-use crate::constants::{EXPIRATION, PUT_TOKEN};
+use crate::constants::{CBS_NODE_ADDR, EXPIRATION, PUT_TOKEN};
 
 /// # Panic
 ///
@@ -29,9 +33,29 @@ pub struct PutTokenRequest<'a> {
 
     /// The locales to be used for the
     pub locales: Option<Cow<'a, str>>,
+
+    /// The address of the CBS node, which is set as the `to` field of the request message.
+    ///
+    /// Defaults to [`CBS_NODE_ADDR`]
+    pub node_address: Cow<'a, str>,
+
+    /// The name of the operation.
+    ///
+    /// Defaults to [`PUT_TOKEN`]
+    pub operation: Cow<'a, str>,
+
+    /// Additional application properties to include in the request message.
+    ///
+    /// Entries whose key collides with one of the properties defined by the CBS spec are ignored.
+    pub application_properties: OrderedMap<String, SimpleValue>,
 }
 
 impl<'a> PutTokenRequest<'a> {
+    /// Create a builder for [`PutTokenRequest`]
+    pub fn builder() -> PutTokenRequestBuilder<'a> {
+        PutTokenRequestBuilder::default()
+    }
+
     /// Create a new PutTokenRequest
     pub fn new(
         name: impl Into<Cow<'a, str>>,
@@ -46,10 +70,88 @@ impl<'a> PutTokenRequest<'a> {
             expiration: expiration.into(),
             manageable_entity_type: manageable_entity_type.into(),
             locales: locales.into(),
+            node_address: Cow::Borrowed(CBS_NODE_ADDR),
+            operation: Cow::Borrowed(PUT_TOKEN),
+            application_properties: OrderedMap::new(),
+        }
+    }
+}
+
+/// Builder for [`PutTokenRequest`]
+#[derive(Debug, Clone)]
+pub struct PutTokenRequestBuilder<'a> {
+    inner: PutTokenRequest<'a>,
+}
+
+impl<'a> Default for PutTokenRequestBuilder<'a> {
+    fn default() -> Self {
+        Self {
+            inner: PutTokenRequest::new("", "", None, "", None),
         }
     }
 }
 
+impl<'a> PutTokenRequestBuilder<'a> {
+    /// Set the name of the entity to which the token applies
+    pub fn name(mut self, name: impl Into<Cow<'a, str>>) -> Self {
+        self.inner.name = name.into();
+        self
+    }
+
+    /// Set the token to be applied to the entity
+    pub fn token(mut self, token: impl Into<Cow<'a, str>>) -> Self {
+        self.inner.token = token.into();
+        self
+    }
+
+    /// Set the time at which the token expires
+    pub fn expiration(mut self, expiration: impl Into<Option<Timestamp>>) -> Self {
+        self.inner.expiration = expiration.into();
+        self
+    }
+
+    /// Set the type of the entity to which the token applies
+    pub fn manageable_entity_type(mut self, entity_type: impl Into<Cow<'a, str>>) -> Self {
+        self.inner.manageable_entity_type = entity_type.into();
+        self
+    }
+
+    /// Set the locales
+    pub fn locales(mut self, locales: impl Into<Option<Cow<'a, str>>>) -> Self {
+        self.inner.locales = locales.into();
+        self
+    }
+
+    /// Set the address of the CBS node
+    pub fn node_address(mut self, node_address: impl Into<Cow<'a, str>>) -> Self {
+        self.inner.node_address = node_address.into();
+        self
+    }
+
+    /// Set the name of the operation
+    pub fn operation(mut self, operation: impl Into<Cow<'a, str>>) -> Self {
+        self.inner.operation = operation.into();
+        self
+    }
+
+    /// Insert an additional application property
+    pub fn application_property(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<SimpleValue>,
+    ) -> Self {
+        self.inner
+            .application_properties
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Build the [`PutTokenRequest`]
+    pub fn build(self) -> PutTokenRequest<'a> {
+        self.inner
+    }
+}
+
 impl<'a> Request for PutTokenRequest<'a> {
     const OPERATION: &'static str = PUT_TOKEN;
 
@@ -65,17 +167,28 @@ impl<'a> Request for PutTokenRequest<'a> {
         self.locales.as_ref().map(|x| x.to_string())
     }
 
+    fn encode_properties(&mut self) -> Option<Properties> {
+        Some(
+            Properties::builder()
+                .to(self.node_address.to_string())
+                .build(),
+        )
+    }
+
     fn encode_application_properties(&mut self) -> Option<ApplicationProperties> {
         let expiration = match self.expiration.take() {
             Some(timestamp) => SimpleValue::Timestamp(timestamp),
             None => SimpleValue::Null,
         };
-        Some(
-            ApplicationProperties::builder()
-                .insert(NAME, self.name.to_string())
-                .insert(EXPIRATION, expiration)
-                .build(),
-        )
+        let mut properties = ApplicationProperties::builder()
+            .insert(OPERATION, self.operation.to_string())
+            .insert(NAME, self.name.to_string())
+            .insert(EXPIRATION, expiration)
+            .build();
+        for (key, value) in std::mem::take(&mut self.application_properties) {
+            properties.as_inner_mut().entry(key).or_insert(value);
+        }
+        Some(properties)
     }
 
     fn encode_body(self) -> Self::Body {
@@ -100,3 +213,65 @@ impl Response for PutTokenResponse {
         Ok(Self {})
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp::types::primitives::SimpleValue;
+    use fe2o3_amqp_management::{
+        constants::{NAME, OPERATION, TYPE},
+        request::Request,
+    };
+
+    use crate::constants::{CBS_NODE_ADDR, EXPIRATION, PUT_TOKEN};
+
+    use super::PutTokenRequest;
+
+    #[test]
+    fn default_request_targets_cbs_node() {
+        let message = PutTokenRequest::new("entity", "token", None, "jwt", None).into_message();
+        let properties = message.properties.unwrap();
+        assert_eq!(properties.to.as_deref(), Some(CBS_NODE_ADDR));
+        let app_props = message.application_properties.unwrap();
+        assert_eq!(
+            app_props.get(OPERATION),
+            Some(&SimpleValue::String(PUT_TOKEN.to_string()))
+        );
+    }
+
+    #[test]
+    fn builder_sets_custom_node_address_and_properties() {
+        let message = PutTokenRequest::builder()
+            .name("entity")
+            .token("token")
+            .manageable_entity_type("jwt")
+            .node_address("$custom-cbs")
+            .operation("put-custom-token")
+            .application_property("audience", "my-audience")
+            .application_property(NAME, "ignored")
+            .build()
+            .into_message();
+
+        let properties = message.properties.unwrap();
+        assert_eq!(properties.to.as_deref(), Some("$custom-cbs"));
+
+        let app_props = message.application_properties.unwrap();
+        assert_eq!(
+            app_props.get(OPERATION),
+            Some(&SimpleValue::String("put-custom-token".to_string()))
+        );
+        assert_eq!(
+            app_props.get(NAME),
+            Some(&SimpleValue::String("entity".to_string()))
+        );
+        assert_eq!(
+            app_props.get(TYPE),
+            Some(&SimpleValue::String("jwt".to_string()))
+        );
+        assert_eq!(app_props.get(EXPIRATION), Some(&SimpleValue::Null));
+        assert_eq!(
+            app_props.get("audience"),
+            Some(&SimpleValue::String("my-audience".to_string()))
+        );
+        assert_eq!(message.body, "token");
+    }
+}
@@ -48,6 +48,37 @@ impl Error {
             info: info.into(),
         }
     }
+
+    /// Creates a new Error with only the condition
+    ///
+    /// The description and info can be added with [`with_description`](Self::with_description)
+    /// and [`with_info`](Self::with_info).
+    ///
+    /// ```rust
+    /// use fe2o3_amqp_types::definitions::{AmqpError, Error};
+    ///
+    /// let error = Error::from_condition(AmqpError::NotImplemented)
+    ///     .with_description("Transactions are not supported");
+    /// ```
+    pub fn from_condition(condition: impl Into<ErrorCondition>) -> Self {
+        Self {
+            condition: condition.into(),
+            description: None,
+            info: None,
+        }
+    }
+
+    /// Sets the description of the error
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the info of the error
+    pub fn with_info(mut self, info: Fields) -> Self {
+        self.info = Some(info);
+        self
+    }
 }
 
 impl<T> From<T> for Error
@@ -55,11 +86,7 @@ where
     T: Into<ErrorCondition>,
 {
     fn from(condition: T) -> Self {
-        Self {
-            condition: condition.into(),
-            description: None,
-            info: None,
-        }
+        Self::from_condition(condition)
     }
 }
 
@@ -68,7 +95,9 @@ mod tests {
 
     use serde_amqp::{from_slice, to_vec};
 
-    use crate::definitions::AmqpError;
+    use serde_amqp::primitives::{OrderedMap, Symbol};
+
+    use crate::definitions::{AmqpError, ErrorCondition, LinkError};
 
     use super::Error;
 
@@ -79,4 +108,27 @@ mod tests {
         let deserialized: Error = from_slice(&serialized).unwrap();
         assert_eq!(expected, deserialized)
     }
+
+    #[test]
+    fn error_from_condition() {
+        let error: Error = AmqpError::NotImplemented.into();
+        assert_eq!(
+            to_vec(&error.condition).unwrap(),
+            to_vec(&Symbol::from("amqp:not-implemented")).unwrap()
+        );
+        assert_eq!(error.description, None);
+        assert_eq!(error.info, None);
+
+        let mut info = OrderedMap::new();
+        info.insert("address".into(), "q1".into());
+        let error = Error::from_condition(LinkError::Redirect)
+            .with_description("Moved")
+            .with_info(info.clone());
+        assert_eq!(
+            error.condition,
+            ErrorCondition::LinkError(LinkError::Redirect)
+        );
+        assert_eq!(error.description.as_deref(), Some("Moved"));
+        assert_eq!(error.info, Some(info));
+    }
 }
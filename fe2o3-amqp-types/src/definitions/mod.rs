@@ -93,6 +93,10 @@ pub use session_error::SessionError;
 mod link_error;
 pub use link_error::LinkError;
 
+/// Redirect information carried by connection and link errors
+mod redirect;
+pub use redirect::{ConnectionRedirect, LinkRedirect};
+
 /// 2.8.19 Constant definition
 mod constant_def;
 pub use constant_def::{MAJOR, MINOR, MIN_MAX_FRAME_SIZE, PORT, REVISION, SECURE_PORT};
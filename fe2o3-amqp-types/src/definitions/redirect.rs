@@ -0,0 +1,148 @@
+use std::convert::TryFrom;
+
+use serde_amqp::Value;
+
+use super::{ConnectionError, Error, ErrorCondition, Fields, LinkError};
+
+/// The information carried in the `info` map of an `amqp:connection:redirect` error
+///
+/// See 2.8.16 Connection Error in the AMQP1.0 specification
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConnectionRedirect {
+    /// The DNS hostname of the container to redirect to, which should be used as the hostname in
+    /// the Open frame of the new connection
+    pub hostname: Option<String>,
+
+    /// The DNS hostname or IP address of the machine to connect to
+    pub network_host: Option<String>,
+
+    /// The port number on the machine to connect to
+    pub port: Option<u16>,
+}
+
+/// The information carried in the `info` map of an `amqp:link:redirect` error
+///
+/// See 2.8.18 Link Error in the AMQP1.0 specification
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LinkRedirect {
+    /// The DNS hostname of the container to redirect to, which should be used as the hostname in
+    /// the Open frame of the new connection
+    pub hostname: Option<String>,
+
+    /// The DNS hostname or IP address of the machine to connect to
+    pub network_host: Option<String>,
+
+    /// The port number on the machine to connect to
+    pub port: Option<u16>,
+
+    /// The address of the terminus at the container to attach the link to
+    pub address: Option<String>,
+}
+
+impl Error {
+    /// Returns the redirect information if this is an `amqp:connection:redirect` error
+    pub fn connection_redirect(&self) -> Option<ConnectionRedirect> {
+        match &self.condition {
+            ErrorCondition::ConnectionError(ConnectionError::Redirect) => {
+                let info = self.info.as_ref();
+                Some(ConnectionRedirect {
+                    hostname: get_string(info, "hostname"),
+                    network_host: get_string(info, "network-host"),
+                    port: get_port(info),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the redirect information if this is an `amqp:link:redirect` error
+    pub fn link_redirect(&self) -> Option<LinkRedirect> {
+        match &self.condition {
+            ErrorCondition::LinkError(LinkError::Redirect) => {
+                let info = self.info.as_ref();
+                Some(LinkRedirect {
+                    hostname: get_string(info, "hostname"),
+                    network_host: get_string(info, "network-host"),
+                    port: get_port(info),
+                    address: get_string(info, "address"),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn get_string(info: Option<&Fields>, key: &str) -> Option<String> {
+    match info?.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Symbol(s) => Some(s.to_string()),
+        _ => None,
+    }
+}
+
+/// The port is a ushort, but some peers encode it with a wider integer type
+fn get_port(info: Option<&Fields>) -> Option<u16> {
+    match info?.get("port")? {
+        Value::UShort(port) => Some(*port),
+        Value::UInt(port) => u16::try_from(*port).ok(),
+        Value::ULong(port) => u16::try_from(*port).ok(),
+        Value::Short(port) => u16::try_from(*port).ok(),
+        Value::Int(port) => u16::try_from(*port).ok(),
+        Value::Long(port) => u16::try_from(*port).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_amqp::{primitives::OrderedMap, Value};
+
+    use crate::definitions::{AmqpError, ConnectionError, Error, Fields, LinkError};
+
+    use super::{ConnectionRedirect, LinkRedirect};
+
+    fn redirect_info() -> Fields {
+        let mut info = OrderedMap::new();
+        info.insert("hostname".into(), Value::from("broker-2.example.com"));
+        info.insert("network-host".into(), Value::from("10.0.0.2"));
+        info.insert("port".into(), Value::UShort(5671));
+        info.insert("address".into(), Value::from("queue-partition-2"));
+        info
+    }
+
+    #[test]
+    fn connection_redirect_is_extracted_from_info() {
+        let error = Error::new(ConnectionError::Redirect, None, redirect_info());
+        assert_eq!(
+            error.connection_redirect(),
+            Some(ConnectionRedirect {
+                hostname: Some("broker-2.example.com".into()),
+                network_host: Some("10.0.0.2".into()),
+                port: Some(5671),
+            })
+        );
+        assert!(error.link_redirect().is_none());
+    }
+
+    #[test]
+    fn link_redirect_is_extracted_from_info() {
+        let error = Error::new(LinkError::Redirect, None, redirect_info());
+        assert_eq!(
+            error.link_redirect(),
+            Some(LinkRedirect {
+                hostname: Some("broker-2.example.com".into()),
+                network_host: Some("10.0.0.2".into()),
+                port: Some(5671),
+                address: Some("queue-partition-2".into()),
+            })
+        );
+        assert!(error.connection_redirect().is_none());
+
+        // Missing info yields empty fields rather than no redirect
+        let error = Error::new(LinkError::Redirect, None, None);
+        assert_eq!(error.link_redirect(), Some(LinkRedirect::default()));
+
+        let error = Error::new(AmqpError::InternalError, None, redirect_info());
+        assert!(error.link_redirect().is_none());
+    }
+}
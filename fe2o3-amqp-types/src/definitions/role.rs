@@ -12,6 +12,18 @@ pub enum Role {
     Receiver,
 }
 
+impl Role {
+    /// Returns `true` if the role is [`Role::Sender`]
+    pub fn is_sender(&self) -> bool {
+        matches!(self, Role::Sender)
+    }
+
+    /// Returns `true` if the role is [`Role::Receiver`]
+    pub fn is_receiver(&self) -> bool {
+        matches!(self, Role::Receiver)
+    }
+}
+
 impl From<Role> for bool {
     fn from(role: Role) -> Self {
         match role {
@@ -88,4 +100,25 @@ mod tests {
         let role2: Role = from_slice(&buf).unwrap();
         println!("{:?}", role2);
     }
+
+    #[test]
+    fn role_is_encoded_as_boolean() {
+        // 0x42 is the boolean false encoding and 0x41 is the boolean true encoding
+        let buf = to_vec(&Role::Sender).unwrap();
+        assert_eq!(buf, vec![0x42]);
+        let buf = to_vec(&Role::Receiver).unwrap();
+        assert_eq!(buf, vec![0x41]);
+
+        for role in [Role::Sender, Role::Receiver] {
+            let buf = to_vec(&role).unwrap();
+            let decoded: Role = from_slice(&buf).unwrap();
+            assert_eq!(decoded, role);
+        }
+
+        // The one-byte boolean encoding is accepted as well
+        let decoded: Role = from_slice(&[0x56, 0x01]).unwrap();
+        assert_eq!(decoded, Role::Receiver);
+        assert!(decoded.is_receiver());
+        assert!(!decoded.is_sender());
+    }
 }
@@ -16,6 +16,7 @@
 //! - `"messaging"`: enables the types defined in part 2.7 and part 3 defined in the core specification
 //! - `"transaction"`: enables the types defined in part 4.5 of the core specification
 //! - `"security"`: enables the types defined in part 5 of the core specifiction.
+//! - `"json"`: enables helpers to construct and read `Message` bodies encoded as JSON
 //!
 //! ```toml
 //! default = [
@@ -1,7 +1,8 @@
 //! Part 3.4 delivery state
 
 use serde_amqp::macros::{DeserializeComposite, SerializeComposite};
-use serde_amqp::primitives::{Boolean, UInt, ULong};
+use serde_amqp::primitives::{Boolean, OrderedMap, Symbol, UInt, ULong};
+use serde_amqp::Value;
 
 use crate::definitions::{Error, Fields};
 
@@ -593,7 +594,7 @@ impl From<Released> for Outcome {
 /// <type name="modified" class="composite" source="list" provides="delivery-state, outcome">
 ///     <descriptor name="amqp:modified:list" code="0x00000000:0x00000027"/>
 /// </type>
-#[derive(Debug, Clone, DeserializeComposite, SerializeComposite)]
+#[derive(Debug, Clone, Default, DeserializeComposite, SerializeComposite)]
 #[amqp_contract(
     name = "amqp:modified:list",
     code = "0x0000_0000:0x0000_0027",
@@ -611,6 +612,44 @@ pub struct Modified {
     pub message_annotations: Option<Fields>,
 }
 
+impl Modified {
+    /// Creates a [`Modified`] outcome with all fields unset
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a [`Modified`] outcome with `delivery-failed` set to true, which asks the sender
+    /// to increment the delivery-count of the message (eg. for poison message handling)
+    pub fn failed() -> Self {
+        Self::new().delivery_failed(true)
+    }
+
+    /// Set the "delivery-failed" field
+    pub fn delivery_failed(mut self, value: bool) -> Self {
+        self.delivery_failed = Some(value);
+        self
+    }
+
+    /// Set the "undeliverable-here" field
+    pub fn undeliverable_here(mut self, value: bool) -> Self {
+        self.undeliverable_here = Some(value);
+        self
+    }
+
+    /// Add an entry to the "message-annotations" field, which will be merged into the
+    /// message-annotations of the message by the sender
+    pub fn add_message_annotation(
+        mut self,
+        key: impl Into<Symbol>,
+        value: impl Into<Value>,
+    ) -> Self {
+        self.message_annotations
+            .get_or_insert(OrderedMap::new())
+            .insert(key.into(), value.into());
+        self
+    }
+}
+
 impl From<Modified> for DeliveryState {
     fn from(value: Modified) -> Self {
         Self::Modified(value)
@@ -718,6 +757,34 @@ mod tests {
         println!("{:?}", modified2);
     }
 
+    #[test]
+    fn test_serialize_deserialize_modified_with_annotations() {
+        use serde_amqp::{primitives::Symbol, Value};
+
+        let modified = Modified::failed()
+            .undeliverable_here(true)
+            .add_message_annotation("x-opt-reason", "poison")
+            .add_message_annotation("x-opt-attempts", 3u32);
+        let buf = to_vec(&modified).unwrap();
+        assert_eq!(&buf[..3], &[0x00, 0x53, 0x27]);
+
+        let modified2: Modified = from_slice(&buf).unwrap();
+        assert_eq!(modified2.delivery_failed, Some(true));
+        assert_eq!(modified2.undeliverable_here, Some(true));
+        let annotations = modified2.message_annotations.unwrap();
+        let entries: Vec<_> = annotations.into_iter().collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    Symbol::from("x-opt-reason"),
+                    Value::String(String::from("poison"))
+                ),
+                (Symbol::from("x-opt-attempts"), Value::UInt(3)),
+            ]
+        );
+    }
+
     /* ------------------------------ test Received ----------------------------- */
     #[test]
     fn test_serialize_deserialize_received() {
@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
 use serde::{de, ser, Serialize};
-use serde_amqp::{DeserializeComposite, SerializeComposite};
+use serde_amqp::{DeserializeComposite, SerializeComposite, Value};
 
 use crate::messaging::{
     Batch, DeserializableBody, FromBody, FromEmptyBody, IntoBody, SerializableBody,
@@ -27,6 +27,59 @@ impl<T> AmqpSequence<T> {
     pub fn new(vec: Vec<T>) -> Self {
         Self(vec)
     }
+
+    /// Appends an element to the back of the sequence
+    pub fn push(&mut self, value: impl Into<T>) {
+        self.0.push(value.into())
+    }
+
+    /// Returns the number of elements in the sequence
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the sequence contains no elements
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the elements of the sequence
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.0.iter()
+    }
+
+    /// Consumes the sequence and returns the inner `Vec`
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl AmqpSequence<Value> {
+    /// Returns the element at `index` converted to `U`
+    ///
+    /// `None` is returned if the index is out of bounds or if the element is not of type `U`
+    pub fn get_as<U>(&self, index: usize) -> Option<U>
+    where
+        U: TryFrom<Value>,
+    {
+        self.0
+            .get(index)
+            .and_then(|value| U::try_from(value.clone()).ok())
+    }
+
+    /// Converts every element of the sequence to `U`
+    ///
+    /// The first element that is not of type `U` is returned as the error
+    pub fn try_into_typed<U>(self) -> Result<AmqpSequence<U>, Value>
+    where
+        U: TryFrom<Value, Error = Value>,
+    {
+        self.0
+            .into_iter()
+            .map(U::try_from)
+            .collect::<Result<Vec<U>, Value>>()
+            .map(AmqpSequence)
+    }
 }
 
 impl<T> Display for AmqpSequence<T>
@@ -52,6 +105,42 @@ impl<T> From<Vec<T>> for AmqpSequence<T> {
     }
 }
 
+impl<T, U> FromIterator<U> for AmqpSequence<T>
+where
+    U: Into<T>,
+{
+    fn from_iter<I: IntoIterator<Item = U>>(iter: I) -> Self {
+        Self(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T, U> Extend<U> for AmqpSequence<T>
+where
+    U: Into<T>,
+{
+    fn extend<I: IntoIterator<Item = U>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(Into::into))
+    }
+}
+
+impl<T> IntoIterator for AmqpSequence<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a AmqpSequence<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 /* -------------------------------------------------------------------------- */
 /*                                AmqpSequence                                */
 /* -------------------------------------------------------------------------- */
@@ -186,11 +275,11 @@ where
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
-    use serde_amqp::{from_slice, to_vec};
+    use serde_amqp::{from_slice, primitives::Symbol, to_vec, Value};
 
     use crate::messaging::{
         message::__private::{Deserializable, Serializable},
-        AmqpSequence, Batch, Message,
+        AmqpSequence, Batch, Body, Message,
     };
 
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
@@ -250,4 +339,65 @@ mod tests {
                 .collect();
         assert_eq!(decoded.0.body.into_inner(), expected);
     }
+
+    fn mixed_sequences() -> (AmqpSequence<Value>, AmqpSequence<Value>) {
+        let first: AmqpSequence<Value> =
+            vec![Value::from(1i32), Value::from("two"), Value::from(true)]
+                .into_iter()
+                .collect();
+
+        let mut second = AmqpSequence::<Value>::default();
+        second.push(3u64);
+        second.push(Symbol::from("four"));
+        second.push(Value::Null);
+        (first, second)
+    }
+
+    #[test]
+    fn test_amqp_sequence_typed_helpers() {
+        let (first, second) = mixed_sequences();
+
+        assert_eq!(first.len(), 3);
+        assert_eq!(first.get_as::<i32>(0), Some(1));
+        assert_eq!(first.get_as::<String>(1), Some(String::from("two")));
+        assert_eq!(first.get_as::<bool>(2), Some(true));
+        assert_eq!(first.get_as::<u32>(0), None);
+        assert_eq!(first.get_as::<i32>(3), None);
+
+        assert_eq!(second.get_as::<u64>(0), Some(3));
+        assert_eq!(second.get_as::<Symbol>(1), Some(Symbol::from("four")));
+        assert_eq!(second.iter().filter(|v| **v == Value::Null).count(), 1);
+
+        let ints: AmqpSequence<Value> = (1..=3i32).collect();
+        assert_eq!(
+            ints.try_into_typed::<i32>().unwrap().into_inner(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            first.try_into_typed::<i32>().unwrap_err(),
+            Value::from("two")
+        );
+    }
+
+    #[test]
+    fn test_serde_mixed_amqp_sequence_batch() {
+        let (first, second) = mixed_sequences();
+        let msg = Message::builder()
+            .sequence_batch(vec![first.clone(), second.clone()])
+            .build();
+        let buf = to_vec(&Serializable(msg)).unwrap();
+
+        let decoded: Deserializable<Message<Batch<AmqpSequence<Value>>>> =
+            from_slice(&buf).unwrap();
+        assert_eq!(
+            decoded.0.body.into_inner(),
+            vec![first.clone(), second.clone()]
+        );
+
+        let decoded: Deserializable<Message<Body<Value>>> = from_slice(&buf).unwrap();
+        match decoded.0.body {
+            Body::Sequence(batch) => assert_eq!(batch.into_inner(), vec![first, second]),
+            _ => panic!("Expecting Body::Sequence"),
+        }
+    }
 }
@@ -46,6 +46,15 @@ use serde_amqp::{
 /// ```
 pub type Annotations = OrderedMap<OwnedKey, Value>;
 
+/// Symbolic keys beginning with this prefix are not reserved by the specification
+pub const NON_RESERVED_KEY_PREFIX: &str = "x-";
+
+/// Symbolic keys beginning with this prefix MUST be ignored if not understood
+pub const OPTIONAL_KEY_PREFIX: &str = "x-opt-";
+
+/// The reserved `rejected` annotation key
+pub const REJECTED: &str = "rejected";
+
 /// Key type for [`Annotations`]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum OwnedKey {
@@ -82,7 +91,7 @@ impl From<u64> for OwnedKey {
 
 impl From<String> for OwnedKey {
     fn from(value: String) -> Self {
-        Self::Symbol(Symbol(value))
+        Self::Symbol(Symbol::from(value))
     }
 }
 
@@ -92,6 +101,17 @@ impl<'a> From<&'a str> for OwnedKey {
     }
 }
 
+impl OwnedKey {
+    /// Returns `true` if the key is reserved by the specification, which includes all ulong keys
+    /// and all symbolic keys except those beginning with "x-"
+    pub fn is_reserved(&self) -> bool {
+        match self {
+            OwnedKey::Symbol(s) => !s.as_str().starts_with(NON_RESERVED_KEY_PREFIX),
+            OwnedKey::ULong(_) => true,
+        }
+    }
+}
+
 impl Serialize for OwnedKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -208,7 +228,7 @@ impl<'a> AnnotationKey for BorrowedKey<'a> {
 impl AnnotationKey for OwnedKey {
     fn key(&self) -> BorrowedKey<'_> {
         match self {
-            OwnedKey::Symbol(Symbol(s)) => BorrowedKey::Symbol(SymbolRef(s)),
+            OwnedKey::Symbol(s) => BorrowedKey::Symbol(SymbolRef(s.as_str())),
             OwnedKey::ULong(v) => BorrowedKey::ULong(v),
         }
     }
@@ -240,7 +260,7 @@ impl AnnotationKey for String {
 
 impl AnnotationKey for Symbol {
     fn key(&self) -> BorrowedKey<'_> {
-        BorrowedKey::Symbol(SymbolRef(&self.0))
+        BorrowedKey::Symbol(SymbolRef(self.as_str()))
     }
 }
 
@@ -282,6 +302,56 @@ impl<'a> Hash for (dyn AnnotationKey + 'a) {
     }
 }
 
+/// Typed key helpers for [`Annotations`]
+///
+/// This is also available on [`DeliveryAnnotations`](super::DeliveryAnnotations),
+/// [`MessageAnnotations`](super::MessageAnnotations) and [`Footer`](super::Footer) through
+/// `DerefMut`.
+///
+/// # Example
+///
+/// ```rust
+/// use fe2o3_amqp_types::messaging::{annotations::AnnotationsExt, MessageAnnotations};
+///
+/// let mut annotations = MessageAnnotations::default();
+/// annotations.insert_symbol("x-opt-partition-key", "device-1");
+/// annotations.insert_ulong(42, 1u32);
+///
+/// assert!(annotations.get_symbol("x-opt-partition-key").is_some());
+/// assert!(annotations.get_ulong(42).is_some());
+/// ```
+pub trait AnnotationsExt {
+    /// Inserts an entry with a symbol key, returning the previous value if any
+    fn insert_symbol(&mut self, key: impl Into<Symbol>, value: impl Into<Value>) -> Option<Value>;
+
+    /// Inserts an entry with a ulong key, returning the previous value if any
+    fn insert_ulong(&mut self, key: u64, value: impl Into<Value>) -> Option<Value>;
+
+    /// Gets the value of a symbol key
+    fn get_symbol(&self, key: &str) -> Option<&Value>;
+
+    /// Gets the value of a ulong key
+    fn get_ulong(&self, key: u64) -> Option<&Value>;
+}
+
+impl AnnotationsExt for Annotations {
+    fn insert_symbol(&mut self, key: impl Into<Symbol>, value: impl Into<Value>) -> Option<Value> {
+        self.insert(OwnedKey::Symbol(key.into()), value.into())
+    }
+
+    fn insert_ulong(&mut self, key: u64, value: impl Into<Value>) -> Option<Value> {
+        self.insert(OwnedKey::ULong(key), value.into())
+    }
+
+    fn get_symbol(&self, key: &str) -> Option<&Value> {
+        self.get(&SymbolRef(key) as &dyn AnnotationKey)
+    }
+
+    fn get_ulong(&self, key: u64) -> Option<&Value> {
+        self.get(&key as &dyn AnnotationKey)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_amqp::{
@@ -292,7 +362,7 @@ mod tests {
 
     use crate::messaging::format::annotations::AnnotationKey;
 
-    use super::{Annotations, OwnedKey};
+    use super::{Annotations, AnnotationsExt, OwnedKey, REJECTED};
 
     const STRING_KEY: &str = "string_key";
     const STR_KEY: &str = "str_key";
@@ -429,4 +499,33 @@ mod tests {
         let deserialized: Annotations = from_slice(&buf).unwrap();
         assert_ne!(deserialized, annotations_2)
     }
+
+    #[test]
+    fn test_insert_and_get_with_typed_keys() {
+        let mut annotations = Annotations::new();
+        assert!(annotations.insert_symbol("x-opt-key", "value").is_none());
+        assert!(annotations.insert_ulong(42, 1u32).is_none());
+
+        assert_eq!(
+            annotations.get_symbol("x-opt-key"),
+            Some(&Value::String(String::from("value")))
+        );
+        assert_eq!(annotations.get_ulong(42), Some(&Value::UInt(1)));
+        assert!(annotations.get_symbol("42").is_none());
+        assert!(annotations.get_ulong(43).is_none());
+
+        // The keys are encoded as symbol and ulong
+        let buf = to_vec(&annotations).unwrap();
+        let decoded: Annotations = from_slice(&buf).unwrap();
+        assert_eq!(decoded, annotations);
+        assert!(decoded.contains_key(&OwnedKey::Symbol(Symbol::from("x-opt-key"))));
+        assert!(decoded.contains_key(&OwnedKey::ULong(42)));
+    }
+
+    #[test]
+    fn test_reserved_keys() {
+        assert!(OwnedKey::from(REJECTED).is_reserved());
+        assert!(OwnedKey::from(42u64).is_reserved());
+        assert!(!OwnedKey::from("x-opt-key").is_reserved());
+    }
 }
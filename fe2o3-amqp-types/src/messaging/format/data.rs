@@ -168,6 +168,24 @@ where
     }
 }
 
+/* -------------------------------------------------------------------------- */
+/*                                 DataStream                                 */
+/* -------------------------------------------------------------------------- */
+
+/// The body of a message with a single data section whose binary is read from `reader` while the
+/// message is being sent, so that the binary doesn't have to be held in memory
+///
+/// This is not a [`SerializableBody`] and can only be sent with `Sender::send_data_stream` of
+/// `fe2o3-amqp`. See [`Message::stream_data`](crate::messaging::Message::stream_data).
+#[derive(Debug)]
+pub struct DataStream<R> {
+    /// The source of the binary
+    pub reader: R,
+
+    /// The number of bytes that will be read from `reader`
+    pub len: usize,
+}
+
 /* -------------------------------------------------------------------------- */
 /*                                    Test                                    */
 /* -------------------------------------------------------------------------- */
@@ -1,5 +1,7 @@
 //! Implementation of message header
 
+use std::{convert::TryFrom, time::Duration};
+
 use serde_amqp::{
     primitives::{Boolean, UInt},
     DeserializeComposite, SerializeComposite,
@@ -76,6 +78,29 @@ impl Builder {
         self
     }
 
+    /// Set the `ttl` field of [`Header`] from a [`Duration`]
+    ///
+    /// The duration is truncated to whole milliseconds. A duration longer than `u32::MAX`
+    /// milliseconds (about 49.7 days) saturates to `u32::MAX`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use fe2o3_amqp_types::messaging::Header;
+    ///
+    /// let header = Header::builder()
+    ///     .durable(true)
+    ///     .ttl_duration(Duration::from_secs(30))
+    ///     .build();
+    /// assert_eq!(header.ttl, Some(30_000));
+    /// ```
+    pub fn ttl_duration(mut self, duration: Duration) -> Self {
+        let millis = Milliseconds::try_from(duration.as_millis()).unwrap_or(Milliseconds::MAX);
+        self.inner.ttl = Some(millis);
+        self
+    }
+
     /// Set the `first_acquirer` field of [`Header`]
     pub fn first_acquirer(mut self, value: Boolean) -> Self {
         self.inner.first_acquirer = value;
@@ -105,3 +130,33 @@ impl From<Builder> for Option<Header> {
         Some(builder.build())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_amqp::{from_slice, to_vec, Error};
+
+    use super::Header;
+
+    #[test]
+    fn truncated_header_reports_offset() {
+        let header = Header::builder().durable(true).build();
+        let buf = to_vec(&header).unwrap();
+        assert_eq!(&buf[..4], &[0x00, 0x53, 0x70, 0xc0]);
+
+        // Cut right after the list8 format code
+        let err = from_slice::<Header>(&buf[..4]).unwrap_err();
+        assert_eq!(err.offset(), Some(4));
+        assert!(err.is_unexpected_eof());
+        match &err {
+            Error::AtOffset { found, source, .. } => {
+                assert_eq!(*found, Some(0xc0));
+                assert!(source.to_string().contains("Expecting list size"));
+            }
+            _ => panic!("Expecting Error::AtOffset, found {:?}", err),
+        }
+        assert_eq!(
+            err.to_string(),
+            "IO Expecting list size at byte offset 4 (last format code 0xc0)"
+        );
+    }
+}
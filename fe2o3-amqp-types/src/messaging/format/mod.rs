@@ -303,6 +303,78 @@ mod tests {
         println!("{:x?}", &serialized);
     }
 
+    #[test]
+    fn test_decode_concatenated_headers() {
+        use serde_amqp::from_slice_prefix;
+
+        let first = Header::builder().durable(true).ttl(1000).build();
+        let second = Header::builder().priority(9).delivery_count(3).build();
+        let mut buf = to_vec(&first).unwrap();
+        let first_len = buf.len();
+        buf.extend(to_vec(&second).unwrap());
+
+        let (decoded, consumed) = from_slice_prefix::<Header>(&buf).unwrap();
+        assert_eq!(decoded, first);
+        assert_eq!(consumed, first_len);
+
+        let (decoded, consumed) = from_slice_prefix::<Header>(&buf[first_len..]).unwrap();
+        assert_eq!(decoded, second);
+        assert_eq!(first_len + consumed, buf.len());
+    }
+
+    #[test]
+    fn test_header_builder_round_trip() {
+        use std::time::Duration;
+
+        use serde_amqp::from_slice;
+
+        let header = Header::builder()
+            .durable(true)
+            .priority(9)
+            .ttl_duration(Duration::from_millis(1500))
+            .first_acquirer(true)
+            .delivery_count(2)
+            .build();
+        assert_eq!(header.priority, Priority(9));
+        assert_eq!(header.ttl, Some(1500));
+
+        let buf = to_vec(&header).unwrap();
+        let decoded: Header = from_slice(&buf).unwrap();
+        assert_eq!(decoded, header);
+
+        // Durations that do not fit in a u32 of milliseconds saturate
+        let header = Header::builder()
+            .ttl_duration(Duration::from_secs(u64::MAX))
+            .build();
+        assert_eq!(header.ttl, Some(u32::MAX));
+    }
+
+    #[test]
+    fn test_peek_header_descriptor() {
+        use serde_amqp::{descriptor::Descriptor, peek_descriptor};
+
+        let header = Header {
+            durable: true,
+            ..Default::default()
+        };
+        let serialized = to_vec(&header).unwrap();
+        let descriptor = peek_descriptor(&serialized).unwrap();
+        assert_eq!(descriptor, Descriptor::Code(0x70));
+    }
+
+    #[test]
+    fn test_deserialize_list0_into_default_header() {
+        use serde_amqp::from_slice;
+
+        // descriptor 0x70 followed by List0
+        let buf = [0x00, 0x53, 0x70, 0x45];
+        let header: Header = from_slice(&buf).unwrap();
+        assert_eq!(header, Header::default());
+
+        // A described type cannot be decoded without its descriptor
+        assert!(from_slice::<Header>(&[0x45]).is_err());
+    }
+
     #[test]
     fn test_display_data() {
         let b = Binary::from(vec![1u8, 2]);
@@ -92,6 +92,28 @@ impl Properties {
     }
 
     /// Creates a new builder for [`Properties`]
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fe2o3_amqp_types::{messaging::Properties, primitives::Timestamp};
+    ///
+    /// let properties = Properties::builder()
+    ///     .message_id(1u64)
+    ///     .to("q1")
+    ///     .subject("greeting")
+    ///     .reply_to("reply-q")
+    ///     .correlation_id(String::from("request-1"))
+    ///     .content_type("text/plain")
+    ///     .creation_time(Timestamp::from(1_600_000_000_000))
+    ///     .group_id(String::from("group-1"))
+    ///     .group_sequence(3)
+    ///     .build();
+    ///
+    /// assert_eq!(properties.subject.as_deref(), Some("greeting"));
+    /// assert_eq!(properties.group_sequence, Some(3));
+    /// assert!(properties.user_id.is_none());
+    /// ```
     pub fn builder() -> Builder {
         Builder::new()
     }
@@ -0,0 +1,206 @@
+//! Constructors and accessors that keep the body section and the `content-type` property in sync
+
+use serde_amqp::primitives::Symbol;
+
+#[cfg(feature = "json")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "json")]
+use serde_amqp::primitives::Binary;
+
+use crate::messaging::{Data, Properties};
+
+use super::Message;
+
+/// Content type for JSON encoded bodies
+pub const APPLICATION_JSON: &str = "application/json";
+
+/// Content type for opaque binary bodies
+pub const APPLICATION_OCTET_STREAM: &str = "application/octet-stream";
+
+impl Message<Data> {
+    /// Creates a [`Message`] with a single `Data` body section and the `content-type` property
+    /// set to `content_type`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fe2o3_amqp_types::messaging::{Message, message::APPLICATION_OCTET_STREAM};
+    ///
+    /// let message = Message::data(vec![1u8, 2, 3], APPLICATION_OCTET_STREAM);
+    /// assert_eq!(message.content_type().map(|s| s.as_str()), Some("application/octet-stream"));
+    /// ```
+    pub fn data(data: impl Into<Data>, content_type: impl Into<Symbol>) -> Self {
+        Message::builder()
+            .properties(Properties::builder().content_type(content_type).build())
+            .data(data)
+            .build()
+    }
+
+    /// Returns the `content-type` property of the message if any
+    pub fn content_type(&self) -> Option<&Symbol> {
+        self.properties
+            .as_ref()
+            .and_then(|properties| properties.content_type.as_ref())
+    }
+}
+
+/// Error with getting the body of a [`Message`] as JSON
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+#[derive(Debug)]
+pub enum JsonBodyError {
+    /// The `content-type` property of the message is not `application/json`
+    ContentTypeMismatch {
+        /// The `content-type` found in the message properties
+        found: Option<Symbol>,
+    },
+
+    /// Error deserializing the body
+    Json(serde_json::Error),
+}
+
+#[cfg(feature = "json")]
+impl std::fmt::Display for JsonBodyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonBodyError::ContentTypeMismatch { found } => write!(
+                f,
+                "Expecting content-type {:?}, found {:?}",
+                APPLICATION_JSON, found
+            ),
+            JsonBodyError::Json(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl std::error::Error for JsonBodyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JsonBodyError::ContentTypeMismatch { .. } => None,
+            JsonBodyError::Json(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for JsonBodyError {
+    fn from(err: serde_json::Error) -> Self {
+        JsonBodyError::Json(err)
+    }
+}
+
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+impl Message<Data> {
+    /// Creates a [`Message`] with the JSON encoded `value` as a single `Data` body section and
+    /// the `content-type` property set to `application/json`
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fe2o3_amqp_types::messaging::Message;
+    ///
+    /// let message = Message::json(&vec![1, 2, 3]).unwrap();
+    /// let value: Vec<i32> = message.body_as_json().unwrap();
+    /// assert_eq!(value, vec![1, 2, 3]);
+    /// ```
+    pub fn json<T: Serialize + ?Sized>(value: &T) -> Result<Self, serde_json::Error> {
+        let buf = serde_json::to_vec(value)?;
+        Ok(Self::data(Binary::from(buf), APPLICATION_JSON))
+    }
+
+    /// Deserializes the body as JSON
+    ///
+    /// A [`JsonBodyError::ContentTypeMismatch`] will be returned if the media type of the
+    /// `content-type` property is not `application/json`. Parameters such as `charset` are
+    /// ignored, and the media type is compared case-insensitively.
+    pub fn body_as_json<T: DeserializeOwned>(&self) -> Result<T, JsonBodyError> {
+        match self.content_type() {
+            Some(content_type) if is_media_type(content_type.as_str(), APPLICATION_JSON) => {}
+            found => {
+                return Err(JsonBodyError::ContentTypeMismatch {
+                    found: found.cloned(),
+                })
+            }
+        }
+        serde_json::from_slice(&self.body.0).map_err(Into::into)
+    }
+}
+
+/// Compares the media type of `content_type`, ie. the part before any parameters
+#[cfg(feature = "json")]
+fn is_media_type(content_type: &str, media_type: &str) -> bool {
+    let essence = match content_type.split_once(';') {
+        Some((essence, _parameters)) => essence,
+        None => content_type,
+    };
+    essence.trim().eq_ignore_ascii_case(media_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::messaging::{Data, Message};
+
+    use super::APPLICATION_OCTET_STREAM;
+
+    #[test]
+    fn data_sets_content_type() {
+        let message = Message::data(vec![1u8, 2, 3], APPLICATION_OCTET_STREAM);
+        assert_eq!(message.body, Data::from(vec![1u8, 2, 3]));
+        assert_eq!(
+            message.content_type().map(|s| s.as_str()),
+            Some(APPLICATION_OCTET_STREAM)
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        use super::APPLICATION_JSON;
+
+        let message = Message::json(&vec![String::from("hello"), String::from("world")]).unwrap();
+        assert_eq!(
+            message.content_type().map(|s| s.as_str()),
+            Some(APPLICATION_JSON)
+        );
+        assert_eq!(&message.body.0[..], br#"["hello","world"]"#);
+
+        let value: Vec<String> = message.body_as_json().unwrap();
+        assert_eq!(value, vec!["hello", "world"]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn body_as_json_ignores_content_type_parameters_and_case() {
+        let message = Message::data(br#"[1, 2, 3]"#.to_vec(), "Application/JSON; charset=utf-8");
+        let value: Vec<i32> = message.body_as_json().unwrap();
+        assert_eq!(value, vec![1, 2, 3]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn body_as_json_with_mismatched_content_type() {
+        use super::JsonBodyError;
+
+        let message = Message::data(br#"[1, 2, 3]"#.to_vec(), APPLICATION_OCTET_STREAM);
+        let result = message.body_as_json::<Vec<i32>>();
+        match result {
+            Err(JsonBodyError::ContentTypeMismatch { found }) => {
+                assert_eq!(
+                    found.as_ref().map(|s| s.as_str()),
+                    Some(APPLICATION_OCTET_STREAM)
+                )
+            }
+            _ => panic!("Expecting ContentTypeMismatch"),
+        }
+
+        // Missing content-type
+        let message = Message::builder().data(br#"[1, 2, 3]"#.to_vec()).build();
+        let result = message.body_as_json::<Vec<i32>>();
+        assert!(matches!(
+            result,
+            Err(JsonBodyError::ContentTypeMismatch { found: None })
+        ));
+    }
+}
@@ -10,13 +10,16 @@ use serde::{
 use serde_amqp::__constants::{DESCRIBED_BASIC, DESCRIPTOR};
 
 use super::{
-    AmqpSequence, AmqpValue, ApplicationProperties, Batch, Data, DeliveryAnnotations, Footer,
-    FromBody, Header, IntoBody, MessageAnnotations, Properties, SerializableBody,
+    AmqpSequence, AmqpValue, ApplicationProperties, Batch, Data, DataStream, DeliveryAnnotations,
+    Footer, FromBody, Header, IntoBody, MessageAnnotations, Properties, SerializableBody,
 };
 
 mod body;
 pub use body::*;
 
+mod content_type;
+pub use content_type::*;
+
 #[doc(hidden)]
 pub mod __private {
     #[derive(Debug)]
@@ -124,6 +127,24 @@ impl Message<EmptyBody> {
     }
 }
 
+impl<R> Message<DataStream<R>> {
+    /// Creates a message with a single data section of `len` bytes that is read from `reader`
+    /// while the message is being sent
+    ///
+    /// The other sections are left empty and can be set through the public fields.
+    pub fn stream_data(reader: R, len: usize) -> Self {
+        Self {
+            header: None,
+            delivery_annotations: None,
+            message_annotations: None,
+            properties: None,
+            application_properties: None,
+            body: DataStream { reader, len },
+            footer: None,
+        }
+    }
+}
+
 impl<T> Message<T> {
     /// Count number of sections
     pub fn sections(&self) -> u32 {
@@ -177,6 +198,54 @@ impl<T> Message<T> {
             footer: self.footer,
         }
     }
+
+    /// Split the message into its sections without cloning any of them
+    pub fn into_parts(self) -> MessageParts<T> {
+        MessageParts {
+            header: self.header,
+            delivery_annotations: self.delivery_annotations,
+            message_annotations: self.message_annotations,
+            properties: self.properties,
+            application_properties: self.application_properties,
+            body: self.body,
+            footer: self.footer,
+        }
+    }
+
+    /// Re-assemble a message from sections obtained from [`Message::into_parts`]
+    pub fn from_parts(parts: MessageParts<T>) -> Self {
+        Self {
+            header: parts.header,
+            delivery_annotations: parts.delivery_annotations,
+            message_annotations: parts.message_annotations,
+            properties: parts.properties,
+            application_properties: parts.application_properties,
+            body: parts.body,
+            footer: parts.footer,
+        }
+    }
+}
+
+/// Owned sections of a [`Message`]
+///
+/// This allows moving sections (especially the body) out of a received message and into a
+/// new one without cloning.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MessageParts<B> {
+    /// header
+    pub header: Option<Header>,
+    /// delivery annotations
+    pub delivery_annotations: Option<DeliveryAnnotations>,
+    /// message annotations
+    pub message_annotations: Option<MessageAnnotations>,
+    /// properties
+    pub properties: Option<Properties>,
+    /// application properties
+    pub application_properties: Option<ApplicationProperties>,
+    /// body sections
+    pub body: B,
+    /// footer
+    pub footer: Option<Footer>,
 }
 
 // impl<T> Serialize for Message<T>
@@ -936,4 +1005,32 @@ mod tests {
             .build();
         assert_eq!(message.0, expected);
     }
+
+    #[test]
+    fn into_parts_moves_data_body_without_clone() {
+        let payload = vec![7u8; 1024];
+        let ptr = payload.as_ptr();
+        let capacity = payload.capacity();
+        let received = Message::builder()
+            .header(Header::default())
+            .properties(Properties::builder().to("old-address").build())
+            .data(Binary::from(payload))
+            .build();
+
+        let mut parts = received.into_parts();
+        parts.header = Some(Header {
+            durable: true,
+            ..Default::default()
+        });
+        parts.properties = Some(Properties::builder().to("new-address").build());
+        let forwarded = Message::from_parts(parts);
+
+        assert_eq!(forwarded.body.0.as_ptr(), ptr);
+        assert_eq!(forwarded.body.0.capacity(), capacity);
+        assert!(forwarded.header.unwrap().durable);
+        assert_eq!(
+            forwarded.properties.unwrap().to.as_deref(),
+            Some("new-address")
+        );
+    }
 }
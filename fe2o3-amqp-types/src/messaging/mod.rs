@@ -11,6 +11,10 @@ pub use body_section::*;
 pub mod message;
 pub use message::{Body, Message};
 
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-compat")))]
+#[cfg(feature = "serde-compat")]
+pub mod serde_compat;
+
 /* -------------------------- 3.2 Messaging Format -------------------------- */
 mod format;
 pub use format::*;
@@ -21,7 +25,7 @@ pub use delivery_state::*;
 
 /* -------------------------- 3.5 Source and Target ------------------------- */
 mod source;
-pub use source::{Source, SourceBuilder};
+pub use source::{FilterSetBuilder, Source, SourceBuilder};
 
 mod target;
 pub use target::{Target, TargetArchetype, TargetBuilder};
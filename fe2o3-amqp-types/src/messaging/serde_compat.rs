@@ -0,0 +1,265 @@
+//! Plain serde representations of the message sections
+//!
+//! The `Serialize` and `Deserialize` impls of [`Header`] and [`Properties`] drive the AMQP wire
+//! format, which carries the descriptor and relies on hints that only the AMQP serializer
+//! understands. The types in this module mirror those sections with plain serde derives so that
+//! they can be (de)serialized with any other serde format (eg. JSON for logging). They convert
+//! from and into the AMQP types with `From`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use fe2o3_amqp_types::messaging::{serde_compat::PropertiesRepr, Properties};
+//!
+//! let properties = Properties::builder()
+//!     .message_id(1u64)
+//!     .subject("greeting")
+//!     .build();
+//! let json = serde_json::to_string(&PropertiesRepr::from(&properties)).unwrap();
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_amqp::primitives::{Binary, Timestamp, Uuid};
+
+use super::{Header, Message, MessageId, Priority, Properties};
+
+/// Plain serde representation of a [`MessageId`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageIdRepr {
+    /// A `message-id-ulong`
+    ULong(u64),
+
+    /// A `message-id-uuid`
+    Uuid([u8; 16]),
+
+    /// A `message-id-binary`
+    Binary(Vec<u8>),
+
+    /// A `message-id-string`
+    String(String),
+}
+
+impl From<&MessageId> for MessageIdRepr {
+    fn from(value: &MessageId) -> Self {
+        match value {
+            MessageId::ULong(value) => Self::ULong(*value),
+            MessageId::Uuid(value) => Self::Uuid(*value.as_inner()),
+            MessageId::Binary(value) => Self::Binary(value.to_vec()),
+            MessageId::String(value) => Self::String(value.clone()),
+        }
+    }
+}
+
+impl From<MessageIdRepr> for MessageId {
+    fn from(value: MessageIdRepr) -> Self {
+        match value {
+            MessageIdRepr::ULong(value) => Self::ULong(value),
+            MessageIdRepr::Uuid(value) => Self::Uuid(Uuid::from(value)),
+            MessageIdRepr::Binary(value) => Self::Binary(Binary::from(value)),
+            MessageIdRepr::String(value) => Self::String(value),
+        }
+    }
+}
+
+/// Plain serde representation of a [`Header`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderRepr {
+    /// The `durable` field
+    pub durable: bool,
+
+    /// The `priority` field
+    pub priority: u8,
+
+    /// The `ttl` field in milliseconds
+    pub ttl: Option<u32>,
+
+    /// The `first-acquirer` field
+    pub first_acquirer: bool,
+
+    /// The `delivery-count` field
+    pub delivery_count: u32,
+}
+
+impl From<&Header> for HeaderRepr {
+    fn from(value: &Header) -> Self {
+        Self {
+            durable: value.durable,
+            priority: value.priority.0,
+            ttl: value.ttl,
+            first_acquirer: value.first_acquirer,
+            delivery_count: value.delivery_count,
+        }
+    }
+}
+
+impl From<HeaderRepr> for Header {
+    fn from(value: HeaderRepr) -> Self {
+        Self {
+            durable: value.durable,
+            priority: Priority(value.priority),
+            ttl: value.ttl,
+            first_acquirer: value.first_acquirer,
+            delivery_count: value.delivery_count,
+        }
+    }
+}
+
+/// Plain serde representation of [`Properties`]
+///
+/// Timestamps are in milliseconds since the unix epoch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertiesRepr {
+    /// The `message-id` field
+    pub message_id: Option<MessageIdRepr>,
+
+    /// The `user-id` field
+    pub user_id: Option<Vec<u8>>,
+
+    /// The `to` field
+    pub to: Option<String>,
+
+    /// The `subject` field
+    pub subject: Option<String>,
+
+    /// The `reply-to` field
+    pub reply_to: Option<String>,
+
+    /// The `correlation-id` field
+    pub correlation_id: Option<MessageIdRepr>,
+
+    /// The `content-type` field
+    pub content_type: Option<String>,
+
+    /// The `content-encoding` field
+    pub content_encoding: Option<String>,
+
+    /// The `absolute-expiry-time` field
+    pub absolute_expiry_time: Option<i64>,
+
+    /// The `creation-time` field
+    pub creation_time: Option<i64>,
+
+    /// The `group-id` field
+    pub group_id: Option<String>,
+
+    /// The `group-sequence` field
+    pub group_sequence: Option<u32>,
+
+    /// The `reply-to-group-id` field
+    pub reply_to_group_id: Option<String>,
+}
+
+impl From<&Properties> for PropertiesRepr {
+    fn from(value: &Properties) -> Self {
+        Self {
+            message_id: value.message_id.as_ref().map(Into::into),
+            user_id: value.user_id.as_ref().map(|id| id.to_vec()),
+            to: value.to.clone(),
+            subject: value.subject.clone(),
+            reply_to: value.reply_to.clone(),
+            correlation_id: value.correlation_id.as_ref().map(Into::into),
+            content_type: value.content_type.as_ref().map(|s| s.to_string()),
+            content_encoding: value.content_encoding.as_ref().map(|s| s.to_string()),
+            absolute_expiry_time: value
+                .absolute_expiry_time
+                .as_ref()
+                .map(|t| t.milliseconds()),
+            creation_time: value.creation_time.as_ref().map(|t| t.milliseconds()),
+            group_id: value.group_id.clone(),
+            group_sequence: value.group_sequence,
+            reply_to_group_id: value.reply_to_group_id.clone(),
+        }
+    }
+}
+
+impl From<PropertiesRepr> for Properties {
+    fn from(value: PropertiesRepr) -> Self {
+        Self {
+            message_id: value.message_id.map(Into::into),
+            user_id: value.user_id.map(Binary::from),
+            to: value.to,
+            subject: value.subject,
+            reply_to: value.reply_to,
+            correlation_id: value.correlation_id.map(Into::into),
+            content_type: value.content_type.map(Into::into),
+            content_encoding: value.content_encoding.map(Into::into),
+            absolute_expiry_time: value.absolute_expiry_time.map(Timestamp::from_milliseconds),
+            creation_time: value.creation_time.map(Timestamp::from_milliseconds),
+            group_id: value.group_id,
+            group_sequence: value.group_sequence,
+            reply_to_group_id: value.reply_to_group_id,
+        }
+    }
+}
+
+/// Plain serde representation of the `header` and `properties` sections of a [`Message`]
+///
+/// The body and the annotations are left out because they may hold arbitrary AMQP values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageRepr {
+    /// The `header` section
+    pub header: Option<HeaderRepr>,
+
+    /// The `properties` section
+    pub properties: Option<PropertiesRepr>,
+}
+
+impl<T> From<&Message<T>> for MessageRepr {
+    fn from(value: &Message<T>) -> Self {
+        Self {
+            header: value.header.as_ref().map(Into::into),
+            properties: value.properties.as_ref().map(Into::into),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_amqp::primitives::Timestamp;
+
+    use crate::messaging::{Header, Message, MessageId, Properties};
+
+    use super::{MessageRepr, PropertiesRepr};
+
+    #[test]
+    fn properties_to_json() {
+        let properties = Properties::builder()
+            .message_id(1u64)
+            .subject("greeting")
+            .content_type("text/plain")
+            .correlation_id(MessageId::String("request-1".into()))
+            .creation_time(Timestamp::from_milliseconds(1_000))
+            .build();
+        let repr = PropertiesRepr::from(&properties);
+        let json = serde_json::to_value(&repr).unwrap();
+        assert_eq!(json["message_id"], serde_json::json!({ "ulong": 1 }));
+        assert_eq!(json["subject"], "greeting");
+        assert_eq!(json["content_type"], "text/plain");
+        assert_eq!(
+            json["correlation_id"],
+            serde_json::json!({ "string": "request-1" })
+        );
+        assert_eq!(json["creation_time"], 1_000);
+        assert!(json["to"].is_null());
+
+        let decoded: PropertiesRepr = serde_json::from_value(json).unwrap();
+        assert_eq!(Properties::from(decoded), properties);
+    }
+
+    #[test]
+    fn message_header_and_properties_to_json() {
+        let header = Header::builder().durable(true).ttl(30_000).build();
+        let message = Message::builder()
+            .header(header.clone())
+            .properties(Properties::builder().subject("greeting").build())
+            .value("hello")
+            .build();
+        let repr = MessageRepr::from(&message);
+        let json = serde_json::to_string(&repr).unwrap();
+
+        let decoded: MessageRepr = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, repr);
+        assert_eq!(Header::from(decoded.header.unwrap()), header);
+    }
+}
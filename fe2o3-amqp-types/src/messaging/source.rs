@@ -1,4 +1,5 @@
 use serde_amqp::described::Described;
+use serde_amqp::descriptor::Descriptor;
 use serde_amqp::macros::{DeserializeComposite, SerializeComposite};
 use serde_amqp::primitives::{Array, Boolean, OrderedMap, Symbol};
 use serde_amqp::Value;
@@ -231,8 +232,19 @@ impl SourceBuilder {
     /// format is deprecated and SHOULD NOT be used in new implementations. However,
     /// for compatibility with existing implementations, the following type alias
     /// is provided since "0.7.0" to allow the legacy format to be used.
-    pub fn filter(mut self, filter_set: FilterSet) -> Self {
-        self.source.filter = Some(filter_set);
+    ///
+    /// A [`FilterSetBuilder`] can be used to populate the well-known filters
+    ///
+    /// ```rust
+    /// use fe2o3_amqp_types::messaging::{FilterSetBuilder, Source};
+    ///
+    /// let source = Source::builder()
+    ///     .address("q1")
+    ///     .filter(FilterSetBuilder::new().selector("amqp.property like 'x%'"))
+    ///     .build();
+    /// ```
+    pub fn filter(mut self, filter_set: impl Into<FilterSet>) -> Self {
+        self.source.filter = Some(filter_set.into());
         self
     }
 
@@ -295,3 +307,206 @@ impl<T: Into<Address>> From<T> for Source {
         Self::builder().address(val.into()).build()
     }
 }
+
+const LEGACY_AMQP_DIRECT_BINDING_NAME: &str = "apache.org:legacy-amqp-direct-binding:string";
+const LEGACY_AMQP_DIRECT_BINDING_CODE: u64 = 0x0000_468c_0000_0000;
+const LEGACY_AMQP_TOPIC_BINDING_NAME: &str = "apache.org:legacy-amqp-topic-binding:string";
+const LEGACY_AMQP_TOPIC_BINDING_CODE: u64 = 0x0000_468c_0000_0001;
+const SELECTOR_FILTER_NAME: &str = "apache.org:selector-filter:string";
+const SELECTOR_FILTER_CODE: u64 = 0x0000_468c_0000_0004;
+
+/// [`FilterSet`] builder
+///
+/// The well-known filters from the AMQP filter registry are keyed by their descriptor names and
+/// their values are encoded as described types with the descriptor codes.
+///
+/// ```rust
+/// use fe2o3_amqp_types::messaging::{FilterSetBuilder, Source};
+///
+/// let filter_set = FilterSetBuilder::new()
+///     .selector("amqp.property like 'x%'")
+///     .legacy_amqp_topic("news.*")
+///     .build();
+/// let source = Source::builder().filter(filter_set).build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FilterSetBuilder {
+    filter_set: FilterSet,
+}
+
+impl FilterSetBuilder {
+    /// Creates a [`FilterSet`] builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry whose value is either null or a described type
+    pub fn insert(
+        mut self,
+        key: impl Into<Symbol>,
+        value: impl Into<Option<Described<Value>>>,
+    ) -> Self {
+        let value = value.into().map(Into::into).unwrap_or(Value::Null);
+        self.filter_set.insert(key.into(), value);
+        self
+    }
+
+    /// Add an entry using the legacy format, in which the value is any AMQP type
+    pub fn insert_using_legacy_format(
+        mut self,
+        key: impl Into<Symbol>,
+        value: impl Into<Value>,
+    ) -> Self {
+        self.filter_set.insert(key.into(), value.into());
+        self
+    }
+
+    /// Add an `apache.org:selector-filter:string` filter with an SQL-like selector expression
+    pub fn selector(self, expression: impl Into<String>) -> Self {
+        self.described_string(SELECTOR_FILTER_NAME, SELECTOR_FILTER_CODE, expression)
+    }
+
+    /// Add an `apache.org:legacy-amqp-direct-binding:string` filter with the routing key
+    pub fn legacy_amqp_direct(self, routing_key: impl Into<String>) -> Self {
+        self.described_string(
+            LEGACY_AMQP_DIRECT_BINDING_NAME,
+            LEGACY_AMQP_DIRECT_BINDING_CODE,
+            routing_key,
+        )
+    }
+
+    /// Add an `apache.org:legacy-amqp-topic-binding:string` filter with the binding pattern
+    pub fn legacy_amqp_topic(self, pattern: impl Into<String>) -> Self {
+        self.described_string(
+            LEGACY_AMQP_TOPIC_BINDING_NAME,
+            LEGACY_AMQP_TOPIC_BINDING_CODE,
+            pattern,
+        )
+    }
+
+    fn described_string(self, name: &str, code: u64, value: impl Into<String>) -> Self {
+        let described = Described {
+            descriptor: Descriptor::Code(code),
+            value: Value::String(value.into()),
+        };
+        self.insert(name, described)
+    }
+
+    /// Build the [`FilterSet`]
+    pub fn build(self) -> FilterSet {
+        self.filter_set
+    }
+}
+
+impl From<FilterSetBuilder> for FilterSet {
+    fn from(builder: FilterSetBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_amqp::{
+        described::Described, descriptor::Descriptor, from_slice, primitives::Symbol, to_vec, Value,
+    };
+
+    use super::{FilterSetBuilder, Source};
+
+    fn described(code: u64, value: &str) -> Value {
+        Value::Described(Box::new(Described {
+            descriptor: Descriptor::Code(code),
+            value: Value::String(value.to_string()),
+        }))
+    }
+
+    #[test]
+    fn filter_set_builder_populates_well_known_filters() {
+        let filter_set = FilterSetBuilder::new()
+            .selector("amqp.property like 'x%'")
+            .legacy_amqp_direct("key")
+            .legacy_amqp_topic("news.*")
+            .build();
+
+        let entries: Vec<_> = filter_set.iter().collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    &Symbol::from("apache.org:selector-filter:string"),
+                    &described(0x0000_468c_0000_0004, "amqp.property like 'x%'")
+                ),
+                (
+                    &Symbol::from("apache.org:legacy-amqp-direct-binding:string"),
+                    &described(0x0000_468c_0000_0000, "key")
+                ),
+                (
+                    &Symbol::from("apache.org:legacy-amqp-topic-binding:string"),
+                    &described(0x0000_468c_0000_0001, "news.*")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn source_builder_accepts_filter_set_builder() {
+        let source = Source::builder()
+            .address("q1")
+            .filter(FilterSetBuilder::new().selector("color = 'red'"))
+            .build();
+
+        let buf = to_vec(&source).unwrap();
+        let decoded: Source = from_slice(&buf).unwrap();
+        let filter = decoded.filter.unwrap();
+        assert_eq!(filter.len(), 1);
+        assert_eq!(
+            filter.get(&Symbol::from("apache.org:selector-filter:string")),
+            Some(&described(0x0000_468c_0000_0004, "color = 'red'"))
+        );
+    }
+
+    #[test]
+    fn filter_set_builder_inserts_null_and_legacy_entries() {
+        let filter_set = FilterSetBuilder::new()
+            .insert("no-op", None)
+            .insert_using_legacy_format("legacy", "value")
+            .build();
+
+        assert_eq!(filter_set.get(&Symbol::from("no-op")), Some(&Value::Null));
+        assert_eq!(
+            filter_set.get(&Symbol::from("legacy")),
+            Some(&Value::String(String::from("value")))
+        );
+    }
+
+    #[test]
+    fn source_builder_sets_expiry_policy_and_timeout() {
+        use crate::messaging::TerminusExpiryPolicy;
+
+        let source = Source::builder().address("q1").build();
+        assert_eq!(source.expiry_policy, TerminusExpiryPolicy::SessionEnd);
+        assert_eq!(source.timeout, 0);
+
+        let source = Source::builder()
+            .address("q1")
+            .expiry_policy(TerminusExpiryPolicy::Never)
+            .timeout(30)
+            .build();
+        let buf = to_vec(&source).unwrap();
+        let decoded: Source = from_slice(&buf).unwrap();
+        assert_eq!(decoded.expiry_policy, TerminusExpiryPolicy::Never);
+        assert_eq!(decoded.timeout, 30);
+    }
+
+    #[test]
+    fn source_builder_sets_durability() {
+        use crate::messaging::TerminusDurability;
+
+        let source = Source::builder()
+            .address("q1")
+            .durable(TerminusDurability::UnsettledState)
+            .build();
+        let buf = to_vec(&source).unwrap();
+        let decoded: Source = from_slice(&buf).unwrap();
+        assert_eq!(decoded.durable, TerminusDurability::UnsettledState);
+    }
+}
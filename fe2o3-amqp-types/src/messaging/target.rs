@@ -399,6 +399,21 @@ mod tests {
         // println!("{:?}", std::mem::size_of::<Target>());
     }
 
+    #[test]
+    fn target_builder_sets_expiry_policy_and_timeout() {
+        use crate::messaging::TerminusExpiryPolicy;
+
+        let target = Target::builder()
+            .address("q1")
+            .expiry_policy(TerminusExpiryPolicy::LinkDetach)
+            .timeout(5)
+            .build();
+        let buf = to_vec(&target).unwrap();
+        let decoded: Target = from_slice(&buf).unwrap();
+        assert_eq!(decoded.expiry_policy, TerminusExpiryPolicy::LinkDetach);
+        assert_eq!(decoded.timeout, 5);
+    }
+
     #[cfg(feature = "transaction")]
     #[test]
     fn test_target_archetype_variant_coordinator() {
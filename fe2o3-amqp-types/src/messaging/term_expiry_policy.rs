@@ -88,3 +88,42 @@ impl<'de> de::Deserialize<'de> for TerminusExpiryPolicy {
             .map_err(|_| de::Error::custom("Invalid symbol value for TerminusExpiryPolicy"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_amqp::{from_slice, primitives::Symbol, to_vec};
+
+    use super::TerminusExpiryPolicy;
+
+    #[test]
+    fn expiry_policy_serializes_as_spec_symbol() {
+        let policies = [
+            (TerminusExpiryPolicy::LinkDetach, "link-detach"),
+            (TerminusExpiryPolicy::SessionEnd, "session-end"),
+            (TerminusExpiryPolicy::ConnectionClose, "connection-close"),
+            (TerminusExpiryPolicy::Never, "never"),
+        ];
+
+        for (policy, symbol) in policies {
+            let buf = to_vec(&policy).unwrap();
+            assert_eq!(buf, to_vec(&Symbol::from(symbol)).unwrap());
+
+            let decoded: TerminusExpiryPolicy = from_slice(&buf).unwrap();
+            assert_eq!(decoded, policy);
+        }
+    }
+
+    #[test]
+    fn unknown_expiry_policy_symbol_is_rejected() {
+        let buf = to_vec(&Symbol::from("forever")).unwrap();
+        assert!(from_slice::<TerminusExpiryPolicy>(&buf).is_err());
+    }
+
+    #[test]
+    fn default_expiry_policy_is_session_end() {
+        assert_eq!(
+            TerminusExpiryPolicy::default(),
+            TerminusExpiryPolicy::SessionEnd
+        );
+    }
+}
@@ -22,4 +22,32 @@ impl Default for TerminusDurability {
     }
 }
 
-// TODO: test serialization and deserialization
+#[cfg(test)]
+mod tests {
+    use serde_amqp::{from_slice, primitives::UInt, to_vec};
+
+    use super::TerminusDurability;
+
+    #[test]
+    fn durability_round_trips_as_uint() {
+        let durabilities = [
+            (TerminusDurability::None, 0),
+            (TerminusDurability::Configuration, 1),
+            (TerminusDurability::UnsettledState, 2),
+        ];
+
+        for (durability, value) in durabilities {
+            let buf = to_vec(&durability).unwrap();
+            assert_eq!(buf, to_vec(&(value as UInt)).unwrap());
+
+            let decoded: TerminusDurability = from_slice(&buf).unwrap();
+            assert_eq!(decoded, durability);
+        }
+    }
+
+    #[test]
+    fn unknown_durability_is_rejected() {
+        let buf = to_vec(&(3 as UInt)).unwrap();
+        assert!(from_slice::<TerminusDurability>(&buf).is_err());
+    }
+}
@@ -149,6 +149,40 @@ mod tests {
         println!("{:?}", deserialized);
     }
 
+    #[test]
+    fn empty_properties_and_capabilities_are_distinct_from_none() {
+        use serde_amqp::primitives::{Array, OrderedMap};
+
+        let attach = |properties, desired_capabilities| Attach {
+            name: "sender-link-1".into(),
+            handle: 0.into(),
+            role: Role::Sender,
+            snd_settle_mode: SenderSettleMode::Mixed,
+            rcv_settle_mode: ReceiverSettleMode::First,
+            source: None,
+            target: None,
+            unsettled: None,
+            incomplete_unsettled: false,
+            initial_delivery_count: Some(0),
+            max_message_size: None,
+            offered_capabilities: None,
+            desired_capabilities,
+            properties,
+        };
+
+        let none = to_vec(&attach(None, None)).unwrap();
+        let empty = to_vec(&attach(Some(OrderedMap::new()), Some(Array(vec![])))).unwrap();
+        assert_ne!(none, empty);
+
+        let decoded: Attach = from_slice(&none).unwrap();
+        assert_eq!(decoded.properties, None);
+        assert_eq!(decoded.desired_capabilities, None);
+
+        let decoded: Attach = from_slice(&empty).unwrap();
+        assert_eq!(decoded.properties, Some(OrderedMap::new()));
+        assert_eq!(decoded.desired_capabilities, Some(Array(vec![])));
+    }
+
     #[test]
     fn test_deserialize_attach_service_bus() {
         let buf: &[u8] = &[
@@ -11,4 +11,4 @@ pub use serde_amqp::primitives::*;
 pub use serde_amqp::value::Value;
 
 mod simple_value;
-pub use simple_value::SimpleValue;
+pub use simple_value::{SimpleValue, SimpleValueTypeMismatch};
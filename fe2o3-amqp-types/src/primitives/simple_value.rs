@@ -246,8 +246,66 @@ impl SimpleValue {
         };
         code as u8
     }
+
+    /// Get the name of the AMQP type of the value
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            SimpleValue::Null => "null",
+            SimpleValue::Bool(_) => "boolean",
+            SimpleValue::UByte(_) => "ubyte",
+            SimpleValue::UShort(_) => "ushort",
+            SimpleValue::UInt(_) => "uint",
+            SimpleValue::ULong(_) => "ulong",
+            SimpleValue::Byte(_) => "byte",
+            SimpleValue::Short(_) => "short",
+            SimpleValue::Int(_) => "int",
+            SimpleValue::Long(_) => "long",
+            SimpleValue::Float(_) => "float",
+            SimpleValue::Double(_) => "double",
+            SimpleValue::Decimal32(_) => "decimal32",
+            SimpleValue::Decimal64(_) => "decimal64",
+            SimpleValue::Decimal128(_) => "decimal128",
+            SimpleValue::Char(_) => "char",
+            SimpleValue::Timestamp(_) => "timestamp",
+            SimpleValue::Uuid(_) => "uuid",
+            SimpleValue::Binary(_) => "binary",
+            SimpleValue::String(_) => "string",
+            SimpleValue::Symbol(_) => "symbol",
+        }
+    }
+}
+
+/// Error converting a [`SimpleValue`] into a rust type that doesn't match the variant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleValueTypeMismatch {
+    /// The name of the expected AMQP type
+    pub expected: &'static str,
+
+    /// The value that failed to convert
+    pub found: SimpleValue,
+}
+
+impl SimpleValueTypeMismatch {
+    /// Get back the value that failed to convert
+    pub fn into_value(self) -> SimpleValue {
+        self.found
+    }
+}
+
+impl std::fmt::Display for SimpleValueTypeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expecting {}, found {} ({:?})",
+            self.expected,
+            self.found.type_name(),
+            self.found
+        )
+    }
 }
 
+impl std::error::Error for SimpleValueTypeMismatch {}
+
 impl ser::Serialize for SimpleValue {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -408,43 +466,134 @@ impl From<&str> for SimpleValue {
 }
 
 macro_rules! impl_try_from_for_simple_value_variant {
-    ($variant:ident, $variant_ty:ty) => {
+    ($variant:ident, $variant_ty:ty, $expected:literal) => {
         impl TryFrom<SimpleValue> for $variant_ty {
-            type Error = SimpleValue;
+            type Error = SimpleValueTypeMismatch;
 
             fn try_from(val: SimpleValue) -> Result<Self, Self::Error> {
                 match val {
-                    SimpleValue::$variant(inner) => Ok(inner),
-                    _ => Err(val)
+                    SimpleValue::$variant(inner) => Ok(inner.into()),
+                    _ => Err(SimpleValueTypeMismatch {
+                        expected: $expected,
+                        found: val,
+                    }),
                 }
             }
         }
     };
 
-    ($($variant:ident, $variant_ty:ty),*) => {
-        $(impl_try_from_for_simple_value_variant!($variant, $variant_ty);)*
+    ($($variant:ident, $variant_ty:ty, $expected:literal),*) => {
+        $(impl_try_from_for_simple_value_variant!($variant, $variant_ty, $expected);)*
     }
 }
 
 impl_try_from_for_simple_value_variant! {
-    Bool, bool,
-    UByte, u8,
-    UShort, u16,
-    UInt, u32,
-    ULong, u64,
-    Byte, i8,
-    Short, i16,
-    Int, i32,
-    Long, i64,
-    Float, OrderedFloat<f32>,
-    Double, OrderedFloat<f64>,
-    Decimal32, Dec32,
-    Decimal64, Dec64,
-    Decimal128, Dec128,
-    Char, char,
-    Timestamp, Timestamp,
-    Uuid, Uuid,
-    Binary, ByteBuf,
-    String, String,
-    Symbol, Symbol
+    Bool, bool, "boolean",
+    UByte, u8, "ubyte",
+    UShort, u16, "ushort",
+    UInt, u32, "uint",
+    ULong, u64, "ulong",
+    Byte, i8, "byte",
+    Short, i16, "short",
+    Int, i32, "int",
+    Long, i64, "long",
+    Float, OrderedFloat<f32>, "float",
+    Float, f32, "float",
+    Double, OrderedFloat<f64>, "double",
+    Double, f64, "double",
+    Decimal32, Dec32, "decimal32",
+    Decimal64, Dec64, "decimal64",
+    Decimal128, Dec128, "decimal128",
+    Char, char, "char",
+    Timestamp, Timestamp, "timestamp",
+    Uuid, Uuid, "uuid",
+    Binary, ByteBuf, "binary",
+    String, String, "string",
+    Symbol, Symbol, "symbol"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use serde_amqp::primitives::{Binary, Symbol, Timestamp, Uuid};
+
+    use super::{SimpleValue, SimpleValueTypeMismatch};
+
+    /// Converts `$value` into a `SimpleValue`, checks the variant and converts it back
+    macro_rules! assert_round_trip {
+        ($($value:expr, $ty:ty, $expected:expr);* $(;)?) => {
+            $(
+                let value: $ty = $value;
+                let simple = SimpleValue::from(value.clone());
+                assert_eq!(simple, $expected);
+                assert_eq!(<$ty>::try_from(simple).unwrap(), value);
+            )*
+        };
+    }
+
+    #[test]
+    fn scalar_conversions_round_trip() {
+        let uuid = Uuid::from([1u8; 16]);
+        let timestamp = Timestamp::from_milliseconds(1_000);
+        let binary = Binary::from(vec![1u8, 2, 3]);
+
+        assert_round_trip! {
+            true, bool, SimpleValue::Bool(true);
+            1, u8, SimpleValue::UByte(1);
+            2, u16, SimpleValue::UShort(2);
+            3, u32, SimpleValue::UInt(3);
+            4, u64, SimpleValue::ULong(4);
+            -1, i8, SimpleValue::Byte(-1);
+            -2, i16, SimpleValue::Short(-2);
+            -3, i32, SimpleValue::Int(-3);
+            -4, i64, SimpleValue::Long(-4);
+            1.5, f32, SimpleValue::Float(1.5.into());
+            2.5, f64, SimpleValue::Double(2.5.into());
+            'a', char, SimpleValue::Char('a');
+            String::from("amqp"), String, SimpleValue::String("amqp".into());
+            Symbol::from("amqp"), Symbol, SimpleValue::Symbol(Symbol::from("amqp"));
+            uuid.clone(), Uuid, SimpleValue::Uuid(uuid);
+            timestamp.clone(), Timestamp, SimpleValue::Timestamp(timestamp);
+            binary.clone(), Binary, SimpleValue::Binary(binary);
+        }
+
+        assert_eq!(
+            SimpleValue::from("amqp"),
+            SimpleValue::String("amqp".into())
+        );
+    }
+
+    #[test]
+    fn mismatched_conversion_returns_descriptive_error() {
+        let cases = [
+            (u64::try_from(SimpleValue::UInt(1)).unwrap_err(), "ulong"),
+            (i32::try_from(SimpleValue::Long(1)).unwrap_err(), "int"),
+            (
+                f64::try_from(SimpleValue::Float(1.0.into())).unwrap_err(),
+                "double",
+            ),
+            (
+                String::try_from(SimpleValue::Symbol("a".into())).unwrap_err(),
+                "string",
+            ),
+            (bool::try_from(SimpleValue::Null).unwrap_err(), "boolean"),
+        ];
+
+        for (err, expected) in cases {
+            assert_eq!(err.expected, expected);
+            assert_ne!(err.found.type_name(), expected);
+        }
+
+        let err = u64::try_from(SimpleValue::UInt(1)).unwrap_err();
+        assert_eq!(
+            err,
+            SimpleValueTypeMismatch {
+                expected: "ulong",
+                found: SimpleValue::UInt(1),
+            }
+        );
+        assert_eq!(err.to_string(), "Expecting ulong, found uint (UInt(1))");
+        assert_eq!(err.into_value(), SimpleValue::UInt(1));
+    }
 }
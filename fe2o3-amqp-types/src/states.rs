@@ -1,7 +1,7 @@
 //! Definition of connection state and session state
 
 /// Connection states as defined in the AMQP 1.0 Protocol Part 2.4.6
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConnectionState {
     /// In this state a connection exists, but nothing has been sent or received. This is the state an
     /// implementation would be in immediately after performing a socket connect or socket accept
@@ -4,11 +4,11 @@ use std::marker::PhantomData;
 
 use fe2o3_amqp_types::{
     definitions::{
-        Fields, Handle, IetfLanguageTag, Milliseconds, ReceiverSettleMode, SenderSettleMode,
+        self, Fields, Handle, IetfLanguageTag, Milliseconds, ReceiverSettleMode, SenderSettleMode,
         SequenceNo, TransferNumber, MIN_MAX_FRAME_SIZE,
     },
     messaging::{Source, Target},
-    performatives::{ChannelMax, MaxFrameSize, Open},
+    performatives::{Attach, Begin, ChannelMax, MaxFrameSize, Open},
     primitives::{Array, Symbol, ULong},
 };
 
@@ -18,9 +18,11 @@ use crate::{
 };
 
 use super::{
-    link::LinkAcceptor, local_receiver_link::LocalReceiverLinkAcceptor,
-    local_sender_link::LocalSenderLinkAcceptor, session::SessionAcceptor, ConnectionAcceptor,
-    SaslAcceptor, SupportedReceiverSettleModes, SupportedSenderSettleModes,
+    link::{AttachHook, LinkAcceptor},
+    local_receiver_link::LocalReceiverLinkAcceptor,
+    local_sender_link::LocalSenderLinkAcceptor,
+    session::{BeginHook, SessionAcceptor},
+    ConnectionAcceptor, SaslAcceptor, SupportedReceiverSettleModes, SupportedSenderSettleModes,
 };
 
 #[cfg(feature = "transaction")]
@@ -74,6 +76,7 @@ impl Builder<ConnectionAcceptor<(), ()>, Uninitialized> {
             tls_acceptor: (),
             sasl_acceptor: (),
             buffer_size: DEFAULT_OUTGOING_BUFFER_SIZE,
+            max_unsettled_deliveries: None,
         };
 
         Self {
@@ -215,6 +218,7 @@ impl<M, Tls, Sasl> Builder<ConnectionAcceptor<Tls, Sasl>, M> {
             tls_acceptor,
             sasl_acceptor: self.inner.sasl_acceptor,
             buffer_size: self.inner.buffer_size,
+            max_unsettled_deliveries: self.inner.max_unsettled_deliveries,
         };
         Builder {
             inner,
@@ -232,6 +236,7 @@ impl<M, Tls, Sasl> Builder<ConnectionAcceptor<Tls, Sasl>, M> {
             tls_acceptor: self.inner.tls_acceptor,
             sasl_acceptor,
             buffer_size: self.inner.buffer_size,
+            max_unsettled_deliveries: self.inner.max_unsettled_deliveries,
         };
         Builder {
             inner,
@@ -244,6 +249,15 @@ impl<M, Tls, Sasl> Builder<ConnectionAcceptor<Tls, Sasl>, M> {
         self.inner.buffer_size = buffer_size;
         self
     }
+
+    /// Maximum number of unsettled outgoing deliveries across all sessions and links on an
+    /// accepted connection. Sending an unsettled delivery will wait until another delivery is
+    /// settled once this limit is reached. Incoming deliveries are not counted, and receiving is
+    /// not throttled. A value of zero means no limit.
+    pub fn max_unsettled_deliveries(mut self, max: usize) -> Self {
+        self.inner.max_unsettled_deliveries = Some(max);
+        self
+    }
 }
 
 // =============================================================================
@@ -274,17 +288,26 @@ impl Builder<SessionAcceptor, Initialized> {
     }
 
     /// The initial incoming-window of the sender
+    ///
+    /// The session sends a flow to reopen the window once half of it has been consumed, and ends
+    /// with a `window-violation` error if the peer sends beyond it
     pub fn incoming_window(mut self, value: TransferNumber) -> Self {
         self.inner.0.incoming_window = value;
         self
     }
 
     /// The initial outgoing-window of the sender
-    pub fn outgoing_widnow(mut self, value: TransferNumber) -> Self {
+    pub fn outgoing_window(mut self, value: TransferNumber) -> Self {
         self.inner.0.outgoing_window = value;
         self
     }
 
+    /// The initial outgoing-window of the sender
+    #[deprecated = "Since 0.8.15, please use `outgoing_window` instead"]
+    pub fn outgoing_widnow(self, value: TransferNumber) -> Self {
+        self.outgoing_window(value)
+    }
+
     /// The maximum handle value that can be used on the session
     pub fn handle_max(mut self, value: impl Into<Handle>) -> Self {
         self.inner.0.handle_max = value.into();
@@ -334,6 +357,13 @@ impl Builder<SessionAcceptor, Initialized> {
         self
     }
 
+    /// Buffer size of the channel that carries outgoing frames from the links to the session.
+    /// Falls back to [`buffer_size`](#method.buffer_size) if not set
+    pub fn outgoing_buffer_size(mut self, outgoing_buffer_size: usize) -> Self {
+        self.inner.0.outgoing_buffer_size = Some(outgoing_buffer_size);
+        self
+    }
+
     /// Enable handling remotely initiated control link and transaction by setting the
     /// `control_link_acceptor` field
     #[cfg_attr(docsrs, doc(cfg(all(feature = "transaction", feature = "acceptor"))))]
@@ -345,6 +375,30 @@ impl Builder<SessionAcceptor, Initialized> {
         self.inner.0.control_link_acceptor = control_link_acceptor.into();
         self
     }
+
+    /// Sets a hook that inspects the Begin of an incoming session before it is accepted
+    ///
+    /// If the hook returns an error, the session is refused by sending a Begin immediately
+    /// followed by an End carrying the error, and accepting the session fails with
+    /// `BeginError::Rejected`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let session_acceptor = SessionAcceptor::builder()
+    ///     .on_incoming_begin(|begin| match begin.properties {
+    ///         Some(_) => Ok(()),
+    ///         None => Err(definitions::Error::new(AmqpError::NotAllowed, None, None)),
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_incoming_begin(
+        mut self,
+        hook: impl Fn(&Begin) -> Result<(), definitions::Error> + Send + Sync + 'static,
+    ) -> Self {
+        self.inner.0.on_incoming_begin = Some(BeginHook::new(hook));
+        self
+    }
 }
 
 // =============================================================================
@@ -477,12 +531,53 @@ where
         self
     }
 
+    /// Sets a hook that inspects the Attach of an incoming link before it is accepted
+    ///
+    /// If the hook returns an error, the link is refused by sending an Attach without a local
+    /// terminus immediately followed by a closing Detach carrying the error, and accepting the
+    /// link fails with `Rejected`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let link_acceptor = LinkAcceptor::builder()
+    ///     .on_incoming_attach(|attach| match attach.target.as_deref() {
+    ///         Some(TargetArchetype::Target(target))
+    ///             if target.address.as_deref() == Some("forbidden") =>
+    ///         {
+    ///             Err(definitions::Error::new(AmqpError::UnauthorizedAccess, None, None))
+    ///         }
+    ///         _ => Ok(()),
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_incoming_attach(
+        mut self,
+        hook: impl Fn(&Attach) -> Result<(), definitions::Error> + Send + Sync + 'static,
+    ) -> Self {
+        self.inner.shared.on_incoming_attach = Some(AttachHook::new(hook));
+        self
+    }
+
     /// Sets how to handle dynamic target
     ///
-    /// If a valid target is created, a `Some(target)` should be returned. If dynamic
-    /// node creation is not supported, then a `None` should be returned.
+    /// If a valid target is created, a `Some(target)` should be returned with the `address` set
+    /// to the address of the created node. The `dynamic` flag is echoed back as is. If dynamic
+    /// node creation is not supported, then a `None` should be returned. A returned target
+    /// without an `address` is treated as a `None`.
     ///
     /// The default handler simply rejects the request by returning a `None`
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let link_acceptor = LinkAcceptor::builder()
+    ///     .on_dynamic_target(|mut target| {
+    ///         target.address = Some(create_temporary_queue());
+    ///         Some(target)
+    ///     })
+    ///     .build();
+    /// ```
     pub fn on_dynamic_target<F>(self, op: F) -> Builder<LinkAcceptor<FS, F>, Initialized>
     where
         F: Fn(Target) -> Option<Target>,
@@ -491,6 +586,11 @@ where
             credit_mode: self.inner.local_receiver_acceptor.credit_mode,
             target_capabilities: self.inner.local_receiver_acceptor.target_capabilities,
             auto_accept: self.inner.local_receiver_acceptor.auto_accept,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self
+                .inner
+                .local_receiver_acceptor
+                .incomplete_transfer_timeout,
             on_dynamic_target: op,
             target_marker: PhantomData,
         };
@@ -508,8 +608,10 @@ where
 
     /// Sets how to handle dynamic source
     ///
-    /// If a valid source is created, a `Some(source)` should be returned. If dynamic
-    /// node creation is not supported, then a `None` should be returned.
+    /// If a valid source is created, a `Some(source)` should be returned with the `address` set
+    /// to the address of the created node. The `dynamic` flag is echoed back as is. If dynamic
+    /// node creation is not supported, then a `None` should be returned. A returned source
+    /// without an `address` is treated as a `None`.
     ///
     /// The default handler simply rejects the request by returning a `None`
     pub fn on_dynamic_source<F>(self, op: F) -> Builder<LinkAcceptor<F, FT>, Initialized>
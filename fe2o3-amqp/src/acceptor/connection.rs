@@ -19,7 +19,8 @@ use tokio_util::codec::{FramedRead, FramedWrite};
 use crate::{
     acceptor::sasl_acceptor::SaslServerFrame,
     connection::{
-        self, engine::ConnectionEngine, ConnectionHandle, OpenError, DEFAULT_CONTROL_CHAN_BUF,
+        self, engine::ConnectionEngine, ConnectionHandle, OpenError, UnsettledDeliveries,
+        DEFAULT_CONTROL_CHAN_BUF,
     },
     endpoint::{self, IncomingChannel, OutgoingChannel},
     frames::{
@@ -148,6 +149,10 @@ pub struct ConnectionAcceptor<Tls, Sasl> {
 
     /// Buffer size for the underlying channel
     pub buffer_size: usize,
+
+    /// Maximum number of unsettled outgoing deliveries across all sessions and links on an
+    /// accepted connection
+    pub max_unsettled_deliveries: Option<usize>,
 }
 
 impl ConnectionAcceptor<(), ()> {
@@ -198,6 +203,7 @@ impl<Tls, Sasl> ConnectionAcceptor<Tls, Sasl> {
         let (begin_tx, begin_rx) = mpsc::channel(self.buffer_size);
 
         let connection = connection::Connection::new(local_state, self.local_open.clone());
+        let state = connection.state_watcher();
         let listener_connection = ListenerConnection {
             connection,
             session_listener: begin_tx,
@@ -205,6 +211,9 @@ impl<Tls, Sasl> ConnectionAcceptor<Tls, Sasl> {
 
         let engine =
             ConnectionEngine::open(transport, listener_connection, control_rx, outgoing_rx).await?;
+        let remote_open = engine.remote_open().ok_or(OpenError::IllegalState)?;
+        let idle_timeout = engine.idle_timeout();
+        let channel_max = engine.channel_max().ok_or(OpenError::IllegalState)?;
         let (handle, outcome) = engine.spawn();
 
         let connection_handle = ConnectionHandle {
@@ -214,6 +223,12 @@ impl<Tls, Sasl> ConnectionAcceptor<Tls, Sasl> {
             outcome,
             outgoing: outgoing_tx,
             session_listener: begin_rx,
+            remote_open,
+            idle_timeout,
+            channel_max,
+            unsettled_deliveries: UnsettledDeliveries::new(self.max_unsettled_deliveries),
+            state,
+            close_on_drop: None,
         };
         Ok(connection_handle)
     }
@@ -486,6 +501,11 @@ impl endpoint::Connection for ListenerConnection {
         self.connection.local_open()
     }
 
+    #[inline]
+    fn remote_open(&self) -> Option<&fe2o3_amqp_types::performatives::Open> {
+        self.connection.remote_open()
+    }
+
     #[inline]
     fn allocate_session(
         &mut self,
@@ -4,10 +4,10 @@
 // #[derive(Debug)]
 // pub struct LinkListener {}
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 use fe2o3_amqp_types::{
-    definitions::{Fields, ReceiverSettleMode, Role, SenderSettleMode},
+    definitions::{self, Fields, ReceiverSettleMode, Role, SenderSettleMode},
     messaging::{Source, Target},
     performatives::Attach,
     primitives::{Symbol, ULong},
@@ -31,6 +31,26 @@ pub enum LinkEndpoint {
     Receiver(crate::link::Receiver),
 }
 
+type AttachHookFn = dyn Fn(&Attach) -> Result<(), definitions::Error> + Send + Sync;
+
+/// Callback that inspects the Attach of an incoming link before the link is accepted
+#[derive(Clone)]
+pub(crate) struct AttachHook(Arc<AttachHookFn>);
+
+impl AttachHook {
+    pub(crate) fn new(
+        hook: impl Fn(&Attach) -> Result<(), definitions::Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl std::fmt::Debug for AttachHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AttachHook").finish()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct SharedLinkAcceptorFields {
     /// The maximum message size supported by the link endpoint
@@ -67,6 +87,18 @@ pub(crate) struct SharedLinkAcceptorFields {
     /// If this field is None, an incoming attach whose desired receiver settle
     /// mode is not supported will then be rejected
     pub fallback_rcv_settle_mode: ReceiverSettleMode,
+
+    /// Inspects the Attach of an incoming link before it is accepted
+    pub on_incoming_attach: Option<AttachHook>,
+}
+
+impl SharedLinkAcceptorFields {
+    /// Returns the error to refuse the incoming attach with, if any
+    pub(crate) fn inspect_incoming_attach(&self, attach: &Attach) -> Option<definitions::Error> {
+        self.on_incoming_attach
+            .as_ref()
+            .and_then(|hook| (hook.0)(attach).err())
+    }
 }
 
 impl Default for SharedLinkAcceptorFields {
@@ -81,6 +113,7 @@ impl Default for SharedLinkAcceptorFields {
             fallback_snd_settle_mode: SenderSettleMode::default(),
             supported_rcv_settle_modes: SupportedReceiverSettleModes::default(),
             fallback_rcv_settle_mode: ReceiverSettleMode::default(),
+            on_incoming_attach: None,
         }
     }
 }
@@ -5,13 +5,16 @@ use std::{
     sync::{atomic::AtomicU32, Arc},
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
 use fe2o3_amqp_types::{
     messaging::{Target, TargetArchetype},
     performatives::Attach,
     primitives::Symbol,
 };
 use parking_lot::RwLock;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 
 use crate::{
     control::SessionControl,
@@ -23,6 +26,7 @@ use crate::{
         LinkFrame, LinkIncomingItem, LinkRelay, ReceiverAttachError, ReceiverLink,
     },
     session::SessionHandle,
+    util::{Consumer, Producer},
     Receiver,
 };
 
@@ -51,6 +55,10 @@ where
     /// ```
     pub auto_accept: bool,
 
+    /// How long the receiver waits for the next transfer frame of a multi-frame delivery
+    #[cfg(not(target_arch = "wasm32"))]
+    pub incomplete_transfer_timeout: Option<Duration>,
+
     pub on_dynamic_target: F,
     pub target_marker: PhantomData<T>,
 }
@@ -65,6 +73,8 @@ impl<C, T> Default for LocalReceiverLinkAcceptor<C, T, fn(T) -> Option<T>> {
             credit_mode: CreditMode::default(),
             target_capabilities: None,
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: None,
             on_dynamic_target: reject_dynamic_target,
             target_marker: PhantomData,
         }
@@ -145,8 +155,9 @@ where
             properties: shared.properties.clone(), // Will be set in `on_incoming_attach`
         };
         let flow_state = Arc::new(LinkFlowState::receiver(flow_state_inner));
-        let flow_state_producer = flow_state.clone();
-        let flow_state_consumer = flow_state;
+        let notifier = Arc::new(Notify::new());
+        let flow_state_producer = Producer::new(notifier.clone(), flow_state.clone());
+        let flow_state_consumer = Consumer::new(notifier, flow_state);
 
         // Comparing unsettled should be taken care of in `on_incoming_attach`
         let unsettled = Arc::new(RwLock::new(None));
@@ -161,7 +172,7 @@ where
 
         // Allocate link in session
         let input_handle = InputHandle::from(remote_attach.handle.clone());
-        let output_handle = super::session::allocate_incoming_link(
+        let output_handle = crate::session::allocate_incoming_link(
             &control,
             remote_attach.name.clone(),
             link_handle,
@@ -169,31 +180,41 @@ where
         )
         .await?;
 
-        let mut err = None;
+        let mut err = shared
+            .inspect_incoming_attach(&remote_attach)
+            .map(ReceiverAttachError::Rejected);
         // **the receiver is considered to hold the authoritative version of the target properties**,
-        let local_target = remote_attach
-            .target
-            .clone()
-            .map(|t| T::try_from(*t))
-            .transpose()
-            .map(|target| {
-                target.and_then(|mut t| {
-                    if matches!(t.is_dynamic(), Some(true)) {
-                        (self.on_dynamic_target)(t).map(|mut t| {
+        let local_target = match err {
+            // A refused link is attached without a local terminus
+            Some(_) => None,
+            None => remote_attach
+                .target
+                .clone()
+                .map(|t| T::try_from(*t))
+                .transpose()
+                .map(|target| {
+                    target.and_then(|mut t| {
+                        if matches!(t.is_dynamic(), Some(true)) {
+                            // The address of the created node MUST be set in the response
+                            (self.on_dynamic_target)(t)
+                                .filter(|t| t.has_address())
+                                .map(|mut t| {
+                                    *t.capabilities_mut() =
+                                        self.target_capabilities.clone().map(Into::into);
+                                    t
+                                })
+                        } else {
                             *t.capabilities_mut() =
                                 self.target_capabilities.clone().map(Into::into);
-                            t
-                        })
-                    } else {
-                        *t.capabilities_mut() = self.target_capabilities.clone().map(Into::into);
-                        Some(t)
-                    }
+                            Some(t)
+                        }
+                    })
                 })
-            })
-            .unwrap_or_else(|_| {
-                err = Some(ReceiverAttachError::CoordinatorIsNotImplemented);
-                None
-            });
+                .unwrap_or_else(|_| {
+                    err = Some(ReceiverAttachError::CoordinatorIsNotImplemented);
+                    None
+                }),
+        };
 
         let mut link = ReceiverLink::<T> {
             role: PhantomData,
@@ -205,9 +226,11 @@ where
             rcv_settle_mode,
             source: None,         // Will take value from incoming attach
             target: local_target, // Will take value from incoming attach
-            max_message_size: shared.max_message_size.unwrap_or(0),
+            max_message_size: shared.max_message_size,
             offered_capabilities: shared.offered_capabilities.clone(),
             desired_capabilities: shared.desired_capabilities.clone(),
+            required_capabilities: Vec::new(),
+            remote_offered_capabilities: None,
             flow_state: flow_state_consumer,
             unsettled,
         };
@@ -247,6 +270,9 @@ where
             outgoing,
             incoming: incoming_rx,
             incomplete_transfer: None,
+            skip_streamed_delivery: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self.incomplete_transfer_timeout,
         };
 
         if let CreditMode::Auto(credit) = inner.credit_mode {
@@ -14,6 +14,7 @@ use tokio::sync::{mpsc, Notify};
 use crate::{
     endpoint::{InputHandle, LinkAttach, LinkExt},
     link::{
+        delivery::DeliveryTagGenerator,
         sender::SenderInner,
         state::{LinkFlowState, LinkFlowStateInner, LinkState},
         LinkRelay, SenderAttachError, SenderLink,
@@ -116,7 +117,7 @@ where
 
         // Allocate link in session
         let input_handle = InputHandle::from(remote_attach.handle.clone());
-        let output_handle = super::session::allocate_incoming_link(
+        let output_handle = crate::session::allocate_incoming_link(
             &session.control,
             remote_attach.name.clone(),
             link_handle,
@@ -126,18 +127,26 @@ where
 
         // In this case, the sender is considered to hold the authoritative version of the
         // version of the source properties
-        let local_source = remote_attach.source.clone().and_then(|s| {
-            if s.dynamic {
-                (self.on_dynamic_source)(*s).map(|mut s| {
-                    s.capabilities = self.source_capabilities.clone().map(Into::into);
-                    s
-                })
-            } else {
-                let mut source = *s;
-                source.capabilities = self.source_capabilities.clone().map(Into::into);
-                Some(source)
-            }
-        });
+        let rejection = shared.inspect_incoming_attach(&remote_attach);
+        let local_source = remote_attach
+            .source
+            .clone()
+            .filter(|_| rejection.is_none())
+            .and_then(|s| {
+                if s.dynamic {
+                    // The address of the created node MUST be set in the response
+                    (self.on_dynamic_source)(*s)
+                        .filter(|s| s.address.is_some())
+                        .map(|mut s| {
+                            s.capabilities = self.source_capabilities.clone().map(Into::into);
+                            s
+                        })
+                } else {
+                    let mut source = *s;
+                    source.capabilities = self.source_capabilities.clone().map(Into::into);
+                    Some(source)
+                }
+            });
 
         let mut link = SenderLink::<Target> {
             role: PhantomData,
@@ -150,16 +159,25 @@ where
             rcv_settle_mode,
             source: local_source,
             target: None, // Will take value from incoming attach
-            max_message_size: shared.max_message_size.unwrap_or(0),
+            max_message_size: shared.max_message_size,
             offered_capabilities: shared.offered_capabilities.clone(),
             desired_capabilities: shared.desired_capabilities.clone(),
+            required_capabilities: Vec::new(),
+            remote_offered_capabilities: None,
             flow_state: flow_state_consumer,
             unsettled,
         };
 
         let outgoing = session.outgoing.clone();
 
-        match link.on_incoming_attach(remote_attach) {
+        // `on_incoming_attach` should always be evaluated
+        let result = link.on_incoming_attach(remote_attach);
+        let result = match rejection {
+            // A refused link is attached without a local terminus
+            Some(error) => Err(SenderAttachError::Rejected(error)),
+            None => result,
+        };
+        match result {
             Ok(_) => link.send_attach(&outgoing, &session.control, false).await?,
             Err(attach_error) => {
                 // Complete attach then detach should any error happen
@@ -189,6 +207,9 @@ where
             session: session.control.clone(),
             outgoing,
             incoming: incoming_rx,
+            unsettled_deliveries: session.unsettled_deliveries.clone(),
+            delivery_tag_generator: DeliveryTagGenerator::default(),
+            increment_delivery_count_on_resend: false,
         };
         Ok(Sender { inner })
     }
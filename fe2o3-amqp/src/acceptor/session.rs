@@ -1,12 +1,14 @@
 //! Session Listener
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use fe2o3_amqp_types::{
     definitions::{self, ConnectionError},
     performatives::{Attach, Begin, Detach, Disposition, End, Flow, Transfer},
     states::SessionState,
 };
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
 use crate::{
@@ -20,8 +22,7 @@ use crate::{
         self,
         engine::SessionEngine,
         frame::{SessionFrame, SessionIncomingItem, SessionOutgoingItem},
-        AllocLinkError, BeginError, Error, SessionHandle, SessionInnerError,
-        DEFAULT_SESSION_CONTROL_BUFFER_SIZE,
+        BeginError, Error, SessionHandle, SessionInnerError, DEFAULT_SESSION_CONTROL_BUFFER_SIZE,
     },
     util::Initialized,
     Payload,
@@ -45,6 +46,30 @@ impl ListenerSessionEndpoint for TxnSession<ListenerSession> {}
 
 type SessionBuilder = crate::session::Builder;
 
+type BeginHookFn = dyn Fn(&Begin) -> Result<(), definitions::Error> + Send + Sync;
+
+/// Callback that inspects the Begin of an incoming session before the session is accepted
+#[derive(Clone)]
+pub(crate) struct BeginHook(Arc<BeginHookFn>);
+
+impl BeginHook {
+    pub(crate) fn new(
+        hook: impl Fn(&Begin) -> Result<(), definitions::Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, begin: &Begin) -> Result<(), definitions::Error> {
+        (self.0)(begin)
+    }
+}
+
+impl std::fmt::Debug for BeginHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BeginHook").finish()
+    }
+}
+
 /// Type alias for listener session handle
 pub type ListenerSessionHandle = SessionHandle<mpsc::Receiver<Attach>>;
 
@@ -55,35 +80,6 @@ impl ListenerSessionHandle {
     }
 }
 
-pub(crate) async fn allocate_incoming_link(
-    control: &mpsc::Sender<SessionControl>,
-    link_name: String,
-    link_relay: LinkRelay<()>,
-    input_handle: InputHandle,
-) -> Result<OutputHandle, AllocLinkError> {
-    let (responder, resp_rx) = oneshot::channel();
-
-    control
-        .send(SessionControl::AllocateIncomingLink {
-            link_name,
-            link_relay,
-            input_handle,
-            responder,
-        })
-        .await
-        // The `SendError` could only happen when the receiving half is
-        // dropped, meaning the `SessionEngine::event_loop` has stopped.
-        // This would also mean the `Session` is Unmapped, and thus it
-        // may be treated as illegal state
-        .map_err(|_| AllocLinkError::IllegalSessionState)?;
-    resp_rx
-        .await
-        // The error could only occur when the sending half is dropped,
-        // indicating the `SessionEngine::even_loop` has stopped or
-        // unmapped. Thus it could be considered as illegal state
-        .map_err(|_| AllocLinkError::IllegalSessionState)?
-}
-
 /// An acceptor for incoming session
 ///
 /// This is simply a wrapper around the session builder since there is not
@@ -223,11 +219,16 @@ impl SessionAcceptor {
         incoming_session: IncomingSession,
         connection: &mut ListenerConnectionHandle,
     ) -> Result<ListenerSessionHandle, BeginError> {
+        let rejection = match &self.0.on_incoming_begin {
+            Some(hook) => hook.call(&incoming_session.begin).err(),
+            None => None,
+        };
         let local_state = SessionState::Unmapped;
         let (session_control_tx, session_control_rx) =
             mpsc::channel::<SessionControl>(DEFAULT_SESSION_CONTROL_BUFFER_SIZE);
         let (incoming_tx, incoming_rx) = mpsc::channel(self.0.buffer_size);
-        let (outgoing_tx, outgoing_rx) = mpsc::channel(self.0.buffer_size);
+        let outgoing_buffer_size = self.0.outgoing_buffer_size_or_default();
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(outgoing_buffer_size);
         let (link_listener_tx, link_listener_rx) = mpsc::channel(self.0.buffer_size);
 
         // create session in connection::Engine
@@ -258,6 +259,7 @@ impl SessionAcceptor {
             IncomingChannel(incoming_session.channel),
             incoming_session.begin,
         )?;
+        let handle_max = session.agreed_handle_max;
 
         let listener_session = ListenerSession {
             session,
@@ -276,13 +278,23 @@ impl SessionAcceptor {
             )
             .await?;
 
-        let handle = SessionHandle {
+        let mut handle = SessionHandle {
             is_ended: false,
             control: session_control_tx,
             engine_handle,
+            handle_max,
             outgoing: outgoing_tx,
+            outgoing_buffer_size,
             link_listener: link_listener_rx,
+            unsettled_deliveries: connection.unsettled_deliveries.clone(),
+            end_on_drop: None,
         };
+
+        // A session is refused by sending a Begin immediately followed by an End with the error
+        if let Some(error) = rejection {
+            let _ = handle.end_with_error(error.clone()).await;
+            return Err(BeginError::Rejected(error));
+        }
         Ok(handle)
     }
 
@@ -363,6 +375,10 @@ impl endpoint::Session for ListenerSession {
         self.session.outgoing_channel()
     }
 
+    fn agreed_handle_max(&self) -> u32 {
+        self.session.agreed_handle_max()
+    }
+
     fn allocate_link(
         &mut self,
         link_name: String,
@@ -385,6 +401,10 @@ impl endpoint::Session for ListenerSession {
         self.session.deallocate_link(output_handle)
     }
 
+    fn detach_all_links(&mut self, error: Option<definitions::Error>) {
+        self.session.detach_all_links(error)
+    }
+
     fn on_incoming_begin(
         &mut self,
         channel: IncomingChannel,
@@ -455,6 +475,14 @@ impl endpoint::Session for ListenerSession {
         self.session.on_incoming_transfer(transfer, payload).await
     }
 
+    fn consume_incoming_window(&mut self) -> Result<(), Self::Error> {
+        self.session.consume_incoming_window()
+    }
+
+    fn replenish_incoming_window(&mut self) -> Option<SessionFrame> {
+        self.session.replenish_incoming_window()
+    }
+
     fn on_incoming_disposition(
         &mut self,
         disposition: Disposition,
@@ -3,6 +3,9 @@
 
 pub const GS2_HEADER: &str = "n,,";
 
+/// GS2 header of a client that requires `tls-server-end-point` channel binding
+pub const GS2_HEADER_TLS_SERVER_END_POINT: &str = "p=tls-server-end-point,,";
+
 /// n: This attribute specifies the name of the user whose password is used for authentication
 pub const USERNAME_KEY: &str = "n=";
 
@@ -0,0 +1,171 @@
+//! `tls-server-end-point` channel binding (RFC 5929) for the SCRAM `-PLUS` mechanisms
+
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+
+use super::{
+    attributes::{GS2_HEADER, GS2_HEADER_TLS_SERVER_END_POINT},
+    hash,
+};
+
+const DER_SEQUENCE: u8 = 0x30;
+const DER_OID: u8 = 0x06;
+
+// 1.2.840.113549.1.1.x
+const PKCS1_PREFIX: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01];
+// 1.2.840.10045.4.x
+const ECDSA_PREFIX: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04];
+
+/// Channel binding used by the SCRAM client
+#[derive(Debug, Clone)]
+pub(crate) enum ChannelBinding {
+    /// The client does not support channel binding, ie. `gs2-cbind-flag` is `n`
+    None,
+
+    /// Hash of the TLS server certificate
+    TlsServerEndPoint(Vec<u8>),
+}
+
+impl ChannelBinding {
+    /// Computes the `tls-server-end-point` channel binding from the DER encoded server
+    /// certificate
+    ///
+    /// The certificate is hashed with the hash function of its signature algorithm, and SHA-256
+    /// is used if that is MD5 or SHA-1. `None` is returned if the signature algorithm is not
+    /// recognized.
+    pub(crate) fn tls_server_end_point(certificate: &[u8]) -> Option<Self> {
+        let hash = match signature_algorithm(certificate)? {
+            [prefix @ .., 0x04 | 0x05] if prefix == PKCS1_PREFIX => hash::<Sha256>(certificate),
+            [prefix @ .., 0x0b] if prefix == PKCS1_PREFIX => hash::<Sha256>(certificate),
+            [prefix @ .., 0x0c] if prefix == PKCS1_PREFIX => hash::<Sha384>(certificate),
+            [prefix @ .., 0x0d] if prefix == PKCS1_PREFIX => hash::<Sha512>(certificate),
+            [prefix @ .., 0x0e] if prefix == PKCS1_PREFIX => hash::<Sha224>(certificate),
+            [prefix @ .., 0x01] if prefix == ECDSA_PREFIX => hash::<Sha256>(certificate),
+            [prefix @ .., 0x03, 0x01] if prefix == ECDSA_PREFIX => hash::<Sha224>(certificate),
+            [prefix @ .., 0x03, 0x02] if prefix == ECDSA_PREFIX => hash::<Sha256>(certificate),
+            [prefix @ .., 0x03, 0x03] if prefix == ECDSA_PREFIX => hash::<Sha384>(certificate),
+            [prefix @ .., 0x03, 0x04] if prefix == ECDSA_PREFIX => hash::<Sha512>(certificate),
+            _ => return None,
+        };
+        Some(Self::TlsServerEndPoint(hash))
+    }
+
+    pub(crate) fn gs2_header(&self) -> &'static str {
+        match self {
+            ChannelBinding::None => GS2_HEADER,
+            ChannelBinding::TlsServerEndPoint(_) => GS2_HEADER_TLS_SERVER_END_POINT,
+        }
+    }
+
+    /// cbind-input := gs2-header [ cbind-data ]
+    pub(crate) fn cbind_input(&self) -> Vec<u8> {
+        let mut buf = self.gs2_header().as_bytes().to_vec();
+        if let ChannelBinding::TlsServerEndPoint(data) = self {
+            buf.extend_from_slice(data);
+        }
+        buf
+    }
+}
+
+/// Returns the OID of the `signatureAlgorithm` of an X.509 certificate
+///
+/// Certificate ::= SEQUENCE {
+///     tbsCertificate       TBSCertificate,
+///     signatureAlgorithm   AlgorithmIdentifier,
+///     signatureValue       BIT STRING }
+fn signature_algorithm(certificate: &[u8]) -> Option<&[u8]> {
+    let certificate = match der_element(certificate)? {
+        (DER_SEQUENCE, certificate, _) => certificate,
+        _ => return None,
+    };
+    let (_, _tbs_certificate, rest) = der_element(certificate)?;
+    let algorithm_identifier = match der_element(rest)? {
+        (DER_SEQUENCE, algorithm_identifier, _) => algorithm_identifier,
+        _ => return None,
+    };
+    match der_element(algorithm_identifier)? {
+        (DER_OID, oid, _) => Some(oid),
+        _ => None,
+    }
+}
+
+/// Returns the tag, the content and the remaining bytes of a DER element
+fn der_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, input) = input.split_first()?;
+    let (len, input) = match first {
+        0x00..=0x7f => (first as usize, input),
+        0x81..=0x84 => {
+            let num_bytes = (first & 0x7f) as usize;
+            if input.len() < num_bytes {
+                return None;
+            }
+            let (len_bytes, input) = input.split_at(num_bytes);
+            let len = len_bytes
+                .iter()
+                .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+            (len, input)
+        }
+        _ => return None,
+    };
+    if input.len() < len {
+        return None;
+    }
+    let (content, rest) = input.split_at(len);
+    Some((tag, content, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Sha256, Sha384};
+
+    use super::{hash, ChannelBinding};
+
+    /// A certificate that only has the structure needed to find the signature algorithm
+    fn certificate(signature_algorithm: &[u8]) -> Vec<u8> {
+        let mut algorithm_identifier = vec![0x06, signature_algorithm.len() as u8];
+        algorithm_identifier.extend_from_slice(signature_algorithm);
+        algorithm_identifier.extend_from_slice(&[0x05, 0x00]);
+
+        let mut content = vec![0x30, 0x03, 0x02, 0x01, 0x02];
+        content.push(0x30);
+        content.push(algorithm_identifier.len() as u8);
+        content.extend_from_slice(&algorithm_identifier);
+        content.extend_from_slice(&[0x03, 0x02, 0x00, 0xff]);
+
+        let mut certificate = vec![0x30, 0x81, content.len() as u8];
+        certificate.extend_from_slice(&content);
+        certificate
+    }
+
+    #[test]
+    fn tls_server_end_point_uses_signature_hash() {
+        // sha384WithRSAEncryption
+        let certificate = certificate(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c]);
+        match ChannelBinding::tls_server_end_point(&certificate) {
+            Some(ChannelBinding::TlsServerEndPoint(data)) => {
+                assert_eq!(data, hash::<Sha384>(&certificate))
+            }
+            _ => panic!("Expecting tls-server-end-point"),
+        }
+    }
+
+    #[test]
+    fn tls_server_end_point_replaces_sha1_with_sha256() {
+        // ecdsa-with-SHA1
+        let certificate = certificate(&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x01]);
+        match ChannelBinding::tls_server_end_point(&certificate) {
+            Some(ChannelBinding::TlsServerEndPoint(data)) => {
+                assert_eq!(data, hash::<Sha256>(&certificate))
+            }
+            _ => panic!("Expecting tls-server-end-point"),
+        }
+    }
+
+    #[test]
+    fn tls_server_end_point_rejects_unknown_algorithm() {
+        // Ed25519
+        let certificate = certificate(&[0x2b, 0x65, 0x70]);
+        assert!(ChannelBinding::tls_server_end_point(&certificate).is_none());
+        assert!(ChannelBinding::tls_server_end_point(&[0x30, 0x05]).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use bytes::Bytes;
 
-use super::{generate_nonce, ScramErrorKind, ScramVersion};
+use super::{channel_binding::ChannelBinding, generate_nonce, ScramErrorKind, ScramVersion};
 
 #[derive(Debug, Clone)]
 enum ScramClientState {
@@ -20,6 +20,7 @@ pub(crate) struct ScramClient {
     username: String,
     password: String,
     scram: ScramVersion,
+    channel_binding: ChannelBinding,
     state: ScramClientState,
 }
 
@@ -33,17 +34,28 @@ impl ScramClient {
             username: username.into(),
             password: password.into(),
             scram: scram_version,
+            channel_binding: ChannelBinding::None,
             state: ScramClientState::Initial,
         }
     }
 
+    pub fn channel_binding(&self) -> &ChannelBinding {
+        &self.channel_binding
+    }
+
+    pub fn set_channel_binding(&mut self, channel_binding: ChannelBinding) {
+        self.channel_binding = channel_binding;
+    }
+
     pub fn compute_client_first_message(&mut self) -> Bytes {
         use base64::Engine;
 
         let nonce = base64::engine::general_purpose::STANDARD.encode(generate_nonce());
-        let (client_first_message, client_first_message_bare) = self
-            .scram
-            .client_first_message(self.username.as_bytes(), nonce.as_bytes());
+        let (client_first_message, client_first_message_bare) = self.scram.client_first_message(
+            self.username.as_bytes(),
+            nonce.as_bytes(),
+            &self.channel_binding,
+        );
         self.state = ScramClientState::ClientFirstSent {
             client_nonce: nonce,
             client_first_message_bare,
@@ -65,6 +77,7 @@ impl ScramClient {
                     &self.password,
                     server_first,
                     client_first_message_bare,
+                    &self.channel_binding,
                 )?;
                 self.state = ScramClientState::ClientFinalSent { server_signature };
                 Ok(client_final)
@@ -63,6 +63,10 @@ pub enum ScramErrorKind {
     /// Server signature mismatch
     #[error("Server signature mismatch")]
     ServerSignatureMismatch,
+
+    /// Channel binding data is required by a `-PLUS` mechanism but is not available
+    #[error("Channel binding data is not available")]
+    ChannelBindingUnavailable,
 }
 
 impl From<XorLengthMismatch> for ScramErrorKind {
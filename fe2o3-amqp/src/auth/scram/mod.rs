@@ -12,12 +12,14 @@ use sha1::Sha1;
 use sha2::{Sha256, Sha512};
 
 use attributes::{
-    CHANNEL_BINDING_KEY, GS2_HEADER, ITERATION_COUNT_KEY, NONCE_KEY, PROOF_KEY, RESERVED_MEXT,
-    SALT_KEY, USERNAME_KEY, VERIFIER_KEY,
+    CHANNEL_BINDING_KEY, ITERATION_COUNT_KEY, NONCE_KEY, PROOF_KEY, RESERVED_MEXT, SALT_KEY,
+    USERNAME_KEY, VERIFIER_KEY,
 };
+use channel_binding::ChannelBinding;
 use error::XorLengthMismatch;
 
 mod attributes;
+pub(crate) mod channel_binding;
 pub(crate) mod client;
 mod error;
 pub use error::*;
@@ -98,9 +100,15 @@ impl ScramVersion {
     }
 
     /// Returns (client_first_message, client_first_message_bare)
-    pub(crate) fn client_first_message(&self, username: &[u8], nonce: &[u8]) -> (Bytes, Bytes) {
+    pub(crate) fn client_first_message(
+        &self,
+        username: &[u8],
+        nonce: &[u8],
+        channel_binding: &ChannelBinding,
+    ) -> (Bytes, Bytes) {
+        let gs2_header = channel_binding.gs2_header();
         let mut bytes = BytesMut::new();
-        bytes.put_slice(GS2_HEADER.as_bytes());
+        bytes.put_slice(gs2_header.as_bytes());
 
         bytes.put_slice(USERNAME_KEY.as_bytes());
         bytes.put_slice(username);
@@ -111,7 +119,7 @@ impl ScramVersion {
         bytes.put_slice(nonce);
 
         let client_first_message = bytes.freeze();
-        let gs2_header_len = GS2_HEADER.as_bytes().len();
+        let gs2_header_len = gs2_header.len();
         let client_first_message_bare = client_first_message.slice(gs2_header_len..);
         (client_first_message, client_first_message_bare)
     }
@@ -170,6 +178,7 @@ impl ScramVersion {
         password: &str,
         server_first: &str,
         client_first_message_bare: &[u8],
+        channel_binding: &ChannelBinding,
     ) -> Result<(Vec<u8>, Vec<u8>), ScramErrorKind> {
         use base64::Engine;
 
@@ -212,7 +221,8 @@ impl ScramVersion {
         // ServerSignature := HMAC(ServerKey, AuthMessage)
 
         let salted_password = self.compute_salted_password(password, &salt[..], iterations)?;
-        let client_final_message_without_proof = without_proof(client_server_nonce);
+        let client_final_message_without_proof =
+            without_proof(client_server_nonce, &channel_binding.cbind_input());
         let auth_message = auth_message(
             client_first_message_bare,
             server_first.as_bytes(),
@@ -313,19 +323,21 @@ fn auth_message(
     buf
 }
 
-fn without_proof(client_server_nonce: &str) -> Vec<u8> {
+fn without_proof(client_server_nonce: &str, cbind_input: &[u8]) -> Vec<u8> {
     use base64::Engine;
 
-    let encoded_gs2_header = base64::engine::general_purpose::STANDARD.encode(GS2_HEADER).into_bytes();
+    let encoded_cbind_input = base64::engine::general_purpose::STANDARD
+        .encode(cbind_input)
+        .into_bytes();
     let total_len = CHANNEL_BINDING_KEY.len()
-        + encoded_gs2_header.len()
+        + encoded_cbind_input.len()
         + 1
         + NONCE_KEY.len()
         + client_server_nonce.len();
     let mut buf = Vec::with_capacity(total_len);
 
     buf.put_slice(CHANNEL_BINDING_KEY.as_bytes());
-    buf.put_slice(&encoded_gs2_header);
+    buf.put_slice(&encoded_cbind_input);
 
     buf.put_u8(b',');
 
@@ -382,7 +394,7 @@ fn generate_nonce() -> [u8; 32] {
 #[allow(unused)]
 mod tests {
     use super::attributes::{GS2_HEADER, NONCE_KEY, SALT_KEY};
-    use super::{ScramCredentialProvider, ScramVersion, StoredPassword};
+    use super::{ChannelBinding, ScramCredentialProvider, ScramVersion, StoredPassword};
 
     struct TestScramCredential {
         scram_version: ScramVersion,
@@ -486,6 +498,46 @@ mod tests {
         pub(super) static VERSION: ScramVersion = ScramVersion::Sha512;
     }
 
+    /// RFC 7677 test vectors with `tls-server-end-point` channel binding data
+    mod scram_sha256_plus {
+        use super::super::ScramVersion;
+
+        pub(super) static TEST_USERNAME: &str = "user";
+        pub(super) static TEST_PASSWORD: &str = "pencil";
+        pub(super) static CLIENT_NONCE: &str = "rOprNGfwEbeRWgbNEkqO";
+        pub(super) static EXPECTED_CLIENT_INITIAL_RESPONSE: &str =
+            "p=tls-server-end-point,,n=user,r=rOprNGfwEbeRWgbNEkqO";
+        pub(super) static SERVER_FIRST_MESSAGE: &str =
+            "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096";
+        pub(super) static EXPECTED_CLIENT_FINAL_MESSAGE: &str = "c=cD10bHMtc2VydmVyLWVuZC1wb2ludCwsAAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=nY1Wus9a+gM2DrbQ1msXFgyhW6KM5ktOxWiU+/P/EGY=";
+        pub(super) static SERVER_FINAL_MESSAGE: &str =
+            "v=RwppMGddhz/J0lFYaRReBjXcQeNUFP5Qc76Lo5Exrig=";
+        pub(super) static VERSION: ScramVersion = ScramVersion::Sha256;
+
+        pub(super) fn cbind_data() -> Vec<u8> {
+            (0u8..32).collect()
+        }
+    }
+
+    /// RFC 7677 style test vectors for SHA-512 with `tls-server-end-point` channel binding data
+    mod scram_sha512_plus {
+        use super::super::ScramVersion;
+
+        pub(super) static TEST_USERNAME: &str = "user";
+        pub(super) static TEST_PASSWORD: &str = "pencil";
+        pub(super) static CLIENT_NONCE: &str = "rOprNGfwEbeRWgbNEkqO";
+        pub(super) static EXPECTED_CLIENT_INITIAL_RESPONSE: &str =
+            "p=tls-server-end-point,,n=user,r=rOprNGfwEbeRWgbNEkqO";
+        pub(super) static SERVER_FIRST_MESSAGE: &str = "r=rOprNGfwEbeRWgbNEkqO02431b08-2f89-4bad-a4e6-80c0564ec865,s=Yin2FuHTt/M0kJWb0t9OI32n2VmOGi3m+JfjOvuDF88=,i=4096";
+        pub(super) static EXPECTED_CLIENT_FINAL_MESSAGE: &str = "c=cD10bHMtc2VydmVyLWVuZC1wb2ludCwsAAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4vMDEyMzQ1Njc4OTo7PD0+Pw==,r=rOprNGfwEbeRWgbNEkqO02431b08-2f89-4bad-a4e6-80c0564ec865,p=CSreeDSgF62weIYG5ftinIjQzj+xjIgnh3jHt763c66M5tFsNUnSI5OP/KxnEDnWDBQG9RfZ/2BCHE9pXFOyIA==";
+        pub(super) static SERVER_FINAL_MESSAGE: &str = "v=eVibk3f8//dQC3bw/wjU2VgDmB+a7LkXQl9pi9mPeBDP+MaCTNkhKvld50OmE7ARtPbiJtz7BAhTHU5yTCbYjQ==";
+        pub(super) static VERSION: ScramVersion = ScramVersion::Sha512;
+
+        pub(super) fn cbind_data() -> Vec<u8> {
+            (0u8..64).collect()
+        }
+    }
+
     fn get_base64_server_nonce_from_server_first_message<'a>(
         server_first_message: &'a str,
         client_nonce: &'a str,
@@ -508,8 +560,11 @@ mod tests {
     fn test_sasl_scram_sha1_client() {
         use scram_sha1::*;
 
-        let (client_first_message, client_first_message_bare) =
-            VERSION.client_first_message(TEST_USERNAME.as_bytes(), CLIENT_NONCE.as_bytes());
+        let (client_first_message, client_first_message_bare) = VERSION.client_first_message(
+            TEST_USERNAME.as_bytes(),
+            CLIENT_NONCE.as_bytes(),
+            &ChannelBinding::None,
+        );
         assert_eq!(
             client_first_message,
             EXPECTED_CLIENT_INITIAL_RESPONSE.as_bytes()
@@ -521,6 +576,7 @@ mod tests {
                 TEST_PASSWORD,
                 SERVER_FIRST_MESSAGE,
                 &client_first_message_bare,
+                &ChannelBinding::None,
             )
             .unwrap();
         assert_eq!(client_final, EXPECTED_CLIENT_FINAL_MESSAGE.as_bytes());
@@ -533,8 +589,11 @@ mod tests {
     fn test_sasl_scram_sha256_client() {
         use scram_sha256::*;
 
-        let (client_first_message, client_first_message_bare) =
-            VERSION.client_first_message(TEST_USERNAME.as_bytes(), CLIENT_NONCE.as_bytes());
+        let (client_first_message, client_first_message_bare) = VERSION.client_first_message(
+            TEST_USERNAME.as_bytes(),
+            CLIENT_NONCE.as_bytes(),
+            &ChannelBinding::None,
+        );
         assert_eq!(
             client_first_message,
             EXPECTED_CLIENT_INITIAL_RESPONSE.as_bytes()
@@ -546,6 +605,7 @@ mod tests {
                 TEST_PASSWORD,
                 SERVER_FIRST_MESSAGE,
                 &client_first_message_bare,
+                &ChannelBinding::None,
             )
             .unwrap();
         assert_eq!(client_final, EXPECTED_CLIENT_FINAL_MESSAGE.as_bytes());
@@ -558,8 +618,11 @@ mod tests {
     fn test_sasl_scram_sha512_client() {
         use scram_sha512::*;
 
-        let (client_first_message, client_first_message_bare) =
-            VERSION.client_first_message(TEST_USERNAME.as_bytes(), CLIENT_NONCE.as_bytes());
+        let (client_first_message, client_first_message_bare) = VERSION.client_first_message(
+            TEST_USERNAME.as_bytes(),
+            CLIENT_NONCE.as_bytes(),
+            &ChannelBinding::None,
+        );
         assert_eq!(
             client_first_message,
             EXPECTED_CLIENT_INITIAL_RESPONSE.as_bytes()
@@ -571,6 +634,67 @@ mod tests {
                 TEST_PASSWORD,
                 SERVER_FIRST_MESSAGE,
                 &client_first_message_bare,
+                &ChannelBinding::None,
+            )
+            .unwrap();
+        assert_eq!(client_final, EXPECTED_CLIENT_FINAL_MESSAGE.as_bytes());
+        assert!(VERSION
+            .validate_server_final(SERVER_FINAL_MESSAGE.as_bytes(), &server_signature)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_sasl_scram_sha256_plus_client() {
+        use scram_sha256_plus::*;
+
+        let channel_binding = ChannelBinding::TlsServerEndPoint(cbind_data());
+        let (client_first_message, client_first_message_bare) = VERSION.client_first_message(
+            TEST_USERNAME.as_bytes(),
+            CLIENT_NONCE.as_bytes(),
+            &channel_binding,
+        );
+        assert_eq!(
+            client_first_message,
+            EXPECTED_CLIENT_INITIAL_RESPONSE.as_bytes()
+        );
+
+        let (client_final, server_signature) = VERSION
+            .compute_client_final_message(
+                CLIENT_NONCE,
+                TEST_PASSWORD,
+                SERVER_FIRST_MESSAGE,
+                &client_first_message_bare,
+                &channel_binding,
+            )
+            .unwrap();
+        assert_eq!(client_final, EXPECTED_CLIENT_FINAL_MESSAGE.as_bytes());
+        assert!(VERSION
+            .validate_server_final(SERVER_FINAL_MESSAGE.as_bytes(), &server_signature)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_sasl_scram_sha512_plus_client() {
+        use scram_sha512_plus::*;
+
+        let channel_binding = ChannelBinding::TlsServerEndPoint(cbind_data());
+        let (client_first_message, client_first_message_bare) = VERSION.client_first_message(
+            TEST_USERNAME.as_bytes(),
+            CLIENT_NONCE.as_bytes(),
+            &channel_binding,
+        );
+        assert_eq!(
+            client_first_message,
+            EXPECTED_CLIENT_INITIAL_RESPONSE.as_bytes()
+        );
+
+        let (client_final, server_signature) = VERSION
+            .compute_client_final_message(
+                CLIENT_NONCE,
+                TEST_PASSWORD,
+                SERVER_FIRST_MESSAGE,
+                &client_first_message_bare,
+                &channel_binding,
             )
             .unwrap();
         assert_eq!(client_final, EXPECTED_CLIENT_FINAL_MESSAGE.as_bytes());
@@ -582,11 +706,13 @@ mod tests {
     #[cfg(feature = "acceptor")]
     #[test]
     fn test_sasl_scram_sha1_server() {
-        use scram_sha1::*;
         use base64::Engine;
+        use scram_sha1::*;
 
         let base64_salt = get_base64_salt_from_server_first_message(SERVER_FIRST_MESSAGE);
-        let salt = base64::engine::general_purpose::STANDARD.decode(base64_salt).unwrap();
+        let salt = base64::engine::general_purpose::STANDARD
+            .decode(base64_salt)
+            .unwrap();
         let base64_server_nonce =
             get_base64_server_nonce_from_server_first_message(SERVER_FIRST_MESSAGE, CLIENT_NONCE);
         let client_server_nonce = format!("{}{}", CLIENT_NONCE, base64_server_nonce);
@@ -629,11 +755,13 @@ mod tests {
     #[cfg(feature = "acceptor")]
     #[test]
     fn test_sasl_scram_sha256_server() {
-        use scram_sha256::*;
         use base64::Engine;
+        use scram_sha256::*;
 
         let base64_salt = get_base64_salt_from_server_first_message(SERVER_FIRST_MESSAGE);
-        let salt = base64::engine::general_purpose::STANDARD.decode(base64_salt).unwrap();
+        let salt = base64::engine::general_purpose::STANDARD
+            .decode(base64_salt)
+            .unwrap();
         let base64_server_nonce =
             get_base64_server_nonce_from_server_first_message(SERVER_FIRST_MESSAGE, CLIENT_NONCE);
         let client_server_nonce = format!("{}{}", CLIENT_NONCE, base64_server_nonce);
@@ -676,11 +804,13 @@ mod tests {
     #[cfg(feature = "acceptor")]
     #[test]
     fn test_sasl_scram_sha512_server() {
-        use scram_sha512::*;
         use base64::Engine;
+        use scram_sha512::*;
 
         let base64_salt = get_base64_salt_from_server_first_message(SERVER_FIRST_MESSAGE);
-        let salt = base64::engine::general_purpose::STANDARD.decode(base64_salt).unwrap();
+        let salt = base64::engine::general_purpose::STANDARD
+            .decode(base64_salt)
+            .unwrap();
         let base64_server_nonce =
             get_base64_server_nonce_from_server_first_message(SERVER_FIRST_MESSAGE, CLIENT_NONCE);
         let client_server_nonce = format!("{}{}", CLIENT_NONCE, base64_server_nonce);
@@ -1,6 +1,6 @@
 use bytes::Bytes;
 
-use super::*;
+use super::{attributes::GS2_HEADER, *};
 
 impl ScramVersion {
     fn compute_server_first_message<'a, C>(
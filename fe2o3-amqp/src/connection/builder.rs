@@ -1,6 +1,6 @@
 //! Builder for [`crate::Connection`]
 
-use std::{io, marker::PhantomData, time::Duration};
+use std::{io, marker::PhantomData, sync::Arc, time::Duration};
 
 use fe2o3_amqp_types::{
     definitions::{Fields, IetfLanguageTag, Milliseconds, MIN_MAX_FRAME_SIZE},
@@ -14,6 +14,7 @@ use tokio::{
     sync::mpsc::{self},
 };
 use tokio_util::codec::{FramedRead, FramedWrite};
+use uuid::Uuid;
 
 cfg_not_wasm32! {
     use std::convert::TryInto;
@@ -25,7 +26,7 @@ use crate::{
     connection::{Connection, ConnectionState},
     control::ConnectionControl,
     frames::sasl,
-    sasl_profile::{Negotiation, SaslProfile},
+    sasl_profile::{Negotiation, SaslProfile, SaslTrace},
     session::frame::SessionFrame,
     transport::Transport,
     transport::{error::NegotiationError, protocol_header::ProtocolHeaderCodec},
@@ -33,8 +34,8 @@ use crate::{
 };
 
 use super::{
-    engine::ConnectionEngine, ConnectionHandle, OpenError, DEFAULT_CHANNEL_MAX,
-    DEFAULT_MAX_FRAME_SIZE,
+    engine::ConnectionEngine, ConnectionHandle, ConnectionMetrics, FrameEvent, FrameHook,
+    OpenError, StateHook, UnsettledDeliveries, DEFAULT_CHANNEL_MAX, DEFAULT_MAX_FRAME_SIZE,
 };
 
 #[cfg(feature = "tracing")]
@@ -90,6 +91,10 @@ pub struct Builder<'a, Mode, Tls> {
     pub channel_max: ChannelMax,
 
     /// Idle time-out
+    ///
+    /// The connection is closed with `amqp:connection:forced` if nothing is received from the
+    /// remote peer within this duration. Half of this value is advertised to the remote peer in
+    /// the Open frame.
     pub idle_time_out: Option<Milliseconds>,
 
     /// Locales available for outgoing text
@@ -139,12 +144,39 @@ pub struct Builder<'a, Mode, Tls> {
     /// actual TLS handshake
     pub alt_tls_estab: bool,
 
+    /// Whether the SASL exchange should be captured
+    ///
+    /// If enabled, a failed SASL negotiation will be returned as [`OpenError::SaslTraced`] with
+    /// the captured frames (secrets redacted)
+    pub sasl_trace: bool,
+
+    /// Maximum number of unsettled outgoing deliveries across all sessions and links
+    ///
+    /// Sending an unsettled delivery will wait until another delivery is settled once this limit
+    /// is reached. Incoming deliveries are not counted. `None` or `Some(0)` means no limit.
+    pub max_unsettled_deliveries: Option<usize>,
+
+    /// Hook called for every frame sent or received after the AMQP protocol header exchange
+    pub frame_hook: Option<FrameHook>,
+
+    /// Metrics that count the frames and bytes sent or received after the AMQP protocol header
+    /// exchange
+    pub metrics: Option<Arc<dyn ConnectionMetrics>>,
+
+    /// Timeout of the best-effort close that is performed when the connection handle is dropped
+    /// without being closed. `None` means the close is only attempted if it can be queued
+    /// immediately
+    pub close_on_drop: Option<Duration>,
+
+    /// Hook called on every local state transition
+    pub(crate) state_hook: Option<StateHook>,
+
     // type state marker
     marker: PhantomData<Mode>,
 }
 
-impl<'a, Tls> From<Builder<'a, mode::ConnectorWithId, Tls>> for Open {
-    fn from(builder: Builder<'a, mode::ConnectorWithId, Tls>) -> Self {
+impl<'a, Mode, Tls> From<Builder<'a, Mode, Tls>> for Open {
+    fn from(builder: Builder<'a, Mode, Tls>) -> Self {
         let max_frame_size = MaxFrameSize(std::cmp::max(
             MIN_MAX_FRAME_SIZE as u32,
             builder.max_frame_size.0,
@@ -183,6 +215,12 @@ impl<'a, Mode: std::fmt::Debug> std::fmt::Debug for Builder<'a, Mode, ()> {
             .field("tls_connector", &"()")
             .field("buffer_size", &self.buffer_size)
             .field("sasl_profile", &self.sasl_profile)
+            .field("sasl_trace", &self.sasl_trace)
+            .field("max_unsettled_deliveries", &self.max_unsettled_deliveries)
+            .field("frame_hook", &self.frame_hook)
+            .field("metrics", &self.metrics)
+            .field("close_on_drop", &self.close_on_drop)
+            .field("state_hook", &self.state_hook)
             .field("marker", &self.marker)
             .finish()
     }
@@ -207,6 +245,12 @@ impl<'a, Mode: std::fmt::Debug> std::fmt::Debug for Builder<'a, Mode, tokio_rust
             .field("tls_connector", &"tokio_rustls::TlsConnector")
             .field("buffer_size", &self.buffer_size)
             .field("sasl_profile", &self.sasl_profile)
+            .field("sasl_trace", &self.sasl_trace)
+            .field("max_unsettled_deliveries", &self.max_unsettled_deliveries)
+            .field("frame_hook", &self.frame_hook)
+            .field("metrics", &self.metrics)
+            .field("close_on_drop", &self.close_on_drop)
+            .field("state_hook", &self.state_hook)
             .field("marker", &self.marker)
             .finish()
     }
@@ -233,6 +277,12 @@ impl<'a, Mode: std::fmt::Debug> std::fmt::Debug
             .field("tls_connector", &"tokio_native_tls::TlsConnector")
             .field("buffer_size", &self.buffer_size)
             .field("sasl_profile", &self.sasl_profile)
+            .field("sasl_trace", &self.sasl_trace)
+            .field("max_unsettled_deliveries", &self.max_unsettled_deliveries)
+            .field("frame_hook", &self.frame_hook)
+            .field("metrics", &self.metrics)
+            .field("close_on_drop", &self.close_on_drop)
+            .field("state_hook", &self.state_hook)
             .field("marker", &self.marker)
             .finish()
     }
@@ -246,9 +296,11 @@ impl<'a, Mode> Default for Builder<'a, Mode, ()> {
 
 impl<'a, Mode> Builder<'a, Mode, ()> {
     /// Creates a new builder for [`crate::Connection`]
+    ///
+    /// The container-id defaults to a randomly generated UUID if none is set.
     pub fn new() -> Self {
         Self {
-            container_id: String::new(),
+            container_id: Uuid::new_v4().to_string(),
             hostname: None,
             scheme: "amqp", // Assume non-TLS by default
             domain: None,
@@ -267,6 +319,12 @@ impl<'a, Mode> Builder<'a, Mode, ()> {
             buffer_size: DEFAULT_OUTGOING_BUFFER_SIZE,
             sasl_profile: None,
             alt_tls_estab: false,
+            sasl_trace: false,
+            max_unsettled_deliveries: None,
+            frame_hook: None,
+            metrics: None,
+            close_on_drop: None,
+            state_hook: None,
 
             marker: PhantomData,
         }
@@ -275,12 +333,32 @@ impl<'a, Mode> Builder<'a, Mode, ()> {
 
 impl<'a, Tls> Builder<'a, mode::ConnectorNoId, Tls> {
     /// The id of the source container
+    ///
+    /// An empty id is rejected with [`OpenError::EmptyContainerId`] when the connection is opened.
     pub fn container_id(self, id: impl Into<String>) -> Builder<'a, mode::ConnectorWithId, Tls> {
         // In Rust, it’s more common to pass slices as arguments
         // rather than vectors when you just want to provide read access.
         // The same goes for String and &str.
+        let mut builder = self.into_mode();
+        builder.container_id = id.into();
+        builder
+    }
+
+    /// Sets the id of the source container to `"<prefix>-<uuid>"` where `<uuid>` is randomly
+    /// generated
+    pub fn container_id_prefix(
+        self,
+        prefix: impl AsRef<str>,
+    ) -> Builder<'a, mode::ConnectorWithId, Tls> {
+        let id = format!("{}-{}", prefix.as_ref(), Uuid::new_v4());
+        self.container_id(id)
+    }
+}
+
+impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
+    fn into_mode<M>(self) -> Builder<'a, M, Tls> {
         Builder {
-            container_id: id.into(),
+            container_id: self.container_id,
             hostname: self.hostname,
             scheme: self.scheme,
             domain: self.domain,
@@ -299,6 +377,12 @@ impl<'a, Tls> Builder<'a, mode::ConnectorNoId, Tls> {
             buffer_size: self.buffer_size,
             sasl_profile: self.sasl_profile,
             alt_tls_estab: self.alt_tls_estab,
+            sasl_trace: self.sasl_trace,
+            max_unsettled_deliveries: self.max_unsettled_deliveries,
+            frame_hook: self.frame_hook,
+            metrics: self.metrics,
+            close_on_drop: self.close_on_drop,
+            state_hook: self.state_hook,
 
             marker: PhantomData,
         }
@@ -348,11 +432,46 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
             buffer_size: self.buffer_size,
             sasl_profile: self.sasl_profile,
             alt_tls_estab: self.alt_tls_estab,
+            sasl_trace: self.sasl_trace,
+            max_unsettled_deliveries: self.max_unsettled_deliveries,
+            frame_hook: self.frame_hook,
+            metrics: self.metrics,
+            close_on_drop: self.close_on_drop,
+            state_hook: self.state_hook,
 
             marker: PhantomData,
         }
     }
 
+    /// Set the TLS connector with a `rustls::ClientConfig`
+    ///
+    /// This is a shortcut for [`rustls_connector`](#method.rustls_connector) with a
+    /// `tokio_rustls::TlsConnector` built from the config, which allows using a custom root store,
+    /// a client certificate for mutual TLS, or ALPN protocols. The TLS protocol header is still
+    /// exchanged before the TLS handshake unless
+    /// [`alt_tls_establishment`](#method.alt_tls_establishment) is set.
+    ///
+    /// ```rust, ignore
+    /// let config = rustls::ClientConfig::builder()
+    ///     .with_safe_defaults()
+    ///     .with_root_certificates(root_cert_store)
+    ///     .with_single_cert(client_cert_chain, client_key)?;
+    ///
+    /// let connection = Connection::builder()
+    ///     .container_id("connection-1")
+    ///     .rustls_config(config)
+    ///     .open("amqps://localhost:5671")
+    ///     .await?;
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+    #[cfg(feature = "rustls")]
+    pub fn rustls_config(
+        self,
+        config: impl Into<std::sync::Arc<librustls::ClientConfig>>,
+    ) -> Builder<'a, Mode, tokio_rustls::TlsConnector> {
+        self.rustls_connector(tokio_rustls::TlsConnector::from(config.into()))
+    }
+
     /// Alias for [`native_tls_connector`](#method.native_tls_connector) if only `"native-tls"` is
     /// enabled.
     #[cfg_attr(
@@ -413,6 +532,12 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
             buffer_size: self.buffer_size,
             sasl_profile: self.sasl_profile,
             alt_tls_estab: self.alt_tls_estab,
+            sasl_trace: self.sasl_trace,
+            max_unsettled_deliveries: self.max_unsettled_deliveries,
+            frame_hook: self.frame_hook,
+            metrics: self.metrics,
+            close_on_drop: self.close_on_drop,
+            state_hook: self.state_hook,
 
             marker: PhantomData,
         }
@@ -468,6 +593,10 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
     }
 
     /// Idle time-out
+    ///
+    /// The connection is closed with `amqp:connection:forced` if nothing is received from the
+    /// remote peer within this duration. Half of this value is advertised to the remote peer in
+    /// the Open frame.
     pub fn idle_time_out(mut self, idle_time_out: impl Into<Milliseconds>) -> Self {
         self.idle_time_out = Some(idle_time_out.into());
         self
@@ -534,6 +663,10 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
     }
 
     /// Connection properties
+    ///
+    /// The properties are sent in the outgoing Open frame, eg. to advertise the `product`,
+    /// `version` and `platform` of the client. The properties sent by the remote peer can be read
+    /// with [`ConnectionHandle::remote_open()`](crate::connection::ConnectionHandle::remote_open).
     pub fn properties(mut self, properties: Fields) -> Self {
         self.properties = Some(properties);
         self
@@ -563,23 +696,121 @@ impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
         self.alt_tls_estab = value;
         self
     }
+
+    /// Capture the SASL exchange for debugging
+    ///
+    /// If the SASL negotiation fails, the captured frames (with the PLAIN password and the SCRAM
+    /// client proof redacted, and the payloads of other mechanisms except ANONYMOUS redacted
+    /// entirely) are returned with [`OpenError::SaslTraced`]
+    pub fn sasl_trace(mut self, value: bool) -> Self {
+        self.sasl_trace = value;
+        self
+    }
+
+    /// Limit the number of unsettled outgoing deliveries across all sessions and links
+    ///
+    /// Sending an unsettled delivery will wait until another delivery is settled once this limit
+    /// is reached. Incoming deliveries are not counted, and receiving is not throttled. A value of
+    /// zero means no limit.
+    pub fn max_unsettled_deliveries(mut self, max: usize) -> Self {
+        self.max_unsettled_deliveries = Some(max);
+        self
+    }
+
+    /// Set a hook that is called for every performative sent or received on the connection
+    ///
+    /// The hook receives a [`FrameEvent`] with the direction, the channel and a borrow of the
+    /// frame body. It is called from the connection event loop and should return quickly.
+    pub fn on_frame(mut self, hook: impl Fn(&FrameEvent<'_>) + Send + Sync + 'static) -> Self {
+        self.frame_hook = Some(FrameHook::new(hook));
+        self
+    }
+
+    /// Set the metrics that count the frames, bytes and errors of the connection
+    ///
+    /// The methods of [`ConnectionMetrics`] are called from the connection event loop. Nothing
+    /// is counted if this is not set
+    pub fn metrics(mut self, metrics: Arc<dyn ConnectionMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Close the connection on a best-effort basis if the handle is dropped without being closed
+    ///
+    /// The Close frame is sent from a detached task that gives up after `timeout`, so that the
+    /// remote peer sees a clean shutdown instead of an abrupt disconnect. Without this option,
+    /// dropping the handle only attempts the close if it can be queued immediately
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn close_on_drop(mut self, timeout: Duration) -> Self {
+        self.close_on_drop = Some(timeout);
+        self
+    }
+
+    /// Set a hook that is called on every local state transition of the connection
+    ///
+    /// The hook is first called with [`ConnectionState::Start`] and then with
+    /// [`ConnectionState::HeaderExchange`] once the AMQP protocol headers are exchanged. Every
+    /// following transition (eg. `OpenSent`, `Opened`, `CloseSent`, `Discarding` and `End`) is
+    /// reported as it happens. Once the connection is open, the current state is also available
+    /// with [`ConnectionHandle::state()`] and [`ConnectionHandle::state_watcher()`].
+    pub fn on_state_change(
+        mut self,
+        hook: impl Fn(&ConnectionState) + Send + Sync + 'static,
+    ) -> Self {
+        self.state_hook = Some(StateHook::new(hook));
+        self
+    }
 }
 
-impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
+impl<'a, Mode, Tls> Builder<'a, Mode, Tls> {
+    /// Binds the `-PLUS` SASL profiles to the certificate of the TLS server
+    #[cfg(any(
+        feature = "rustls",
+        all(feature = "native-tls", not(target_arch = "wasm32"))
+    ))]
+    fn bind_sasl_profile_to_tls(&mut self, server_certificate: Option<Vec<u8>>) {
+        if let (Some(profile), Some(certificate)) = (self.sasl_profile.as_mut(), server_certificate)
+        {
+            profile.bind_tls_server_certificate(&certificate);
+        }
+    }
+
     /// Performs SASL negotiation
     #[cfg_attr(feature = "tracing", instrument(skip_all, fields(hostname = ?self.hostname)))]
     pub async fn negotiate_sasl<Io>(
         &mut self,
         transport: &mut Transport<Io, sasl::Frame>,
         // hostname: Option<&str>,
+        profile: SaslProfile,
+    ) -> Result<(), NegotiationError>
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + Unpin + 'static,
+    {
+        self.negotiate_sasl_with_trace(transport, profile, None, None)
+            .await
+    }
+
+    /// Performs SASL negotiation, starting with the `received` frame if it has already been read
+    /// from the transport
+    async fn negotiate_sasl_with_trace<Io>(
+        &mut self,
+        transport: &mut Transport<Io, sasl::Frame>,
         mut profile: SaslProfile,
+        mut received: Option<sasl::Frame>,
+        mut trace: Option<&mut SaslTrace>,
     ) -> Result<(), NegotiationError>
     where
         Io: AsyncRead + AsyncWrite + std::fmt::Debug + Unpin + 'static,
     {
         // TODO: timeout?
-        while let Some(frame) = transport.next().await {
-            let frame = frame?;
+        loop {
+            let frame = match received.take() {
+                Some(frame) => frame,
+                None => match transport.next().await {
+                    Some(frame) => frame?,
+                    None => break,
+                },
+            };
 
             #[cfg(feature = "tracing")]
             tracing::trace!(received = ?frame);
@@ -587,23 +818,13 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
             #[cfg(feature = "log")]
             log::trace!("received = {:?}", frame);
 
-            match profile.on_frame(frame, self.hostname)? {
-                Negotiation::Init(init) => {
-                    let frame = sasl::Frame::Init(init);
-                    #[cfg(feature = "tracing")]
-                    tracing::trace!(sending = ?frame);
-                    #[cfg(feature = "log")]
-                    log::trace!("sending = {:?}", frame);
-                    transport.send(frame).await?
-                }
-                Negotiation::Response(response) => {
-                    let frame = sasl::Frame::Response(response);
-                    #[cfg(feature = "tracing")]
-                    tracing::trace!(sending = ?frame);
-                    #[cfg(feature = "log")]
-                    log::trace!("sending = {:?}", frame);
-                    transport.send(frame).await?
-                }
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.record(&frame);
+            }
+
+            let frame = match profile.on_frame(frame, self.hostname)? {
+                Negotiation::Init(init) => sasl::Frame::Init(init),
+                Negotiation::Response(response) => sasl::Frame::Response(response),
                 Negotiation::Outcome(outcome) => match outcome.code {
                     SaslCode::Ok => return Ok(()),
                     code => {
@@ -613,7 +834,17 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
                         })
                     }
                 },
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::trace!(sending = ?frame);
+            #[cfg(feature = "log")]
+            log::trace!("sending = {:?}", frame);
+
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.record(&frame);
             }
+            transport.send(frame).await?
         }
         Err(NegotiationError::Io(io::Error::new(
             io::ErrorKind::UnexpectedEof,
@@ -621,6 +852,14 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
         )))
     }
 
+    /// An explicitly set container-id must not be empty
+    fn check_container_id(&self) -> Result<(), OpenError> {
+        if self.container_id.is_empty() {
+            return Err(OpenError::EmptyContainerId);
+        }
+        Ok(())
+    }
+
     async fn connect_with_stream<Io, F>(
         mut self,
         stream: Io,
@@ -632,6 +871,7 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
             ConnectionEngine<Io, Connection>,
             mpsc::Sender<ConnectionControl>,
             mpsc::Sender<SessionFrame>,
+            UnsettledDeliveries,
         ) -> Result<ConnectionHandle<()>, OpenError>,
     {
         match self.sasl_profile.take() {
@@ -641,22 +881,72 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
                 let framed_read = FramedRead::new(reader, ProtocolHeaderCodec::new());
                 let mut transport =
                     Transport::negotiate_sasl_header(framed_write, framed_read).await?;
-                self.negotiate_sasl(&mut transport, profile).await?;
-
-                // NOTE: LengthDelimitedCodec itself doesn't seem to carry any buffer, so
-                // it should be fine to simply drop it.
-                let (framed_write, framed_read) = transport.into_framed_codec();
-                let framed_write = framed_write.map_encoder(|_| ProtocolHeaderCodec::new());
-                let framed_read = framed_read.map_decoder(|_| ProtocolHeaderCodec::new());
-
-                // Then perform AMQP negotiation
-                self.connect_amqp_with_framed(framed_write, framed_read, spawn_engine_fn)
+                self.authenticate_sasl(&mut transport, profile, None)
+                    .await?;
+                self.connect_amqp_with_sasl_transport(transport, spawn_engine_fn)
                     .await
             }
             None => self.connect_amqp_with_stream(stream, spawn_engine_fn).await,
         }
     }
 
+    /// Performs SASL negotiation and captures the exchange in the error if `sasl_trace` is
+    /// enabled
+    async fn authenticate_sasl<Io>(
+        &mut self,
+        transport: &mut Transport<Io, sasl::Frame>,
+        profile: SaslProfile,
+        received: Option<sasl::Frame>,
+    ) -> Result<(), OpenError>
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + Unpin + 'static,
+    {
+        let mut trace = self.sasl_trace.then(|| SaslTrace::new(profile.mechanism()));
+        match self
+            .negotiate_sasl_with_trace(transport, profile, received, trace.as_mut())
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                let error = OpenError::from(error);
+                match trace {
+                    Some(trace) => Err(OpenError::SaslTraced {
+                        error: Box::new(error),
+                        trace,
+                    }),
+                    None => Err(error),
+                }
+            }
+        }
+    }
+
+    /// Performs the AMQP negotiation on the stream of a transport that has completed SASL
+    /// negotiation
+    async fn connect_amqp_with_sasl_transport<Io, F>(
+        self,
+        transport: Transport<Io, sasl::Frame>,
+        spawn_engine_fn: F,
+    ) -> Result<ConnectionHandle<()>, OpenError>
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + SendBound + Unpin + 'static,
+        F: FnOnce(
+            ConnectionEngine<Io, Connection>,
+            mpsc::Sender<ConnectionControl>,
+            mpsc::Sender<SessionFrame>,
+            UnsettledDeliveries,
+        ) -> Result<ConnectionHandle<()>, OpenError>,
+    {
+        // NOTE: LengthDelimitedCodec itself doesn't seem to carry any buffer, so
+        // it should be fine to simply drop it.
+        let (framed_write, framed_read) = transport.into_framed_codec();
+        let framed_write = framed_write.map_encoder(|_| ProtocolHeaderCodec::new());
+        let framed_read = framed_read.map_decoder(|_| ProtocolHeaderCodec::new());
+
+        // Then perform AMQP negotiation
+        self.connect_amqp_with_framed(framed_write, framed_read, spawn_engine_fn)
+            .await
+    }
+
     async fn connect_amqp_with_stream<Io, F>(
         self,
         stream: Io,
@@ -668,6 +958,7 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
             ConnectionEngine<Io, Connection>,
             mpsc::Sender<ConnectionControl>,
             mpsc::Sender<SessionFrame>,
+            UnsettledDeliveries,
         ) -> Result<ConnectionHandle<()>, OpenError>,
     {
         let (reader, writer) = tokio::io::split(stream);
@@ -689,6 +980,7 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
             ConnectionEngine<Io, Connection>,
             mpsc::Sender<ConnectionControl>,
             mpsc::Sender<SessionFrame>,
+            UnsettledDeliveries,
         ) -> Result<ConnectionHandle<()>, OpenError>,
     {
         // Exchange AMQP headers
@@ -697,24 +989,39 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
             .idle_time_out
             .map(|millis| Duration::from_millis(millis as u64));
         let buffer_size = self.buffer_size;
-        let transport = Transport::negotiate_amqp_header(
+        let max_unsettled_deliveries = self.max_unsettled_deliveries;
+        let close_on_drop = self.close_on_drop;
+        let state_hook = self.state_hook.clone();
+        if let Some(hook) = &state_hook {
+            hook.call(&local_state);
+        }
+        let mut transport = Transport::negotiate_amqp_header(
             framed_write,
             framed_read,
             &mut local_state,
             idle_timeout,
         )
         .await?;
+        if let Some(hook) = &state_hook {
+            hook.call(&local_state);
+        }
+        transport.set_frame_hook(self.frame_hook.clone());
+        transport.set_metrics(self.metrics.clone());
 
         let local_open = Open::from(self);
 
         // Create channels
         let (control_tx, control_rx) = mpsc::channel(DEFAULT_CONTROL_CHAN_BUF);
         let (outgoing_tx, outgoing_rx) = mpsc::channel(buffer_size);
-        let connection = Connection::new(local_state, local_open);
+        let mut connection = Connection::new(local_state, local_open);
+        connection.state_hook = state_hook;
 
         let engine = ConnectionEngine::open(transport, connection, control_rx, outgoing_rx).await?;
         // Self::spawn_engine(engine, control_tx, outgoing_tx)
-        (spawn_engine_fn)(engine, control_tx, outgoing_tx)
+        let unsettled_deliveries = UnsettledDeliveries::new(max_unsettled_deliveries);
+        let mut handle = (spawn_engine_fn)(engine, control_tx, outgoing_tx, unsettled_deliveries)?;
+        handle.close_on_drop = close_on_drop;
+        Ok(handle)
     }
 }
 
@@ -722,10 +1029,10 @@ impl<'a, Tls> Builder<'a, mode::ConnectorWithId, Tls> {
 /*                                 Without TLS                                */
 /* -------------------------------------------------------------------------- */
 
-impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
+impl<'a, Mode> Builder<'a, Mode, ()> {
     #[cfg(all(feature = "rustls", not(feature = "native-tls")))]
     async fn connect_tls_with_rustls_default<Io, F>(
-        self,
+        mut self,
         stream: Io,
         domain: &str,
         spawn_engine_fn: F,
@@ -736,6 +1043,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
             ConnectionEngine<tokio_rustls::client::TlsStream<Io>, Connection>,
             mpsc::Sender<ConnectionControl>,
             mpsc::Sender<SessionFrame>,
+            UnsettledDeliveries,
         ) -> Result<ConnectionHandle<()>, OpenError>,
     {
         use librustls::{ClientConfig, OwnedTrustAnchor, RootCertStore};
@@ -760,6 +1068,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
         let tls_stream =
             Transport::connect_tls_with_rustls(stream, domain, &connector, self.alt_tls_estab)
                 .await?;
+        self.bind_sasl_profile_to_tls(Transport::rustls_server_certificate(&tls_stream));
         self.connect_with_stream(tls_stream, spawn_engine_fn).await
     }
 
@@ -769,7 +1078,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
         not(target_arch = "wasm32")
     ))]
     async fn connect_tls_with_native_tls_default<Io, F>(
-        self,
+        mut self,
         stream: Io,
         domain: &str,
         spawn_engine_fn: F,
@@ -780,6 +1089,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
             ConnectionEngine<tokio_native_tls::TlsStream<Io>, Connection>,
             mpsc::Sender<ConnectionControl>,
             mpsc::Sender<SessionFrame>,
+            UnsettledDeliveries,
         ) -> Result<ConnectionHandle<()>, OpenError>,
     {
         let connector = libnative_tls::TlsConnector::new()
@@ -788,12 +1098,13 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
         let tls_stream =
             Transport::connect_tls_with_native_tls(stream, domain, &connector, self.alt_tls_estab)
                 .await?;
+        self.bind_sasl_profile_to_tls(Transport::native_tls_server_certificate(&tls_stream));
         self.connect_with_stream(tls_stream, spawn_engine_fn).await
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
+impl<'a, Mode> Builder<'a, Mode, ()> {
     /// Open a [`crate::Connection`] with an url
     ///
     /// # Raw AMQP connection
@@ -865,6 +1176,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
         mut self,
         url: impl TryInto<Url, Error = impl Into<OpenError>>,
     ) -> Result<ConnectionHandle<()>, OpenError> {
+        self.check_container_id()?;
         let url = url.try_into().map_err(Into::into)?;
 
         // Url info will override the builder fields
@@ -891,6 +1203,10 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
     /// The stream will be wrapped in `BufReader` and `BufWriter` so it is not necessary
     /// to wrap the stream in buffer.
     ///
+    /// No URL is parsed, so this works on any pre-established stream, eg. a Unix domain socket
+    /// or a `tokio::io::duplex`. The AMQP protocol header exchange, the SASL negotiation (if a
+    /// `sasl_profile` is set) and the Open exchange are all performed on the given stream.
+    ///
     /// # TLS
     ///
     /// If the `scheme` field is `"amqps"`, the builder will attempt to start with
@@ -928,6 +1244,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
     where
         Io: AsyncRead + AsyncWrite + std::fmt::Debug + SendBound + Unpin + 'static,
     {
+        self.check_container_id()?;
         match self.scheme {
             "amqp" => self.connect_with_stream(stream, spawn_engine).await,
             "amqps" => {
@@ -956,10 +1273,148 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
             _ => Err(OpenError::InvalidScheme),
         }
     }
+
+    /// Connects to the url and starts SASL negotiation without choosing a SASL profile
+    ///
+    /// The returned [`SaslNegotiation`] holds the mechanisms that the server advertised so that
+    /// the SASL profile can be chosen at runtime before calling
+    /// [`SaslNegotiation::authenticate`]. Any credentials in the url are ignored. Only the
+    /// `"amqp"` scheme is supported. For TLS, establish the TLS stream first and use
+    /// [`begin_sasl_with_stream`](#method.begin_sasl_with_stream).
+    ///
+    /// The url is borrowed because the hostname of the url is sent in the SASL init frame.
+    ///
+    /// ```rust,ignore
+    /// let url = Url::parse("amqp://localhost:5672").unwrap();
+    /// let negotiation = Connection::builder()
+    ///     .container_id("connection-1")
+    ///     .begin_sasl(&url)
+    ///     .await
+    ///     .unwrap();
+    /// let profile = if negotiation.mechanisms().contains(&Symbol::from("SCRAM-SHA-256")) {
+    ///     SaslProfile::from(SaslScramSha256::new("guest", "guest"))
+    /// } else {
+    ///     SaslProfile::Plain {
+    ///         username: "guest".to_string(),
+    ///         password: "guest".to_string(),
+    ///     }
+    /// };
+    /// let connection = negotiation.authenticate(profile).await.unwrap();
+    /// ```
+    pub async fn begin_sasl(
+        mut self,
+        url: &'a Url,
+    ) -> Result<SaslNegotiation<'a, TcpStream>, OpenError> {
+        self.check_container_id()?;
+        if url.scheme() != "amqp" {
+            return Err(OpenError::InvalidScheme);
+        }
+        if let Some(hostname) = url.host_str() {
+            self.hostname = Some(hostname);
+        }
+        if let Some(domain) = url.domain() {
+            self.domain = Some(domain);
+        }
+
+        let addr = url.socket_addrs(|| default_port(url.scheme()))?;
+        let stream = TcpStream::connect(&*addr).await?; // std::io::Error
+
+        self.begin_sasl_with_stream(stream).await
+    }
+
+    /// Exchanges the SASL protocol header on the stream and waits for the mechanisms that the
+    /// server advertises
+    ///
+    /// See [`begin_sasl`](#method.begin_sasl). The `scheme` of the builder is not used.
+    pub async fn begin_sasl_with_stream<Io>(
+        mut self,
+        stream: Io,
+    ) -> Result<SaslNegotiation<'a, Io>, OpenError>
+    where
+        Io: AsyncRead + AsyncWrite + std::fmt::Debug + SendBound + Unpin + 'static,
+    {
+        self.check_container_id()?;
+        // A profile set on the builder is replaced by the one passed to `authenticate`
+        self.sasl_profile = None;
+
+        let (reader, writer) = tokio::io::split(stream);
+        let framed_write = FramedWrite::new(writer, ProtocolHeaderCodec::new());
+        let framed_read = FramedRead::new(reader, ProtocolHeaderCodec::new());
+        let mut transport = Transport::negotiate_sasl_header(framed_write, framed_read).await?;
+
+        let mechanisms = match transport.next().await {
+            Some(Ok(sasl::Frame::Mechanisms(mechanisms))) => mechanisms,
+            Some(Ok(_)) => return Err(OpenError::IllegalState),
+            Some(Err(error)) => return Err(error.into()),
+            None => {
+                return Err(OpenError::Io(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Expecting SASL mechanisms",
+                )))
+            }
+        };
+
+        Ok(SaslNegotiation {
+            builder: self.into_mode(),
+            transport,
+            mechanisms,
+        })
+    }
+}
+
+/// A SASL negotiation that has received the mechanisms advertised by the server but has not
+/// chosen a SASL profile yet
+///
+/// This is created by [`Builder::begin_sasl`] or [`Builder::begin_sasl_with_stream`].
+#[cfg(not(target_arch = "wasm32"))]
+pub struct SaslNegotiation<'a, Io> {
+    builder: Builder<'a, mode::ConnectorWithId, ()>,
+    transport: Transport<Io, sasl::Frame>,
+    mechanisms: fe2o3_amqp_types::sasl::SaslMechanisms,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a, Io> std::fmt::Debug for SaslNegotiation<'a, Io> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaslNegotiation")
+            .field("builder", &self.builder)
+            .field("mechanisms", &self.mechanisms)
+            .finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a, Io> SaslNegotiation<'a, Io>
+where
+    Io: AsyncRead + AsyncWrite + std::fmt::Debug + SendBound + Unpin + 'static,
+{
+    /// Returns the SASL mechanisms advertised by the server in decreasing level of preference
+    pub fn mechanisms(&self) -> &[Symbol] {
+        &self.mechanisms.sasl_server_mechanisms.0
+    }
+
+    /// Authenticates with the given SASL profile and then opens the connection
+    pub async fn authenticate(
+        self,
+        profile: impl Into<SaslProfile>,
+    ) -> Result<ConnectionHandle<()>, OpenError> {
+        let Self {
+            mut builder,
+            mut transport,
+            mechanisms,
+        } = self;
+        let received = sasl::Frame::Mechanisms(mechanisms);
+        builder
+            .authenticate_sasl(&mut transport, profile.into(), Some(received))
+            .await?;
+        builder
+            .connect_amqp_with_sasl_transport(transport, spawn_engine)
+            .await
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
-impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
+impl<'a, Mode> Builder<'a, Mode, ()> {
     /// Open a connection with the given stream onto a [`tokio::task::LocalSet`].
     pub async fn open_with_stream_on_local_set<Io>(
         self,
@@ -969,10 +1424,17 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
     where
         Io: AsyncRead + AsyncWrite + std::fmt::Debug + Unpin + 'static,
     {
+        self.check_container_id()?;
         match self.scheme {
             "amqp" => {
-                let spawn_engine_fn = |engine, control_tx, outgoing_tx| {
-                    spawn_local_engine(engine, control_tx, outgoing_tx, local_set)
+                let spawn_engine_fn = |engine, control_tx, outgoing_tx, unsettled_deliveries| {
+                    spawn_local_engine(
+                        engine,
+                        control_tx,
+                        outgoing_tx,
+                        unsettled_deliveries,
+                        local_set,
+                    )
                 };
                 self.connect_with_stream(stream, spawn_engine_fn).await
             }
@@ -980,9 +1442,16 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
                 #[cfg(all(feature = "rustls", not(feature = "native-tls")))]
                 {
                     let domain = self.domain.ok_or(OpenError::InvalidDomain)?;
-                    let spawn_engine_fn = |engine, control_tx, outgoing_tx| {
-                        spawn_local_engine(engine, control_tx, outgoing_tx, local_set)
-                    };
+                    let spawn_engine_fn =
+                        |engine, control_tx, outgoing_tx, unsettled_deliveries| {
+                            spawn_local_engine(
+                                engine,
+                                control_tx,
+                                outgoing_tx,
+                                unsettled_deliveries,
+                                local_set,
+                            )
+                        };
                     return self
                         .connect_tls_with_rustls_default(stream, domain, spawn_engine_fn)
                         .await;
@@ -1014,7 +1483,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, ()> {
 
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(all(feature = "rustls"))]
-impl<'a> Builder<'a, mode::ConnectorWithId, tokio_rustls::TlsConnector> {
+impl<'a, Mode> Builder<'a, Mode, tokio_rustls::TlsConnector> {
     /// Open a [`crate::Connection`] with an url
     ///
     /// # Raw AMQP connection
@@ -1084,6 +1553,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, tokio_rustls::TlsConnector> {
         mut self,
         url: impl TryInto<Url, Error = impl Into<OpenError>>,
     ) -> Result<ConnectionHandle<()>, OpenError> {
+        self.check_container_id()?;
         let url = url.try_into().map_err(Into::into)?;
 
         // Url info will override the builder fields
@@ -1112,10 +1582,14 @@ impl<'a> Builder<'a, mode::ConnectorWithId, tokio_rustls::TlsConnector> {
     /// If the `scheme` field is `"amqps"`, the builder will attempt to start with
     /// exchanging TLS protocol header and establish TLS stream using the user-supplied
     /// `tokio_rustls::TlsConnector`.
-    pub async fn open_with_stream<Io>(self, stream: Io) -> Result<ConnectionHandle<()>, OpenError>
+    pub async fn open_with_stream<Io>(
+        mut self,
+        stream: Io,
+    ) -> Result<ConnectionHandle<()>, OpenError>
     where
         Io: AsyncRead + AsyncWrite + std::fmt::Debug + SendBound + Unpin + 'static,
     {
+        self.check_container_id()?;
         match self.scheme {
             "amqp" => self.connect_with_stream(stream, spawn_engine).await,
             "amqps" => {
@@ -1127,6 +1601,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, tokio_rustls::TlsConnector> {
                     self.alt_tls_estab,
                 )
                 .await?;
+                self.bind_sasl_profile_to_tls(Transport::rustls_server_certificate(&tls_stream));
                 self.connect_with_stream(tls_stream, spawn_engine).await
             }
             _ => Err(OpenError::InvalidScheme),
@@ -1140,7 +1615,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, tokio_rustls::TlsConnector> {
 
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(all(feature = "native-tls"))]
-impl<'a> Builder<'a, mode::ConnectorWithId, tokio_native_tls::TlsConnector> {
+impl<'a, Mode> Builder<'a, Mode, tokio_native_tls::TlsConnector> {
     /// Open a [`crate::Connection`] with an url
     ///
     /// # Raw AMQP connection
@@ -1210,6 +1685,7 @@ impl<'a> Builder<'a, mode::ConnectorWithId, tokio_native_tls::TlsConnector> {
         mut self,
         url: impl TryInto<Url, Error = impl Into<OpenError>>,
     ) -> Result<ConnectionHandle<()>, OpenError> {
+        self.check_container_id()?;
         let url = url.try_into().map_err(Into::into)?;
 
         // Url info will override the builder fields
@@ -1238,10 +1714,14 @@ impl<'a> Builder<'a, mode::ConnectorWithId, tokio_native_tls::TlsConnector> {
     /// If the `scheme` field is `"amqps"`, the builder will attempt to start with
     /// exchanging TLS protocol header and establish TLS stream using the user-supplied
     /// `tokio_rustls::TlsConnector`.
-    pub async fn open_with_stream<Io>(self, stream: Io) -> Result<ConnectionHandle<()>, OpenError>
+    pub async fn open_with_stream<Io>(
+        mut self,
+        stream: Io,
+    ) -> Result<ConnectionHandle<()>, OpenError>
     where
         Io: AsyncRead + AsyncWrite + std::fmt::Debug + SendBound + Unpin + 'static,
     {
+        self.check_container_id()?;
         match self.scheme {
             "amqp" => self.connect_with_stream(stream, spawn_engine).await,
             "amqps" => {
@@ -1253,6 +1733,9 @@ impl<'a> Builder<'a, mode::ConnectorWithId, tokio_native_tls::TlsConnector> {
                     self.alt_tls_estab,
                 )
                 .await?;
+                self.bind_sasl_profile_to_tls(Transport::native_tls_server_certificate(
+                    &tls_stream,
+                ));
                 self.connect_with_stream(tls_stream, spawn_engine).await
             }
             _ => Err(OpenError::InvalidScheme),
@@ -1265,10 +1748,15 @@ fn spawn_engine<Io>(
     engine: ConnectionEngine<Io, Connection>,
     control_tx: mpsc::Sender<ConnectionControl>,
     outgoing_tx: mpsc::Sender<SessionFrame>,
+    unsettled_deliveries: UnsettledDeliveries,
 ) -> Result<ConnectionHandle<()>, OpenError>
 where
     Io: AsyncRead + AsyncWrite + std::fmt::Debug + Send + Unpin + 'static,
 {
+    let remote_open = engine.remote_open().ok_or(OpenError::IllegalState)?;
+    let idle_timeout = engine.idle_timeout();
+    let channel_max = engine.channel_max().ok_or(OpenError::IllegalState)?;
+    let state = engine.state_watcher();
     let (handle, outcome) = engine.spawn();
 
     let connection_handle = ConnectionHandle {
@@ -1278,6 +1766,12 @@ where
         outcome,
         outgoing: outgoing_tx, // session_control: session_control_tx
         session_listener: (),
+        remote_open,
+        idle_timeout,
+        channel_max,
+        unsettled_deliveries,
+        state,
+        close_on_drop: None,
     };
 
     Ok(connection_handle)
@@ -1288,11 +1782,16 @@ fn spawn_local_engine<Io>(
     engine: ConnectionEngine<Io, Connection>,
     control_tx: mpsc::Sender<ConnectionControl>,
     outgoing_tx: mpsc::Sender<SessionFrame>,
+    unsettled_deliveries: UnsettledDeliveries,
     local_set: &tokio::task::LocalSet,
 ) -> Result<ConnectionHandle<()>, OpenError>
 where
     Io: AsyncRead + AsyncWrite + std::fmt::Debug + Unpin + 'static,
 {
+    let remote_open = engine.remote_open().ok_or(OpenError::IllegalState)?;
+    let idle_timeout = engine.idle_timeout();
+    let channel_max = engine.channel_max().ok_or(OpenError::IllegalState)?;
+    let state = engine.state_watcher();
     let (handle, outcome) = engine.spawn_local(local_set);
 
     let connection_handle = ConnectionHandle {
@@ -1302,6 +1801,12 @@ where
         outcome,
         outgoing: outgoing_tx, // session_control: session_control_tx
         session_listener: (),
+        remote_open,
+        idle_timeout,
+        channel_max,
+        unsettled_deliveries,
+        state,
+        close_on_drop: None,
     };
 
     Ok(connection_handle)
@@ -1317,4 +1822,360 @@ mod tests {
         assert_eq!(url.port(), Some(5672));
         let _addrs = url.socket_addrs(|| Some(5672)).unwrap();
     }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn container_id_defaults_to_generated_uuid() {
+        use uuid::Uuid;
+
+        use crate::{test_util, Connection};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            let remote_container_id = connection.remote_container_id().to_string();
+            let _ = connection.on_close().await;
+            remote_container_id
+        });
+
+        let mut connection = Connection::builder()
+            .open_with_stream(client)
+            .await
+            .unwrap();
+        connection.close().await.unwrap();
+
+        let remote_container_id = server.await.unwrap();
+        assert!(Uuid::parse_str(&remote_container_id).is_ok());
+    }
+
+    #[test]
+    fn container_id_prefix_is_followed_by_uuid() {
+        use uuid::Uuid;
+
+        use crate::Connection;
+
+        let builder = Connection::builder().container_id_prefix("my-app");
+        let uuid = builder.container_id.strip_prefix("my-app-").unwrap();
+        assert!(Uuid::parse_str(uuid).is_ok());
+    }
+
+    #[tokio::test]
+    async fn empty_container_id_is_rejected() {
+        use crate::{connection::OpenError, Connection};
+
+        let (client, _server) = tokio::io::duplex(4096);
+        let result = Connection::builder()
+            .container_id("")
+            .open_with_stream(client)
+            .await;
+        assert!(matches!(result, Err(OpenError::EmptyContainerId)));
+    }
+
+    #[tokio::test]
+    async fn sasl_mechanisms_are_available_before_authentication() {
+        use fe2o3_amqp_types::{
+            performatives::{Close, Open},
+            primitives::Symbol,
+            sasl::{SaslCode, SaslMechanisms, SaslOutcome},
+        };
+        use futures_util::{SinkExt, StreamExt};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio_util::codec::{FramedRead, FramedWrite};
+
+        use crate::{
+            frames::{
+                amqp::{Frame, FrameBody},
+                sasl,
+            },
+            sasl_profile::SaslProfile,
+            transport::{protocol_header::ProtocolHeaderCodec, Transport},
+            Connection,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        // Simulated server that advertises two mechanisms and accepts PLAIN
+        let server = tokio::spawn(async move {
+            let (reader, writer) = tokio::io::split(server);
+            let mut transport = Transport::<_, sasl::Frame>::negotiate_sasl_header(
+                FramedWrite::new(writer, ProtocolHeaderCodec::new()),
+                FramedRead::new(reader, ProtocolHeaderCodec::new()),
+            )
+            .await
+            .unwrap();
+
+            let mechanisms = SaslMechanisms {
+                sasl_server_mechanisms: vec![Symbol::from("SCRAM-SHA-256"), Symbol::from("PLAIN")]
+                    .into(),
+            };
+            transport
+                .send(sasl::Frame::Mechanisms(mechanisms))
+                .await
+                .unwrap();
+
+            match transport.next().await {
+                Some(Ok(sasl::Frame::Init(init))) => {
+                    assert_eq!(init.mechanism, Symbol::from("PLAIN"));
+                    assert_eq!(init.initial_response.unwrap().as_ref(), b"\0user\0password");
+                }
+                frame => panic!("Expecting SASL Init, found {:?}", frame),
+            }
+            let outcome = SaslOutcome {
+                code: SaslCode::Ok,
+                additional_data: None,
+            };
+            transport.send(sasl::Frame::Outcome(outcome)).await.unwrap();
+
+            let (framed_write, framed_read) = transport.into_framed_codec();
+            let mut server = framed_read.into_inner().unsplit(framed_write.into_inner());
+
+            let mut header = [0u8; 8];
+            server.read_exact(&mut header).await.unwrap();
+            assert_eq!(&header, b"AMQP\x00\x01\x00\x00");
+            server.write_all(&header).await.unwrap();
+
+            let mut transport: Transport<_, Frame> = Transport::bind(server, 512, None);
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Open(open) => assert_eq!(open.container_id, "client"),
+                body => panic!("Expecting open, found {:?}", body),
+            }
+            let open = Open {
+                container_id: String::from("sasl-server"),
+                hostname: None,
+                max_frame_size: Default::default(),
+                channel_max: Default::default(),
+                idle_time_out: None,
+                outgoing_locales: None,
+                incoming_locales: None,
+                offered_capabilities: None,
+                desired_capabilities: None,
+                properties: None,
+            };
+            transport
+                .send(Frame::new(0u16, FrameBody::Open(open)))
+                .await
+                .unwrap();
+
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Close(_) => {}
+                body => panic!("Expecting close, found {:?}", body),
+            }
+            let frame = Frame::new(0u16, FrameBody::Close(Close { error: None }));
+            transport.send(frame).await.unwrap();
+        });
+
+        let negotiation = Connection::builder()
+            .container_id("client")
+            .begin_sasl_with_stream(client)
+            .await
+            .unwrap();
+        assert_eq!(
+            negotiation.mechanisms(),
+            &[Symbol::from("SCRAM-SHA-256"), Symbol::from("PLAIN")]
+        );
+
+        let profile = SaslProfile::Plain {
+            username: String::from("user"),
+            password: String::from("password"),
+        };
+        let mut connection = negotiation.authenticate(profile).await.unwrap();
+        assert_eq!(connection.remote_container_id(), "sasl-server");
+
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "scram")]
+    #[tokio::test]
+    async fn sasl_trace_captures_failed_scram_exchange() {
+        use fe2o3_amqp_types::{
+            primitives::{Binary, Symbol},
+            sasl::{SaslChallenge, SaslCode, SaslMechanisms, SaslOutcome},
+        };
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_util::codec::{FramedRead, FramedWrite};
+
+        use crate::{
+            connection::OpenError,
+            frames::sasl,
+            sasl_profile::{SaslDirection, SaslScramSha256, SaslTraceEntry},
+            transport::{protocol_header::ProtocolHeaderCodec, Transport},
+            Connection,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        // Simulated server that rejects the client proof
+        let server = tokio::spawn(async move {
+            let (reader, writer) = tokio::io::split(server);
+            let mut transport = Transport::<_, sasl::Frame>::negotiate_sasl_header(
+                FramedWrite::new(writer, ProtocolHeaderCodec::new()),
+                FramedRead::new(reader, ProtocolHeaderCodec::new()),
+            )
+            .await
+            .unwrap();
+
+            let mechanisms = SaslMechanisms {
+                sasl_server_mechanisms: vec![Symbol::from("SCRAM-SHA-256")].into(),
+            };
+            transport
+                .send(sasl::Frame::Mechanisms(mechanisms))
+                .await
+                .unwrap();
+
+            let client_first = match transport.next().await {
+                Some(Ok(sasl::Frame::Init(init))) => init.initial_response.unwrap(),
+                frame => panic!("Expecting SASL Init, found {:?}", frame),
+            };
+            let client_first = std::str::from_utf8(&client_first).unwrap();
+            let client_nonce = client_first
+                .split(',')
+                .find_map(|attr| attr.strip_prefix("r="))
+                .unwrap();
+            let server_first = format!("r={}server-nonce,s=c2FsdA==,i=4096", client_nonce);
+            let challenge = SaslChallenge {
+                challenge: Binary::from(server_first.into_bytes()),
+            };
+            transport
+                .send(sasl::Frame::Challenge(challenge))
+                .await
+                .unwrap();
+
+            let client_final = match transport.next().await {
+                Some(Ok(sasl::Frame::Response(response))) => response.response,
+                frame => panic!("Expecting SASL Response, found {:?}", frame),
+            };
+            let outcome = SaslOutcome {
+                code: SaslCode::Auth,
+                additional_data: None,
+            };
+            transport.send(sasl::Frame::Outcome(outcome)).await.unwrap();
+
+            String::from_utf8(client_final.to_vec()).unwrap()
+        });
+
+        let error = Connection::builder()
+            .container_id("sasl-trace")
+            .sasl_profile(SaslScramSha256::new("user", "password"))
+            .sasl_trace(true)
+            .open_with_stream(client)
+            .await
+            .unwrap_err();
+        let client_final = server.await.unwrap();
+
+        let trace = error.sasl_trace().unwrap();
+        match &error {
+            OpenError::SaslTraced { error, .. } => assert!(matches!(
+                **error,
+                OpenError::SaslError {
+                    code: SaslCode::Auth,
+                    ..
+                }
+            )),
+            _ => panic!("Expecting OpenError::SaslTraced"),
+        }
+
+        let entries = trace.entries();
+        let directions: Vec<_> = entries.iter().map(|entry| entry.direction()).collect();
+        assert_eq!(
+            directions,
+            vec![
+                SaslDirection::Received,
+                SaslDirection::Sent,
+                SaslDirection::Received,
+                SaslDirection::Sent,
+                SaslDirection::Received,
+            ]
+        );
+        assert!(matches!(&entries[0], SaslTraceEntry::Mechanisms(m) if m.len() == 1));
+        match &entries[1] {
+            SaslTraceEntry::Init {
+                mechanism,
+                initial_response,
+                ..
+            } => {
+                assert_eq!(mechanism.as_str(), "SCRAM-SHA-256");
+                assert!(initial_response
+                    .as_ref()
+                    .unwrap()
+                    .starts_with("n,,n=user,r="));
+            }
+            entry => panic!("Expecting Init, found {:?}", entry),
+        }
+        assert!(matches!(&entries[2], SaslTraceEntry::Challenge(c) if c.ends_with(",i=4096")));
+        match &entries[3] {
+            SaslTraceEntry::Response(response) => {
+                assert!(response.ends_with(",p=<redacted>"));
+                let proof = client_final
+                    .split(',')
+                    .find_map(|attr| attr.strip_prefix("p="))
+                    .unwrap();
+                assert!(!trace.to_string().contains(proof));
+            }
+            entry => panic!("Expecting Response, found {:?}", entry),
+        }
+        assert_eq!(
+            entries[4],
+            SaslTraceEntry::Outcome {
+                code: SaslCode::Auth,
+                additional_data: None
+            }
+        );
+    }
+
+    #[cfg(feature = "rustls")]
+    #[tokio::test]
+    async fn rustls_config_is_used_after_tls_protocol_header_exchange() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::{connection::OpenError, Connection};
+
+        let (client, mut server) = tokio::io::duplex(4096);
+
+        let mut root_store = librustls::RootCertStore::empty();
+        root_store.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
+            librustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        let mut config = librustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+        config.alpn_protocols = vec![b"fe2o3-test-alpn".to_vec()];
+
+        let server_handle = tokio::spawn(async move {
+            let tls_header = [b'A', b'M', b'Q', b'P', 2, 1, 0, 0];
+            let mut header = [0u8; 8];
+            server.read_exact(&mut header).await.unwrap();
+            assert_eq!(header, tls_header);
+            server.write_all(&tls_header).await.unwrap();
+
+            // TLS record header followed by the ClientHello
+            let mut record_header = [0u8; 5];
+            server.read_exact(&mut record_header).await.unwrap();
+            assert_eq!(record_header[0], 0x16); // handshake
+            let len = u16::from_be_bytes([record_header[3], record_header[4]]) as usize;
+            let mut client_hello = vec![0u8; len];
+            server.read_exact(&mut client_hello).await.unwrap();
+            client_hello
+        });
+
+        let result = Connection::builder()
+            .container_id("test-connection")
+            .scheme("amqps")
+            .domain("broker.example.com")
+            .rustls_config(config)
+            .open_with_stream(client)
+            .await;
+        assert!(matches!(result, Err(OpenError::Io(_))));
+
+        let client_hello = server_handle.await.unwrap();
+        let contains = |needle: &[u8]| client_hello.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"broker.example.com"));
+        assert!(contains(b"fe2o3-test-alpn"));
+    }
 }
@@ -1,15 +1,16 @@
 //! The engine handles incoming and outgoing frames and messages to reduce
 //! transferring frames/messages over channels
 
+use std::cmp::min;
 use std::io;
 use std::time::Duration;
 
-use fe2o3_amqp_types::definitions::{self, AmqpError};
-use fe2o3_amqp_types::performatives::Close;
+use fe2o3_amqp_types::definitions::{self, AmqpError, ConnectionError, Milliseconds};
+use fe2o3_amqp_types::performatives::{Close, Open};
 use futures_util::{SinkExt, StreamExt};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::mpsc::Receiver;
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, watch};
 use tokio::task::JoinHandle;
 
 use crate::control::ConnectionControl;
@@ -32,6 +33,13 @@ pub(crate) struct ConnectionEngine<Io, C> {
     heartbeat: HeartBeat,
 }
 
+impl<Io> ConnectionEngine<Io, super::Connection> {
+    /// Returns a receiver that is notified of the local state transitions
+    pub fn state_watcher(&self) -> watch::Receiver<ConnectionState> {
+        self.connection.state_watcher()
+    }
+}
+
 impl<Io, C> ConnectionEngine<Io, C>
 where
     Io: AsyncRead + AsyncWrite + std::fmt::Debug + SendBound + Unpin + 'static,
@@ -43,6 +51,31 @@ where
     ConnectionStateError: From<C::OpenError> + From<C::CloseError>,
     OpenError: From<C::OpenError>,
 {
+    /// Returns the Open frame sent by the remote peer, which is available once the
+    /// remote Open frame has been received
+    pub fn remote_open(&self) -> Option<Open> {
+        self.connection.remote_open().cloned()
+    }
+
+    /// Returns the idle timeout advertised by the remote peer, which is available once the
+    /// remote Open frame has been received
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.connection
+            .remote_open()
+            .and_then(|open| open.idle_time_out)
+            .filter(|millis| *millis > 0)
+            .map(|millis| Duration::from_millis(millis as u64))
+    }
+
+    /// Returns the smaller of the local and the remote channel-max, which is available once the
+    /// remote Open frame has been received
+    pub fn channel_max(&self) -> Option<u16> {
+        let local_channel_max = self.connection.local_open().channel_max.0;
+        self.connection
+            .remote_open()
+            .map(|open| min(local_channel_max, open.channel_max.0))
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn spawn(self) -> (JoinHandle<()>, oneshot::Receiver<Result<(), Error>>) {
         let (tx, rx) = oneshot::channel();
@@ -176,13 +209,7 @@ where
 
         // Set heartbeat here because in pipelined-open, the Open frame
         // may be recved after mux loop is started
-        match &remote_idle_timeout {
-            Some(0) | None => self.heartbeat = HeartBeat::never(),
-            Some(millis) => {
-                let period = Duration::from_millis(*millis as u64);
-                self.heartbeat = HeartBeat::new(period);
-            }
-        };
+        self.set_heartbeat(remote_idle_timeout);
 
         Ok(())
     }
@@ -259,6 +286,15 @@ where
 
         let Frame { channel, body } = frame;
         let channel = IncomingChannel(channel);
+
+        // After closing with an error, all frames other than Close are discarded
+        if let (ConnectionState::Discarding, false) = (
+            self.connection.local_state(),
+            matches!(body, FrameBody::Close(_)),
+        ) {
+            return Ok(Running::Continue);
+        }
+
         match body {
             FrameBody::Open(open) => {
                 let remote_idle_timeout = open.idle_time_out;
@@ -266,13 +302,7 @@ where
 
                 // Set heartbeat here because in pipelined-open, the Open frame
                 // may be recved after mux loop is started
-                match &remote_idle_timeout {
-                    Some(millis) => {
-                        let period = Duration::from_millis(*millis as u64);
-                        self.heartbeat = HeartBeat::new(period);
-                    }
-                    None => self.heartbeat = HeartBeat::never(),
-                };
+                self.set_heartbeat(remote_idle_timeout);
             }
             FrameBody::Begin(begin) => {
                 self.connection.on_incoming_begin(channel, begin).await?;
@@ -377,6 +407,16 @@ where
                     log::error!("{:?}", error);
                 }
             }
+            ConnectionControl::Ping(resp) => {
+                // The responder is dropped without a reply if no frame can be sent anymore
+                match self.connection.local_state() {
+                    ConnectionState::Start | ConnectionState::CloseSent | ConnectionState::End => {}
+                    _ => {
+                        self.transport.send(Frame::empty()).await?;
+                        let _ = resp.send(());
+                    }
+                }
+            }
         }
 
         match self.connection.local_state() {
@@ -399,6 +439,11 @@ where
         let SessionFrame { channel, body } = frame;
         let channel = OutgoingChannel(channel);
         let frame = match body {
+            SessionFrameBody::Flush(resp) => {
+                // All frames queued before this one have already been sent and flushed
+                let _ = resp.send(());
+                return Ok(Running::Continue);
+            }
             SessionFrameBody::Begin(begin) => self.connection.on_outgoing_begin(channel, begin)?,
             SessionFrameBody::Attach(attach) => Frame::new(channel, FrameBody::Attach(attach)),
             SessionFrameBody::Flow(flow) => Frame::new(channel, FrameBody::Flow(flow)),
@@ -427,6 +472,18 @@ where
         Ok(Running::Continue)
     }
 
+    /// Send an empty frame every half of the remote idle timeout so that the remote peer does
+    /// not time out because of small variations in the timing
+    fn set_heartbeat(&mut self, remote_idle_timeout: Option<Milliseconds>) {
+        self.heartbeat = match remote_idle_timeout {
+            Some(0) | None => HeartBeat::never(),
+            Some(millis) => {
+                let period = Duration::from_millis(std::cmp::max(millis / 2, 1) as u64);
+                HeartBeat::new(period)
+            }
+        };
+    }
+
     #[inline]
     async fn on_heartbeat(&mut self) -> Result<Running, ConnectionInnerError> {
         match &self.connection.local_state() {
@@ -446,6 +503,26 @@ where
         error: &ConnectionInnerError,
     ) -> Result<Running, ConnectionInnerError> {
         match error {
+            ConnectionInnerError::TransportError(transport::Error::IdleTimeoutElapsed) => {
+                // The remote peer is not responding, so the remote Close is not waited for
+                if let ConnectionState::OpenPipe
+                | ConnectionState::OpenClosePipe
+                | ConnectionState::OpenReceived
+                | ConnectionState::OpenSent
+                | ConnectionState::Opened
+                | ConnectionState::CloseReceived = self.connection.local_state()
+                {
+                    let error = definitions::Error::new(
+                        ConnectionError::ConnectionForced,
+                        Some(String::from("Idle timeout elapsed")),
+                        None,
+                    );
+                    self.connection
+                        .send_close(&mut self.transport, Some(error))
+                        .await?;
+                }
+                Ok(Running::Stop)
+            }
             ConnectionInnerError::TransportError(_) => Ok(Running::Stop),
             ConnectionInnerError::IllegalState => {
                 let error = definitions::Error::new(AmqpError::IllegalState, None, None);
@@ -581,3 +658,131 @@ where
         let _ = tx.send(result);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use fe2o3_amqp_types::{
+        definitions::{ConnectionError, ErrorCondition, Role},
+        messaging::{Accepted, DeliveryState},
+        performatives::{ChannelMax, Disposition, MaxFrameSize, Open},
+        states::ConnectionState,
+    };
+    use futures_util::StreamExt;
+    use tokio::sync::{mpsc, oneshot};
+
+    use crate::{
+        connection::{heartbeat::HeartBeat, Connection, Error},
+        frames::amqp::{Frame, FrameBody},
+        session::frame::{SessionFrame, SessionFrameBody},
+        transport::{self, Transport},
+    };
+
+    use super::ConnectionEngine;
+
+    #[tokio::test]
+    async fn flush_responds_after_previous_frames_are_written() {
+        let (local, remote) = tokio::io::duplex(1024);
+        let local_open = Open {
+            container_id: String::from("local"),
+            hostname: None,
+            max_frame_size: MaxFrameSize::default(),
+            channel_max: ChannelMax::default(),
+            idle_time_out: None,
+            outgoing_locales: None,
+            incoming_locales: None,
+            offered_capabilities: None,
+            desired_capabilities: None,
+            properties: None,
+        };
+        let (_control_tx, control) = mpsc::channel(1);
+        let (_outgoing_tx, outgoing_session_frames) = mpsc::channel(1);
+        let mut engine = ConnectionEngine {
+            transport: Transport::bind(local, 512, None),
+            connection: Connection::new(ConnectionState::Opened, local_open),
+            control,
+            outgoing_session_frames,
+            heartbeat: HeartBeat::never(),
+        };
+        let mut remote = Transport::<_, Frame>::bind(remote, 512, None);
+
+        let disposition = Disposition {
+            role: Role::Receiver,
+            first: 0,
+            last: None,
+            settled: true,
+            state: Some(DeliveryState::Accepted(Accepted {})),
+            batchable: false,
+        };
+        let frame = SessionFrame::new(0u16, SessionFrameBody::Disposition(disposition));
+        engine.on_outgoing_session_frames(frame).await.unwrap();
+
+        let (tx, mut rx) = oneshot::channel();
+        let frame = SessionFrame::new(0u16, SessionFrameBody::Flush(tx));
+        engine.on_outgoing_session_frames(frame).await.unwrap();
+        rx.try_recv().expect("Flush should be responded to");
+
+        // The disposition must already be on the wire
+        let frame = remote.next().await.unwrap().unwrap();
+        assert!(matches!(frame.body, FrameBody::Disposition(d) if d.settled));
+    }
+
+    #[tokio::test]
+    async fn silent_remote_gets_keepalives_then_forced_close() {
+        let (local, remote) = tokio::io::duplex(1024);
+        let open = |container_id: &str, idle_time_out| Open {
+            container_id: String::from(container_id),
+            hostname: None,
+            max_frame_size: MaxFrameSize::default(),
+            channel_max: ChannelMax::default(),
+            idle_time_out,
+            outgoing_locales: None,
+            incoming_locales: None,
+            offered_capabilities: None,
+            desired_capabilities: None,
+            properties: None,
+        };
+        let mut connection = Connection::new(ConnectionState::Opened, open("local", Some(250)));
+        connection.remote_open = Some(open("remote", Some(100)));
+        let (_control_tx, control) = mpsc::channel(1);
+        let (_outgoing_tx, outgoing_session_frames) = mpsc::channel(1);
+        let mut engine = ConnectionEngine {
+            transport: Transport::bind(local, 512, Some(Duration::from_millis(500))),
+            connection,
+            control,
+            outgoing_session_frames,
+            heartbeat: HeartBeat::never(),
+        };
+        engine.set_heartbeat(Some(100));
+        assert_eq!(engine.idle_timeout(), Some(Duration::from_millis(100)));
+
+        // The remote never sends anything
+        let mut remote = Transport::<_, Frame>::bind(remote, 512, None);
+        let start = Instant::now();
+        let (_handle, outcome) = engine.spawn();
+
+        let mut empty_frames = 0;
+        let close = loop {
+            let frame = remote.next().await.unwrap().unwrap();
+            match frame.body {
+                FrameBody::Empty => empty_frames += 1,
+                FrameBody::Close(close) => break close,
+                _ => panic!("Unexpected frame"),
+            }
+        };
+        let elapsed = start.elapsed();
+
+        // Empty frames are sent every 50ms until the local idle timeout elapses after 500ms
+        assert!(elapsed >= Duration::from_millis(500));
+        assert!(empty_frames >= 5, "only {} empty frames", empty_frames);
+        assert_eq!(
+            close.error.unwrap().condition,
+            ErrorCondition::ConnectionError(ConnectionError::ConnectionForced)
+        );
+        assert!(matches!(
+            outcome.await.unwrap(),
+            Err(Error::TransportError(transport::Error::IdleTimeoutElapsed))
+        ));
+    }
+}
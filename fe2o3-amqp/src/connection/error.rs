@@ -3,10 +3,17 @@
 use std::{convert::Infallible, io};
 
 use bytes::Bytes;
-use fe2o3_amqp_types::{definitions, primitives::Binary, sasl::SaslCode};
+use fe2o3_amqp_types::{
+    definitions::{self, ConnectionRedirect},
+    primitives::{Binary, Symbol},
+    sasl::SaslCode,
+};
 use tokio::{sync::mpsc, task::JoinError};
 
-use crate::transport::{self, error::NegotiationError};
+use crate::{
+    sasl_profile::SaslTrace,
+    transport::{self, error::NegotiationError},
+};
 
 #[cfg(feature = "scram")]
 use crate::auth::error::ScramErrorKind;
@@ -34,6 +41,10 @@ pub enum OpenError {
     #[error(r#"Invalid scheme. Only "amqp" and "amqps" are supported."#)]
     InvalidScheme,
 
+    /// The container-id set on the builder is empty
+    #[error("container-id must not be empty")]
+    EmptyContainerId,
+
     /// Protocol negotiation failed due to protocol header mismatch
     #[error("Protocol header mismatch. Found {0:?}")]
     ProtocolHeaderMismatch(Bytes),
@@ -47,6 +58,15 @@ pub enum OpenError {
         additional_data: Option<Binary>,
     },
 
+    /// None of the mechanisms of a negotiated SASL profile is offered by the server
+    #[error("None of {preferred:?} is offered by the server, which offers {offered:?}")]
+    NoMatchingMechanism {
+        /// SASL mechanisms offered by the server
+        offered: Vec<Symbol>,
+        /// SASL mechanisms of the negotiated profile in order of preference
+        preferred: Vec<Symbol>,
+    },
+
     /// Error with SCRAM
     #[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
     #[cfg(feature = "scram")]
@@ -76,6 +96,38 @@ pub enum OpenError {
     /// Remote peer closed connection with error during openning process
     #[error("Remote peer closed connection with error {}", .0)]
     RemoteClosedWithError(definitions::Error),
+
+    /// SASL negotiation failed and the SASL exchange was captured
+    ///
+    /// This is only returned if enabled with
+    /// [`Builder::sasl_trace`](crate::connection::Builder::sasl_trace)
+    #[error("{}", .error)]
+    SaslTraced {
+        /// The error that caused the SASL negotiation to fail
+        error: Box<OpenError>,
+        /// The captured SASL exchange with secrets redacted
+        trace: SaslTrace,
+    },
+}
+
+impl OpenError {
+    /// Returns the captured SASL exchange if SASL tracing was enabled and the SASL negotiation
+    /// failed
+    pub fn sasl_trace(&self) -> Option<&SaslTrace> {
+        match self {
+            OpenError::SaslTraced { trace, .. } => Some(trace),
+            _ => None,
+        }
+    }
+
+    /// Returns the host to reconnect to if the remote peer closed the connection with an
+    /// `amqp:connection:redirect` error
+    pub fn redirect(&self) -> Option<ConnectionRedirect> {
+        match self {
+            OpenError::RemoteClosedWithError(error) => error.connection_redirect(),
+            _ => None,
+        }
+    }
 }
 
 impl From<NegotiationError> for OpenError {
@@ -93,6 +145,9 @@ impl From<NegotiationError> for OpenError {
             },
             NegotiationError::DecodeError(val) => Self::DecodeError(val),
             NegotiationError::NotImplemented(description) => Self::NotImplemented(description),
+            NegotiationError::NoMatchingMechanism { offered, preferred } => {
+                Self::NoMatchingMechanism { offered, preferred }
+            }
             NegotiationError::IllegalState => Self::IllegalState,
 
             #[cfg(feature = "scram")]
@@ -224,6 +279,29 @@ pub enum Error {
     JoinError(#[from] JoinError),
 }
 
+impl Error {
+    /// Returns the host to reconnect to if the remote peer closed the connection with an
+    /// `amqp:connection:redirect` error
+    pub fn redirect(&self) -> Option<ConnectionRedirect> {
+        match self {
+            Error::RemoteClosedWithError(error) => error.connection_redirect(),
+            _ => None,
+        }
+    }
+}
+
+/// Error with pinging the connection event loop
+#[derive(Debug, thiserror::Error)]
+pub enum PingError {
+    /// The event loop did not service the ping within the timeout
+    #[error("The connection event loop did not respond within the timeout")]
+    Timeout,
+
+    /// The event loop has stopped or the connection is closing
+    #[error("The connection event loop has stopped")]
+    Closed,
+}
+
 impl From<ConnectionInnerError> for Error {
     fn from(error: ConnectionInnerError) -> Self {
         match error {
@@ -0,0 +1,222 @@
+//! Frame level hook for observing the performatives exchanged on a connection
+
+use std::{fmt, sync::Arc};
+
+use crate::frames::amqp::FrameBody;
+
+/// Direction of a frame observed by a [`FrameHook`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameDirection {
+    /// The frame was received from the remote peer
+    Incoming,
+
+    /// The frame is being sent to the remote peer
+    Outgoing,
+}
+
+/// Kind of a frame, named after the performative it carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    /// Open
+    Open,
+
+    /// Begin
+    Begin,
+
+    /// Attach
+    Attach,
+
+    /// Flow
+    Flow,
+
+    /// Transfer
+    Transfer,
+
+    /// Disposition
+    Disposition,
+
+    /// Detach
+    Detach,
+
+    /// End
+    End,
+
+    /// Close
+    Close,
+
+    /// Empty frame, which is used as a heartbeat
+    Empty,
+}
+
+impl From<&FrameBody> for FrameKind {
+    fn from(body: &FrameBody) -> Self {
+        match body {
+            FrameBody::Open(_) => Self::Open,
+            FrameBody::Begin(_) => Self::Begin,
+            FrameBody::Attach(_) => Self::Attach,
+            FrameBody::Flow(_) => Self::Flow,
+            FrameBody::Transfer { .. } => Self::Transfer,
+            FrameBody::Disposition(_) => Self::Disposition,
+            FrameBody::Detach(_) => Self::Detach,
+            FrameBody::End(_) => Self::End,
+            FrameBody::Close(_) => Self::Close,
+            FrameBody::Empty => Self::Empty,
+        }
+    }
+}
+
+impl FrameKind {
+    /// Name of the performative, or `"empty"` for a heartbeat frame
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Begin => "begin",
+            Self::Attach => "attach",
+            Self::Flow => "flow",
+            Self::Transfer => "transfer",
+            Self::Disposition => "disposition",
+            Self::Detach => "detach",
+            Self::End => "end",
+            Self::Close => "close",
+            Self::Empty => "empty",
+        }
+    }
+}
+
+/// A frame observed on the connection transport
+///
+/// The event only borrows the frame, so inspecting it does not clone the performative or the
+/// transfer payload. The [`Display`](fmt::Display) impl gives a one-line summary of the key
+/// fields of the performative.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameEvent<'a> {
+    direction: FrameDirection,
+    channel: u16,
+    body: &'a FrameBody,
+}
+
+impl<'a> FrameEvent<'a> {
+    pub(crate) fn new(direction: FrameDirection, channel: u16, body: &'a FrameBody) -> Self {
+        Self {
+            direction,
+            channel,
+            body,
+        }
+    }
+
+    /// Whether the frame is incoming or outgoing
+    pub fn direction(&self) -> FrameDirection {
+        self.direction
+    }
+
+    /// Channel the frame is sent on
+    pub fn channel(&self) -> u16 {
+        self.channel
+    }
+
+    /// Body of the frame
+    pub fn body(&self) -> &'a FrameBody {
+        self.body
+    }
+
+    /// Kind of the frame
+    pub fn kind(&self) -> FrameKind {
+        FrameKind::from(self.body)
+    }
+
+    /// Name of the performative carried by the frame, or `"empty"` for a heartbeat frame
+    pub fn performative_name(&self) -> &'static str {
+        self.kind().as_str()
+    }
+}
+
+impl<'a> fmt::Display for FrameEvent<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = match self.direction {
+            FrameDirection::Incoming => "<-",
+            FrameDirection::Outgoing => "->",
+        };
+        write!(
+            f,
+            "{} [{}] {}",
+            arrow,
+            self.channel,
+            self.performative_name()
+        )?;
+
+        match self.body {
+            FrameBody::Open(open) => write!(
+                f,
+                " container_id={} max_frame_size={} channel_max={}",
+                open.container_id, open.max_frame_size.0, open.channel_max.0
+            ),
+            FrameBody::Begin(begin) => write!(
+                f,
+                " remote_channel={:?} next_outgoing_id={}",
+                begin.remote_channel, begin.next_outgoing_id
+            ),
+            FrameBody::Attach(attach) => write!(
+                f,
+                " name={} handle={} role={:?}",
+                attach.name, attach.handle.0, attach.role
+            ),
+            FrameBody::Flow(flow) => write!(
+                f,
+                " handle={:?} delivery_count={:?} link_credit={:?}",
+                flow.handle.as_ref().map(|h| h.0),
+                flow.delivery_count,
+                flow.link_credit
+            ),
+            FrameBody::Transfer {
+                performative,
+                payload,
+            } => write!(
+                f,
+                " handle={} delivery_id={:?} more={} settled={:?} payload_len={}",
+                performative.handle.0,
+                performative.delivery_id,
+                performative.more,
+                performative.settled,
+                payload.len()
+            ),
+            FrameBody::Disposition(disposition) => write!(
+                f,
+                " role={:?} first={} last={:?} settled={}",
+                disposition.role, disposition.first, disposition.last, disposition.settled
+            ),
+            FrameBody::Detach(detach) => write!(
+                f,
+                " handle={} closed={} error={}",
+                detach.handle.0,
+                detach.closed,
+                detach.error.is_some()
+            ),
+            FrameBody::End(end) => write!(f, " error={}", end.error.is_some()),
+            FrameBody::Close(close) => write!(f, " error={}", close.error.is_some()),
+            FrameBody::Empty => Ok(()),
+        }
+    }
+}
+
+/// Callback invoked for every frame sent or received on a connection
+///
+/// The hook is called from the connection event loop and should therefore return quickly.
+#[derive(Clone)]
+pub struct FrameHook(Arc<dyn Fn(&FrameEvent<'_>) + Send + Sync>);
+
+impl FrameHook {
+    /// Creates a new hook from a closure
+    pub fn new(hook: impl Fn(&FrameEvent<'_>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, event: &FrameEvent<'_>) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for FrameHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrameHook").finish()
+    }
+}
@@ -0,0 +1,38 @@
+//! Counters for the frames and bytes exchanged on a connection
+
+use std::fmt;
+
+use crate::transport;
+
+use super::FrameKind;
+
+/// Receives the counters of a connection, for example to export them to a metrics backend
+///
+/// All methods default to doing nothing, so an implementation only needs to override the
+/// counters it is interested in. The methods are called from the connection event loop and
+/// should therefore return quickly. Only AMQP frames exchanged after the protocol header and SASL
+/// negotiation are counted.
+pub trait ConnectionMetrics: Send + Sync {
+    /// Called after a frame is encoded with the number of bytes written to the transport,
+    /// including the frame header
+    fn on_bytes_sent(&self, _bytes: usize) {}
+
+    /// Called when a frame is read from the transport with its size in bytes, including the frame
+    /// header
+    fn on_bytes_received(&self, _bytes: usize) {}
+
+    /// Called for every frame sent
+    fn on_frame_sent(&self, _kind: FrameKind) {}
+
+    /// Called for every frame received
+    fn on_frame_received(&self, _kind: FrameKind) {}
+
+    /// Called when reading or writing a frame fails
+    fn on_error(&self, _error: &transport::Error) {}
+}
+
+impl fmt::Debug for dyn ConnectionMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionMetrics").finish_non_exhaustive()
+    }
+}
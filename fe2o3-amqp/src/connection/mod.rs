@@ -1,6 +1,6 @@
 //! Implements AMQP1.0 Connection
 
-use std::{cmp::min, collections::HashMap, sync::Arc};
+use std::{cmp::min, collections::HashMap, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 
@@ -12,7 +12,7 @@ use fe2o3_amqp_types::{
 use futures_util::{Sink, SinkExt};
 use slab::Slab;
 use tokio::{
-    sync::{mpsc::Sender, oneshot},
+    sync::{mpsc::Sender, oneshot, watch},
     task::JoinHandle,
 };
 
@@ -39,6 +39,15 @@ mod error;
 pub mod heartbeat;
 pub use error::*;
 
+mod frame_event;
+pub use frame_event::*;
+
+mod metrics;
+pub use metrics::*;
+
+mod unsettled;
+pub(crate) use unsettled::{UnsettledDeliveries, UnsettledPermit};
+
 /// Default max-frame-size.
 ///
 /// Please note that this is different from `MaxFrameSize::default()`.
@@ -66,6 +75,24 @@ pub struct ConnectionHandle<R> {
     // outgoing channel for session
     pub(crate) outgoing: Sender<SessionFrame>,
     pub(crate) session_listener: R,
+
+    // Open frame sent by the remote peer
+    pub(crate) remote_open: Open,
+
+    // idle timeout advertised by the remote peer
+    pub(crate) idle_timeout: Option<Duration>,
+
+    // the smaller of the local and the remote channel-max
+    pub(crate) channel_max: u16,
+
+    // unsettled outgoing deliveries across all sessions and links
+    pub(crate) unsettled_deliveries: UnsettledDeliveries,
+
+    // local connection state published by the event loop
+    pub(crate) state: watch::Receiver<ConnectionState>,
+
+    // how long a best-effort close may take when the handle is dropped without being closed
+    pub(crate) close_on_drop: Option<Duration>,
 }
 
 impl<R> std::fmt::Debug for ConnectionHandle<R> {
@@ -76,6 +103,23 @@ impl<R> std::fmt::Debug for ConnectionHandle<R> {
 
 impl<R> Drop for ConnectionHandle<R> {
     fn drop(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(timeout), false) = (self.close_on_drop, self.is_closed) {
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                let control = self.control.clone();
+                let (_, placeholder) = oneshot::channel();
+                let outcome = std::mem::replace(&mut self.outcome, placeholder);
+                runtime.spawn(async move {
+                    let close = async {
+                        let _ = control.send(ConnectionControl::Close(None)).await;
+                        let _ = outcome.await;
+                    };
+                    let _ = tokio::time::timeout(timeout, close).await;
+                });
+                return;
+            }
+        }
+
         let _ = self.control.try_send(ConnectionControl::Close(None));
     }
 }
@@ -89,6 +133,67 @@ impl<R> ConnectionHandle<R> {
         }
     }
 
+    /// Returns the `container-id` that the remote peer sent in its Open frame
+    ///
+    /// Unlike [`Connection::remote_container_id`], this is always available because the handle is
+    /// only created after the remote Open frame is received
+    pub fn remote_container_id(&self) -> &str {
+        &self.remote_open.container_id
+    }
+
+    /// Returns the Open frame sent by the remote peer
+    ///
+    /// This exposes everything the remote peer advertised when the connection was opened, eg.
+    /// its `max-frame-size`, `channel-max`, capabilities and `properties`.
+    pub fn remote_open(&self) -> &Open {
+        &self.remote_open
+    }
+
+    /// Returns a snapshot of the local connection state
+    pub fn state(&self) -> ConnectionState {
+        self.state.borrow().clone()
+    }
+
+    /// Returns a receiver that is notified whenever the local connection state changes
+    ///
+    /// The receiver only keeps the latest state, so intermediate states may be skipped if the
+    /// connection changes state faster than the receiver is polled. The last state (eg.
+    /// [`ConnectionState::End`]) is still available after the event loop has stopped.
+    pub fn state_watcher(&self) -> watch::Receiver<ConnectionState> {
+        self.state.clone()
+    }
+
+    /// Returns the idle timeout that the remote peer advertised in its Open frame
+    ///
+    /// An empty frame is sent every half of this duration to keep the connection alive. This is
+    /// `None` if the remote peer does not have an idle timeout.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// Returns the channel-max in effect for the connection, which is the smaller of the local
+    /// and the remote channel-max
+    ///
+    /// Beginning a session on a channel beyond this limit fails with
+    /// [`BeginError::LocalChannelMaxReached`](crate::session::BeginError::LocalChannelMaxReached)
+    pub fn channel_max(&self) -> u16 {
+        self.channel_max
+    }
+
+    /// Returns the number of outgoing deliveries that are not yet settled across all sessions
+    /// and links on this connection
+    pub fn unsettled_deliveries(&self) -> usize {
+        self.unsettled_deliveries.count()
+    }
+
+    /// Returns the maximum number of unsettled outgoing deliveries allowed on this connection
+    ///
+    /// Sending an unsettled delivery will wait until another delivery is settled if this limit is
+    /// reached.
+    pub fn max_unsettled_deliveries(&self) -> Option<usize> {
+        self.unsettled_deliveries.limit()
+    }
+
     cfg_not_wasm32! {
         /// Close the connection
         ///
@@ -131,6 +236,30 @@ impl<R> ConnectionHandle<R> {
         }
     }
 
+    /// Checks that the connection event loop is still servicing requests
+    ///
+    /// An empty frame is sent to the remote peer by the event loop, and this returns once the event
+    /// loop has sent it. Unlike the empty frames sent for the idle timeout, this tells a wedged
+    /// event loop apart from an idle but healthy connection.
+    ///
+    /// [`PingError::Timeout`] is returned if the event loop does not respond within `timeout`, and
+    /// [`PingError::Closed`] is returned if the event loop has stopped or the connection is
+    /// closing.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn ping(&self, timeout: Duration) -> Result<(), PingError> {
+        let ping = async {
+            let (resp, rx) = oneshot::channel();
+            self.control
+                .send(ConnectionControl::Ping(resp))
+                .await
+                .map_err(|_| PingError::Closed)?;
+            rx.await.map_err(|_| PingError::Closed)
+        };
+        tokio::time::timeout(timeout, ping)
+            .await
+            .map_err(|_| PingError::Timeout)?
+    }
+
     /// Returns when the underlying event loop has stopped
     ///
     /// An `Error::IllegalState` will be returned if this is called after executing any of
@@ -380,6 +509,10 @@ pub struct Connection {
 
     // mutually agreed channel max
     pub(crate) agreed_channel_max: u16,
+
+    // observers of local state transitions
+    pub(crate) state_tx: watch::Sender<ConnectionState>,
+    pub(crate) state_hook: Option<StateHook>,
 }
 
 /* ------------------------------- Public API ------------------------------- */
@@ -389,6 +522,36 @@ impl Connection {
         builder::Builder::new()
     }
 
+    /// Returns the Open frame sent by the remote peer
+    ///
+    /// This will be `None` if the remote Open frame has not been received yet
+    pub fn remote_open(&self) -> Option<&Open> {
+        self.remote_open.as_ref()
+    }
+
+    /// Returns the `container-id` of the remote peer
+    ///
+    /// This will be `None` if the remote Open frame has not been received yet. Use
+    /// [`ConnectionHandle::remote_container_id`] on an opened connection.
+    pub fn remote_container_id(&self) -> Option<&str> {
+        self.remote_open
+            .as_ref()
+            .map(|open| open.container_id.as_str())
+    }
+
+    /// Returns the idle timeout advertised by the remote peer
+    ///
+    /// An empty frame is sent every half of this duration to keep the connection alive. This will
+    /// be `None` if the remote Open frame has not been received yet or if the remote peer does not
+    /// have an idle timeout.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.remote_open
+            .as_ref()
+            .and_then(|open| open.idle_time_out)
+            .filter(|millis| *millis > 0)
+            .map(|millis| Duration::from_millis(millis as u64))
+    }
+
     cfg_not_wasm32! {
         /// Negotiate and open a [`Connection`] with the default configuration
         ///
@@ -484,6 +647,7 @@ impl Connection {
         local_open: Open,
     ) -> Self {
         let agreed_channel_max = local_open.channel_max.0;
+        let (state_tx, _) = watch::channel(local_state.clone());
         Self {
             // control,
             local_state,
@@ -493,8 +657,42 @@ impl Connection {
 
             remote_open: None,
             agreed_channel_max,
+            state_tx,
+            state_hook: None,
         }
     }
+
+    pub(crate) fn state_watcher(&self) -> watch::Receiver<ConnectionState> {
+        self.state_tx.subscribe()
+    }
+
+    pub(crate) fn set_local_state(&mut self, state: ConnectionState) {
+        if let Some(hook) = &self.state_hook {
+            hook.call(&state);
+        }
+        self.state_tx.send_replace(state.clone());
+        self.local_state = state;
+    }
+}
+
+/// Callback invoked on every local state transition of a connection
+#[derive(Clone)]
+pub(crate) struct StateHook(Arc<dyn Fn(&ConnectionState) + Send + Sync>);
+
+impl StateHook {
+    pub(crate) fn new(hook: impl Fn(&ConnectionState) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, state: &ConnectionState) {
+        (self.0)(state)
+    }
+}
+
+impl std::fmt::Debug for StateHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("StateHook").finish()
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -519,6 +717,10 @@ impl endpoint::Connection for Connection {
         &self.local_open
     }
 
+    fn remote_open(&self) -> Option<&Open> {
+        self.remote_open.as_ref()
+    }
+
     fn allocate_session(
         &mut self,
         tx: Sender<SessionIncomingItem>,
@@ -566,9 +768,9 @@ impl endpoint::Connection for Connection {
         log::trace!("frame = {:?}", open);
 
         match &self.local_state {
-            ConnectionState::HeaderExchange => self.local_state = ConnectionState::OpenReceived,
-            ConnectionState::OpenSent => self.local_state = ConnectionState::Opened,
-            ConnectionState::ClosePipe => self.local_state = ConnectionState::CloseSent,
+            ConnectionState::HeaderExchange => self.set_local_state(ConnectionState::OpenReceived),
+            ConnectionState::OpenSent => self.set_local_state(ConnectionState::Opened),
+            ConnectionState::ClosePipe => self.set_local_state(ConnectionState::CloseSent),
             _ => return Err(Self::OpenError::IllegalState),
         }
 
@@ -639,7 +841,7 @@ impl endpoint::Connection for Connection {
             | ConnectionState::OpenClosePipe
             | ConnectionState::OpenReceived
             | ConnectionState::OpenSent => {
-                self.local_state = ConnectionState::CloseReceived;
+                self.set_local_state(ConnectionState::CloseReceived);
 
                 match close.error {
                     Some(error) => Err(CloseError::RemoteClosedWithError(error)),
@@ -647,7 +849,7 @@ impl endpoint::Connection for Connection {
                 }
             }
             ConnectionState::CloseSent | ConnectionState::Discarding => {
-                self.local_state = ConnectionState::End;
+                self.set_local_state(ConnectionState::End);
 
                 match close.error {
                     Some(error) => Err(CloseError::RemoteClosedWithError(error)),
@@ -674,9 +876,9 @@ impl endpoint::Connection for Connection {
 
         // change local state after successfully sending the frame
         match &self.local_state {
-            ConnectionState::HeaderExchange => self.local_state = ConnectionState::OpenSent,
-            ConnectionState::OpenReceived => self.local_state = ConnectionState::Opened,
-            ConnectionState::HeaderSent => self.local_state = ConnectionState::OpenPipe,
+            ConnectionState::HeaderExchange => self.set_local_state(ConnectionState::OpenSent),
+            ConnectionState::OpenReceived => self.set_local_state(ConnectionState::Opened),
+            ConnectionState::HeaderSent => self.set_local_state(ConnectionState::OpenPipe),
             _ => return Err(Self::OpenError::IllegalState),
         }
 
@@ -699,17 +901,17 @@ impl endpoint::Connection for Connection {
 
         match &self.local_state {
             ConnectionState::Opened => match error_is_some {
-                true => self.local_state = ConnectionState::Discarding,
-                false => self.local_state = ConnectionState::CloseSent,
+                true => self.set_local_state(ConnectionState::Discarding),
+                false => self.set_local_state(ConnectionState::CloseSent),
             },
-            ConnectionState::CloseReceived => self.local_state = ConnectionState::End,
+            ConnectionState::CloseReceived => self.set_local_state(ConnectionState::End),
             ConnectionState::OpenSent => match error_is_some {
-                true => self.local_state = ConnectionState::Discarding,
-                false => self.local_state = ConnectionState::ClosePipe,
+                true => self.set_local_state(ConnectionState::Discarding),
+                false => self.set_local_state(ConnectionState::ClosePipe),
             },
             ConnectionState::OpenPipe => match error_is_some {
-                true => self.local_state = ConnectionState::Discarding,
-                false => self.local_state = ConnectionState::OpenClosePipe,
+                true => self.set_local_state(ConnectionState::Discarding),
+                false => self.set_local_state(ConnectionState::OpenClosePipe),
             },
             _ => return Err(CloseError::IllegalState),
         }
@@ -794,3 +996,703 @@ impl Connection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        performatives::{ChannelMax, MaxFrameSize, Open},
+        states::ConnectionState,
+    };
+
+    use crate::endpoint::{self, IncomingChannel};
+
+    use super::{Connection, ConnectionMetrics, FrameDirection, FrameKind};
+
+    fn open(container_id: &str) -> Open {
+        Open {
+            container_id: container_id.to_string(),
+            hostname: None,
+            max_frame_size: MaxFrameSize::default(),
+            channel_max: ChannelMax::default(),
+            idle_time_out: None,
+            outgoing_locales: None,
+            incoming_locales: None,
+            offered_capabilities: None,
+            desired_capabilities: None,
+            properties: None,
+        }
+    }
+
+    #[test]
+    fn remote_container_id_is_stored_on_incoming_open() {
+        let mut connection = Connection::new(ConnectionState::OpenSent, open("local"));
+        assert!(connection.remote_container_id().is_none());
+
+        endpoint::Connection::on_incoming_open(
+            &mut connection,
+            IncomingChannel(0),
+            open("remote-peer"),
+        )
+        .unwrap();
+        assert_eq!(connection.remote_container_id(), Some("remote-peer"));
+    }
+
+    #[test]
+    fn idle_timeout_is_taken_from_remote_open() {
+        let mut connection = Connection::new(ConnectionState::OpenSent, open("local"));
+        assert!(connection.idle_timeout().is_none());
+
+        let mut remote_open = open("remote-peer");
+        remote_open.idle_time_out = Some(0);
+        connection.remote_open = Some(remote_open.clone());
+        assert!(connection.idle_timeout().is_none());
+
+        remote_open.idle_time_out = Some(30_000);
+        connection.remote_open = Some(remote_open);
+        assert_eq!(
+            connection.idle_timeout(),
+            Some(std::time::Duration::from_secs(30))
+        );
+    }
+
+    #[tokio::test]
+    async fn open_with_stream_against_scripted_peer() {
+        use fe2o3_amqp_types::performatives::Close;
+        use futures_util::{SinkExt, StreamExt};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::{
+            frames::amqp::{Frame, FrameBody},
+            transport::Transport,
+        };
+
+        let (client, mut server) = tokio::io::duplex(4096);
+
+        let peer = tokio::spawn(async move {
+            // Protocol header exchange
+            let mut header = [0u8; 8];
+            server.read_exact(&mut header).await.unwrap();
+            assert_eq!(&header, b"AMQP\x00\x01\x00\x00");
+            server.write_all(&header).await.unwrap();
+
+            let mut transport: Transport<_, Frame> = Transport::bind(server, 512, None);
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Open(open) => assert_eq!(open.container_id, "client"),
+                body => panic!("Expecting open, found {:?}", body),
+            }
+            let frame = Frame::new(0u16, FrameBody::Open(open("scripted-peer")));
+            transport.send(frame).await.unwrap();
+
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Close(close) => assert!(close.error.is_none()),
+                body => panic!("Expecting close, found {:?}", body),
+            }
+            let frame = Frame::new(0u16, FrameBody::Close(Close { error: None }));
+            transport.send(frame).await.unwrap();
+        });
+
+        let mut connection = crate::Connection::builder()
+            .container_id("client")
+            .open_with_stream(client)
+            .await
+            .unwrap();
+        assert_eq!(connection.remote_container_id(), "scripted-peer");
+
+        connection.close().await.unwrap();
+        peer.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn close_with_error_sends_error_and_discards_until_remote_close() {
+        use std::sync::{Arc, Mutex};
+
+        use fe2o3_amqp_types::{
+            definitions::{AmqpError, Error},
+            performatives::{Close, Detach},
+        };
+        use futures_util::{SinkExt, StreamExt};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::{
+            frames::amqp::{Frame, FrameBody},
+            transport::Transport,
+        };
+
+        let (client, mut server) = tokio::io::duplex(4096);
+
+        let peer = tokio::spawn(async move {
+            let mut header = [0u8; 8];
+            server.read_exact(&mut header).await.unwrap();
+            server.write_all(&header).await.unwrap();
+
+            let mut transport: Transport<_, Frame> = Transport::bind(server, 512, None);
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Open(_) => {}
+                body => panic!("Expecting open, found {:?}", body),
+            }
+            let frame = Frame::new(0u16, FrameBody::Open(open("scripted-peer")));
+            transport.send(frame).await.unwrap();
+
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Close(close) => {
+                    let error = close.error.expect("Expecting an error");
+                    assert_eq!(error.condition, AmqpError::InternalError.into());
+                    assert_eq!(error.description.as_deref(), Some("fatal condition"));
+                }
+                body => panic!("Expecting close, found {:?}", body),
+            }
+
+            // A frame that was in flight is discarded by the closing peer
+            let detach = Detach {
+                handle: 0.into(),
+                closed: true,
+                error: None,
+            };
+            let frame = Frame::new(0u16, FrameBody::Detach(detach));
+            transport.send(frame).await.unwrap();
+
+            let frame = Frame::new(0u16, FrameBody::Close(Close { error: None }));
+            transport.send(frame).await.unwrap();
+        });
+
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let states_clone = states.clone();
+        let mut connection = crate::Connection::builder()
+            .container_id("client")
+            .on_state_change(move |state| states_clone.lock().unwrap().push(state.clone()))
+            .open_with_stream(client)
+            .await
+            .unwrap();
+
+        let error = Error::new(
+            AmqpError::InternalError,
+            Some(String::from("fatal condition")),
+            None,
+        );
+        connection.close_with_error(error).await.unwrap();
+        peer.await.unwrap();
+
+        let states = states.lock().unwrap();
+        assert_eq!(
+            &states[states.len() - 2..],
+            &[ConnectionState::Discarding, ConnectionState::End]
+        );
+    }
+
+    #[tokio::test]
+    async fn dropping_connection_with_close_on_drop_sends_close() {
+        use std::time::Duration;
+
+        use fe2o3_amqp_types::performatives::Close;
+        use futures_util::{SinkExt, StreamExt};
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            sync::oneshot,
+        };
+
+        use crate::{
+            control::ConnectionControl,
+            frames::amqp::{Frame, FrameBody},
+            transport::Transport,
+        };
+
+        let (client, mut server) = tokio::io::duplex(4096);
+
+        let peer = tokio::spawn(async move {
+            let mut header = [0u8; 8];
+            server.read_exact(&mut header).await.unwrap();
+            server.write_all(&header).await.unwrap();
+
+            let mut transport: Transport<_, Frame> = Transport::bind(server, 512, None);
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Open(_) => {}
+                body => panic!("Expecting open, found {:?}", body),
+            }
+            let frame = Frame::new(0u16, FrameBody::Open(open("scripted-peer")));
+            transport.send(frame).await.unwrap();
+
+            match transport.next().await.unwrap().unwrap().body {
+                FrameBody::Close(close) => assert!(close.error.is_none()),
+                body => panic!("Expecting close, found {:?}", body),
+            }
+            let frame = Frame::new(0u16, FrameBody::Close(Close { error: None }));
+            transport.send(frame).await.unwrap();
+        });
+
+        let connection = crate::Connection::builder()
+            .container_id("client")
+            .close_on_drop(Duration::from_millis(500))
+            .open_with_stream(client)
+            .await
+            .unwrap();
+
+        // The event loop cannot drain the control channel until the test yields, so the Close
+        // can only be queued by waiting for capacity
+        while connection
+            .control
+            .try_send(ConnectionControl::GetMaxFrameSize(oneshot::channel().0))
+            .is_ok()
+        {}
+        drop(connection);
+
+        tokio::time::timeout(Duration::from_secs(1), peer)
+            .await
+            .expect("Expecting a close before the timeout")
+            .unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn begin_beyond_agreed_channel_max_is_rejected() {
+        use crate::{
+            acceptor::{ConnectionAcceptor, SessionAcceptor},
+            session::BeginError,
+            Session,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = ConnectionAcceptor::builder()
+                .container_id("server")
+                .channel_max(5)
+                .build()
+                .accept(server)
+                .await
+                .unwrap();
+            assert_eq!(connection.channel_max(), 1);
+            let session_acceptor = SessionAcceptor::new();
+            let _first = session_acceptor.accept(&mut connection).await.unwrap();
+            let _second = session_acceptor.accept(&mut connection).await.unwrap();
+            assert!(matches!(
+                connection.on_close().await,
+                Err(super::Error::RemoteClosed)
+            ));
+        });
+
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .channel_max(1)
+            .open_with_stream(client)
+            .await
+            .unwrap();
+        assert_eq!(connection.channel_max(), 1);
+
+        let mut first = Session::begin(&mut connection).await.unwrap();
+        let mut second = Session::begin(&mut connection).await.unwrap();
+        let result = Session::begin(&mut connection).await;
+        assert!(matches!(result, Err(BeginError::LocalChannelMaxReached)));
+
+        first.end().await.unwrap();
+        second.end().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn ping_is_serviced_by_event_loop() {
+        use std::time::Duration;
+
+        use crate::test_util;
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            let _ = connection.on_close().await;
+        });
+
+        let mut connection = test_util::open_connection(client).await;
+        connection.ping(Duration::from_secs(1)).await.unwrap();
+        connection.ping(Duration::from_secs(1)).await.unwrap();
+
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn ping_fails_after_event_loop_stopped() {
+        use std::time::Duration;
+
+        use crate::{connection::PingError, test_util};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            let _ = connection.on_close().await;
+        });
+
+        let mut connection = test_util::open_connection(client).await;
+        connection.handle.abort();
+        assert!((&mut connection.handle).await.unwrap_err().is_cancelled());
+
+        assert!(matches!(
+            connection.ping(Duration::from_secs(1)).await,
+            Err(PingError::Closed)
+        ));
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn remote_open_is_exposed_on_handle() {
+        use fe2o3_amqp_types::primitives::{OrderedMap, Symbol, Value};
+
+        use crate::{acceptor::ConnectionAcceptor, test_util};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut properties = OrderedMap::new();
+            properties.insert(Symbol::from("product"), Value::from("mock-broker"));
+            let mut connection = ConnectionAcceptor::builder()
+                .container_id("mock-broker-1")
+                .max_frame_size(4096)
+                .channel_max(9)
+                .idle_time_out(60_000u32)
+                .add_offered_capabilities("ANONYMOUS-RELAY")
+                .add_desired_capabilities("DELAYED-DELIVERY")
+                .properties(properties)
+                .build()
+                .accept(server)
+                .await
+                .unwrap();
+            assert_eq!(connection.remote_container_id(), "client");
+            assert!(matches!(
+                connection.on_close().await,
+                Err(super::Error::RemoteClosed)
+            ));
+        });
+
+        let mut connection = test_util::open_connection(client).await;
+
+        let remote_open = connection.remote_open();
+        assert_eq!(connection.remote_container_id(), "mock-broker-1");
+        assert_eq!(remote_open.container_id, "mock-broker-1");
+        assert_eq!(remote_open.max_frame_size.0, 4096);
+        assert_eq!(remote_open.channel_max.0, 9);
+        assert_eq!(remote_open.idle_time_out, Some(60_000));
+        assert_eq!(
+            remote_open.offered_capabilities.as_ref().unwrap().0,
+            vec![Symbol::from("ANONYMOUS-RELAY")]
+        );
+        assert_eq!(
+            remote_open.desired_capabilities.as_ref().unwrap().0,
+            vec![Symbol::from("DELAYED-DELIVERY")]
+        );
+        assert_eq!(
+            remote_open
+                .properties
+                .as_ref()
+                .unwrap()
+                .get(&Symbol::from("product")),
+            Some(&Value::from("mock-broker"))
+        );
+
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn outgoing_open_carries_properties_and_capabilities() {
+        use fe2o3_amqp_types::primitives::{OrderedMap, Symbol, Value};
+
+        use crate::test_util;
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+
+            let remote_open = connection.remote_open().clone();
+            assert!(matches!(
+                connection.on_close().await,
+                Err(super::Error::RemoteClosed)
+            ));
+            remote_open
+        });
+
+        let mut properties = OrderedMap::new();
+        properties.insert(Symbol::from("product"), Value::from("fe2o3-amqp"));
+        properties.insert(Symbol::from("version"), Value::from("0.8.14"));
+        properties.insert(Symbol::from("platform"), Value::from("rust"));
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .properties(properties.clone())
+            .set_offered_capabilities(vec![Symbol::from("ANONYMOUS-RELAY")])
+            .set_desired_capabilities(vec![Symbol::from("DELAYED-DELIVERY")])
+            .open_with_stream(client)
+            .await
+            .unwrap();
+        connection.close().await.unwrap();
+
+        let remote_open = server.await.unwrap();
+        assert_eq!(remote_open.properties, Some(properties));
+        assert_eq!(
+            remote_open.offered_capabilities.unwrap().0,
+            vec![Symbol::from("ANONYMOUS-RELAY")]
+        );
+        assert_eq!(
+            remote_open.desired_capabilities.unwrap().0,
+            vec![Symbol::from("DELAYED-DELIVERY")]
+        );
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn frame_hook_fires_for_open_exchange() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::acceptor::ConnectionAcceptor;
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = ConnectionAcceptor::new("mock-broker")
+                .accept(server)
+                .await
+                .unwrap();
+            assert!(matches!(
+                connection.on_close().await,
+                Err(super::Error::RemoteClosed)
+            ));
+        });
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .on_frame(move |event| {
+                events_clone.lock().unwrap().push((
+                    event.direction(),
+                    event.channel(),
+                    event.performative_name(),
+                    event.to_string(),
+                ))
+            })
+            .open_with_stream(client)
+            .await
+            .unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .map(|(direction, channel, name, _)| (*direction, *channel, *name))
+                .collect::<Vec<_>>(),
+            vec![
+                (FrameDirection::Outgoing, 0, "open"),
+                (FrameDirection::Incoming, 0, "open"),
+                (FrameDirection::Outgoing, 0, "close"),
+                (FrameDirection::Incoming, 0, "close"),
+            ]
+        );
+        assert!(events[0].3.contains("container_id=client"));
+        assert!(events[1].3.contains("container_id=mock-broker"));
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn metrics_count_frames_of_open_exchange() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        };
+
+        use crate::test_util;
+
+        #[derive(Default)]
+        struct CountingMetrics {
+            bytes_sent: AtomicUsize,
+            bytes_received: AtomicUsize,
+            frames_sent: Mutex<Vec<FrameKind>>,
+            frames_received: Mutex<Vec<FrameKind>>,
+            errors: AtomicUsize,
+        }
+
+        impl ConnectionMetrics for CountingMetrics {
+            fn on_bytes_sent(&self, bytes: usize) {
+                self.bytes_sent.fetch_add(bytes, Ordering::Relaxed);
+            }
+
+            fn on_bytes_received(&self, bytes: usize) {
+                self.bytes_received.fetch_add(bytes, Ordering::Relaxed);
+            }
+
+            fn on_frame_sent(&self, kind: FrameKind) {
+                self.frames_sent.lock().unwrap().push(kind);
+            }
+
+            fn on_frame_received(&self, kind: FrameKind) {
+                self.frames_received.lock().unwrap().push(kind);
+            }
+
+            fn on_error(&self, _error: &crate::transport::Error) {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            assert!(matches!(
+                connection.on_close().await,
+                Err(super::Error::RemoteClosed)
+            ));
+        });
+
+        let metrics = Arc::new(CountingMetrics::default());
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .metrics(metrics.clone())
+            .open_with_stream(client)
+            .await
+            .unwrap();
+
+        assert_eq!(*metrics.frames_sent.lock().unwrap(), vec![FrameKind::Open]);
+        assert_eq!(
+            *metrics.frames_received.lock().unwrap(),
+            vec![FrameKind::Open]
+        );
+        assert!(metrics.bytes_sent.load(Ordering::Relaxed) > 8);
+        assert!(metrics.bytes_received.load(Ordering::Relaxed) > 8);
+
+        connection.close().await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(
+            *metrics.frames_sent.lock().unwrap(),
+            vec![FrameKind::Open, FrameKind::Close]
+        );
+        assert_eq!(
+            *metrics.frames_received.lock().unwrap(),
+            vec![FrameKind::Open, FrameKind::Close]
+        );
+        assert_eq!(metrics.errors.load(Ordering::Relaxed), 0);
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn state_transitions_are_observable() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::test_util;
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            assert_eq!(connection.state(), ConnectionState::Opened);
+            assert!(matches!(
+                connection.on_close().await,
+                Err(super::Error::RemoteClosed)
+            ));
+        });
+
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let states_clone = states.clone();
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .on_state_change(move |state| states_clone.lock().unwrap().push(state.clone()))
+            .open_with_stream(client)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *states.lock().unwrap(),
+            vec![
+                ConnectionState::Start,
+                ConnectionState::HeaderExchange,
+                ConnectionState::OpenSent,
+                ConnectionState::Opened,
+            ]
+        );
+        assert_eq!(connection.state(), ConnectionState::Opened);
+
+        let mut watcher = connection.state_watcher();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(connection.state(), ConnectionState::End);
+        assert_eq!(*watcher.borrow_and_update(), ConnectionState::End);
+        assert_eq!(
+            &states.lock().unwrap()[4..],
+            &[ConnectionState::CloseSent, ConnectionState::End]
+        );
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn send_awaits_capacity_when_unsettled_limit_is_reached() {
+        use std::time::Duration;
+
+        use tokio::sync::oneshot;
+
+        use crate::{acceptor::LinkAcceptor, test_util, Sender, Session};
+
+        let (client, server) = tokio::io::duplex(4096);
+        let (settle_tx, settle_rx) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let first = receiver.recv::<String>().await.unwrap();
+            settle_rx.await.unwrap();
+            receiver.accept(&first).await.unwrap();
+
+            let second = receiver.recv::<String>().await.unwrap();
+            receiver.accept(&second).await.unwrap();
+
+            // The client closes the link, session and connection
+            assert!(receiver.recv::<String>().await.is_err());
+            assert!(matches!(
+                session.on_end().await,
+                Err(crate::session::Error::RemoteEnded)
+            ));
+            assert!(matches!(
+                connection.on_close().await,
+                Err(super::Error::RemoteClosed)
+            ));
+        });
+
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .max_unsettled_deliveries(1)
+            .open_with_stream(client)
+            .await
+            .unwrap();
+        assert_eq!(connection.max_unsettled_deliveries(), Some(1));
+        let mut session = Session::begin(&mut connection).await.unwrap();
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let first = sender.send_batchable("first").await.unwrap();
+        assert_eq!(connection.unsettled_deliveries(), 1);
+
+        // The limit is reached, so the second send waits for the first to be settled
+        let blocked =
+            tokio::time::timeout(Duration::from_millis(100), sender.send_batchable("second")).await;
+        assert!(blocked.is_err());
+        assert_eq!(connection.unsettled_deliveries(), 1);
+
+        settle_tx.send(()).unwrap();
+        first.await.unwrap().accepted_or("Not accepted").unwrap();
+        assert_eq!(connection.unsettled_deliveries(), 0);
+
+        let second = sender.send_batchable("second").await.unwrap();
+        second.await.unwrap().accepted_or("Not accepted").unwrap();
+        assert_eq!(connection.unsettled_deliveries(), 0);
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,104 @@
+//! Connection-wide accounting of unsettled outgoing deliveries
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Shared by all sessions and links on the same connection
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UnsettledDeliveries {
+    count: Arc<AtomicUsize>,
+    limit: Option<(usize, Arc<Semaphore>)>,
+}
+
+impl UnsettledDeliveries {
+    /// A limit of zero is treated as no limit
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        let limit = limit
+            .filter(|limit| *limit > 0)
+            .map(|limit| (limit, Arc::new(Semaphore::new(limit))));
+        Self {
+            count: Arc::new(AtomicUsize::new(0)),
+            limit,
+        }
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn limit(&self) -> Option<usize> {
+        self.limit.as_ref().map(|(limit, _)| *limit)
+    }
+
+    /// Waits until the number of unsettled deliveries is below the limit
+    ///
+    /// # Cancel safety
+    ///
+    /// This is cancel safe because acquiring a semaphore permit is cancel safe
+    pub(crate) async fn acquire(&self) -> UnsettledPermit {
+        let permit = match &self.limit {
+            // The semaphore is never closed
+            Some((_, semaphore)) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        self.count.fetch_add(1, Ordering::AcqRel);
+        UnsettledPermit {
+            count: self.count.clone(),
+            _permit: permit,
+        }
+    }
+
+    /// Returns `None` if the number of unsettled deliveries has reached the limit
+    pub(crate) fn try_acquire(&self) -> Option<UnsettledPermit> {
+        let permit = match &self.limit {
+            Some((_, semaphore)) => Some(semaphore.clone().try_acquire_owned().ok()?),
+            None => None,
+        };
+        self.count.fetch_add(1, Ordering::AcqRel);
+        Some(UnsettledPermit {
+            count: self.count.clone(),
+            _permit: permit,
+        })
+    }
+}
+
+/// Held by an unsettled delivery and released when the delivery is settled
+#[derive(Debug)]
+pub(crate) struct UnsettledPermit {
+    count: Arc<AtomicUsize>,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for UnsettledPermit {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::UnsettledDeliveries;
+
+    #[tokio::test]
+    async fn acquire_waits_for_capacity() {
+        let unsettled = UnsettledDeliveries::new(Some(1));
+        let permit = unsettled.acquire().await;
+        assert_eq!(unsettled.count(), 1);
+
+        let pending = tokio::time::timeout(Duration::from_millis(50), unsettled.acquire()).await;
+        assert!(pending.is_err());
+
+        drop(permit);
+        assert_eq!(unsettled.count(), 0);
+        let _permit = unsettled.acquire().await;
+        assert_eq!(unsettled.count(), 1);
+        assert!(unsettled.try_acquire().is_none());
+        assert_eq!(unsettled.count(), 1);
+    }
+}
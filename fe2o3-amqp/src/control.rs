@@ -31,6 +31,7 @@ pub(crate) enum ConnectionControl {
     },
     DeallocateSession(OutgoingChannel),
     GetMaxFrameSize(oneshot::Sender<usize>),
+    Ping(oneshot::Sender<()>),
 }
 
 impl std::fmt::Display for ConnectionControl {
@@ -43,6 +44,7 @@ impl std::fmt::Display for ConnectionControl {
             } => write!(f, "AllocateSession"),
             Self::DeallocateSession(id) => write!(f, "DeallocateSession({})", id.0),
             Self::GetMaxFrameSize(_) => write!(f, "GetMaxFrameSize"),
+            Self::Ping(_) => write!(f, "Ping"),
         }
     }
 }
@@ -26,6 +26,7 @@ pub(crate) trait Connection {
     fn local_state(&self) -> &Self::State;
     fn local_state_mut(&mut self) -> &mut Self::State;
     fn local_open(&self) -> &Open;
+    fn remote_open(&self) -> Option<&Open>;
 
     // Allocate outgoing channel id and session id to a new session
     fn allocate_session(
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use fe2o3_amqp_types::{
     definitions::{
         DeliveryNumber, DeliveryTag, Error, Fields, MessageFormat, ReceiverSettleMode, Role,
-        SequenceNo,
+        SenderSettleMode, SequenceNo,
     },
     messaging::{DeliveryState, FromBody},
     performatives::{Attach, Detach, Transfer},
@@ -79,6 +79,8 @@ pub(crate) trait LinkExt: Link {
 
     fn unsettled(&self) -> &Self::Unsettled;
 
+    fn snd_settle_mode(&self) -> &SenderSettleMode;
+
     fn rcv_settle_mode(&self) -> &ReceiverSettleMode;
 
     fn target(&self) -> &Option<Self::Target>;
@@ -139,6 +141,10 @@ pub(crate) trait SenderLink: Link + LinkExt {
         // 2. transaction
         // The delivery state should be attached on every transfer if specified
         state: Option<DeliveryState>,
+        // Overrides the receiver settle mode negotiated on attach if set
+        rcv_settle_mode: Option<ReceiverSettleMode>,
+        // The delivery-count is used as the delivery tag if not set
+        delivery_tag: Option<DeliveryTag>,
         batchable: bool,
     ) -> Result<Settlement, Self::TransferError>
     where
@@ -21,12 +21,13 @@
 
 use fe2o3_amqp_types::{
     definitions::{DeliveryTag, Fields, Handle, SequenceNo},
-    messaging::DeliveryState,
     performatives::Flow,
     primitives::{Boolean, UInt},
 };
 use tokio::sync::oneshot;
 
+use crate::link::delivery::DeliveryOutcome;
+
 mod connection;
 pub(crate) use self::connection::*;
 
@@ -147,6 +148,6 @@ pub(crate) enum Settlement {
     Settled(DeliveryTag),
     Unsettled {
         delivery_tag: DeliveryTag,
-        outcome: oneshot::Receiver<Option<DeliveryState>>,
+        outcome: oneshot::Receiver<DeliveryOutcome>,
     },
 }
@@ -30,6 +30,9 @@ pub(crate) trait Session {
 
     fn outgoing_channel(&self) -> OutgoingChannel;
 
+    /// The smaller of the local and the remote handle-max
+    fn agreed_handle_max(&self) -> u32;
+
     // Allocate new local handle for new Link
     fn allocate_link(
         &mut self,
@@ -46,6 +49,9 @@ pub(crate) trait Session {
 
     fn deallocate_link(&mut self, output_handle: OutputHandle);
 
+    /// Drops the relays of all links on the session after the session has ended with `error`
+    fn detach_all_links(&mut self, error: Option<Error>);
+
     fn on_incoming_begin(
         &mut self,
         channel: IncomingChannel,
@@ -66,6 +72,14 @@ pub(crate) trait Session {
         payload: Payload,
     ) -> Result<Option<Disposition>, Self::Error>;
 
+    /// Decrements the incoming-window for an incoming transfer, which is a window violation if
+    /// the window is already exhausted
+    fn consume_incoming_window(&mut self) -> Result<(), Self::Error>;
+
+    /// An `Some(SessionFrame)` means a session flow should be sent to the peer to reopen the
+    /// incoming-window
+    fn replenish_incoming_window(&mut self) -> Option<SessionFrame>;
+
     /// An `Ok(Some(Disposition))` means an immediate disposition should be sent back
     fn on_incoming_disposition(
         &mut self,
@@ -77,6 +77,10 @@ impl FrameEncoder {
         }
     }
 
+    /// Splits a transfer whose payload doesn't fit in a single frame into multiple transfer frames
+    ///
+    /// All but the last frame have `more` set to true. The delivery-id and delivery-tag are only
+    /// carried by the first frame and omitted on the continuation frames.
     fn encode_transfer(
         &self,
         dst: &mut BytesMut,
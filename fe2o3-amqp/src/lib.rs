@@ -194,6 +194,7 @@ pub mod auth;
 pub mod connection;
 pub mod frames;
 pub mod link;
+pub mod payload;
 pub mod sasl_profile;
 pub mod session;
 pub mod transport;
@@ -206,6 +207,13 @@ cfg_transaction! {
     pub mod transaction;
 }
 
+cfg_not_wasm32! {
+    pub mod rpc;
+}
+
+#[cfg(all(test, feature = "acceptor"))]
+pub(crate) mod test_util;
+
 pub mod types {
     //! Re-exporting `fe2o3-amqp-types`
     pub use fe2o3_amqp_types::*;
@@ -218,6 +226,8 @@ pub use link::{
 };
 pub use session::Session;
 
+/// The payload of a transfer. A user-provided [`payload::TransferPayload`] is converted into this
+/// without copying the data
 type Payload = bytes::Bytes;
 
 cfg_not_wasm32! {
@@ -5,12 +5,15 @@ use std::{
     sync::{atomic::AtomicU32, Arc},
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
 use fe2o3_amqp_types::{
     definitions::{Fields, ReceiverSettleMode, SenderSettleMode, SequenceNo},
     messaging::{Source, Target, TargetArchetype},
     primitives::{Symbol, ULong},
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::{mpsc, Notify};
 
 use crate::{
@@ -22,6 +25,7 @@ use crate::{
 };
 
 use super::{
+    delivery::{DeliveryTagGenerator, DeliveryTagStrategy},
     receiver::{CreditMode, ReceiverInner},
     role,
     sender::SenderInner,
@@ -94,6 +98,9 @@ pub struct Builder<Role, T, NameState, SS, TS> {
     /// The extension capabilities the sender can use if the receiver supports them
     pub desired_capabilities: Option<Vec<Symbol>>,
 
+    /// The extension capabilities that the remote peer must offer for the attach to succeed
+    pub required_capabilities: Vec<Symbol>,
+
     /// Link properties
     pub properties: Option<Fields>,
 
@@ -114,6 +121,22 @@ pub struct Builder<Role, T, NameState, SS, TS> {
     /// ```
     pub auto_accept: bool,
 
+    /// How long the receiver waits for the next transfer frame of a multi-frame delivery before
+    /// dropping the partial delivery. `None` waits indefinitely
+    ///
+    /// This field has no effect on Sender
+    #[cfg(not(target_arch = "wasm32"))]
+    pub incomplete_transfer_timeout: Option<Duration>,
+
+    /// How the sender generates delivery tags. This has no effect if a receiver is built
+    delivery_tag_strategy: Option<Arc<Mutex<DeliveryTagStrategy>>>,
+
+    /// Whether the sender increments the `delivery-count` in the message header when an
+    /// unsettled delivery is resent after the link is resumed
+    ///
+    /// This field has no effect on Receiver
+    pub increment_delivery_count_on_resend: bool,
+
     // Type state markers
     role: PhantomData<Role>,
     name_state: PhantomData<NameState>,
@@ -133,6 +156,7 @@ impl<Role, T> Default for Builder<Role, T, WithoutName, WithoutSource, WithoutTa
             max_message_size: Default::default(),
             offered_capabilities: Default::default(),
             desired_capabilities: Default::default(),
+            required_capabilities: Default::default(),
             properties: Default::default(),
 
             buffer_size: DEFAULT_OUTGOING_BUFFER_SIZE,
@@ -143,6 +167,10 @@ impl<Role, T> Default for Builder<Role, T, WithoutName, WithoutSource, WithoutTa
             target_state: PhantomData,
 
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: None,
+            delivery_tag_strategy: None,
+            increment_delivery_count_on_resend: false,
         }
     }
 }
@@ -168,6 +196,19 @@ impl<T, NameState, SS, TS> Builder<role::ReceiverMarker, T, NameState, SS, TS> {
         self.auto_accept = value;
         self
     }
+
+    /// Sets how long the receiver waits for the next transfer frame of a multi-frame delivery
+    ///
+    /// If the next frame doesn't arrive in time, the partial delivery is dropped and
+    /// [`Receiver::recv`] returns `RecvError::IncompleteTransferTimeout`. The timer is reset on
+    /// every received frame.
+    ///
+    /// Default value: `None`, which waits indefinitely
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn incomplete_transfer_timeout(mut self, duration: impl Into<Option<Duration>>) -> Self {
+        self.incomplete_transfer_timeout = duration.into();
+        self
+    }
 }
 
 impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
@@ -183,6 +224,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             max_message_size: self.max_message_size,
             offered_capabilities: self.offered_capabilities,
             desired_capabilities: self.desired_capabilities,
+            required_capabilities: self.required_capabilities,
             buffer_size: self.buffer_size,
             credit_mode: self.credit_mode,
             properties: Default::default(),
@@ -193,6 +235,10 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self.incomplete_transfer_timeout,
+            delivery_tag_strategy: self.delivery_tag_strategy,
+            increment_delivery_count_on_resend: self.increment_delivery_count_on_resend,
         }
     }
 
@@ -208,6 +254,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             max_message_size: self.max_message_size,
             offered_capabilities: self.offered_capabilities,
             desired_capabilities: self.desired_capabilities,
+            required_capabilities: self.required_capabilities,
             buffer_size: self.buffer_size,
             credit_mode: self.credit_mode,
             properties: Default::default(),
@@ -218,6 +265,10 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self.incomplete_transfer_timeout,
+            delivery_tag_strategy: self.delivery_tag_strategy,
+            increment_delivery_count_on_resend: self.increment_delivery_count_on_resend,
         }
     }
 
@@ -233,6 +284,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             max_message_size: self.max_message_size,
             offered_capabilities: self.offered_capabilities,
             desired_capabilities: self.desired_capabilities,
+            required_capabilities: self.required_capabilities,
             buffer_size: self.buffer_size,
             credit_mode: self.credit_mode,
             properties: Default::default(),
@@ -243,6 +295,10 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self.incomplete_transfer_timeout,
+            delivery_tag_strategy: self.delivery_tag_strategy,
+            increment_delivery_count_on_resend: self.increment_delivery_count_on_resend,
         }
     }
 
@@ -270,6 +326,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             max_message_size: self.max_message_size,
             offered_capabilities: self.offered_capabilities,
             desired_capabilities: self.desired_capabilities,
+            required_capabilities: self.required_capabilities,
             properties: self.properties,
             buffer_size: self.buffer_size,
             credit_mode: self.credit_mode,
@@ -280,6 +337,10 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: self.target_state,
 
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self.incomplete_transfer_timeout,
+            delivery_tag_strategy: self.delivery_tag_strategy,
+            increment_delivery_count_on_resend: self.increment_delivery_count_on_resend,
         }
     }
 
@@ -298,6 +359,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             max_message_size: self.max_message_size,
             offered_capabilities: self.offered_capabilities,
             desired_capabilities: self.desired_capabilities,
+            required_capabilities: self.required_capabilities,
             buffer_size: self.buffer_size,
             credit_mode: self.credit_mode,
             properties: Default::default(),
@@ -308,6 +370,10 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: PhantomData,
 
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self.incomplete_transfer_timeout,
+            delivery_tag_strategy: self.delivery_tag_strategy,
+            increment_delivery_count_on_resend: self.increment_delivery_count_on_resend,
         }
     }
 
@@ -327,6 +393,7 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             max_message_size: self.max_message_size,
             offered_capabilities: self.offered_capabilities,
             desired_capabilities: self.desired_capabilities,
+            required_capabilities: self.required_capabilities,
             buffer_size: self.buffer_size,
             credit_mode: self.credit_mode,
             properties: Default::default(),
@@ -337,10 +404,18 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             target_state: PhantomData,
 
             auto_accept: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout: self.incomplete_transfer_timeout,
+            delivery_tag_strategy: self.delivery_tag_strategy,
+            increment_delivery_count_on_resend: self.increment_delivery_count_on_resend,
         }
     }
 
     /// The maximum message size supported by the link endpoint
+    ///
+    /// A value of zero or leaving this unset indicates no limit. A receiver will reject an
+    /// incoming delivery whose (reassembled) payload exceeds this limit and detach the link with
+    /// `amqp:link:message-size-exceeded`
     pub fn max_message_size(mut self, max_size: impl Into<ULong>) -> Self {
         self.max_message_size = Some(max_size.into());
         self
@@ -376,6 +451,21 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
         self
     }
 
+    /// Require the remote peer to support an extension capability
+    ///
+    /// The capability is added to the desired capabilities. If the remote peer does not include
+    /// it in the offered capabilities of its Attach, the link is detached with
+    /// `amqp:not-implemented` and the attach fails with `RequiredCapabilityNotOffered`
+    pub fn require_capability(mut self, capability: impl Into<Symbol>) -> Self {
+        let capability = capability.into();
+        let desired = self.desired_capabilities.get_or_insert_with(Vec::new);
+        if !desired.contains(&capability) {
+            desired.push(capability.clone());
+        }
+        self.required_capabilities.push(capability);
+        self
+    }
+
     /// Link properties
     pub fn properties(mut self, properties: Fields) -> Self {
         self.properties = Some(properties);
@@ -391,8 +481,6 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
     ) -> Link<Role, T, C, M> {
         let local_state = LinkState::Unattached;
 
-        let max_message_size = self.max_message_size.unwrap_or(0);
-
         // Create a link
         Link::<Role, T, C, M> {
             role: PhantomData,
@@ -405,9 +493,11 @@ impl<Role, T, NameState, SS, TS> Builder<Role, T, NameState, SS, TS> {
             rcv_settle_mode: self.rcv_settle_mode,
             source: self.source,
             target: self.target,
-            max_message_size,
+            max_message_size: self.max_message_size,
             offered_capabilities: self.offered_capabilities,
             desired_capabilities: self.desired_capabilities,
+            required_capabilities: self.required_capabilities,
+            remote_offered_capabilities: None,
 
             // delivery_count: self.initial_delivery_count,
             // properties: self.properties,
@@ -426,6 +516,57 @@ impl<T, NameState, SS, TS> Builder<role::SenderMarker, T, NameState, SS, TS> {
         self.initial_delivery_count = count;
         self
     }
+
+    /// Sets how the sender generates the delivery tag of a delivery that doesn't carry an
+    /// explicit tag
+    ///
+    /// If this is not set, the delivery-count of the delivery is used as the delivery tag.
+    /// Generated tags must be unique among the unsettled deliveries of the sender, otherwise
+    /// sending will fail with `SendError::DuplicateDeliveryTag`
+    pub fn delivery_tag_strategy(mut self, strategy: DeliveryTagStrategy) -> Self {
+        self.delivery_tag_strategy = Some(Arc::new(Mutex::new(strategy)));
+        self
+    }
+
+    /// Sets whether the `delivery-count` in the message header is incremented when an unsettled
+    /// delivery is resent after the link is resumed
+    ///
+    /// A header is added to the message if it doesn't carry one. This is disabled by default
+    pub fn increment_delivery_count_on_resend(mut self, value: bool) -> Self {
+        self.increment_delivery_count_on_resend = value;
+        self
+    }
+}
+
+impl<NameState, SS, TS> Builder<role::SenderMarker, Target, NameState, SS, TS> {
+    /// Request the remote peer to dynamically create a node at the target
+    ///
+    /// The address of the target is cleared because it MUST NOT be set when `dynamic` is true.
+    /// The address of the created node is available with [`Sender::target_address()`] once the
+    /// link is attached.
+    pub fn dynamic(
+        mut self,
+        dynamic: bool,
+    ) -> Builder<role::SenderMarker, Target, NameState, SS, WithTarget> {
+        let mut target = self.target.take().unwrap_or_default();
+        target.dynamic = dynamic;
+        if dynamic {
+            target.address = None;
+        }
+        self.target(target)
+    }
+
+    /// Set the desired properties of the dynamically created node
+    ///
+    /// This MUST be left unset if `dynamic` is not set to true
+    pub fn dynamic_node_properties(
+        mut self,
+        properties: impl Into<Fields>,
+    ) -> Builder<role::SenderMarker, Target, NameState, SS, WithTarget> {
+        let mut target = self.target.take().unwrap_or_default();
+        target.dynamic_node_properties = Some(properties.into());
+        self.target(target)
+    }
 }
 
 impl<T, NameState, SS, TS> Builder<role::ReceiverMarker, T, NameState, SS, TS> {
@@ -438,6 +579,35 @@ impl<T, NameState, SS, TS> Builder<role::ReceiverMarker, T, NameState, SS, TS> {
         self.credit_mode = credit_mode;
         self
     }
+
+    /// Request the remote peer to dynamically create a node at the source
+    ///
+    /// The address of the source is cleared because it MUST NOT be set when `dynamic` is true.
+    /// The address of the created node is available with [`Receiver::source_address()`] once
+    /// the link is attached.
+    pub fn dynamic(
+        mut self,
+        dynamic: bool,
+    ) -> Builder<role::ReceiverMarker, T, NameState, WithSource, TS> {
+        let mut source = self.source.take().unwrap_or_default();
+        source.dynamic = dynamic;
+        if dynamic {
+            source.address = None;
+        }
+        self.source(source)
+    }
+
+    /// Set the desired properties of the dynamically created node
+    ///
+    /// This MUST be left unset if `dynamic` is not set to true
+    pub fn dynamic_node_properties(
+        mut self,
+        properties: impl Into<Fields>,
+    ) -> Builder<role::ReceiverMarker, T, NameState, WithSource, TS> {
+        let mut source = self.source.take().unwrap_or_default();
+        source.dynamic_node_properties = Some(properties.into());
+        self.source(source)
+    }
 }
 
 impl Builder<role::SenderMarker, Target, WithName, WithSource, WithTarget> {
@@ -459,6 +629,11 @@ impl Builder<role::SenderMarker, Target, WithName, WithSource, WithTarget> {
         self,
         session: &mut SessionHandle<R>,
     ) -> Result<Sender, SenderAttachError> {
+        if let Some(target) = &self.target {
+            if target.dynamic && target.address.is_some() {
+                return Err(SenderAttachError::TargetAddressIsSomeWhenDynamicIsTrue);
+            }
+        }
         self.attach_inner(session)
             .await
             .map(|inner| Sender { inner })
@@ -496,6 +671,8 @@ where
         session: &mut SessionHandle<R>,
     ) -> Result<SenderInner<SenderLink<T>>, SenderAttachError> {
         let buffer_size = self.buffer_size;
+        let delivery_tag_generator = DeliveryTagGenerator::new(self.delivery_tag_strategy.take());
+        let increment_delivery_count_on_resend = self.increment_delivery_count_on_resend;
         let (incoming_tx, mut incoming_rx) = mpsc::channel::<LinkIncomingItem>(self.buffer_size);
         let outgoing = session.outgoing.clone();
         let (producer, consumer) = self.create_flow_state_containers();
@@ -541,6 +718,9 @@ where
             session: session.control.clone(),
             outgoing,
             incoming: incoming_rx,
+            unsettled_deliveries: session.unsettled_deliveries.clone(),
+            delivery_tag_generator,
+            increment_delivery_count_on_resend,
             // marker: PhantomData,
         };
         Ok(inner)
@@ -565,6 +745,11 @@ impl Builder<role::ReceiverMarker, Target, WithName, WithSource, WithTarget> {
         self,
         session: &mut SessionHandle<R>,
     ) -> Result<Receiver, ReceiverAttachError> {
+        if let Some(source) = &self.source {
+            if source.dynamic && source.address.is_some() {
+                return Err(ReceiverAttachError::SourceAddressIsSomeWhenDynamicIsTrue);
+            }
+        }
         self.attach_inner(session)
             .await
             .map(|inner| Receiver { inner })
@@ -591,7 +776,10 @@ where
             properties: self.properties.take(),
         };
         let flow_state = Arc::new(LinkFlowState::receiver(flow_state_inner));
-        (flow_state.clone(), flow_state)
+        let notifier = Arc::new(Notify::new());
+        let producer = Producer::new(notifier.clone(), flow_state.clone());
+        let consumer = Consumer::new(notifier, flow_state);
+        (producer, consumer)
     }
 
     async fn attach_inner<R>(
@@ -606,6 +794,8 @@ where
         let (relay_flow_state, flow_state) = self.create_flow_state_containers();
         let unsettled = Arc::new(RwLock::new(None));
         let auto_accept = self.auto_accept;
+        #[cfg(not(target_arch = "wasm32"))]
+        let incomplete_transfer_timeout = self.incomplete_transfer_timeout;
 
         let link_relay = LinkRelay::new_receiver(
             incoming_tx,
@@ -647,6 +837,9 @@ where
             outgoing,
             incoming: incoming_rx,
             incomplete_transfer: None,
+            skip_streamed_delivery: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            incomplete_transfer_timeout,
         };
 
         if let CreditMode::Auto(credit) = inner.credit_mode {
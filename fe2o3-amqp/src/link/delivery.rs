@@ -2,21 +2,27 @@
 
 use fe2o3_amqp_types::{
     definitions::{DeliveryNumber, DeliveryTag, Handle, MessageFormat, ReceiverSettleMode},
-    messaging::{Accepted, DeliveryState, Message, Outcome, SerializableBody, MESSAGE_FORMAT},
-    primitives::BinaryRef,
+    messaging::{
+        Accepted, AmqpValue, Body, DeliveryState, Message, Outcome, SerializableBody,
+        MESSAGE_FORMAT,
+    },
+    primitives::{BinaryRef, Value},
 };
 use futures_util::FutureExt;
+use parking_lot::Mutex;
 use pin_project_lite::pin_project;
-use std::{future::Future, marker::PhantomData, task::Poll};
+use serde::de::DeserializeOwned;
+use std::{future::Future, marker::PhantomData, sync::Arc, task::Poll};
 use tokio::sync::oneshot::{self, error::RecvError};
 
 use crate::{
+    connection::UnsettledPermit,
     endpoint::Settlement,
     util::{Sealed, Uninitialized},
 };
 use crate::{util::AsDeliveryState, Payload};
 
-use super::{LinkStateError, SendError};
+use super::{DetachError, LinkStateError, SendError};
 
 /// Delivery information that is needed for disposing a message
 #[derive(Clone)]
@@ -30,7 +36,7 @@ pub struct DeliveryInfo {
     /// Receiver settle mode that is carried by the transfer frame
     pub(crate) rcv_settle_mode: Option<ReceiverSettleMode>,
 
-    _sealed: Sealed,
+    pub(crate) _sealed: Sealed,
 }
 
 impl DeliveryInfo {
@@ -93,6 +99,9 @@ pub struct Delivery<T> {
     pub(crate) message_format: Option<MessageFormat>,
     pub(crate) rcv_settle_mode: Option<ReceiverSettleMode>,
 
+    /// Delivery state sent by the remote sender on the transfer
+    pub(crate) state: Option<DeliveryState>,
+
     pub(crate) message: Message<T>,
 }
 
@@ -118,8 +127,42 @@ impl<T> Delivery<T> {
     }
 
     /// Get the message format
-    pub fn message_format(&self) -> &Option<MessageFormat> {
-        &self.message_format
+    ///
+    /// This is the `message-format` field of the transfer, which defaults to the AMQP 1.0 message
+    /// format ([`MESSAGE_FORMAT`]) if the remote sender did not set it
+    pub fn message_format(&self) -> MessageFormat {
+        self.message_format.unwrap_or(MESSAGE_FORMAT)
+    }
+
+    /// Get the delivery state sent by the remote sender
+    ///
+    /// This is the `state` field of the transfer, which is, for example, a `TransactionalState`
+    /// if the message is transferred as part of a transaction
+    pub fn state(&self) -> &Option<DeliveryState> {
+        &self.state
+    }
+
+    /// Get the `delivery-count` from the message header
+    ///
+    /// This is the number of prior unsuccessful delivery attempts, which is `0` if the message
+    /// doesn't carry a header. A non-zero value indicates that the message is a redelivery
+    pub fn delivery_count(&self) -> u32 {
+        self.message
+            .header
+            .as_ref()
+            .map(|header| header.delivery_count)
+            .unwrap_or_default()
+    }
+
+    /// Get the `first-acquirer` flag from the message header
+    ///
+    /// This is `false` if the message doesn't carry a header
+    pub fn is_first_acquirer(&self) -> bool {
+        self.message
+            .header
+            .as_ref()
+            .map(|header| header.first_acquirer)
+            .unwrap_or_default()
     }
 
     /// Consume the delivery into the message
@@ -152,6 +195,45 @@ impl<T> Delivery<T> {
     }
 }
 
+impl Delivery<Body<Value>> {
+    /// Deserialize the message body into `T`
+    ///
+    /// An `amqp-value` body is deserialized from the contained value. The binary of `data`
+    /// sections (concatenated if there is more than one) is deserialized as an AMQP encoded
+    /// value. An error is returned if the body doesn't match `T` (eg. a described type with a
+    /// different descriptor) or if the body is an `amqp-sequence` or empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let delivery: Delivery<Body<Value>> = receiver.recv().await?;
+    /// let header: Header = delivery.body_into()?;
+    /// ```
+    pub fn body_into<T: DeserializeOwned>(&self) -> Result<T, serde_amqp::Error> {
+        match &self.message.body {
+            // Re-encode the value so that described types are decoded the same way as they
+            // would be off the wire
+            Body::Value(AmqpValue(value)) => serde_amqp::from_slice(&serde_amqp::to_vec(value)?),
+            Body::Data(batch) => match batch.len() {
+                1 => serde_amqp::from_slice(&batch[0].0),
+                _ => {
+                    let buf: Vec<u8> = batch
+                        .iter()
+                        .flat_map(|data| data.0.iter().copied())
+                        .collect();
+                    serde_amqp::from_slice(&buf)
+                }
+            },
+            Body::Sequence(_) => Err(serde_amqp::Error::Message(String::from(
+                "Expecting an amqp-value or data body, found amqp-sequence",
+            ))),
+            Body::Empty => Err(serde_amqp::Error::Message(String::from(
+                "Expecting an amqp-value or data body, found an empty body",
+            ))),
+        }
+    }
+}
+
 impl<T: std::fmt::Display> std::fmt::Display for Delivery<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -191,6 +273,25 @@ pub struct Sendable<T> {
     /// Please note that this field will be neglected if the negotiated
     /// sender settle mode is NOT equal to `SenderSettleMode::Mixed`
     pub settled: Option<bool>,
+
+    /// The initial delivery state carried by the transfer
+    ///
+    /// This is neglected if the message is sent with an explicit state, eg. when posting within a
+    /// transaction
+    pub state: Option<DeliveryState>,
+
+    /// Overrides the receiver settle mode negotiated on link attach for this delivery
+    ///
+    /// If the negotiated value is `ReceiverSettleMode::First`, then it is illegal to set this to
+    /// `ReceiverSettleMode::Second`, and sending will fail with
+    /// [`SendError::IllegalRcvSettleModeInTransfer`]
+    pub rcv_settle_mode: Option<ReceiverSettleMode>,
+
+    /// An explicit delivery tag that overrides the sender's [`DeliveryTagStrategy`]
+    ///
+    /// The tag must be unique among the unsettled deliveries of the sender, otherwise sending
+    /// will fail with [`SendError::DuplicateDeliveryTag`]
+    pub delivery_tag: Option<DeliveryTag>,
 }
 
 impl Sendable<Uninitialized> {
@@ -210,6 +311,9 @@ where
             message: value.into(),
             message_format: MESSAGE_FORMAT,
             settled: None,
+            state: None,
+            rcv_settle_mode: None,
+            delivery_tag: None,
         }
     }
 }
@@ -227,6 +331,15 @@ pub struct Builder<T> {
 
     /// Indicates whether the message is considered settled by the sender
     pub settled: Option<bool>,
+
+    /// The initial delivery state carried by the transfer
+    pub state: Option<DeliveryState>,
+
+    /// Overrides the receiver settle mode negotiated on link attach for this delivery
+    pub rcv_settle_mode: Option<ReceiverSettleMode>,
+
+    /// An explicit delivery tag that overrides the sender's [`DeliveryTagStrategy`]
+    pub delivery_tag: Option<DeliveryTag>,
     // pub batchable: bool,
 }
 
@@ -243,6 +356,9 @@ impl Builder<Uninitialized> {
             message: Uninitialized {},
             message_format: MESSAGE_FORMAT,
             settled: None,
+            state: None,
+            rcv_settle_mode: None,
+            delivery_tag: None,
             // batchable: false,
         }
     }
@@ -255,6 +371,9 @@ impl<State> Builder<State> {
             message: message.into(),
             message_format: self.message_format,
             settled: self.settled,
+            state: self.state,
+            rcv_settle_mode: self.rcv_settle_mode,
+            delivery_tag: self.delivery_tag,
             // batchable: self.batchable,
         }
     }
@@ -272,6 +391,29 @@ impl<State> Builder<State> {
         self.settled = settled.into();
         self
     }
+
+    /// The initial delivery state carried by the transfer
+    pub fn state(mut self, state: impl Into<Option<DeliveryState>>) -> Self {
+        self.state = state.into();
+        self
+    }
+
+    /// Overrides the receiver settle mode negotiated on link attach for this delivery
+    ///
+    /// If the negotiated value is `ReceiverSettleMode::First`, then it is illegal to set this to
+    /// `ReceiverSettleMode::Second`
+    pub fn rcv_settle_mode(mut self, mode: impl Into<Option<ReceiverSettleMode>>) -> Self {
+        self.rcv_settle_mode = mode.into();
+        self
+    }
+
+    /// An explicit delivery tag that overrides the sender's [`DeliveryTagStrategy`]
+    ///
+    /// The tag must be unique among the unsettled deliveries of the sender
+    pub fn delivery_tag(mut self, delivery_tag: impl Into<Option<DeliveryTag>>) -> Self {
+        self.delivery_tag = delivery_tag.into();
+        self
+    }
 }
 
 impl<T> Builder<Message<T>> {
@@ -281,6 +423,9 @@ impl<T> Builder<Message<T>> {
             message: self.message,
             message_format: self.message_format,
             settled: self.settled,
+            state: self.state,
+            rcv_settle_mode: self.rcv_settle_mode,
+            delivery_tag: self.delivery_tag,
             // batchable: self.batchable,
         }
     }
@@ -292,13 +437,78 @@ impl<T> From<Builder<Message<T>>> for Sendable<T> {
     }
 }
 
+/// How the sender generates the delivery tag of a delivery that doesn't carry an explicit tag
+///
+/// If no strategy is set on the link builder, the delivery-count of the delivery is used as the
+/// delivery tag.
+pub enum DeliveryTagStrategy {
+    /// A per-sender counter starting from zero, encoded as eight big-endian bytes
+    Sequential,
+
+    /// Sixteen random bytes of a version 4 UUID
+    Random,
+
+    /// Tags produced by a user supplied closure
+    Custom(Box<dyn FnMut() -> DeliveryTag + Send>),
+}
+
+impl std::fmt::Debug for DeliveryTagStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sequential => write!(f, "Sequential"),
+            Self::Random => write!(f, "Random"),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Generates delivery tags according to a [`DeliveryTagStrategy`]
+///
+/// The strategy is shared so that the link builder stays `Clone`
+#[derive(Debug, Default)]
+pub(crate) struct DeliveryTagGenerator {
+    strategy: Option<Arc<Mutex<DeliveryTagStrategy>>>,
+    sequence: u64,
+}
+
+impl DeliveryTagGenerator {
+    pub(crate) fn new(strategy: Option<Arc<Mutex<DeliveryTagStrategy>>>) -> Self {
+        Self {
+            strategy,
+            sequence: 0,
+        }
+    }
+
+    /// Returns `None` if the delivery-count should be used as the delivery tag
+    pub(crate) fn next_tag(&mut self) -> Option<DeliveryTag> {
+        let mut strategy = self.strategy.as_ref()?.lock();
+        let tag = match &mut *strategy {
+            DeliveryTagStrategy::Sequential => {
+                let tag = DeliveryTag::from(self.sequence.to_be_bytes());
+                self.sequence = self.sequence.wrapping_add(1);
+                tag
+            }
+            DeliveryTagStrategy::Random => DeliveryTag::from(*uuid::Uuid::new_v4().as_bytes()),
+            DeliveryTagStrategy::Custom(f) => f(),
+        };
+        Some(tag)
+    }
+}
+
+/// Settlement of an unsettled delivery, or the error that detached the link before the delivery
+/// was settled
+pub(crate) type DeliveryOutcome = Result<Option<DeliveryState>, DetachError>;
+
 /// An unsettled message stored in the Sender's unsettled map
 #[derive(Debug)]
 pub(crate) struct UnsettledMessage {
     pub(crate) payload: Payload,
     pub(crate) state: Option<DeliveryState>,
     pub(crate) message_format: u32,
-    pub(crate) sender: oneshot::Sender<Option<DeliveryState>>,
+    pub(crate) sender: oneshot::Sender<DeliveryOutcome>,
+
+    // Counts towards the connection-wide unsettled limit until the message is settled
+    pub(crate) permit: Option<UnsettledPermit>,
 }
 
 impl UnsettledMessage {
@@ -306,25 +516,29 @@ impl UnsettledMessage {
         payload: Payload,
         state: Option<DeliveryState>,
         message_format: u32,
-        sender: oneshot::Sender<Option<DeliveryState>>,
+        sender: oneshot::Sender<DeliveryOutcome>,
     ) -> Self {
         Self {
             payload,
             state,
             message_format,
             sender,
+            permit: None,
         }
     }
 
-    pub fn settle(self) -> Result<(), Option<DeliveryState>> {
-        self.sender.send(self.state)
+    pub fn settle(self) -> Result<(), DeliveryOutcome> {
+        self.sender.send(Ok(self.state))
     }
 
-    pub fn settle_with_state(
-        self,
-        state: Option<DeliveryState>,
-    ) -> Result<(), Option<DeliveryState>> {
-        self.sender.send(state)
+    pub fn settle_with_state(self, state: Option<DeliveryState>) -> Result<(), DeliveryOutcome> {
+        self.sender.send(Ok(state))
+    }
+
+    /// Resolves the pending `DeliveryFut` with the error that detached the link
+    pub fn detach(self, error: DetachError) {
+        // The `DeliveryFut` may have been dropped
+        let _ = self.sender.send(Err(error));
     }
 }
 
@@ -341,6 +555,12 @@ impl AsDeliveryState for UnsettledMessage {
 pin_project! {
     /// A future for delivery that can be `.await`ed for the settlement
     /// from receiver
+    ///
+    /// Dropping the future does not cancel the delivery. The delivery stays in the unsettled map
+    /// of the sender without an awaiter and is removed once the receiver settles it or sends a
+    /// terminal outcome, so no memory is held beyond the settlement. The
+    /// [`delivery_tag`](Self::delivery_tag) can be kept to correlate the delivery with
+    /// [`Sender::unsettled_tags`](crate::Sender::unsettled_tags).
     pub struct DeliveryFut<O> {
         #[pin]
         // Reserved for future use on actively sending disposition from Sender
@@ -404,6 +624,21 @@ impl FromOneshotRecvError for SendResult {
     }
 }
 
+/// This trait defines how to interprete the error that detached the link before the delivery
+/// is settled
+///
+/// This is public for compatibility with rust versions <= 1.58.0
+pub trait FromDetachError {
+    /// how to interprete the error that detached the link
+    fn from_detach_error(err: DetachError) -> Self;
+}
+
+impl FromDetachError for SendResult {
+    fn from_detach_error(err: DetachError) -> Self {
+        Err(SendError::Detached(err))
+    }
+}
+
 pub(crate) type SendResult = Result<Outcome, SendError>;
 
 impl FromPreSettled for SendResult {
@@ -438,7 +673,7 @@ impl FromDeliveryState for SendResult {
 
 impl<O> Future for DeliveryFut<O>
 where
-    O: FromPreSettled + FromDeliveryState + FromOneshotRecvError,
+    O: FromPreSettled + FromDeliveryState + FromOneshotRecvError + FromDetachError,
 {
     type Output = O;
 
@@ -459,8 +694,9 @@ where
                     Poll::Pending => Poll::Pending,
                     Poll::Ready(result) => {
                         match result {
-                            Ok(Some(state)) => Poll::Ready(O::from_delivery_state(state)),
-                            Ok(None) => Poll::Ready(O::from_none()),
+                            Ok(Ok(Some(state))) => Poll::Ready(O::from_delivery_state(state)),
+                            Ok(Ok(None)) => Poll::Ready(O::from_none()),
+                            Ok(Err(err)) => Poll::Ready(O::from_detach_error(err)),
                             Err(err) => {
                                 // If the sender is dropped, there is likely issues with the connection
                                 // or the session, and thus the error should propagate to the user
@@ -477,12 +713,27 @@ where
 #[cfg(test)]
 mod tests {
     use fe2o3_amqp_types::{
-        messaging::{AmqpValue, Body, Data, Message},
-        primitives::Binary,
+        definitions::{DeliveryTag, Handle},
+        messaging::{AmqpValue, Batch, Body, Data, Header, Message, Priority, Properties},
+        primitives::{Binary, Value},
     };
 
     use crate::Sendable;
 
+    use super::Delivery;
+
+    fn delivery_with_body(body: Body<Value>) -> Delivery<Body<Value>> {
+        Delivery {
+            link_output_handle: Handle(0),
+            delivery_id: 0,
+            delivery_tag: DeliveryTag::from([0u8; 4]),
+            message_format: None,
+            rcv_settle_mode: None,
+            state: None,
+            message: Message::builder().body(body).build(),
+        }
+    }
+
     struct Foo {}
 
     impl From<Foo> for Message<Data> {
@@ -522,4 +773,53 @@ mod tests {
         let sendable = Sendable::from(value);
         assert_eq!(sendable.message.body, Data(Binary::from("Foo")));
     }
+
+    #[test]
+    fn body_into_deserializes_value_body() {
+        let header = Header {
+            durable: true,
+            priority: Priority(7),
+            ..Default::default()
+        };
+        let value = serde_amqp::to_value(&header).unwrap();
+        let delivery = delivery_with_body(Body::Value(AmqpValue(value)));
+
+        let decoded: Header = delivery.body_into().unwrap();
+        assert_eq!(decoded, header);
+    }
+
+    #[test]
+    fn body_into_deserializes_data_body() {
+        let properties = Properties {
+            subject: Some(String::from("greeting")),
+            ..Default::default()
+        };
+        let buf = serde_amqp::to_vec(&properties).unwrap();
+        let (first, second) = buf.split_at(buf.len() / 2);
+        let batch: Batch<Data> = vec![
+            Data(Binary::from(first.to_vec())),
+            Data(Binary::from(second.to_vec())),
+        ]
+        .into();
+        let delivery = delivery_with_body(Body::Data(batch));
+
+        let decoded: Properties = delivery.body_into().unwrap();
+        assert_eq!(decoded, properties);
+    }
+
+    #[test]
+    fn body_into_returns_error_on_mismatch() {
+        let value = serde_amqp::to_value(&Header::default()).unwrap();
+        let delivery = delivery_with_body(Body::Value(AmqpValue(value)));
+        let error = delivery.body_into::<Properties>().unwrap_err();
+        assert!(error.to_string().contains("Descriptor mismatch"));
+
+        let data = Data(Binary::from(serde_amqp::to_vec(&"not a number").unwrap()));
+        let delivery = delivery_with_body(Body::Data(vec![data].into()));
+        assert!(delivery.body_into::<u32>().is_err());
+
+        let delivery = delivery_with_body(Body::Empty);
+        let error = delivery.body_into::<String>().unwrap_err();
+        assert!(error.to_string().contains("empty body"));
+    }
 }
@@ -1,4 +1,7 @@
-use fe2o3_amqp_types::definitions::{self, AmqpError, ErrorCondition, SessionError};
+use fe2o3_amqp_types::{
+    definitions::{self, AmqpError, ErrorCondition, LinkRedirect, SessionError},
+    primitives::Symbol,
+};
 use tokio::sync::TryLockError;
 
 use crate::session::AllocLinkError;
@@ -37,6 +40,43 @@ pub enum DetachError {
     /// Remote peer closed the link with an error
     #[error("Remote peer closed the link with an error: {}", .0)]
     RemoteClosedWithError(definitions::Error),
+
+    /// The session ended, which detached the link, with the error the session was ended with
+    #[error("Session ended with an error: {:?}", .0)]
+    SessionEnded(Option<definitions::Error>),
+}
+
+impl DetachError {
+    /// Returns where to re-attach the link if the remote peer detached it with an
+    /// `amqp:link:redirect` error
+    pub fn redirect(&self) -> Option<LinkRedirect> {
+        match self {
+            DetachError::RemoteDetachedWithError(error)
+            | DetachError::RemoteClosedWithError(error) => error.link_redirect(),
+            _ => None,
+        }
+    }
+}
+
+/// Whether an error condition that a link endpoint detaches with is fatal to the link only or
+/// to the whole session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorScope {
+    /// The link is detached
+    Link,
+
+    /// The link is detached and the session is ended with the same error
+    Session,
+}
+
+impl From<&ErrorCondition> for ErrorScope {
+    fn from(condition: &ErrorCondition) -> Self {
+        match condition {
+            // eg. `amqp:session:errant-link`, `amqp:session:handle-in-use`
+            ErrorCondition::SessionError(_) => ErrorScope::Session,
+            _ => ErrorScope::Link,
+        }
+    }
 }
 
 /// Errors associated with attaching a link as sender
@@ -51,6 +91,10 @@ pub enum SenderAttachError {
     #[error("Link name is not unique.")]
     DuplicatedLinkName,
 
+    /// All handles up to the negotiated handle-max of the session are in use
+    #[error("Reached session handle max")]
+    HandleMaxReached,
+
     /// Illegal link state
     #[error("Illegal session state")]
     IllegalState,
@@ -107,6 +151,15 @@ pub enum SenderAttachError {
     #[error("If the dynamic field is not set to true this field MUST be left unset")]
     DynamicNodePropertiesIsSomeWhenDynamicIsFalse,
 
+    /// The local target requests a dynamically created node but also sets the address, which
+    /// MUST NOT be set when dynamic is true. Nothing is sent to the remote peer.
+    #[error("Target address MUST not be set when dynamic is true")]
+    TargetAddressIsSomeWhenDynamicIsTrue,
+
+    /// The remote peer did not offer a capability that is required by the local link endpoint
+    #[error("The required capability {:?} is not offered by the remote peer", .0)]
+    RequiredCapabilityNotOffered(Symbol),
+
     /// Desired TransactionCapabilities is not supported
     #[cfg(feature = "transaction")]
     #[error("Desired transaction capability is not supported")]
@@ -115,6 +168,11 @@ pub enum SenderAttachError {
     /// Remote peer closed the link with an error
     #[error("Remote peer closed with error {:?}", .0)]
     RemoteClosedWithError(definitions::Error),
+
+    /// The incoming attach is rejected by the `on_incoming_attach` hook of the link acceptor.
+    /// The link has been detached with the error returned by the hook
+    #[error("Incoming attach is rejected {:?}", .0)]
+    Rejected(definitions::Error),
 }
 
 /// Error associated with sending a message
@@ -140,6 +198,28 @@ pub enum SendError {
     /// Error serializing message
     #[error("Error encoding message")]
     MessageEncodeError,
+
+    /// A non-blocking send could not be completed without waiting for link credit, for capacity
+    /// on the session outgoing channel or for the connection-wide unsettled limit
+    #[error("Send would block")]
+    WouldBlock,
+
+    /// The serialized message exceeds the `max-message-size` of the link. Nothing is sent.
+    #[error("Message exceeds the max message size of the link")]
+    MessageSizeExceeded,
+
+    /// The delivery asked for `ReceiverSettleMode::Second` while the negotiated value is
+    /// `ReceiverSettleMode::First`. Nothing is sent.
+    #[error("Negotiated value is first. Setting mode to second is illegal")]
+    IllegalRcvSettleModeInTransfer,
+
+    /// The delivery tag is already used by an unsettled delivery of the sender. Nothing is sent.
+    #[error("The delivery tag is already used by an unsettled delivery")]
+    DuplicateDeliveryTag,
+
+    /// Reading the data section of a streamed message failed. The delivery has been aborted.
+    #[error("Error reading the streamed data section: {0}")]
+    Io(std::io::Error),
 }
 
 impl From<serde_amqp::Error> for SendError {
@@ -154,6 +234,36 @@ impl From<DetachError> for SendError {
     }
 }
 
+impl From<MessageSizeExceeded> for SendError {
+    fn from(_: MessageSizeExceeded) -> Self {
+        Self::MessageSizeExceeded
+    }
+}
+
+/// The serialized message exceeds the `max-message-size` of the link
+#[derive(Debug)]
+pub(crate) struct MessageSizeExceeded;
+
+impl From<IllegalRcvSettleMode> for SendError {
+    fn from(_: IllegalRcvSettleMode) -> Self {
+        Self::IllegalRcvSettleModeInTransfer
+    }
+}
+
+/// The `rcv-settle-mode` of a transfer is `second` while the negotiated value is `first`
+#[derive(Debug)]
+pub(crate) struct IllegalRcvSettleMode;
+
+impl From<DuplicateDeliveryTag> for SendError {
+    fn from(_: DuplicateDeliveryTag) -> Self {
+        Self::DuplicateDeliveryTag
+    }
+}
+
+/// The delivery tag is already used by an unsettled delivery of the sender
+#[derive(Debug)]
+pub(crate) struct DuplicateDeliveryTag;
+
 /// Error with the sender trying consume link credit
 ///
 /// This is only used in
@@ -186,6 +296,10 @@ pub enum ReceiverAttachError {
     #[error("Link name is not unique.")]
     DuplicatedLinkName,
 
+    /// All handles up to the negotiated handle-max of the session are in use
+    #[error("Reached session handle max")]
+    HandleMaxReached,
+
     /// Illegal link state
     #[error("Illegal session state")]
     IllegalState,
@@ -245,9 +359,34 @@ pub enum ReceiverAttachError {
     #[error("If the dynamic field is not set to true this field MUST be left unset")]
     DynamicNodePropertiesIsSomeWhenDynamicIsFalse,
 
+    /// The local source requests a dynamically created node but also sets the address, which
+    /// MUST NOT be set when dynamic is true. Nothing is sent to the remote peer.
+    #[error("Source address MUST not be set when dynamic is true")]
+    SourceAddressIsSomeWhenDynamicIsTrue,
+
+    /// The remote peer did not offer a capability that is required by the local link endpoint
+    #[error("The required capability {:?} is not offered by the remote peer", .0)]
+    RequiredCapabilityNotOffered(Symbol),
+
     /// Remote peer closed the link with an error
     #[error("Remote peer closed with error {:?}", .0)]
     RemoteClosedWithError(definitions::Error),
+
+    /// The incoming attach is rejected by the `on_incoming_attach` hook of the link acceptor.
+    /// The link has been detached with the error returned by the hook
+    #[error("Incoming attach is rejected {:?}", .0)]
+    Rejected(definitions::Error),
+}
+
+impl ReceiverAttachError {
+    /// Returns where to re-attach the link if the remote peer refused the attach with an
+    /// `amqp:link:redirect` error
+    pub fn redirect(&self) -> Option<LinkRedirect> {
+        match self {
+            ReceiverAttachError::RemoteClosedWithError(error) => error.link_redirect(),
+            _ => None,
+        }
+    }
 }
 
 impl From<AllocLinkError> for ReceiverAttachError {
@@ -255,6 +394,7 @@ impl From<AllocLinkError> for ReceiverAttachError {
         match value {
             AllocLinkError::IllegalSessionState => Self::IllegalSessionState,
             AllocLinkError::DuplicatedLinkName => Self::DuplicatedLinkName,
+            AllocLinkError::HandleMaxReached => Self::HandleMaxReached,
         }
     }
 }
@@ -266,10 +406,14 @@ impl<'a> TryFrom<&'a ReceiverAttachError> for definitions::Error {
         let condition: ErrorCondition = match value {
             ReceiverAttachError::IllegalSessionState => AmqpError::IllegalState.into(),
             ReceiverAttachError::DuplicatedLinkName => SessionError::HandleInUse.into(),
+            ReceiverAttachError::HandleMaxReached => AmqpError::ResourceLimitExceeded.into(),
             ReceiverAttachError::IllegalState => AmqpError::IllegalState.into(),
             ReceiverAttachError::NonAttachFrameReceived => AmqpError::NotAllowed.into(),
             ReceiverAttachError::ExpectImmediateDetach => AmqpError::NotAllowed.into(),
             ReceiverAttachError::CoordinatorIsNotImplemented => AmqpError::NotImplemented.into(),
+            ReceiverAttachError::RequiredCapabilityNotOffered(_) => {
+                AmqpError::NotImplemented.into()
+            }
             ReceiverAttachError::InitialDeliveryCountIsNone => AmqpError::InvalidField.into(),
             ReceiverAttachError::TargetAddressIsSomeWhenDynamicIsTrue => {
                 AmqpError::InvalidField.into()
@@ -277,13 +421,28 @@ impl<'a> TryFrom<&'a ReceiverAttachError> for definitions::Error {
             ReceiverAttachError::SourceAddressIsNoneWhenDynamicIsTrue => {
                 AmqpError::InvalidField.into()
             }
+            ReceiverAttachError::SourceAddressIsSomeWhenDynamicIsTrue => {
+                AmqpError::InvalidField.into()
+            }
             ReceiverAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse => {
                 AmqpError::InvalidField.into()
             }
+            ReceiverAttachError::Rejected(error) => return Ok(error.clone()),
             _ => return Err(value),
         };
 
-        Ok(Self::new(condition, format!("{:?}", value), None))
+        Ok(Self::from_condition(condition).with_description(format!("{:?}", value)))
+    }
+}
+
+impl SenderAttachError {
+    /// Returns where to re-attach the link if the remote peer refused the attach with an
+    /// `amqp:link:redirect` error
+    pub fn redirect(&self) -> Option<LinkRedirect> {
+        match self {
+            SenderAttachError::RemoteClosedWithError(error) => error.link_redirect(),
+            _ => None,
+        }
     }
 }
 
@@ -292,6 +451,7 @@ impl From<AllocLinkError> for SenderAttachError {
         match value {
             AllocLinkError::IllegalSessionState => Self::IllegalSessionState,
             AllocLinkError::DuplicatedLinkName => Self::DuplicatedLinkName,
+            AllocLinkError::HandleMaxReached => Self::HandleMaxReached,
         }
     }
 }
@@ -302,7 +462,9 @@ impl TryFrom<DetachError> for SenderAttachError {
     fn try_from(value: DetachError) -> Result<Self, Self::Error> {
         match value {
             DetachError::IllegalState => Ok(Self::IllegalState),
-            DetachError::IllegalSessionState => Ok(Self::IllegalSessionState),
+            DetachError::IllegalSessionState | DetachError::SessionEnded(_) => {
+                Ok(Self::IllegalSessionState)
+            }
             DetachError::RemoteDetachedWithError(error)
             | DetachError::RemoteClosedWithError(error) => {
                 // A closing detach is used for errors during attach anyway
@@ -320,7 +482,9 @@ impl TryFrom<DetachError> for ReceiverAttachError {
     fn try_from(value: DetachError) -> Result<Self, Self::Error> {
         match value {
             DetachError::IllegalState => Ok(Self::IllegalState),
-            DetachError::IllegalSessionState => Ok(Self::IllegalSessionState),
+            DetachError::IllegalSessionState | DetachError::SessionEnded(_) => {
+                Ok(Self::IllegalSessionState)
+            }
             DetachError::RemoteDetachedWithError(error)
             | DetachError::RemoteClosedWithError(error) => {
                 // A closing detach is used for errors during attach anyway
@@ -339,10 +503,12 @@ impl<'a> TryFrom<&'a SenderAttachError> for definitions::Error {
         let condition: ErrorCondition = match value {
             SenderAttachError::IllegalSessionState => AmqpError::IllegalState.into(),
             SenderAttachError::DuplicatedLinkName => SessionError::HandleInUse.into(),
+            SenderAttachError::HandleMaxReached => AmqpError::ResourceLimitExceeded.into(),
             SenderAttachError::IllegalState => AmqpError::IllegalState.into(),
             SenderAttachError::NonAttachFrameReceived => AmqpError::NotAllowed.into(),
             SenderAttachError::ExpectImmediateDetach => AmqpError::NotAllowed.into(),
             SenderAttachError::CoordinatorIsNotImplemented => AmqpError::NotImplemented.into(),
+            SenderAttachError::RequiredCapabilityNotOffered(_) => AmqpError::NotImplemented.into(),
             SenderAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse => {
                 AmqpError::InvalidField.into()
             }
@@ -352,6 +518,10 @@ impl<'a> TryFrom<&'a SenderAttachError> for definitions::Error {
             SenderAttachError::SourceAddressIsSomeWhenDynamicIsTrue => {
                 AmqpError::InvalidField.into()
             }
+            SenderAttachError::TargetAddressIsSomeWhenDynamicIsTrue => {
+                AmqpError::InvalidField.into()
+            }
+            SenderAttachError::Rejected(error) => return Ok(error.clone()),
 
             #[cfg(feature = "transaction")]
             SenderAttachError::DesireTxnCapabilitiesNotSupported => return Err(value),
@@ -359,7 +529,7 @@ impl<'a> TryFrom<&'a SenderAttachError> for definitions::Error {
             _ => return Err(value),
         };
 
-        Ok(Self::new(condition, format!("{:?}", value), None))
+        Ok(Self::from_condition(condition).with_description(format!("{:?}", value)))
     }
 }
 
@@ -421,6 +591,10 @@ pub enum LinkStateError {
     /// an incoming Detach frame
     #[error("Expecting an immediate detach")]
     ExpectImmediateDetach,
+
+    /// The session ended, which detached the link, with the error the session was ended with
+    #[error("Session ended with an error: {:?}", .0)]
+    SessionEnded(Option<definitions::Error>),
 }
 
 impl From<DetachError> for LinkStateError {
@@ -432,6 +606,7 @@ impl From<DetachError> for LinkStateError {
             DetachError::ClosedByRemote => Self::RemoteClosed,
             DetachError::DetachedByRemote => Self::RemoteDetached,
             DetachError::RemoteClosedWithError(error) => Self::RemoteClosedWithError(error),
+            DetachError::SessionEnded(error) => Self::SessionEnded(error),
             // DetachError::NonDetachFrameReceived => Self::ExpectImmediateDetach,
         }
     }
@@ -505,6 +680,24 @@ pub enum RecvError {
     /// Transactional acquision is not supported yet
     #[error("Transactional acquisition is not implemented")]
     TransactionalAcquisitionIsNotImeplemented,
+
+    /// The incoming message exceeds the `max_message_size` of the link. The delivery has been
+    /// rejected and the link has been detached with `amqp:link:message-size-exceeded`
+    #[error("The incoming message exceeds the max message size of the link")]
+    MessageSizeExceeded,
+
+    /// The next transfer frame of a multi-frame delivery did not arrive within the
+    /// `incomplete_transfer_timeout` of the receiver. The partial delivery has been dropped
+    #[error("Timed out waiting for the next transfer frame of a multi-frame delivery")]
+    IncompleteTransferTimeout,
+
+    /// The remote sender aborted the delivery whose data section was being streamed
+    #[error("The streamed delivery was aborted by the sender")]
+    DeliveryAborted,
+
+    /// Writing the streamed data section failed
+    #[error("Error writing the streamed data section: {0}")]
+    Io(std::io::Error),
 }
 
 impl From<ReceiverTransferError> for RecvError {
@@ -543,12 +736,19 @@ pub enum IllegalLinkStateError {
     /// Session has dropped
     #[error("Session has dropped")]
     IllegalSessionState,
+
+    /// A disposition that leaves the delivery unsettled was requested while the receiver settle
+    /// mode is `ReceiverSettleMode::First`
+    #[error("Delivery must be settled in ReceiverSettleMode::First")]
+    UnsettledInFirstMode,
 }
 
 impl From<IllegalLinkStateError> for LinkStateError {
     fn from(value: IllegalLinkStateError) -> Self {
         match value {
-            IllegalLinkStateError::IllegalState => LinkStateError::IllegalState,
+            IllegalLinkStateError::IllegalState | IllegalLinkStateError::UnsettledInFirstMode => {
+                LinkStateError::IllegalState
+            }
             IllegalLinkStateError::IllegalSessionState => LinkStateError::IllegalSessionState,
         }
     }
@@ -557,7 +757,9 @@ impl From<IllegalLinkStateError> for LinkStateError {
 impl From<IllegalLinkStateError> for ReceiverAttachError {
     fn from(value: IllegalLinkStateError) -> Self {
         match value {
-            IllegalLinkStateError::IllegalState => ReceiverAttachError::IllegalState,
+            IllegalLinkStateError::IllegalState | IllegalLinkStateError::UnsettledInFirstMode => {
+                ReceiverAttachError::IllegalState
+            }
             IllegalLinkStateError::IllegalSessionState => ReceiverAttachError::IllegalSessionState,
         }
     }
@@ -566,7 +768,9 @@ impl From<IllegalLinkStateError> for ReceiverAttachError {
 impl From<IllegalLinkStateError> for SenderAttachError {
     fn from(value: IllegalLinkStateError) -> Self {
         match value {
-            IllegalLinkStateError::IllegalState => SenderAttachError::IllegalState,
+            IllegalLinkStateError::IllegalState | IllegalLinkStateError::UnsettledInFirstMode => {
+                SenderAttachError::IllegalState
+            }
             IllegalLinkStateError::IllegalSessionState => SenderAttachError::IllegalSessionState,
         }
     }
@@ -575,7 +779,9 @@ impl From<IllegalLinkStateError> for SenderAttachError {
 impl From<IllegalLinkStateError> for SendError {
     fn from(value: IllegalLinkStateError) -> Self {
         match value {
-            IllegalLinkStateError::IllegalState => LinkStateError::IllegalState.into(),
+            IllegalLinkStateError::IllegalState | IllegalLinkStateError::UnsettledInFirstMode => {
+                LinkStateError::IllegalState.into()
+            }
             IllegalLinkStateError::IllegalSessionState => {
                 LinkStateError::IllegalSessionState.into()
             }
@@ -586,7 +792,9 @@ impl From<IllegalLinkStateError> for SendError {
 impl From<IllegalLinkStateError> for DetachError {
     fn from(value: IllegalLinkStateError) -> Self {
         match value {
-            IllegalLinkStateError::IllegalState => Self::IllegalState,
+            IllegalLinkStateError::IllegalState | IllegalLinkStateError::UnsettledInFirstMode => {
+                Self::IllegalState
+            }
             IllegalLinkStateError::IllegalSessionState => Self::IllegalSessionState,
         }
     }
@@ -724,16 +932,11 @@ pub(crate) enum LinkRelayError {
 impl From<LinkRelayError> for definitions::Error {
     fn from(error: LinkRelayError) -> Self {
         match error {
-            LinkRelayError::UnattachedHandle => definitions::Error {
-                condition: SessionError::UnattachedHandle.into(),
-                description: None,
-                info: None,
-            },
-            LinkRelayError::TransferFrameToSender => definitions::Error {
-                condition: AmqpError::NotAllowed.into(),
-                description: Some(String::from("Transfer frame must not be sent to Sender")),
-                info: None,
-            },
+            LinkRelayError::UnattachedHandle => SessionError::UnattachedHandle.into(),
+            LinkRelayError::TransferFrameToSender => {
+                definitions::Error::from_condition(AmqpError::NotAllowed)
+                    .with_description("Transfer frame must not be sent to Sender")
+            }
         }
     }
 }
@@ -761,3 +964,64 @@ pub enum DetachThenResumeReceiverError {
     #[error(transparent)]
     Resume(#[from] ReceiverResumeErrorKind),
 }
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::definitions::{AmqpError, ErrorCondition, LinkError, SessionError};
+
+    use super::ErrorScope;
+
+    #[test]
+    fn error_scope_of_conditions() {
+        let session_fatal: [ErrorCondition; 4] = [
+            SessionError::ErrantLink.into(),
+            SessionError::HandleInUse.into(),
+            SessionError::UnattachedHandle.into(),
+            SessionError::WindowViolation.into(),
+        ];
+        for condition in &session_fatal {
+            assert_eq!(ErrorScope::from(condition), ErrorScope::Session);
+        }
+
+        let link_fatal: [ErrorCondition; 3] = [
+            LinkError::MessageSizeExceeded.into(),
+            LinkError::DetachForced.into(),
+            AmqpError::InternalError.into(),
+        ];
+        for condition in &link_fatal {
+            assert_eq!(ErrorScope::from(condition), ErrorScope::Link);
+        }
+    }
+
+    #[test]
+    fn redirect_is_exposed_on_refused_attach() {
+        use std::convert::TryFrom;
+
+        use fe2o3_amqp_types::{definitions, primitives::OrderedMap};
+
+        use super::{DetachError, ReceiverAttachError};
+
+        let mut info = OrderedMap::new();
+        info.insert("network-host".into(), "10.0.0.2".into());
+        info.insert("port".into(), 5672u16.into());
+        info.insert("address".into(), "q1-partition-2".into());
+        let error = definitions::Error::from_condition(LinkError::Redirect).with_info(info);
+
+        let detach_error = DetachError::RemoteClosedWithError(error);
+        let redirect = detach_error.redirect().unwrap();
+        assert_eq!(redirect.network_host.as_deref(), Some("10.0.0.2"));
+        assert_eq!(redirect.port, Some(5672));
+        assert_eq!(redirect.address.as_deref(), Some("q1-partition-2"));
+
+        let attach_error = ReceiverAttachError::try_from(detach_error).unwrap();
+        assert_eq!(attach_error.redirect(), Some(redirect));
+        // The raw error is still available
+        assert!(matches!(
+            attach_error,
+            ReceiverAttachError::RemoteClosedWithError(definitions::Error {
+                condition: ErrorCondition::LinkError(LinkError::Redirect),
+                ..
+            })
+        ));
+    }
+}
@@ -34,12 +34,16 @@ pub(crate) struct IncompleteTransfer {
     pub buffer: Vec<Payload>,
     pub section_number: Option<u32>,
     pub section_offset: u64,
+
+    /// Total size of the buffered payload in bytes
+    buffered_len: u64,
 }
 
 impl IncompleteTransfer {
     pub fn new(transfer: Transfer, partial_payload: Payload) -> Self {
         let (number, offset) = count_number_of_sections_and_offset(&partial_payload);
         Self {
+            buffered_len: partial_payload.len() as u64,
             performative: transfer,
             buffer: vec![partial_payload], // TODO: handle payload split across re-attachment
             section_number: Some(number),
@@ -91,6 +95,11 @@ impl IncompleteTransfer {
         Ok(())
     }
 
+    /// Total size of the buffered payload in bytes
+    pub fn buffered_size(&self) -> u64 {
+        self.buffered_len
+    }
+
     /// Append to the buffered payload
     pub fn append(&mut self, other: Payload) {
         // Count section numbers
@@ -112,6 +121,7 @@ impl IncompleteTransfer {
             }
         }
 
+        self.buffered_len += other.len() as u64;
         self.buffer.push(other);
     }
 
@@ -160,6 +170,7 @@ impl IncompleteTransfer {
                     let _ = chunk.split_off(index);
                 }
             }
+            self.buffered_len = self.buffer.iter().map(|payload| payload.len() as u64).sum();
         }
     }
 }
@@ -21,6 +21,7 @@ pub use receiver::Receiver;
 pub use sender::Sender;
 use serde::Serialize;
 use serde_amqp::ser::Serializer;
+pub use state::RemoteFlowState;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::{
@@ -65,10 +66,10 @@ pub const DEFAULT_CREDIT: SequenceNo = 200;
 pub(crate) type UnsettledMap<M> = OrderedMap<DeliveryTag, M>;
 
 pub(crate) type SenderFlowState = Consumer<Arc<LinkFlowState<role::SenderMarker>>>;
-pub(crate) type ReceiverFlowState = Arc<LinkFlowState<role::ReceiverMarker>>;
+pub(crate) type ReceiverFlowState = Consumer<Arc<LinkFlowState<role::ReceiverMarker>>>;
 
 pub(crate) type SenderRelayFlowState = Producer<Arc<LinkFlowState<role::SenderMarker>>>;
-pub(crate) type ReceiverRelayFlowState = ReceiverFlowState;
+pub(crate) type ReceiverRelayFlowState = Producer<Arc<LinkFlowState<role::ReceiverMarker>>>;
 
 /// Type alias for sender link that ONLY represents the inner state of a Sender
 pub(crate) type SenderLink<T> = Link<role::SenderMarker, T, SenderFlowState, UnsettledMessage>;
@@ -203,19 +204,52 @@ pub(crate) struct Link<R, T, F, M> {
     pub(crate) source: Option<Source>,
     pub(crate) target: Option<T>,
 
-    /// If zero, the max size is not set.
-    /// If zero, the attach frame should treated is None
-    pub(crate) max_message_size: u64,
+    /// `None` if the field is not set. Both `None` and `Some(0)` impose no limit, but only the
+    /// latter is carried by the attach frame
+    pub(crate) max_message_size: Option<u64>,
 
     // capabilities
     pub(crate) offered_capabilities: Option<Vec<Symbol>>, // TODO: Add accessor fns
     pub(crate) desired_capabilities: Option<Vec<Symbol>>, // TODO: Add accessor fns
 
+    /// Capabilities that the remote peer must offer for the attach to succeed
+    pub(crate) required_capabilities: Vec<Symbol>,
+    pub(crate) remote_offered_capabilities: Option<Vec<Symbol>>,
+
     /// See Section 2.6.7 Flow Control
     pub(crate) flow_state: F,
     pub(crate) unsettled: ArcUnsettledMap<M>,
 }
 
+impl<R, T, F, M> Link<R, T, F, M> {
+    /// Returns the first required capability that is not offered by the remote peer
+    pub(crate) fn missing_required_capability(&self) -> Option<&Symbol> {
+        self.required_capabilities.iter().find(|required| {
+            !self
+                .remote_offered_capabilities
+                .iter()
+                .flatten()
+                .any(|offered| offered == *required)
+        })
+    }
+
+    pub(crate) fn unsettled_count(&self) -> usize {
+        self.unsettled
+            .read()
+            .as_ref()
+            .map(|map| map.len())
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn unsettled_tags(&self) -> Vec<DeliveryTag> {
+        self.unsettled
+            .read()
+            .as_ref()
+            .map(|map| map.iter().map(|(tag, _)| tag.clone()).collect())
+            .unwrap_or_default()
+    }
+}
+
 impl<R, T, F, M> Link<R, T, F, M>
 where
     R: role::IntoRole + Send + Sync,
@@ -267,10 +301,7 @@ where
     ) -> Attach {
         let unsettled = self.get_unsettled_map(is_reattaching, partial_unsettled);
 
-        let max_message_size = match self.max_message_size {
-            0 => None,
-            val => Some(val),
-        };
+        let max_message_size = self.max_message_size;
         let initial_delivery_count = Some(self.flow_state.as_ref().initial_delivery_count());
         let properties = self.flow_state.as_ref().properties();
         let incomplete_unsettled = !matches!(partial_unsettled, 0..=1);
@@ -650,11 +681,23 @@ impl LinkRelay<OutputHandle> {
                 Ok(ret)
             }
             LinkRelay::Receiver {
+                tx,
                 flow_state,
                 output_handle,
                 ..
             } => {
-                let ret = flow_state.on_incoming_flow(flow, output_handle.clone());
+                // The delivery-count of the sender is applied by the link in order with the
+                // transfers that precede the flow
+                let sender_flow = flow.delivery_count.map(|delivery_count| LinkFlow {
+                    handle: flow.handle.clone(),
+                    delivery_count: Some(delivery_count),
+                    ..Default::default()
+                });
+                let ret = flow_state.produce((flow, output_handle.clone())).await;
+                if let Some(sender_flow) = sender_flow {
+                    // The link may have been dropped already
+                    let _ = tx.send(LinkFrame::Flow(sender_flow)).await;
+                }
                 Ok(ret)
             }
         }
@@ -825,17 +868,15 @@ impl LinkRelay<OutputHandle> {
     }
 }
 
-pub(crate) fn get_max_message_size(local: u64, remote: Option<u64>) -> u64 {
-    let remote_max_msg_size = remote.unwrap_or(0);
-    match local {
-        0 => remote_max_msg_size,
-        val => {
-            if remote_max_msg_size == 0 {
-                val
-            } else {
-                u64::min(val, remote_max_msg_size)
-            }
-        }
+/// The smaller of the two limits is used. If neither side imposes a limit, an explicit zero is
+/// kept so that it can still be told apart from an unset value
+pub(crate) fn get_max_message_size(local: Option<u64>, remote: Option<u64>) -> Option<u64> {
+    let local_limit = local.filter(|size| *size > 0);
+    let remote_limit = remote.filter(|size| *size > 0);
+    match (local_limit, remote_limit) {
+        (Some(local), Some(remote)) => Some(u64::min(local, remote)),
+        (Some(limit), None) | (None, Some(limit)) => Some(limit),
+        (None, None) => local.or(remote),
     }
 }
 
@@ -874,4 +915,1540 @@ mod tests {
         notified.await;
         handle.await.unwrap();
     }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn sender_attaches_when_required_capability_is_offered() {
+        use fe2o3_amqp_types::primitives::Symbol;
+
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .add_offered_capabilities("com.example:shared")
+                .add_offered_capabilities("com.example:priority")
+                .build();
+            let mut receiver = test_util::accept_receiver(&link_acceptor, &mut session).await;
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let sender = Sender::builder()
+            .name("sender")
+            .target("q1")
+            .require_capability("com.example:priority")
+            .attach(&mut session)
+            .await
+            .unwrap();
+        assert_eq!(
+            sender.offered_capabilities(),
+            Some(
+                &[
+                    Symbol::from("com.example:shared"),
+                    Symbol::from("com.example:priority")
+                ][..]
+            )
+        );
+
+        sender.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn sender_detaches_when_required_capability_is_not_offered() {
+        use fe2o3_amqp_types::{definitions::AmqpError, primitives::Symbol};
+
+        use crate::{
+            acceptor::LinkAcceptor,
+            link::{LinkStateError, RecvError, SenderAttachError},
+            test_util, Sender,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .add_offered_capabilities("com.example:shared")
+                .build();
+            let mut receiver = test_util::accept_receiver(&link_acceptor, &mut session).await;
+            match receiver.recv::<String>().await {
+                Err(RecvError::LinkStateError(LinkStateError::RemoteClosedWithError(error))) => {
+                    assert_eq!(error.condition, AmqpError::NotImplemented.into())
+                }
+                other => panic!("Expecting a detach with error, found {:?}", other),
+            }
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let result = Sender::builder()
+            .name("sender")
+            .target("q1")
+            .require_capability("com.example:priority")
+            .attach(&mut session)
+            .await;
+        match result {
+            Err(SenderAttachError::RequiredCapabilityNotOffered(capability)) => {
+                assert_eq!(capability, Symbol::from("com.example:priority"))
+            }
+            other => panic!("Expecting RequiredCapabilityNotOffered, found {:?}", other),
+        }
+
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn dynamic_receiver_reads_back_created_source_address() {
+        use fe2o3_amqp_types::messaging::{DeleteOnClose, LifetimePolicy};
+
+        use crate::{acceptor::LinkAcceptor, test_util, Receiver};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .on_dynamic_source(|mut source| {
+                    assert!(source.address.is_none());
+                    assert!(source.dynamic_node_properties.is_some());
+                    source.address = Some(String::from("temp-queue-1"));
+                    Some(source)
+                })
+                .build();
+            let mut sender = test_util::accept_sender(&link_acceptor, &mut session).await;
+            sender.send("reply").await.unwrap();
+            let _ = sender.on_detach().await;
+            let _ = sender.close().await;
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut receiver = Receiver::builder()
+            .name("reply-receiver")
+            .dynamic(true)
+            .dynamic_node_properties(LifetimePolicy::from(DeleteOnClose {}))
+            .attach(&mut session)
+            .await
+            .unwrap();
+        assert_eq!(receiver.source_address().unwrap(), "temp-queue-1");
+        assert!(receiver.source().as_ref().unwrap().dynamic);
+
+        let delivery = receiver.recv::<String>().await.unwrap();
+        assert_eq!(delivery.body(), "reply");
+        receiver.accept(&delivery).await.unwrap();
+
+        receiver.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn dynamic_sender_reads_back_created_target_address() {
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .on_dynamic_target(|mut target| {
+                    assert!(target.address.is_none());
+                    target.address = Some(String::from("temp-queue-2"));
+                    Some(target)
+                })
+                .build();
+            let mut receiver = test_util::accept_receiver(&link_acceptor, &mut session).await;
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let sender = Sender::builder()
+            .name("dynamic-sender")
+            .target("ignored")
+            .dynamic(true)
+            .attach(&mut session)
+            .await
+            .unwrap();
+        assert_eq!(sender.target_address().unwrap(), "temp-queue-2");
+
+        sender.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn dynamic_source_without_created_address_is_refused() {
+        use crate::{
+            acceptor::{LinkAcceptor, LinkEndpoint},
+            link::ReceiverAttachError,
+            test_util, Receiver,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            // The handler "creates" the node but forgets to set the address
+            let link_acceptor = LinkAcceptor::builder().on_dynamic_source(Some).build();
+            if let Ok(LinkEndpoint::Sender(mut sender)) = link_acceptor.accept(&mut session).await {
+                let _ = sender.on_detach().await;
+                let _ = sender.close().await;
+            }
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let result = Receiver::builder()
+            .name("refused-dynamic-receiver")
+            .dynamic(true)
+            .attach(&mut session)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ReceiverAttachError::IncomingSourceIsNone)
+        ));
+
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn dynamic_source_with_address_is_rejected_before_attach() {
+        use fe2o3_amqp_types::messaging::Source;
+
+        use crate::{link::ReceiverAttachError, test_util, Receiver};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let result = Receiver::builder()
+            .name("invalid-dynamic-receiver")
+            .source(Source::builder().address("q1").dynamic(true).build())
+            .attach(&mut session)
+            .await;
+        assert!(matches!(
+            result,
+            Err(ReceiverAttachError::SourceAddressIsSomeWhenDynamicIsTrue)
+        ));
+
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn unsettled_deliveries_are_tracked_until_disposition() {
+        use tokio::sync::oneshot;
+
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+        let (sent_tx, sent_rx) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let first = receiver.recv::<String>().await.unwrap();
+            let second = receiver.recv::<String>().await.unwrap();
+            assert_eq!(receiver.unsettled_count(), 2);
+            assert_eq!(
+                receiver.unsettled_tags(),
+                vec![first.delivery_tag().clone(), second.delivery_tag().clone()]
+            );
+
+            // Wait until the sender has checked its unsettled deliveries
+            sent_rx.await.unwrap();
+            receiver.accept(&first).await.unwrap();
+            receiver.accept(&second).await.unwrap();
+            assert_eq!(receiver.unsettled_count(), 0);
+            assert!(receiver.unsettled_tags().is_empty());
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+        assert_eq!(sender.unsettled_count(), 0);
+
+        let first = sender.send_batchable("first").await.unwrap();
+        let second = sender.send_batchable("second").await.unwrap();
+        assert_eq!(sender.unsettled_count(), 2);
+        assert_eq!(sender.unsettled_tags().len(), 2);
+
+        sent_tx.send(()).unwrap();
+        first.await.unwrap().accepted_or("Not accepted").unwrap();
+        second.await.unwrap().accepted_or("Not accepted").unwrap();
+        assert_eq!(sender.unsettled_count(), 0);
+        assert!(sender.unsettled_tags().is_empty());
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn settled_sendable_is_sent_without_waiting_for_disposition() {
+        use std::{
+            sync::{Arc, Mutex},
+            time::Duration,
+        };
+
+        use fe2o3_amqp_types::messaging::{Accepted, DeliveryState};
+
+        use crate::{
+            acceptor::LinkAcceptor, connection::FrameDirection, frames::amqp::FrameBody, test_util,
+            Connection, Sendable, Sender, Session,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            // The delivery is never disposed by the receiver
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.body(), "hello");
+            assert!(matches!(delivery.state(), Some(DeliveryState::Accepted(_))));
+            assert_eq!(receiver.unsettled_count(), 0);
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let transfers = Arc::new(Mutex::new(Vec::new()));
+        let transfers_clone = transfers.clone();
+        let mut connection = Connection::builder()
+            .container_id("client")
+            .on_frame(move |event| {
+                if let (FrameDirection::Outgoing, FrameBody::Transfer { performative, .. }) =
+                    (event.direction(), event.body())
+                {
+                    transfers_clone.lock().unwrap().push(performative.clone());
+                }
+            })
+            .open_with_stream(client)
+            .await
+            .unwrap();
+        let mut session = Session::begin(&mut connection).await.unwrap();
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let sendable = Sendable::builder()
+            .message("hello")
+            .settled(true)
+            .state(DeliveryState::Accepted(Accepted {}))
+            .message_format(0)
+            .build();
+        tokio::time::timeout(Duration::from_secs(1), sender.send(sendable))
+            .await
+            .expect("A settled delivery should not wait for a disposition")
+            .unwrap()
+            .accepted_or("Not accepted")
+            .unwrap();
+        assert_eq!(sender.unsettled_count(), 0);
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+
+        let transfers = transfers.lock().unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].settled, Some(true));
+        assert_eq!(transfers[0].message_format, Some(0));
+        assert!(matches!(
+            transfers[0].state,
+            Some(DeliveryState::Accepted(_))
+        ));
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn message_format_is_carried_from_sendable_to_delivery() {
+        use crate::{acceptor::LinkAcceptor, test_util, Sendable, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.message_format(), 1);
+            receiver.accept(&delivery).await.unwrap();
+
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.message_format(), 0);
+            receiver.accept(&delivery).await.unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let sendable = Sendable::builder()
+            .message("versioned")
+            .message_format(1)
+            .build();
+        sender
+            .send(sendable)
+            .await
+            .unwrap()
+            .accepted_or("Not accepted")
+            .unwrap();
+        sender
+            .send("default")
+            .await
+            .unwrap()
+            .accepted_or("Not accepted")
+            .unwrap();
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn dropped_delivery_fut_is_removed_from_unsettled_on_disposition() {
+        use tokio::sync::oneshot;
+
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+        let (accept_tx, accept_rx) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let first = receiver.recv::<String>().await.unwrap();
+            let second = receiver.recv::<String>().await.unwrap();
+            accept_rx.await.unwrap();
+            receiver.accept(&first).await.unwrap();
+            receiver.accept(&second).await.unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let first = sender.send_batchable("first").await.unwrap();
+        let first_tag = first.delivery_tag().clone();
+        drop(first);
+        let second = sender.send_batchable("second").await.unwrap();
+        assert_eq!(sender.unsettled_count(), 2);
+        assert!(sender.unsettled_tags().contains(&first_tag));
+
+        accept_tx.send(()).unwrap();
+        // Dispositions are handled in order, so the first delivery is settled by now
+        second.await.unwrap().accepted_or("Not accepted").unwrap();
+        assert_eq!(sender.unsettled_count(), 0);
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn unsettled_delivery_is_reconciled_on_resume() {
+        use crate::{acceptor::LinkAcceptor, link::receiver::ResumingReceiver, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let mut link_acceptor = LinkAcceptor::new();
+            link_acceptor.local_receiver_acceptor.auto_accept = false;
+            let mut receiver = test_util::accept_receiver(&link_acceptor, &mut session).await;
+
+            // Receive the delivery but leave it in-doubt
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.body(), "in-doubt");
+            assert_eq!(receiver.unsettled_count(), 1);
+
+            // The client suspends the link
+            assert!(receiver.recv::<String>().await.is_err());
+            let detached = receiver.detach().await.unwrap();
+
+            // The re-attaching Attach carries the unsettled delivery
+            let attach = session.next_incoming_attach().await.unwrap();
+            let unsettled = attach.unsettled.as_ref().unwrap();
+            assert!(unsettled.contains_key(delivery.delivery_tag()));
+            let mut receiver = match detached.resume_incoming_attach(attach).await.unwrap() {
+                ResumingReceiver::Resume(receiver) => receiver,
+                other => panic!("Expecting Resume, found {:?}", other),
+            };
+
+            // The in-doubt delivery is resumed and settled
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.body(), "in-doubt");
+            receiver.accept(&delivery).await.unwrap();
+            assert_eq!(receiver.unsettled_count(), 0);
+
+            // The client suspends the link again after the resumed delivery and the second
+            // re-attach completes with nothing left in-doubt
+            assert!(receiver.recv::<String>().await.is_err());
+            let detached = receiver.detach().await.unwrap();
+            let attach = session.next_incoming_attach().await.unwrap();
+            let mut receiver = detached
+                .resume_incoming_attach(attach)
+                .await
+                .unwrap()
+                .complete_or("Expecting Complete")
+                .unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = receiver.close().await;
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let outcome = sender.send_batchable("in-doubt").await.unwrap();
+        assert_eq!(sender.unsettled_count(), 1);
+
+        // The unsettled map survives the detach and is reconciled on re-attach
+        let detached = sender.detach().await.unwrap();
+        let sender = detached.resume().await.unwrap();
+
+        outcome.await.unwrap().accepted_or("Not accepted").unwrap();
+        assert_eq!(sender.unsettled_count(), 0);
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn detached_sender_reattaches_with_same_name_and_handle() {
+        use crate::{
+            acceptor::{LinkAcceptor, LinkEndpoint},
+            test_util, Sender,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::new();
+
+            let attach = session.next_incoming_attach().await.unwrap();
+            let first = (attach.name.clone(), attach.handle.clone());
+            let mut receiver = match link_acceptor
+                .accept_incoming_attach(attach, &mut session)
+                .await
+                .unwrap()
+            {
+                LinkEndpoint::Receiver(receiver) => receiver,
+                LinkEndpoint::Sender(_) => panic!("Expecting a receiver"),
+            };
+
+            // The client detaches with closed=false
+            assert!(receiver.recv::<String>().await.is_err());
+            let detached = receiver.detach().await.unwrap();
+
+            let attach = session.next_incoming_attach().await.unwrap();
+            let second = (attach.name.clone(), attach.handle.clone());
+            let mut receiver = detached
+                .resume_incoming_attach(attach)
+                .await
+                .unwrap()
+                .complete_or("Expecting Complete")
+                .unwrap();
+
+            let delivery = receiver.recv::<String>().await.unwrap();
+            receiver.accept(&delivery).await.unwrap();
+            assert_eq!(delivery.body(), "after re-attach");
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = receiver.close().await;
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            (first, second)
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let detached = sender.detach().await.unwrap();
+        let mut sender = detached.resume_on_session(&session).await.unwrap();
+        assert_eq!(sender.name(), "sender");
+        sender.send("after re-attach").await.unwrap();
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        let (first, second) = server.await.unwrap();
+        assert_eq!(first.0, "sender");
+        assert_eq!(first, second);
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn sender_advertises_max_message_size_in_attach() {
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+            // The acceptor doesn't impose a limit, so the limit from the remote Attach is used
+            assert_eq!(receiver.max_message_size(), Some(1024));
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::builder()
+            .name("sender")
+            .target("q1")
+            .max_message_size(1024u64)
+            .attach(&mut session)
+            .await
+            .unwrap();
+        assert_eq!(sender.max_message_size(), Some(1024));
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn explicit_zero_max_message_size_is_kept_apart_from_unset() {
+        use super::get_max_message_size;
+
+        assert_eq!(get_max_message_size(None, None), None);
+        assert_eq!(get_max_message_size(Some(0), None), Some(0));
+        assert_eq!(get_max_message_size(None, Some(0)), Some(0));
+        assert_eq!(get_max_message_size(Some(0), Some(512)), Some(512));
+        assert_eq!(get_max_message_size(None, Some(512)), Some(512));
+        assert_eq!(get_max_message_size(Some(1024), Some(512)), Some(512));
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn attach_with_unset_and_zero_max_message_size() {
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::new();
+            let mut unset = test_util::accept_receiver(&link_acceptor, &mut session).await;
+            let mut unlimited = test_util::accept_receiver(&link_acceptor, &mut session).await;
+            let sizes = (unset.max_message_size(), unlimited.max_message_size());
+
+            let delivery = unlimited.recv::<String>().await.unwrap();
+            unlimited.accept(&delivery).await.unwrap();
+            let len = delivery.body().len();
+
+            assert!(unlimited.recv::<String>().await.is_err());
+            assert!(unset.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            (sizes, len)
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let unset = Sender::attach(&mut session, "unset", "q1").await.unwrap();
+        let mut unlimited = Sender::builder()
+            .name("unlimited")
+            .target("q1")
+            .max_message_size(0u64)
+            .attach(&mut session)
+            .await
+            .unwrap();
+        assert_eq!(unset.max_message_size(), None);
+        assert_eq!(unlimited.max_message_size(), Some(0));
+
+        // An explicit zero imposes no limit
+        unlimited.send("a".repeat(8 * 1024)).await.unwrap();
+
+        unlimited.close().await.unwrap();
+        unset.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        assert_eq!(server.await.unwrap(), ((None, Some(0)), 8 * 1024));
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn sender_rejects_message_exceeding_remote_max_message_size() {
+        use crate::{acceptor::LinkAcceptor, link::SendError, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder().max_message_size(256u64).build();
+            let mut receiver = test_util::accept_receiver(&link_acceptor, &mut session).await;
+
+            // Only the message within the limit is transferred
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.body(), "small");
+            receiver.accept(&delivery).await.unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+        assert_eq!(sender.max_message_size(), Some(256));
+
+        let oversized = "x".repeat(1024);
+        assert!(matches!(
+            sender.send(oversized.clone()).await,
+            Err(SendError::MessageSizeExceeded)
+        ));
+        assert!(matches!(
+            sender.try_send(oversized),
+            Err(SendError::MessageSizeExceeded)
+        ));
+        assert_eq!(sender.unsettled_count(), 0);
+
+        sender
+            .send("small")
+            .await
+            .unwrap()
+            .accepted_or("Not accepted")
+            .unwrap();
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn receiver_stream_yields_deliveries_until_detached() {
+        use futures_util::StreamExt;
+
+        use crate::{
+            acceptor::LinkAcceptor,
+            link::{LinkStateError, RecvError},
+            test_util, Receiver,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let mut sender = test_util::accept_sender(&LinkAcceptor::new(), &mut session).await;
+
+            for body in ["first", "second", "third"] {
+                sender
+                    .send(body)
+                    .await
+                    .unwrap()
+                    .accepted_or("Not accepted")
+                    .unwrap();
+            }
+            sender.close().await.unwrap();
+
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let receiver = Receiver::builder()
+            .name("receiver")
+            .source("q1")
+            .auto_accept(true)
+            .attach(&mut session)
+            .await
+            .unwrap();
+
+        let mut deliveries = receiver.into_stream::<String>();
+        for expected in ["first", "second", "third"] {
+            let delivery = deliveries.next().await.unwrap().unwrap();
+            assert_eq!(delivery.body(), expected);
+        }
+        assert!(matches!(
+            deliveries.next().await,
+            Some(Err(RecvError::LinkStateError(LinkStateError::RemoteClosed)))
+        ));
+        assert!(deliveries.next().await.is_none());
+        assert!(deliveries.into_inner().is_none());
+
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn released_delivery_is_redelivered_to_competing_consumer() {
+        use fe2o3_amqp_types::messaging::{Message, MessageId, Outcome, Properties};
+
+        use crate::{acceptor::LinkAcceptor, test_util, Receiver};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let mut sender = test_util::accept_sender(&LinkAcceptor::new(), &mut session).await;
+
+            // Echo the message back until a consumer accepts it
+            let mut outcomes = Vec::new();
+            loop {
+                let message = Message::builder()
+                    .properties(Properties::builder().message_id(1u64).build())
+                    .value("job")
+                    .build();
+                let outcome = sender.send(message).await.unwrap();
+                let accepted = matches!(outcome, Outcome::Accepted(_));
+                outcomes.push(outcome);
+                if accepted {
+                    break;
+                }
+            }
+            sender.close().await.unwrap();
+
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            outcomes
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut receiver = Receiver::attach(&mut session, "receiver", "q1")
+            .await
+            .unwrap();
+
+        // Peek at the properties and leave the message for another consumer
+        let peeked = receiver.recv::<String>().await.unwrap();
+        let properties = peeked.message().properties.as_ref().unwrap();
+        assert_eq!(properties.message_id, Some(MessageId::from(1u64)));
+        receiver.release(&peeked).await.unwrap();
+
+        let redelivered = receiver.recv::<String>().await.unwrap();
+        assert_eq!(redelivered.body(), "job");
+        assert_eq!(redelivered.delivery_count(), 0);
+        receiver.accept(&redelivered).await.unwrap();
+
+        let _ = receiver.recv::<String>().await;
+        let _ = receiver.close().await;
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        let outcomes = server.await.unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(matches!(outcomes[0], Outcome::Released(_)));
+        assert!(matches!(outcomes[1], Outcome::Accepted(_)));
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn sender_sink_forwards_messages_until_settled() {
+        use fe2o3_amqp_types::messaging::{AmqpValue, Outcome};
+        use futures_util::{stream, SinkExt, StreamExt};
+
+        use crate::{acceptor::LinkAcceptor, test_util, Sendable, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let mut bodies = Vec::new();
+            for _ in 0..3 {
+                let delivery = receiver.recv::<String>().await.unwrap();
+                receiver.accept(&delivery).await.unwrap();
+                bodies.push(delivery.into_body());
+            }
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            bodies
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let mut sink = sender.into_sink::<AmqpValue<String>>();
+        let mut messages = stream::iter(["first", "second", "third"])
+            .map(|body| Ok(Sendable::from(String::from(body))));
+        sink.send_all(&mut messages).await.unwrap();
+
+        let outcomes = sink.take_outcomes();
+        assert_eq!(outcomes.len(), 3);
+        assert!(outcomes
+            .iter()
+            .all(|outcome| matches!(outcome, Outcome::Accepted(_))));
+
+        let sender = sink.into_inner().unwrap();
+        assert_eq!(sender.unsettled_count(), 0);
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        assert_eq!(server.await.unwrap(), ["first", "second", "third"]);
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn rcv_settle_mode_is_overridden_per_delivery() {
+        use fe2o3_amqp_types::{definitions::ReceiverSettleMode, messaging::Outcome};
+
+        use crate::{
+            acceptor::LinkAcceptor, link::delivery::DeliveryInfo, test_util, Sendable, Sender,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let delivery = receiver.recv::<String>().await.unwrap();
+            let mode = DeliveryInfo::from(&delivery).rcv_settle_mode().clone();
+            receiver.accept(&delivery).await.unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            mode
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::builder()
+            .name("sender")
+            .target("q1")
+            .receiver_settle_mode(ReceiverSettleMode::Second)
+            .attach(&mut session)
+            .await
+            .unwrap();
+
+        let sendable = Sendable::builder()
+            .message("hello")
+            .rcv_settle_mode(ReceiverSettleMode::First)
+            .build();
+        let outcome = sender.send(sendable).await.unwrap();
+        assert!(matches!(outcome, Outcome::Accepted(_)));
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        assert_eq!(server.await.unwrap(), Some(ReceiverSettleMode::First));
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn rcv_settle_mode_second_is_rejected_when_negotiated_first() {
+        use fe2o3_amqp_types::definitions::ReceiverSettleMode;
+
+        use crate::{acceptor::LinkAcceptor, link::SendError, test_util, Sendable, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let mut bodies = Vec::new();
+            while let Ok(delivery) = receiver.recv::<String>().await {
+                receiver.accept(&delivery).await.unwrap();
+                bodies.push(delivery.into_body());
+            }
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            bodies
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let sendable = Sendable::builder()
+            .message("illegal")
+            .rcv_settle_mode(ReceiverSettleMode::Second)
+            .build();
+        assert!(matches!(
+            sender.send(sendable).await,
+            Err(SendError::IllegalRcvSettleModeInTransfer)
+        ));
+
+        // Nothing was sent, so the link is still usable
+        sender.send("legal").await.unwrap();
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        assert_eq!(server.await.unwrap(), ["legal"]);
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn attach_to_forbidden_address_is_rejected_by_hook() {
+        use fe2o3_amqp_types::{
+            definitions::{self, AmqpError},
+            messaging::TargetArchetype,
+        };
+
+        use crate::{
+            acceptor::{error::AcceptorAttachError, LinkAcceptor},
+            link::{ReceiverAttachError, SenderAttachError},
+            test_util, Sender,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .on_incoming_attach(|attach| match attach.target.as_deref() {
+                    Some(TargetArchetype::Target(target))
+                        if target.address.as_deref() == Some("forbidden") =>
+                    {
+                        Err(definitions::Error::new(
+                            AmqpError::UnauthorizedAccess,
+                            "forbidden address".to_string(),
+                            None,
+                        ))
+                    }
+                    _ => Ok(()),
+                })
+                .build();
+
+            let rejected = link_acceptor.accept(&mut session).await;
+            assert!(matches!(
+                rejected,
+                Err(AcceptorAttachError::LocalReceiver(
+                    ReceiverAttachError::Rejected(_)
+                ))
+            ));
+
+            let mut receiver = test_util::accept_receiver(&link_acceptor, &mut session).await;
+            let delivery = receiver.recv::<String>().await.unwrap();
+            receiver.accept(&delivery).await.unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+
+        match Sender::attach(&mut session, "rejected", "forbidden").await {
+            Err(SenderAttachError::RemoteClosedWithError(error)) => {
+                assert_eq!(error.condition, AmqpError::UnauthorizedAccess.into());
+            }
+            other => panic!("Expecting the attach to be rejected, found {:?}", other),
+        }
+
+        // Other links on the session are not affected
+        let mut sender = Sender::attach(&mut session, "allowed", "q1").await.unwrap();
+        sender.send("hello").await.unwrap();
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn sequential_delivery_tag_strategy() {
+        use fe2o3_amqp_types::definitions::DeliveryTag;
+
+        use crate::{
+            acceptor::LinkAcceptor, link::delivery::DeliveryTagStrategy, test_util, Sender,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let mut tags = Vec::new();
+            while let Ok(delivery) = receiver.recv::<String>().await {
+                receiver.accept(&delivery).await.unwrap();
+                tags.push(delivery.delivery_tag().clone());
+            }
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            tags
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::builder()
+            .name("sender")
+            .target("q1")
+            .delivery_tag_strategy(DeliveryTagStrategy::Sequential)
+            .attach(&mut session)
+            .await
+            .unwrap();
+
+        for body in ["first", "second", "third"] {
+            sender.send(body).await.unwrap();
+        }
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        let expected: Vec<DeliveryTag> = (0u64..3)
+            .map(|n| DeliveryTag::from(n.to_be_bytes()))
+            .collect();
+        assert_eq!(server.await.unwrap(), expected);
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn duplicate_unsettled_delivery_tag_is_rejected() {
+        use fe2o3_amqp_types::definitions::DeliveryTag;
+
+        use crate::{acceptor::LinkAcceptor, link::SendError, test_util, Sendable, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            // The deliveries are left unsettled
+            let mut bodies = Vec::new();
+            while let Ok(delivery) = receiver.recv::<String>().await {
+                bodies.push(delivery.into_body());
+            }
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            bodies
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let tag = DeliveryTag::from("tag".as_bytes());
+        let first = Sendable::builder()
+            .message("first")
+            .delivery_tag(tag.clone())
+            .build();
+        let _fut = sender.send_batchable(first).await.unwrap();
+
+        let duplicate = Sendable::builder()
+            .message("duplicate")
+            .delivery_tag(tag)
+            .build();
+        assert!(matches!(
+            sender.send_batchable(duplicate).await,
+            Err(SendError::DuplicateDeliveryTag)
+        ));
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        assert_eq!(server.await.unwrap(), ["first"]);
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn data_section_is_streamed_from_reader_to_writer() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        use fe2o3_amqp_types::messaging::{Message, Properties};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        const LEN: usize = 8 * 1024 * 1024;
+        fn byte_at(i: usize) -> u8 {
+            (i % 251) as u8
+        }
+
+        // Bytes written into the source pipe so far
+        let produced = Arc::new(AtomicUsize::new(0));
+        let (mut source_writer, source_reader) = tokio::io::duplex(16 * 1024);
+        let producer = {
+            let produced = produced.clone();
+            tokio::spawn(async move {
+                let chunk: Vec<u8> = (0..1024).map(byte_at).collect();
+                for _ in 0..LEN / 1024 {
+                    source_writer.write_all(&chunk).await.unwrap();
+                    produced.fetch_add(chunk.len(), Ordering::SeqCst);
+                }
+            })
+        };
+
+        let (sink_writer, mut sink_reader) = tokio::io::duplex(16 * 1024);
+        let consumer = {
+            let produced = produced.clone();
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 4096];
+                let mut consumed = 0;
+                loop {
+                    let n = sink_reader.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    // The receiving side is fed before the sending side is done reading
+                    if consumed == 0 {
+                        assert!(produced.load(Ordering::SeqCst) < LEN);
+                    }
+                    assert!(buf[..n]
+                        .iter()
+                        .enumerate()
+                        .all(|(i, b)| *b == byte_at((consumed + i) % 1024)));
+                    consumed += n;
+                }
+                consumed
+            })
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let delivery = receiver.recv_data_stream().await.unwrap();
+            assert_eq!(
+                delivery.message().properties.as_ref().unwrap().subject,
+                Some(String::from("large"))
+            );
+            let mut sink_writer = sink_writer;
+            let delivery = delivery.body_to_writer(&mut sink_writer).await.unwrap();
+            drop(sink_writer);
+            assert_eq!(*delivery.body(), LEN as u64);
+            receiver.accept(&delivery).await.unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let mut message = Message::stream_data(source_reader, LEN);
+        message.properties = Some(Properties::builder().subject("large").build());
+        sender
+            .send_data_stream(message)
+            .await
+            .unwrap()
+            .accepted_or("Not accepted")
+            .unwrap();
+
+        producer.await.unwrap();
+        assert_eq!(consumer.await.unwrap(), LEN);
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn custom_payload_round_trip() {
+        use fe2o3_amqp_types::messaging::{message::__private::Serializable, Message, Outcome};
+
+        use crate::{acceptor::LinkAcceptor, payload::TransferPayload, test_util, Sender};
+
+        // A buffer handed out by a pool
+        struct PooledBuf(Vec<u8>);
+
+        impl AsRef<[u8]> for PooledBuf {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl TransferPayload for PooledBuf {}
+
+        let (client, server) = tokio::io::duplex(4096);
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let delivery = receiver.recv::<String>().await.unwrap();
+            assert_eq!(delivery.body(), "hello pool");
+            receiver.accept(&delivery).await.unwrap();
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let message = Message::from("hello pool");
+        let payload = PooledBuf(serde_amqp::to_vec(&Serializable(message)).unwrap());
+        let outcome = sender.send_encoded(payload).await.unwrap();
+        assert!(matches!(outcome, Outcome::Accepted(_)));
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn streamed_delivery_is_modified_if_writing_the_body_fails() {
+        use fe2o3_amqp_types::{
+            messaging::{Message, Outcome},
+            primitives::Binary,
+        };
+
+        use crate::{acceptor::LinkAcceptor, link::RecvError, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            // Writing fails because the read half is gone
+            let (mut writer, reader) = tokio::io::duplex(16);
+            drop(reader);
+            let delivery = receiver.recv_data_stream().await.unwrap();
+            assert!(matches!(
+                delivery.body_to_writer(&mut writer).await,
+                Err(RecvError::Io(_))
+            ));
+            assert_eq!(receiver.unsettled_count(), 0);
+
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let message = Message::builder().data(Binary::from(vec![1u8; 64])).build();
+        let outcome = sender.send(message).await.unwrap();
+        match outcome {
+            Outcome::Modified(modified) => assert_eq!(modified.delivery_failed, Some(true)),
+            _ => panic!("Expecting modified"),
+        }
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn streamed_non_data_delivery_is_rejected_and_counted() {
+        use fe2o3_amqp_types::{
+            messaging::{Message, Outcome},
+            primitives::Binary,
+        };
+
+        use crate::{acceptor::LinkAcceptor, link::RecvError, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            assert!(matches!(
+                receiver.recv_data_stream().await,
+                Err(RecvError::MessageDecodeError)
+            ));
+
+            let delivery = receiver.recv_data_stream().await.unwrap();
+            let mut body = Vec::new();
+            let delivery = delivery.body_to_writer(&mut body).await.unwrap();
+            assert_eq!(body, vec![1u8, 2, 3]);
+
+            // Both deliveries count towards the delivery-count of the link
+            let remote = receiver.request_flow_echo().await.unwrap();
+            let local = receiver
+                .inner
+                .link
+                .flow_state
+                .as_ref()
+                .lock
+                .read()
+                .delivery_count;
+            assert_eq!(remote.delivery_count, Some(local));
+            assert_eq!(local, 2);
+
+            receiver.accept(&delivery).await.unwrap();
+            assert!(receiver.recv::<String>().await.is_err());
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let outcome = sender.send("not a data section").await.unwrap();
+        assert!(matches!(outcome, Outcome::Rejected(_)));
+
+        let message = Message::builder()
+            .data(Binary::from(vec![1u8, 2, 3]))
+            .build();
+        sender
+            .send(message)
+            .await
+            .unwrap()
+            .accepted_or("Not accepted")
+            .unwrap();
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn increment_delivery_count_rewrites_encoded_header() {
+        use bytes::Bytes;
+        use fe2o3_amqp_types::messaging::{
+            message::__private::{Deserializable, Serializable},
+            Header, Message,
+        };
+
+        use super::sender::increment_delivery_count;
+
+        let with_header = Message::builder()
+            .header(Header {
+                durable: true,
+                delivery_count: 3,
+                ..Default::default()
+            })
+            .value("hello")
+            .build();
+        let payload = Bytes::from(serde_amqp::to_vec(&Serializable(with_header)).unwrap());
+        let payload = increment_delivery_count(&payload).unwrap();
+        let message: Deserializable<Message<String>> = serde_amqp::from_slice(&payload).unwrap();
+        let header = message.0.header.unwrap();
+        assert!(header.durable);
+        assert_eq!(header.delivery_count, 4);
+        assert_eq!(message.0.body, "hello");
+
+        let without_header = Message::builder().value("hello").build();
+        let payload = Bytes::from(serde_amqp::to_vec(&Serializable(without_header)).unwrap());
+        let payload = increment_delivery_count(&payload).unwrap();
+        let message: Deserializable<Message<String>> = serde_amqp::from_slice(&payload).unwrap();
+        assert_eq!(message.0.header.unwrap().delivery_count, 1);
+        assert_eq!(message.0.body, "hello");
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn delivery_exposes_header_delivery_count_and_first_acquirer() {
+        use fe2o3_amqp_types::messaging::{Header, Message};
+
+        use crate::{acceptor::LinkAcceptor, test_util, Sender};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session, mut receiver) =
+                test_util::accept_single_link(server, &LinkAcceptor::new()).await;
+
+            let mut received = Vec::new();
+            while let Ok(delivery) = receiver.recv::<String>().await {
+                receiver.accept(&delivery).await.unwrap();
+                received.push((delivery.delivery_count(), delivery.is_first_acquirer()));
+            }
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            received
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        let redelivered = Message::builder()
+            .header(Header {
+                delivery_count: 3,
+                ..Default::default()
+            })
+            .value("redelivered")
+            .build();
+        sender.send(redelivered).await.unwrap();
+
+        let first = Message::builder()
+            .header(Header {
+                first_acquirer: true,
+                ..Default::default()
+            })
+            .value("first")
+            .build();
+        sender.send(first).await.unwrap();
+
+        sender.send("no header").await.unwrap();
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+
+        assert_eq!(
+            server.await.unwrap(),
+            vec![(3, false), (0, true), (0, false)]
+        );
+    }
 }
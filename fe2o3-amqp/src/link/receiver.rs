@@ -1,18 +1,33 @@
 //! Implementation of AMQP1.0 receiver
 
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::{
+    pin::Pin,
+    sync::atomic::{AtomicU32, Ordering},
+    task::{Context, Poll},
+};
 
 use async_trait::async_trait;
+use bytes::{Buf, BytesMut};
 use fe2o3_amqp_types::{
-    definitions::{self, DeliveryTag, Fields, SequenceNo},
+    definitions::{self, DeliveryTag, Fields, LinkError, SequenceNo},
     messaging::{
-        Accepted, Address, DeliveryState, FromBody, Modified, Rejected, Released, Source, Target,
+        Accepted, Address, Body, DeliveryState, Footer, FromBody, Message, Modified, Rejected,
+        Released, Source, Target,
     },
     performatives::{Attach, Detach, Transfer},
+    primitives::{Symbol, Value},
+};
+use futures_util::{ready, Stream};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::{
+        mpsc::{self, error::TryRecvError},
+        oneshot,
+    },
 };
-use tokio::sync::mpsc;
 
 cfg_not_wasm32! {
+    use futures_util::future::BoxFuture;
     use std::time::Duration;
     use tokio::time::{error::Elapsed, timeout};
 }
@@ -21,6 +36,7 @@ use crate::{
     control::SessionControl,
     endpoint::{self, LinkAttach, LinkDetach, LinkExt},
     session::SessionHandle,
+    util::Sealed,
     Payload,
 };
 
@@ -29,20 +45,25 @@ use super::{
     delivery::{Delivery, DeliveryInfo},
     error::DetachError,
     incomplete_transfer::IncompleteTransfer,
-    receiver_link::count_number_of_sections_and_offset,
+    receiver_link::{
+        count_number_of_sections_and_offset, DATA_CODE, DESCRIBED_TYPE, FOOTER_CODE,
+        SMALL_ULONG_TYPE, ULONG_TYPE, VBIN32_TYPE, VBIN8_TYPE,
+    },
     role,
     shared_inner::{LinkEndpointInner, LinkEndpointInnerDetach, LinkEndpointInnerReattach},
-    ArcReceiverUnsettledMap, DetachThenResumeReceiverError, DispositionError,
+    ArcReceiverUnsettledMap, DetachThenResumeReceiverError, DispositionError, FlowError,
     IllegalLinkStateError, LinkFrame, LinkRelay, LinkStateError, ReceiverAttachError,
     ReceiverAttachExchange, ReceiverFlowState, ReceiverLink, ReceiverResumeError,
-    ReceiverResumeErrorKind, ReceiverTransferError, RecvError, DEFAULT_CREDIT,
+    ReceiverResumeErrorKind, ReceiverTransferError, RecvError, RemoteFlowState, DEFAULT_CREDIT,
 };
 
-#[cfg(feature = "transaction")]
 use fe2o3_amqp_types::definitions::AmqpError;
 
+#[cfg(target_arch = "wasm32")]
+use futures_util::future::LocalBoxFuture;
+
 #[cfg(docsrs)]
-use fe2o3_amqp_types::messaging::{AmqpSequence, AmqpValue, Batch, Body};
+use fe2o3_amqp_types::messaging::{AmqpSequence, AmqpValue, Batch};
 
 /// Credit mode for the link
 #[derive(Debug, Clone)]
@@ -62,6 +83,22 @@ impl Default for CreditMode {
     }
 }
 
+/// Options of a disposition sent by the receiver
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DispositionOptions {
+    /// Whether the delivery is settled by the disposition
+    ///
+    /// If `None`, this is determined by the receiver settle mode, ie. `true` for
+    /// `ReceiverSettleMode::First` and `false` for `ReceiverSettleMode::Second`. `Some(true)`
+    /// settles the delivery immediately even in `ReceiverSettleMode::Second`. `Some(false)` is
+    /// rejected with `DispositionError::UnsettledInFirstMode` if the receiver settle mode is
+    /// `ReceiverSettleMode::First`.
+    pub settled: Option<bool>,
+
+    /// The `batchable` field of the disposition
+    pub batchable: bool,
+}
+
 /// An AMQP1.0 receiver
 ///
 /// # Attach a new receiver with default configurations
@@ -126,8 +163,8 @@ impl Receiver {
         self.inner.link.name()
     }
 
-    /// Returns the `max_message_size` of the link. A value of zero indicates that the link has no
-    /// maximum message size, and thus a zero value is turned into a `None`
+    /// Returns the `max_message_size` of the link. `None` indicates that the field is not set and
+    /// `Some(0)` that there is explicitly no limit. Neither imposes a maximum message size
     pub fn max_message_size(&self) -> Option<u64> {
         self.inner.link.max_message_size()
     }
@@ -154,6 +191,33 @@ impl Receiver {
         self.inner.auto_accept = value;
     }
 
+    /// Returns the address of the source
+    ///
+    /// If a dynamically created node is requested, this is the address of the node created by the
+    /// remote peer
+    pub fn source_address(&self) -> Option<&Address> {
+        self.inner
+            .link
+            .source
+            .as_ref()
+            .and_then(|source| source.address.as_ref())
+    }
+
+    /// Returns the capabilities that the remote peer offered in its Attach frame
+    pub fn offered_capabilities(&self) -> Option<&[Symbol]> {
+        self.inner.link.remote_offered_capabilities.as_deref()
+    }
+
+    /// Returns the number of deliveries received on this link that are not yet settled
+    pub fn unsettled_count(&self) -> usize {
+        self.inner.link.unsettled_count()
+    }
+
+    /// Returns the delivery tags of the deliveries received on this link that are not yet settled
+    pub fn unsettled_tags(&self) -> Vec<DeliveryTag> {
+        self.inner.link.unsettled_tags()
+    }
+
     /// Get a reference to the link's source field
     pub fn source(&self) -> &Option<Source> {
         &self.inner.link.source
@@ -286,6 +350,98 @@ impl Receiver {
         self.inner.recv().await
     }
 
+    /// Receive a message from the link with a timeout
+    ///
+    /// Returns `Ok(None)` if no delivery is completed within `duration`. Link credit is only
+    /// consumed by an incoming transfer, so an elapsed timeout does not affect the link credit.
+    /// A partially received multi-transfer delivery is kept and will be completed by the next call
+    /// to [`recv`](#method.recv), [`recv_with_timeout`](#method.recv_with_timeout) or
+    /// [`try_recv`](#method.try_recv).
+    ///
+    /// # Cancel safety
+    ///
+    /// This function is cancel-safe.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn recv_with_timeout<T>(
+        &mut self,
+        duration: Duration,
+    ) -> Result<Option<Delivery<T>>, RecvError>
+    where
+        for<'de> T: FromBody<'de> + Send,
+    {
+        match timeout(duration, self.inner.recv()).await {
+            Ok(result) => result.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Receive a message from the link if one is already buffered
+    ///
+    /// Returns `Ok(None)` immediately if there is no complete delivery buffered. This does not
+    /// wait for incoming frames, but it may still send out a `Flow` to replenish the link credit
+    /// with [`CreditMode::Auto`] or a `Disposition` if auto accept is enabled.
+    ///
+    /// # Cancel safety
+    ///
+    /// This function is cancel-safe.
+    pub async fn try_recv<T>(&mut self) -> Result<Option<Delivery<T>>, RecvError>
+    where
+        for<'de> T: FromBody<'de> + Send,
+    {
+        self.inner.try_recv().await
+    }
+
+    /// Turns the receiver into a [`Stream`] of deliveries
+    ///
+    /// See [`ReceiverStream`] for how the stream interacts with the link credit and when it ends.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use futures_util::StreamExt;
+    ///
+    /// let mut deliveries = receiver.into_stream::<String>();
+    /// while let Some(delivery) = deliveries.next().await {
+    ///     let delivery = delivery?;
+    ///     deliveries.get_mut().unwrap().accept(&delivery).await?;
+    /// }
+    /// ```
+    pub fn into_stream<T>(self) -> ReceiverStream<T>
+    where
+        for<'de> T: FromBody<'de> + Send + Sync + 'static,
+    {
+        ReceiverStream {
+            receiver: Some(self),
+            recv_fut: None,
+        }
+    }
+
+    /// Receive a message whose body consists of data sections without buffering the binary
+    ///
+    /// This only waits until the sections before the body have arrived. The binary is copied out
+    /// of the transfer frames as they arrive with [`Delivery::body_to_writer`], which also fills
+    /// in the footer and auto accepts the delivery if enabled. The rest of the delivery is
+    /// discarded if the returned delivery is dropped before `body_to_writer` completes.
+    ///
+    /// [`RecvError::MessageDecodeError`] is returned, and the rest of the delivery is discarded,
+    /// if the body is not made of data sections.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut file = tokio::fs::File::create("large.bin").await?;
+    /// let delivery = receiver.recv_data_stream().await?;
+    /// let delivery = delivery.body_to_writer(&mut file).await?;
+    /// receiver.accept(&delivery).await?;
+    /// ```
+    ///
+    /// # Cancel safety
+    ///
+    /// This is NOT cancel safe. A delivery that is partially received is lost if cancelled.
+    pub async fn recv_data_stream(&mut self) -> Result<Delivery<IncomingData<'_>>, RecvError> {
+        self.inner.recv_data_stream().await
+    }
+
     /// Set the link credit. This will stop draining if the link is in a draining cycle
     pub async fn set_credit(&mut self, credit: SequenceNo) -> Result<(), IllegalLinkStateError> {
         self.inner.set_credit(credit).await
@@ -293,12 +449,29 @@ impl Receiver {
 
     /// Drain the link.
     ///
-    /// This will send a `Flow` performative with the `drain` field set to true.
-    /// Setting the credit will set the `drain` field to false and stop draining
-    pub async fn drain(&mut self) -> Result<(), IllegalLinkStateError> {
+    /// This will send a `Flow` performative with the `drain` field set to true and wait for the
+    /// remote sender to reply with a `Flow` indicating that all link credit has been consumed.
+    /// Once this returns, the sender has no more messages available right now. Messages that
+    /// were sent before the drain completed can still be received with [`recv`](#method.recv).
+    ///
+    /// Automatic credit replenishment with [`CreditMode::Auto`] is suspended while the link is
+    /// draining. The link credit consumed by the drain is replenished by the next call to
+    /// [`recv`](#method.recv). Setting the credit will set the `drain` field to false and stop
+    /// draining
+    pub async fn drain(&mut self) -> Result<(), FlowError> {
         self.inner.drain().await
     }
 
+    /// Request the remote sender to echo its link flow state.
+    ///
+    /// This will send a `Flow` performative with the `echo` field set to true and wait for the
+    /// next `Flow` from the remote sender, which carries its `link-credit`, `delivery-count` and
+    /// `available`. Multiple requests can be awaited concurrently, and each is resolved by its own
+    /// incoming `Flow` in the order the requests were made.
+    pub async fn request_flow_echo(&self) -> Result<RemoteFlowState, FlowError> {
+        self.inner.request_flow_echo().await
+    }
+
     /// Detach the link.
     ///
     /// This will send a `Detach` performative with the `closed` field set to false. If the remote
@@ -316,6 +489,10 @@ impl Receiver {
     /// This will send a `Detach` performative with the `closed` field set to false. If the remote
     /// peer responds with a Detach performative whose `closed` field is set to true, the link will
     /// re-attach and then close by exchanging closing Detach performatives.
+    ///
+    /// If the error condition is a session error (eg. `amqp:session:errant-link`), the session
+    /// will also be ended with the same error right after the detach is sent, without waiting for
+    /// the remote peer to reply.
     pub async fn detach_with_error(
         mut self,
         error: impl Into<definitions::Error>,
@@ -380,6 +557,10 @@ impl Receiver {
     /// Close the link with an error.
     ///
     /// This will send a Detach performative with the `closed` field set to true.
+    ///
+    /// If the error condition is a session error (eg. `amqp:session:errant-link`), the session
+    /// will also be ended with the same error right after the detach is sent, without waiting for
+    /// the remote peer to reply.
     pub async fn close_with_error(
         mut self,
         error: impl Into<definitions::Error>,
@@ -410,6 +591,22 @@ impl Receiver {
         self.inner.dispose(delivery_info, None, state).await
     }
 
+    /// Accept the message like [`accept`](#method.accept) and wait until the disposition
+    /// frame has been written to the underlying transport.
+    ///
+    /// This is useful when the application needs assurance that the disposition has actually
+    /// left the process before, for example, checkpointing its own state.
+    ///
+    /// An `IllegalSessionState` error will be returned if the session or connection stops before
+    /// the disposition could be written.
+    pub async fn accept_and_flush(
+        &self,
+        delivery_info: impl Into<DeliveryInfo>,
+    ) -> Result<(), DispositionError> {
+        self.accept(delivery_info).await?;
+        self.inner.flush().await
+    }
+
     /// Accept the message by sending one or more disposition(s) with the `delivery_state` field set
     /// to `Accept`
     ///
@@ -430,7 +627,39 @@ impl Receiver {
     ) -> Result<(), DispositionError> {
         let state = DeliveryState::Accepted(Accepted {});
         let delivery_infos = deliveries.into_iter().map(|d| d.into()).collect();
-        self.inner.dispose_all(delivery_infos, None, state).await
+        self.inner
+            .dispose_all(delivery_infos, None, state, false)
+            .await
+    }
+
+    /// Accept a batch of deliveries with the `batchable` field of the disposition(s) set to true
+    ///
+    /// Deliveries with contiguous delivery-ids are coalesced into a single disposition that
+    /// covers the range from `first` to `last`, so non-contiguous delivery-ids produce the
+    /// minimal number of disposition frames. Setting `batchable` allows the remote peer to delay
+    /// acting on the disposition(s).
+    ///
+    /// Only deliveries that are found in the local unsettled map will be included in the disposition frame(s).
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut deliveries = Vec::new();
+    /// for _ in 0..3 {
+    ///     let delivery: Delivery<Value> = receiver.recv().await.unwrap();
+    ///     deliveries.push(delivery);
+    /// }
+    /// receiver.accept_batch(&deliveries).await.unwrap();
+    /// ```
+    pub async fn accept_batch<T>(
+        &self,
+        deliveries: &[Delivery<T>],
+    ) -> Result<(), DispositionError> {
+        let state = DeliveryState::Accepted(Accepted {});
+        let delivery_infos = deliveries.iter().map(Into::into).collect();
+        self.inner
+            .dispose_all(delivery_infos, None, state, true)
+            .await
     }
 
     /// Reject the message by sending a disposition with the `delivery_state` field set
@@ -461,13 +690,47 @@ impl Receiver {
             error: error.into(),
         });
         let delivery_infos = deliveries.into_iter().map(|d| d.into()).collect();
-        self.inner.dispose_all(delivery_infos, None, state).await
+        self.inner
+            .dispose_all(delivery_infos, None, state, false)
+            .await
+    }
+
+    /// Reject a batch of deliveries with the `batchable` field of the disposition(s) set to true
+    ///
+    /// See [`accept_batch`](#method.accept_batch) for how the deliveries are coalesced.
+    pub async fn reject_batch<T>(
+        &self,
+        deliveries: &[Delivery<T>],
+        error: impl Into<Option<definitions::Error>>,
+    ) -> Result<(), DispositionError> {
+        let state = DeliveryState::Rejected(Rejected {
+            error: error.into(),
+        });
+        let delivery_infos = deliveries.iter().map(Into::into).collect();
+        self.inner
+            .dispose_all(delivery_infos, None, state, true)
+            .await
     }
 
     /// Release the message by sending a disposition with the `delivery_state` field set
     /// to `Release`
     ///
+    /// A released message has not been processed, so the sender may redeliver it to this or
+    /// another receiver without counting it as a failed delivery attempt. This makes it suitable
+    /// for leaving a message that has been inspected after [`recv`](#method.recv) to a competing
+    /// consumer. Use [`modify`](#method.modify) with `delivery_failed` set instead when processing
+    /// has been attempted and failed.
+    ///
     /// This will not send disposition if the delivery is not found in the local unsettled map.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let delivery: Delivery<Value> = receiver.recv().await.unwrap();
+    /// if delivery.message().properties.is_none() {
+    ///     receiver.release(&delivery).await.unwrap();
+    /// }
+    /// ```
     pub async fn release(
         &self,
         delivery_info: impl Into<DeliveryInfo>,
@@ -486,13 +749,43 @@ impl Receiver {
     ) -> Result<(), DispositionError> {
         let state = DeliveryState::Released(Released {});
         let delivery_infos = deliveries.into_iter().map(|d| d.into()).collect();
-        self.inner.dispose_all(delivery_infos, None, state).await
+        self.inner
+            .dispose_all(delivery_infos, None, state, false)
+            .await
+    }
+
+    /// Release a batch of deliveries with the `batchable` field of the disposition(s) set to true
+    ///
+    /// See [`accept_batch`](#method.accept_batch) for how the deliveries are coalesced.
+    pub async fn release_batch<T>(
+        &self,
+        deliveries: &[Delivery<T>],
+    ) -> Result<(), DispositionError> {
+        let state = DeliveryState::Released(Released {});
+        let delivery_infos = deliveries.iter().map(Into::into).collect();
+        self.inner
+            .dispose_all(delivery_infos, None, state, true)
+            .await
     }
 
     /// Modify the message by sending a disposition with the `delivery_state` field set
     /// to `Modify`
     ///
-    /// This will not send disposition if the delivery is not found in the local unsettled map.
+    /// Whether the delivery is settled is determined by the receiver settle mode. This will not
+    /// send disposition if the delivery is not found in the local unsettled map.
+    ///
+    /// # Example
+    ///
+    /// Ask the sender to increment the delivery-count and not to redeliver the message to this
+    /// receiver (eg. for poison message handling)
+    ///
+    /// ```rust,ignore
+    /// let delivery: Delivery<Value> = receiver.recv().await.unwrap();
+    /// let modified = Modified::failed()
+    ///     .undeliverable_here(true)
+    ///     .add_message_annotation("x-opt-reason", "poison");
+    /// receiver.modify(&delivery, modified).await.unwrap();
+    /// ```
     pub async fn modify(
         &self,
         delivery_info: impl Into<DeliveryInfo>,
@@ -513,7 +806,144 @@ impl Receiver {
     ) -> Result<(), DispositionError> {
         let state = DeliveryState::Modified(modified);
         let delivery_infos = deliveries.into_iter().map(|d| d.into()).collect();
-        self.inner.dispose_all(delivery_infos, None, state).await
+        self.inner
+            .dispose_all(delivery_infos, None, state, false)
+            .await
+    }
+
+    /// Dispose the message by sending a disposition with the `delivery_state` field set to the
+    /// given `state`
+    ///
+    /// This is a low-level method that allows sending any delivery state, for example a
+    /// `TransactionalState` when the transaction is managed by the application instead of a
+    /// `Controller`. Whether the delivery is settled is determined by the receiver settle mode.
+    ///
+    /// This will not send disposition if the delivery is not found in the local unsettled map.
+    pub async fn dispose_with_state(
+        &self,
+        delivery_info: impl Into<DeliveryInfo>,
+        state: DeliveryState,
+    ) -> Result<(), DispositionError> {
+        self.inner.dispose(delivery_info, None, state).await
+    }
+
+    /// Accept the message like [`accept`](#method.accept) with the `settled` and `batchable`
+    /// fields of the disposition given by `options`
+    ///
+    /// An `UnsettledInFirstMode` error will be returned if `options.settled` is `Some(false)`
+    /// while the receiver settle mode is `ReceiverSettleMode::First`.
+    pub async fn accept_with_options(
+        &self,
+        delivery_info: impl Into<DeliveryInfo>,
+        options: DispositionOptions,
+    ) -> Result<(), DispositionError> {
+        let state = DeliveryState::Accepted(Accepted {});
+        self.inner
+            .dispose_with_options(delivery_info, state, options)
+            .await
+    }
+
+    /// Dispose the message like [`dispose_with_state`](#method.dispose_with_state) with the
+    /// `settled` and `batchable` fields of the disposition given by `options`
+    ///
+    /// An `UnsettledInFirstMode` error will be returned if `options.settled` is `Some(false)`
+    /// while the receiver settle mode is `ReceiverSettleMode::First`.
+    pub async fn dispose_with_options(
+        &self,
+        delivery_info: impl Into<DeliveryInfo>,
+        state: DeliveryState,
+        options: DispositionOptions,
+    ) -> Result<(), DispositionError> {
+        self.inner
+            .dispose_with_options(delivery_info, state, options)
+            .await
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type RecvFuture<T> = BoxFuture<'static, (Receiver, Result<Delivery<T>, RecvError>)>;
+
+#[cfg(target_arch = "wasm32")]
+type RecvFuture<T> = LocalBoxFuture<'static, (Receiver, Result<Delivery<T>, RecvError>)>;
+
+/// A [`Stream`] of the deliveries received on a [`Receiver`]
+///
+/// This is created by [`Receiver::into_stream`]. A delivery is only received from the link when
+/// the stream is polled, so with [`CreditMode::Auto`] the link credit is replenished only as fast
+/// as the deliveries are consumed, and a slow consumer leaves the remote sender without credit.
+///
+/// An error that terminates the link, ie. a [`RecvError::LinkStateError`] (eg. the remote peer
+/// detaching the link) or a [`RecvError::MessageSizeExceeded`], is yielded as the last item, after
+/// which the stream returns `None`. Other errors are yielded without ending the stream.
+pub struct ReceiverStream<T> {
+    receiver: Option<Receiver>,
+    recv_fut: Option<RecvFuture<T>>,
+}
+
+impl<T> std::fmt::Debug for ReceiverStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReceiverStream")
+            .field("receiver", &self.receiver)
+            .field("receiving", &self.recv_fut.is_some())
+            .finish()
+    }
+}
+
+impl<T> ReceiverStream<T> {
+    /// Returns a mutable reference to the receiver, eg. to settle the received deliveries
+    ///
+    /// Returns `None` if the stream has ended or if a delivery is being received, which only
+    /// happens if a call to `next()` is dropped before it completes.
+    pub fn get_mut(&mut self) -> Option<&mut Receiver> {
+        self.receiver.as_mut()
+    }
+
+    /// Returns the receiver
+    ///
+    /// Returns `None` if the stream has ended or if a delivery is being received, which only
+    /// happens if a call to `next()` is dropped before it completes.
+    pub fn into_inner(self) -> Option<Receiver> {
+        self.receiver
+    }
+}
+
+impl<T> Stream for ReceiverStream<T>
+where
+    for<'de> T: FromBody<'de> + Send + Sync + 'static,
+{
+    type Item = Result<Delivery<T>, RecvError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let recv_fut = match this.recv_fut.as_mut() {
+            Some(recv_fut) => recv_fut,
+            None => {
+                let mut receiver = match this.receiver.take() {
+                    Some(receiver) => receiver,
+                    None => return Poll::Ready(None),
+                };
+                this.recv_fut.insert(Box::pin(async move {
+                    let result = receiver.recv::<T>().await;
+                    (receiver, result)
+                }))
+            }
+        };
+
+        let (receiver, result) = ready!(recv_fut.as_mut().poll(cx));
+        this.recv_fut = None;
+        match result {
+            Err(error @ RecvError::LinkStateError(_))
+            | Err(error @ RecvError::MessageSizeExceeded) => {
+                // The link can no longer receive. Dropping the receiver closes it if it is still
+                // attached
+                drop(receiver);
+                Poll::Ready(Some(Err(error)))
+            }
+            result => {
+                this.receiver = Some(receiver);
+                Poll::Ready(Some(result))
+            }
+        }
     }
 }
 
@@ -534,6 +964,12 @@ pub(crate) struct ReceiverInner<L: endpoint::ReceiverLink> {
 
     // Wrap in a box to avoid clippy warning large_enum_variant on link acceptor's output
     pub(crate) incomplete_transfer: Option<Box<IncompleteTransfer>>,
+
+    // Set when an `IncomingData` is dropped before the end of its delivery
+    pub(crate) skip_streamed_delivery: bool,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) incomplete_transfer_timeout: Option<Duration>,
 }
 
 impl<L: endpoint::ReceiverLink> Drop for ReceiverInner<L> {
@@ -584,7 +1020,7 @@ where
         LinkRelay::Receiver {
             tx,
             output_handle: (),
-            flow_state: self.link.flow_state().clone(),
+            flow_state: self.link.flow_state().producer(),
             unsettled: self.link.unsettled().clone(),
             receiver_settle_mode: self.link.rcv_settle_mode().clone(),
             // This only controls whether a multi-transfer delivery id
@@ -697,11 +1133,45 @@ where
         for<'de> T: FromBody<'de> + Send,
     {
         let frame = self
-            .incoming
-            .recv()
-            .await // cancel safe
+            .recv_next_frame()
+            .await? // cancel safe
             .ok_or(LinkStateError::IllegalSessionState)?;
+        self.on_incoming_frame(frame).await
+    }
+
+    /// Processes the frames that are already buffered until a delivery is completed
+    ///
+    /// # Cancel safety
+    ///
+    /// This should be cancel safe if oneshot channel is cancel safe
+    pub(crate) async fn try_recv<T>(&mut self) -> Result<Option<Delivery<T>>, RecvError>
+    where
+        for<'de> T: FromBody<'de> + Send,
+    {
+        loop {
+            let frame = match self.incoming.try_recv() {
+                Ok(frame) => frame,
+                Err(TryRecvError::Empty) => return Ok(None),
+                Err(TryRecvError::Disconnected) => {
+                    return Err(LinkStateError::IllegalSessionState.into())
+                }
+            };
+            if let Some(delivery) = self.on_incoming_frame(frame).await? {
+                return Ok(Some(delivery));
+            }
+        }
+    }
 
+    /// # Cancel safety
+    ///
+    /// This should be cancel safe if oneshot channel is cancel safe
+    async fn on_incoming_frame<T>(
+        &mut self,
+        frame: LinkFrame,
+    ) -> Result<Option<Delivery<T>>, RecvError>
+    where
+        for<'de> T: FromBody<'de> + Send,
+    {
         match frame {
             LinkFrame::Detach(detach) => {
                 let closed = detach.closed;
@@ -720,11 +1190,36 @@ where
                 payload,
             } => self.on_incoming_transfer(performative, payload).await, // cancel safe
             LinkFrame::Attach(_) => Err(LinkStateError::IllegalState.into()),
-            LinkFrame::Flow(_) | LinkFrame::Disposition(_) => {
-                // Flow and Disposition are handled by LinkRelay which runs
-                // in the session loop
+            LinkFrame::Flow(flow) => {
+                // The rest of the flow has been handled by LinkRelay which runs in the session
+                // loop
+                if let Some(delivery_count) = flow.delivery_count {
+                    // The sender has already counted a partially received delivery
+                    let delivery_count = match self.incomplete_transfer {
+                        Some(_) => delivery_count.wrapping_sub(1),
+                        None => delivery_count,
+                    };
+                    // Credit consumed by the sender without a delivery (eg. when draining) is
+                    // replenished as if the deliveries were processed
+                    let consumed = self
+                        .link
+                        .flow_state()
+                        .as_ref()
+                        .on_sender_delivery_count(delivery_count);
+                    if consumed > 0 {
+                        let prev = self.processed.fetch_add(consumed, Ordering::Release);
+                        self.update_credit_if_auto(prev + consumed).await?; // cancel safe
+                    }
+                }
+                Ok(None)
+            }
+            LinkFrame::Disposition(_) => {
+                // Disposition is handled by LinkRelay which runs in the session loop
                 unreachable!()
             }
+            LinkFrame::Flush(_) => {
+                unreachable!("LinkFrame::Flush should not appear in incoming link frames")
+            }
             #[cfg(feature = "transaction")]
             LinkFrame::Acquisition(_) => {
                 let error = definitions::Error::new(
@@ -738,6 +1233,78 @@ where
         }
     }
 
+    /// Waits for the next incoming frame, giving up on a partial delivery if the next transfer
+    /// frame doesn't arrive within `incomplete_transfer_timeout`
+    ///
+    /// # Cancel safety
+    ///
+    /// This is cancel safe because `mpsc::Receiver::recv` is cancel safe
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn recv_next_frame(&mut self) -> Result<Option<LinkFrame>, RecvError> {
+        match (&self.incomplete_transfer, self.incomplete_transfer_timeout) {
+            (Some(_), Some(duration)) => match timeout(duration, self.incoming.recv()).await {
+                Ok(frame) => Ok(frame),
+                Err(_) => {
+                    self.drop_incomplete_transfer();
+                    Err(RecvError::IncompleteTransferTimeout)
+                }
+            },
+            _ => Ok(self.incoming.recv().await),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn recv_next_frame(&mut self) -> Result<Option<LinkFrame>, RecvError> {
+        Ok(self.incoming.recv().await)
+    }
+
+    /// Waits for the next transfer frame of a streamed delivery, skipping the rest of a previous
+    /// streamed delivery that was dropped
+    async fn recv_stream_transfer(&mut self) -> Result<(Transfer, Payload), RecvError> {
+        loop {
+            let frame = self
+                .recv_next_frame()
+                .await?
+                .ok_or(LinkStateError::IllegalSessionState)?;
+            match frame {
+                LinkFrame::Transfer {
+                    input_handle: _,
+                    performative,
+                    payload,
+                } => {
+                    if self.skip_streamed_delivery {
+                        self.skip_streamed_delivery = performative.more && !performative.aborted;
+                        continue;
+                    }
+                    if let Some(state) = performative.state.clone() {
+                        self.on_transfer_state(
+                            &performative.delivery_tag,
+                            performative.settled,
+                            state,
+                        )?;
+                    }
+                    return Ok((performative, payload));
+                }
+                // Non-transfer frames never yield a delivery
+                frame => {
+                    self.on_incoming_frame::<Body<Value>>(frame).await?;
+                }
+            }
+        }
+    }
+
+    /// Drops the partial delivery and removes it from the unsettled map
+    #[cfg(not(target_arch = "wasm32"))]
+    fn drop_incomplete_transfer(&mut self) {
+        if let Some(incomplete) = self.incomplete_transfer.take() {
+            if let Some(delivery_tag) = &incomplete.performative.delivery_tag {
+                if let Some(unsettled) = self.link.unsettled().write().as_mut() {
+                    let _ = unsettled.remove(delivery_tag);
+                }
+            }
+        }
+    }
+
     fn on_transfer_state(
         &mut self,
         delivery_tag: &Option<DeliveryTag>,
@@ -886,19 +1453,83 @@ where
         Ok(Some(delivery))
     }
 
+    /// Whether `len` bytes received for a delivery exceed the `max_message_size` of the link
+    fn exceeds_max_message_size(&self, len: u64) -> bool {
+        match self.link.max_message_size() {
+            Some(max_message_size) if max_message_size > 0 => len > max_message_size,
+            _ => false,
+        }
+    }
+
+    /// Rejects the delivery and detaches the link with `amqp:link:message-size-exceeded`
+    ///
     /// # Cancel safety
     ///
     /// This is cancel safe because all internal `.await` point(s) are cancel safe
-    #[inline]
-    async fn on_incoming_transfer<T>(
+    async fn on_message_size_exceeded<T>(
         &mut self,
         transfer: Transfer,
-        payload: Payload,
-    ) -> Result<Option<Delivery<T>>, RecvError>
-    where
-        for<'de> T: FromBody<'de> + Send,
-    {
-        // Aborted messages SHOULD be discarded by the recipient (any payload
+    ) -> Result<Option<Delivery<T>>, RecvError> {
+        // Drop the buffered payload
+        let performative = match self.incomplete_transfer.take() {
+            Some(mut incomplete) => {
+                incomplete.or_assign(transfer)?;
+                incomplete.performative
+            }
+            None => transfer,
+        };
+
+        let error = definitions::Error::new(
+            LinkError::MessageSizeExceeded,
+            format!(
+                "Message size exceeds the max message size {:?}",
+                self.link.max_message_size()
+            ),
+            None,
+        );
+
+        if let (Some(delivery_id), Some(delivery_tag)) =
+            (performative.delivery_id, performative.delivery_tag)
+        {
+            // The delivery is only disposed if found in the unsettled map
+            self.link.on_incomplete_transfer(delivery_tag.clone(), 0, 0);
+            let delivery_info = DeliveryInfo {
+                delivery_id,
+                delivery_tag,
+                rcv_settle_mode: performative.rcv_settle_mode,
+                _sealed: Sealed {},
+            };
+            let state = DeliveryState::Rejected(Rejected {
+                error: Some(error.clone()),
+            });
+            self.link
+                .dispose(&self.outgoing, delivery_info, Some(true), state, false)
+                .await?; // cancel safe
+        }
+
+        self.detach_with_error(Some(error)).await?;
+        Err(RecvError::MessageSizeExceeded)
+    }
+
+    /// # Cancel safety
+    ///
+    /// This is cancel safe because all internal `.await` point(s) are cancel safe
+    #[inline]
+    async fn on_incoming_transfer<T>(
+        &mut self,
+        transfer: Transfer,
+        payload: Payload,
+    ) -> Result<Option<Delivery<T>>, RecvError>
+    where
+        for<'de> T: FromBody<'de> + Send,
+    {
+        // The rest of a streamed delivery whose body was not read to the end
+        if self.skip_streamed_delivery {
+            self.skip_streamed_delivery = transfer.more && !transfer.aborted;
+            return Ok(None);
+        }
+
+        // Aborted messages SHOULD be discarded by the recipient (any payload
         // within the frame carrying the performative MUST be ignored). An aborted
         // message is implicitly settled
         if transfer.aborted {
@@ -914,6 +1545,15 @@ where
             self.on_transfer_state(&transfer.delivery_tag, transfer.settled, state)?;
         }
 
+        let buffered = self
+            .incomplete_transfer
+            .as_ref()
+            .map(|incomplete| incomplete.buffered_size())
+            .unwrap_or(0);
+        if self.exceeds_max_message_size(buffered + payload.len() as u64) {
+            return self.on_message_size_exceeded(transfer).await; // cancel safe
+        }
+
         if transfer.more {
             // Partial transfer of the delivery
             // There is only ONE incomplet transfer locally, so the partial transfer must belong to the
@@ -953,10 +1593,32 @@ where
         delivery_info: impl Into<DeliveryInfo>,
         settled: Option<bool>,
         state: DeliveryState,
+    ) -> Result<(), DispositionError> {
+        let options = DispositionOptions {
+            settled,
+            batchable: false,
+        };
+        self.dispose_with_options(delivery_info, state, options)
+            .await
+    }
+
+    /// This is cancel safe because all internal `.await` points are cancel safe
+    #[inline]
+    pub(crate) async fn dispose_with_options(
+        &self,
+        delivery_info: impl Into<DeliveryInfo>,
+        state: DeliveryState,
+        options: DispositionOptions,
     ) -> Result<(), DispositionError> {
         let delivery_info = delivery_info.into();
         self.link
-            .dispose(&self.outgoing, delivery_info, settled, state, false)
+            .dispose(
+                &self.outgoing,
+                delivery_info,
+                options.settled,
+                state,
+                options.batchable,
+            )
             .await?; // cancel safe
 
         let prev = self.processed.fetch_add(1, Ordering::Release);
@@ -964,6 +1626,21 @@ where
         Ok(())
     }
 
+    /// Wait until all frames queued on the outgoing channel have been written to the transport
+    ///
+    /// # Cancel safety
+    ///
+    /// This is cancel safe. Cancelling only drops the notification, and the queued frames
+    /// will still be sent.
+    pub(crate) async fn flush(&self) -> Result<(), DispositionError> {
+        let (tx, rx) = oneshot::channel();
+        self.outgoing
+            .send(LinkFrame::Flush(tx))
+            .await // cancel safe
+            .map_err(|_| DispositionError::IllegalSessionState)?;
+        rx.await.map_err(|_| DispositionError::IllegalSessionState)
+    }
+
     /// This is cancel safe because all internal `.await` points are cancel safe
     #[inline]
     pub(crate) async fn dispose_all(
@@ -971,10 +1648,11 @@ where
         delivery_infos: Vec<DeliveryInfo>,
         settled: Option<bool>,
         state: DeliveryState,
+        batchable: bool,
     ) -> Result<(), DispositionError> {
         let total = delivery_infos.len() as u32;
         self.link
-            .dispose_all(&self.outgoing, delivery_infos, settled, state, false)
+            .dispose_all(&self.outgoing, delivery_infos, settled, state, batchable)
             .await?; // cancel safe
 
         let prev = self.processed.fetch_add(total, Ordering::Release);
@@ -985,6 +1663,11 @@ where
     /// This is cancel safe because it only `.await` on a cancel safe future
     #[inline]
     async fn update_credit_if_auto(&self, processed: u32) -> Result<(), DispositionError> {
+        // Credit should not be replenished until the drain cycle is completed
+        if self.link.flow_state().as_ref().drain() {
+            return Ok(());
+        }
+
         if let CreditMode::Auto(max_credit) = self.credit_mode {
             if processed >= max_credit / 2 {
                 // Reset link credit
@@ -997,54 +1680,484 @@ where
         Ok(())
     }
 
-    /// Drain the link.
+    /// Drain the link and wait for the remote sender to consume all link credit.
     ///
     /// This will send a `Flow` performative with the `drain` field set to true.
     /// Setting the credit will set the `drain` field to false and stop draining
+    ///
+    /// # Cancel safety
+    ///
+    /// This is cancel safe. If cancelled, the link will remain in the draining cycle until
+    /// either the remote sender replies or the credit is set again.
     #[inline]
-    pub async fn drain(&mut self) -> Result<(), DispositionError> {
+    pub async fn drain(&mut self) -> Result<(), FlowError> {
         self.processed = AtomicU32::new(0);
 
-        // Return if already draining
-        if self.link.flow_state().drain() {
-            return Ok(());
+        // Only send a flow with Drain set to true if not already draining
+        if !self.link.flow_state().as_ref().drain() {
+            self.link
+                .send_flow(&self.outgoing, None, Some(true), false)
+                .await?; // cancel safe
         }
 
-        // Send a flow with Drain set to true
+        // Wait for the remote sender to echo the drained flow state
+        loop {
+            let notified = self.link.flow_state().notifier.notified();
+            if !self.link.flow_state().as_ref().drain() {
+                return Ok(());
+            }
+
+            tokio::select! {
+                biased;
+                _ = notified => {},
+                _ = self.outgoing.closed() => return Err(FlowError::IllegalSessionState),
+            }
+        }
+    }
+
+    /// # Cancel safety
+    ///
+    /// This is cancel safe. A cancelled request will not consume the reply of another request.
+    pub(crate) async fn request_flow_echo(&self) -> Result<RemoteFlowState, FlowError> {
+        // Register before sending so that the echoed flow cannot be missed
+        let echoed = self.link.flow_state().as_ref().register_echo_waiter();
         self.link
-            .send_flow(&self.outgoing, None, Some(true), false)
-            .await
+            .send_flow(&self.outgoing, None, None, true)
+            .await?; // cancel safe
+
+        tokio::select! {
+            biased;
+            remote = echoed => remote.map_err(|_| FlowError::IllegalSessionState),
+            _ = self.outgoing.closed() => Err(FlowError::IllegalSessionState),
+        }
     }
 }
 
 impl ReceiverInner<ReceiverLink<Target>> {
+    /// Receives transfer frames until the sections before the body and the header of the first
+    /// data section are complete
+    pub(crate) async fn recv_data_stream(
+        &mut self,
+    ) -> Result<Delivery<IncomingData<'_>>, RecvError> {
+        let mut first: Option<Transfer> = None;
+        let mut buffer = BytesMut::new();
+        let mut head_len = 0;
+        let (more, data_header_len, data_len) = 'head: loop {
+            let (transfer, payload) = self.recv_stream_transfer().await?;
+            if transfer.aborted {
+                first = None;
+                buffer.clear();
+                head_len = 0;
+                continue;
+            }
+            let more = transfer.more;
+            let performative = first.get_or_insert(transfer);
+            buffer.extend_from_slice(&payload);
+            if self.exceeds_max_message_size(buffer.len() as u64) {
+                self.skip_streamed_delivery = more;
+                self.on_message_size_exceeded::<()>(performative.clone())
+                    .await?;
+                return Err(RecvError::MessageSizeExceeded);
+            }
+
+            loop {
+                match peek_section(&buffer[head_len..]) {
+                    Some(StreamedSection::Incomplete) => break,
+                    Some(StreamedSection::Data { header_len, len }) => {
+                        break 'head (more, header_len, len);
+                    }
+                    Some(StreamedSection::Other { code, len }) if code < DATA_CODE => {
+                        head_len += len
+                    }
+                    Some(StreamedSection::Other { .. }) | None => {
+                        self.skip_streamed_delivery = more;
+                        let performative = performative.clone();
+                        return self.on_undecodable_stream(&performative).await;
+                    }
+                }
+            }
+
+            if !more {
+                // The delivery ended without a data section
+                let performative = performative.clone();
+                return self.on_undecodable_stream(&performative).await;
+            }
+        };
+
+        let mut performative = first.ok_or(RecvError::DeliveryIdIsNone)?;
+        let received = buffer.len();
+        let head = buffer.split_to(head_len).freeze();
+        let body = buffer.split_off(data_header_len).freeze();
+        let (section_number, section_offset) = count_number_of_sections_and_offset(&head);
+        performative.more = false;
+        let delivery: Delivery<Body<Value>> = match endpoint::ReceiverLink::on_complete_transfer(
+            &mut self.link,
+            performative.clone(),
+            head,
+            section_number,
+            section_offset,
+        ) {
+            Ok(delivery) => delivery,
+            Err(err) => {
+                self.skip_streamed_delivery = more;
+                return Err(err.into());
+            }
+        };
+
+        let Delivery {
+            link_output_handle,
+            delivery_id,
+            delivery_tag,
+            message_format,
+            rcv_settle_mode,
+            state,
+            message,
+        } = delivery;
+        let message = message.map_body(|_| IncomingData {
+            receiver: self,
+            performative,
+            buffer: body,
+            remaining: data_len,
+            received,
+            more,
+        });
+        Ok(Delivery {
+            link_output_handle,
+            delivery_id,
+            delivery_tag,
+            message_format,
+            rcv_settle_mode,
+            state,
+            message,
+        })
+    }
+
+    /// Accounts for a streamed delivery whose body is not made of data sections and rejects it
+    ///
+    /// The delivery consumes a link credit like any other delivery so that the delivery-count
+    /// stays in sync with the sender.
+    async fn on_undecodable_stream<T>(&mut self, performative: &Transfer) -> Result<T, RecvError> {
+        self.link.flow_state.as_ref().consume(1)?;
+        if let Some(delivery_tag) = &performative.delivery_tag {
+            if !performative.settled.unwrap_or(false) {
+                endpoint::ReceiverLink::on_incomplete_transfer(
+                    &mut self.link,
+                    delivery_tag.clone(),
+                    0,
+                    0,
+                );
+            }
+        }
+        self.reject_undecodable_stream(performative).await?;
+        Err(RecvError::MessageDecodeError)
+    }
+
+    /// Settles a streamed delivery that has already been accounted for with `Rejected`
+    async fn reject_undecodable_stream(&self, performative: &Transfer) -> Result<(), RecvError> {
+        let error = definitions::Error::new(
+            AmqpError::DecodeError,
+            "The body of a streamed delivery must consist of data sections".to_string(),
+            None,
+        );
+        let state = DeliveryState::Rejected(Rejected { error: Some(error) });
+        self.settle_stream(performative, state).await
+    }
+
+    /// Settles a streamed delivery that has already been accounted for with `state`
+    async fn settle_stream(
+        &self,
+        performative: &Transfer,
+        state: DeliveryState,
+    ) -> Result<(), RecvError> {
+        let delivery_info = DeliveryInfo {
+            delivery_id: performative
+                .delivery_id
+                .ok_or(RecvError::DeliveryIdIsNone)?,
+            delivery_tag: performative
+                .delivery_tag
+                .clone()
+                .ok_or(RecvError::DeliveryTagIsNone)?,
+            rcv_settle_mode: performative.rcv_settle_mode.clone(),
+            _sealed: Sealed {},
+        };
+        self.dispose(delivery_info, Some(true), state).await?; // cancel safe
+        Ok(())
+    }
+
     pub(crate) async fn resume_incoming_attach(
         &mut self,
         mut initial_remote_attach: Option<Attach>,
     ) -> Result<ReceiverAttachExchange, ReceiverResumeErrorKind> {
-        self.reallocate_output_handle().await?;
-
         let exchange = match initial_remote_attach.take() {
             Some(remote_attach) => {
+                self.reallocate_incoming_handle(remote_attach.handle.clone().into())
+                    .await?;
                 self.link
                     .send_attach(&self.outgoing, &self.session, false)
                     .await?;
                 self.link.on_incoming_attach(remote_attach)?
             }
-            None => self.exchange_attach(false).await?,
+            None => {
+                self.reallocate_output_handle().await?;
+                self.exchange_attach(false).await?
+            }
         };
         #[cfg(feature = "tracing")]
         tracing::debug!(?exchange);
         #[cfg(feature = "log")]
         log::debug!("exchange = {:?}", exchange);
 
-        let credit = self.link.flow_state.link_credit();
+        let credit = self.link.flow_state.as_ref().link_credit();
         self.set_credit(credit).await?;
 
         Ok(exchange)
     }
 }
 
+/// The data sections of a delivery received with [`Receiver::recv_data_stream`] that have yet
+/// to be copied out of the incoming transfer frames
+///
+/// The binary is read with [`Delivery::body_to_writer`].
+#[derive(Debug)]
+pub struct IncomingData<'a> {
+    receiver: &'a mut ReceiverInner<ReceiverLink<Target>>,
+
+    /// The first transfer of the delivery
+    performative: Transfer,
+
+    /// Bytes received but not yet consumed
+    buffer: Payload,
+
+    /// Number of bytes left in the current data section
+    remaining: usize,
+
+    /// Total number of bytes received
+    received: usize,
+
+    /// Whether more transfer frames of the delivery will follow
+    more: bool,
+}
+
+impl IncomingData<'_> {
+    /// Receives the next transfer frame of the delivery into the buffer
+    async fn fill(&mut self) -> Result<(), RecvError> {
+        if !self.more {
+            // The delivery ended in the middle of a section
+            return Err(RecvError::MessageDecodeError);
+        }
+
+        let (transfer, payload) = self.receiver.recv_stream_transfer().await?;
+        if transfer.aborted {
+            self.more = false;
+            return Err(RecvError::DeliveryAborted);
+        }
+        self.more = transfer.more;
+        self.received += payload.len();
+        if self.receiver.exceeds_max_message_size(self.received as u64) {
+            self.receiver.skip_streamed_delivery = self.more;
+            self.more = false;
+            self.receiver
+                .on_message_size_exceeded::<()>(self.performative.clone())
+                .await?;
+            return Err(RecvError::MessageSizeExceeded);
+        }
+
+        if self.buffer.is_empty() {
+            self.buffer = payload;
+        } else {
+            let mut buffer = BytesMut::with_capacity(self.buffer.len() + payload.len());
+            buffer.extend_from_slice(&self.buffer);
+            buffer.extend_from_slice(&payload);
+            self.buffer = buffer.freeze();
+        }
+        Ok(())
+    }
+
+    /// Copies the binary of all data sections to `writer` and returns the number of bytes written
+    /// and the footer
+    async fn copy_to<W>(&mut self, writer: &mut W) -> Result<(u64, Option<Footer>), RecvError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut written = 0;
+        let mut footer = None;
+        loop {
+            if self.remaining > 0 {
+                if self.buffer.is_empty() {
+                    self.fill().await?;
+                    continue;
+                }
+                let chunk = self
+                    .buffer
+                    .split_to(std::cmp::min(self.remaining, self.buffer.len()));
+                writer.write_all(&chunk).await.map_err(RecvError::Io)?;
+                self.remaining -= chunk.len();
+                written += chunk.len() as u64;
+                continue;
+            }
+
+            if self.buffer.is_empty() && !self.more {
+                break;
+            }
+            match peek_section(&self.buffer).ok_or(RecvError::MessageDecodeError)? {
+                StreamedSection::Incomplete => self.fill().await?,
+                StreamedSection::Data { header_len, len } => {
+                    self.buffer.advance(header_len);
+                    self.remaining = len;
+                }
+                StreamedSection::Other {
+                    code: FOOTER_CODE,
+                    len,
+                } if footer.is_none() => {
+                    let section = self.buffer.split_to(len);
+                    footer = Some(
+                        serde_amqp::from_slice(&section)
+                            .map_err(|_| RecvError::MessageDecodeError)?,
+                    );
+                }
+                StreamedSection::Other { .. } => return Err(RecvError::MessageDecodeError),
+            }
+        }
+
+        writer.flush().await.map_err(RecvError::Io)?;
+        Ok((written, footer))
+    }
+}
+
+impl Drop for IncomingData<'_> {
+    fn drop(&mut self) {
+        if self.more {
+            self.receiver.skip_streamed_delivery = true;
+        }
+    }
+}
+
+impl<'a> Delivery<IncomingData<'a>> {
+    /// Copies the binary of the data sections to `writer` as the transfer frames arrive
+    ///
+    /// Returns the delivery with the footer filled in and the number of bytes written as the body.
+    /// The delivery is accepted if auto accept is enabled on the receiver.
+    ///
+    /// The delivery is settled before an error is returned. If writing to `writer` fails, the
+    /// delivery is settled with [`Modified`] with `delivery-failed` set so that the sender can
+    /// redeliver it. A delivery that exceeds the `max-message-size` of the link is rejected, and
+    /// an aborted delivery is implicitly settled.
+    pub async fn body_to_writer<W>(self, writer: &mut W) -> Result<Delivery<u64>, RecvError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let Delivery {
+            link_output_handle,
+            delivery_id,
+            delivery_tag,
+            message_format,
+            rcv_settle_mode,
+            state,
+            message,
+        } = self;
+        let mut data = message.body;
+        let (written, footer) = match data.copy_to(writer).await {
+            Ok(copied) => copied,
+            Err(RecvError::MessageDecodeError) => {
+                data.receiver
+                    .reject_undecodable_stream(&data.performative)
+                    .await?;
+                return Err(RecvError::MessageDecodeError);
+            }
+            Err(RecvError::Io(err)) => {
+                let state = DeliveryState::Modified(Modified::failed());
+                data.receiver
+                    .settle_stream(&data.performative, state)
+                    .await?;
+                return Err(RecvError::Io(err));
+            }
+            Err(RecvError::DeliveryAborted) => {
+                if let Some(unsettled) = data.receiver.link.unsettled().write().as_mut() {
+                    let _ = unsettled.remove(&delivery_tag);
+                }
+                return Err(RecvError::DeliveryAborted);
+            }
+            // The delivery has already been rejected
+            Err(err) => return Err(err),
+        };
+
+        let delivery = Delivery {
+            link_output_handle,
+            delivery_id,
+            delivery_tag,
+            message_format,
+            rcv_settle_mode,
+            state,
+            message: Message {
+                header: message.header,
+                delivery_annotations: message.delivery_annotations,
+                message_annotations: message.message_annotations,
+                properties: message.properties,
+                application_properties: message.application_properties,
+                body: written,
+                footer,
+            },
+        };
+        if data.receiver.auto_accept {
+            data.receiver
+                .dispose(&delivery, None, Accepted {}.into())
+                .await?;
+        }
+        Ok(delivery)
+    }
+}
+
+/// A section at the start of the bytes received for a streamed delivery
+///
+/// [`peek_section`] returns `None` if the bytes are not a section with a numeric descriptor.
+enum StreamedSection {
+    /// More bytes are needed to tell
+    Incomplete,
+
+    /// The header of a data section whose binary of `len` bytes follows
+    Data { header_len: usize, len: usize },
+
+    /// A section other than data spanning `len` bytes
+    Other { code: u8, len: usize },
+}
+
+fn peek_section(buf: &[u8]) -> Option<StreamedSection> {
+    let (code, descriptor_len) = match buf {
+        [] | [DESCRIBED_TYPE] | [DESCRIBED_TYPE, SMALL_ULONG_TYPE] => {
+            return Some(StreamedSection::Incomplete)
+        }
+        [DESCRIBED_TYPE, SMALL_ULONG_TYPE, code, ..] => (*code, 3),
+        [DESCRIBED_TYPE, ULONG_TYPE, rest @ ..] => match rest.get(..8) {
+            Some([0, 0, 0, 0, 0, 0, 0, code]) => (*code, 10),
+            Some(_) => return None,
+            None => return Some(StreamedSection::Incomplete),
+        },
+        // Sections with a symbolic descriptor are not supported
+        _ => return None,
+    };
+
+    if code != DATA_CODE {
+        return match serde_amqp::from_slice_prefix::<Value>(buf) {
+            Ok((_, len)) => Some(StreamedSection::Other { code, len }),
+            Err(err) if err.is_unexpected_eof() => Some(StreamedSection::Incomplete),
+            Err(_) => None,
+        };
+    }
+
+    let binary = &buf[descriptor_len..];
+    let (header_len, len) = match binary {
+        [] | [VBIN8_TYPE] => return Some(StreamedSection::Incomplete),
+        [VBIN32_TYPE, rest @ ..] if rest.len() < 4 => return Some(StreamedSection::Incomplete),
+        [VBIN8_TYPE, len, ..] => (2, *len as usize),
+        [VBIN32_TYPE, b0, b1, b2, b3, ..] => (5, u32::from_be_bytes([*b0, *b1, *b2, *b3]) as usize),
+        _ => return None,
+    };
+    Some(StreamedSection::Data {
+        header_len: descriptor_len + header_len,
+        len,
+    })
+}
+
 /// A detached receiver
 ///
 /// # Example
@@ -1343,9 +2456,79 @@ impl DetachedReceiver {
 
 #[cfg(test)]
 mod tests {
-    use fe2o3_amqp_types::performatives::Transfer;
+    use std::{
+        marker::PhantomData,
+        sync::{atomic::AtomicU32, Arc},
+        time::Duration,
+    };
+
+    use fe2o3_amqp_types::{
+        definitions::{ReceiverSettleMode, SenderSettleMode},
+        messaging::Target,
+        performatives::Transfer,
+    };
+    use parking_lot::RwLock;
+    use tokio::sync::{mpsc, Notify};
+
+    use crate::{
+        endpoint::{LinkFlow, OutputHandle},
+        link::{
+            shared_inner::LinkEndpointInner,
+            state::{LinkFlowState, LinkFlowStateInner, LinkState},
+            LinkFrame, ReceiverLink,
+        },
+        util::{Consumer, Producer},
+    };
 
-    use super::IncompleteTransfer;
+    use super::{CreditMode, IncompleteTransfer, ReceiverInner};
+
+    fn attached_receiver_inner(
+        link_credit: u32,
+        outgoing: mpsc::Sender<LinkFrame>,
+        incoming: mpsc::Receiver<LinkFrame>,
+    ) -> ReceiverInner<ReceiverLink<Target>> {
+        let flow_state = Arc::new(LinkFlowState::receiver(LinkFlowStateInner {
+            initial_delivery_count: 0,
+            delivery_count: 0,
+            link_credit,
+            available: 0,
+            drain: false,
+            properties: None,
+        }));
+        let notifier = Arc::new(Notify::new());
+        let (session, _) = mpsc::channel(1);
+        let link = ReceiverLink::<Target> {
+            role: PhantomData,
+            local_state: LinkState::Attached,
+            name: String::from("test-receiver"),
+            output_handle: Some(OutputHandle(0)),
+            input_handle: None,
+            snd_settle_mode: SenderSettleMode::Mixed,
+            rcv_settle_mode: ReceiverSettleMode::First,
+            source: None,
+            target: None,
+            max_message_size: None,
+            offered_capabilities: None,
+            desired_capabilities: None,
+            required_capabilities: Vec::new(),
+            remote_offered_capabilities: None,
+            flow_state: Consumer::new(notifier, flow_state),
+            unsettled: Arc::new(RwLock::new(None)),
+        };
+        ReceiverInner {
+            link,
+            buffer_size: 10,
+            credit_mode: CreditMode::Auto(link_credit),
+            processed: AtomicU32::new(0),
+            auto_accept: false,
+            session,
+            outgoing,
+            incoming,
+            incomplete_transfer: None,
+            skip_streamed_delivery: false,
+            incomplete_transfer_timeout: None,
+        }
+    }
 
     #[test]
     fn size_of_incomplete_transfer() {
@@ -1355,4 +2538,907 @@ mod tests {
         let size = std::mem::size_of::<Option<IncompleteTransfer>>();
         println!("Option<IncompleteTransfer> {:?}", size);
     }
+
+    #[tokio::test]
+    async fn drain_waits_for_drained_flow_from_sender() {
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let mut relay = inner
+            .as_new_link_relay(incoming_tx)
+            .with_output_handle(OutputHandle(0));
+
+        // Mock sender that consumes all link credit upon receiving a drain request
+        let handle = tokio::spawn(async move {
+            let flow = match outgoing_rx.recv().await {
+                Some(LinkFrame::Flow(flow)) => flow,
+                _ => panic!("Expecting a Flow"),
+            };
+            assert!(flow.drain);
+            assert_eq!(flow.link_credit, Some(10));
+
+            let echo = LinkFlow {
+                delivery_count: Some(10),
+                link_credit: Some(0),
+                available: Some(0),
+                drain: true,
+                ..Default::default()
+            };
+            relay.on_incoming_flow(echo).await.unwrap();
+            relay
+        });
+
+        tokio::time::timeout(Duration::from_millis(500), inner.drain())
+            .await
+            .expect("drain should complete once the sender echoes the flow")
+            .unwrap();
+        let _relay = handle.await.unwrap();
+        assert!(!inner.link.flow_state.as_ref().drain());
+    }
+
+    #[tokio::test]
+    async fn auto_credit_is_replenished_after_drain() {
+        use serde_amqp::Value;
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let mut relay = inner
+            .as_new_link_relay(incoming_tx)
+            .with_output_handle(OutputHandle(0));
+
+        // Mock sender that sends one delivery and then consumes the rest of the link credit
+        let handle = tokio::spawn(async move {
+            match outgoing_rx.recv().await {
+                Some(LinkFrame::Flow(flow)) => assert!(flow.drain),
+                _ => panic!("Expecting a Flow"),
+            }
+            if let LinkFrame::Transfer {
+                performative,
+                payload,
+                ..
+            } = complete_transfer_frame(0)
+            {
+                relay
+                    .on_incoming_transfer(performative, payload)
+                    .await
+                    .unwrap();
+            }
+
+            let drained = LinkFlow {
+                delivery_count: Some(10),
+                link_credit: Some(0),
+                available: Some(0),
+                drain: true,
+                ..Default::default()
+            };
+            relay.on_incoming_flow(drained).await.unwrap();
+            (relay, outgoing_rx)
+        });
+
+        tokio::time::timeout(Duration::from_millis(500), inner.drain())
+            .await
+            .expect("drain should complete once the sender echoes the flow")
+            .unwrap();
+        let (_relay, mut outgoing_rx) = handle.await.unwrap();
+
+        // The delivery sent before the drain completed is still received
+        let _delivery = inner.recv::<Value>().await.unwrap();
+
+        // The drained credit is replenished once the flow from the sender is processed
+        assert!(inner.try_recv::<Value>().await.unwrap().is_none());
+        match outgoing_rx.recv().await {
+            Some(LinkFrame::Flow(flow)) => {
+                assert!(!flow.drain);
+                assert_eq!(flow.delivery_count, Some(10));
+                assert_eq!(flow.link_credit, Some(10));
+            }
+            _ => panic!("Expecting a Flow"),
+        }
+        assert_eq!(inner.link.flow_state.as_ref().link_credit(), 10);
+    }
+
+    #[tokio::test]
+    async fn drain_is_pending_until_sender_replies() {
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+
+        let result = tokio::time::timeout(Duration::from_millis(100), inner.drain()).await;
+        assert!(result.is_err());
+        assert!(inner.link.flow_state.as_ref().drain());
+        assert!(matches!(outgoing_rx.recv().await, Some(LinkFrame::Flow(flow)) if flow.drain));
+
+        // Auto credit replenishment is suspended while draining
+        inner.update_credit_if_auto(10).await.unwrap();
+        assert!(outgoing_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn concurrent_flow_echo_requests_resolve_independently() {
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let mut relay = inner
+            .as_new_link_relay(incoming_tx)
+            .with_output_handle(OutputHandle(0));
+
+        // Mock sender that echoes its flow state once per request
+        let handle = tokio::spawn(async move {
+            for delivery_count in [3, 7] {
+                let flow = match outgoing_rx.recv().await {
+                    Some(LinkFrame::Flow(flow)) => flow,
+                    _ => panic!("Expecting a Flow"),
+                };
+                assert!(flow.echo);
+
+                let echo = LinkFlow {
+                    delivery_count: Some(delivery_count),
+                    link_credit: Some(10 - delivery_count),
+                    available: Some(delivery_count * 2),
+                    ..Default::default()
+                };
+                assert!(relay.on_incoming_flow(echo).await.unwrap().is_none());
+            }
+            relay
+        });
+
+        let (first, second) = tokio::time::timeout(Duration::from_millis(500), async {
+            tokio::join!(inner.request_flow_echo(), inner.request_flow_echo())
+        })
+        .await
+        .expect("each request should be resolved by its own echoed flow");
+        let _relay = handle.await.unwrap();
+
+        let first = first.unwrap();
+        assert_eq!(first.delivery_count, Some(3));
+        assert_eq!(first.link_credit, Some(7));
+        assert_eq!(first.available, Some(6));
+        let second = second.unwrap();
+        assert_eq!(second.delivery_count, Some(7));
+        assert_eq!(second.link_credit, Some(3));
+        assert_eq!(second.available, Some(14));
+    }
+
+    fn unsettled_delivery_info(
+        inner: &ReceiverInner<ReceiverLink<Target>>,
+        delivery_id: u32,
+        rcv_settle_mode: ReceiverSettleMode,
+    ) -> crate::link::delivery::DeliveryInfo {
+        use fe2o3_amqp_types::definitions::DeliveryTag;
+
+        let delivery_tag = DeliveryTag::from(vec![delivery_id as u8]);
+        inner
+            .link
+            .unsettled
+            .write()
+            .get_or_insert(Default::default())
+            .insert(delivery_tag.clone(), None);
+        crate::link::delivery::DeliveryInfo {
+            delivery_id,
+            delivery_tag,
+            rcv_settle_mode: Some(rcv_settle_mode),
+            _sealed: crate::util::Sealed {},
+        }
+    }
+
+    #[tokio::test]
+    async fn disposition_options_in_first_mode() {
+        use fe2o3_amqp_types::messaging::{Accepted, DeliveryState};
+
+        use crate::link::{receiver::DispositionOptions, DispositionError};
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let state = DeliveryState::Accepted(Accepted {});
+
+        // Leaving the delivery unsettled is not allowed
+        let info = unsettled_delivery_info(&inner, 0, ReceiverSettleMode::First);
+        let options = DispositionOptions {
+            settled: Some(false),
+            batchable: false,
+        };
+        let result = inner
+            .dispose_with_options(info.clone(), state.clone(), options)
+            .await;
+        assert!(matches!(
+            result,
+            Err(DispositionError::UnsettledInFirstMode)
+        ));
+        assert!(outgoing_rx.try_recv().is_err());
+
+        let options = DispositionOptions {
+            settled: None,
+            batchable: true,
+        };
+        inner
+            .dispose_with_options(info, state, options)
+            .await
+            .unwrap();
+        match outgoing_rx.recv().await {
+            Some(LinkFrame::Disposition(disposition)) => {
+                assert!(disposition.settled);
+                assert!(disposition.batchable);
+            }
+            _ => panic!("Expecting a Disposition"),
+        }
+    }
+
+    #[tokio::test]
+    async fn disposition_options_in_second_mode() {
+        use fe2o3_amqp_types::messaging::{Accepted, DeliveryState};
+
+        use crate::link::receiver::DispositionOptions;
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let state = DeliveryState::Accepted(Accepted {});
+
+        // The delivery is left unsettled by default
+        let info = unsettled_delivery_info(&inner, 0, ReceiverSettleMode::Second);
+        inner
+            .dispose_with_options(info, state.clone(), DispositionOptions::default())
+            .await
+            .unwrap();
+        match outgoing_rx.recv().await {
+            Some(LinkFrame::Disposition(disposition)) => assert!(!disposition.settled),
+            _ => panic!("Expecting a Disposition"),
+        }
+        assert_eq!(inner.link.unsettled_count(), 1);
+
+        // Settling immediately removes the delivery from the unsettled map
+        let info = unsettled_delivery_info(&inner, 1, ReceiverSettleMode::Second);
+        let options = DispositionOptions {
+            settled: Some(true),
+            batchable: false,
+        };
+        inner
+            .dispose_with_options(info, state, options)
+            .await
+            .unwrap();
+        match outgoing_rx.recv().await {
+            Some(LinkFrame::Disposition(disposition)) => assert!(disposition.settled),
+            _ => panic!("Expecting a Disposition"),
+        }
+        assert_eq!(inner.link.unsettled_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn flush_waits_until_disposition_is_written() {
+        use fe2o3_amqp_types::{
+            definitions::{DeliveryTag, ReceiverSettleMode},
+            messaging::{Accepted, DeliveryState},
+        };
+
+        use crate::{link::delivery::DeliveryInfo, util::Sealed};
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+
+        let delivery_tag = DeliveryTag::from(vec![1u8]);
+        inner
+            .link
+            .unsettled
+            .write()
+            .get_or_insert(Default::default())
+            .insert(delivery_tag.clone(), None);
+        let delivery_info = DeliveryInfo {
+            delivery_id: 0,
+            delivery_tag,
+            rcv_settle_mode: Some(ReceiverSettleMode::First),
+            _sealed: Sealed {},
+        };
+
+        let state = DeliveryState::Accepted(Accepted {});
+        inner.dispose(delivery_info, None, state).await.unwrap();
+
+        // Mock session that "writes" frames in order
+        let handle = tokio::spawn(async move {
+            let mut written = Vec::new();
+            while let Some(frame) = outgoing_rx.recv().await {
+                match frame {
+                    LinkFrame::Flush(resp) => {
+                        resp.send(()).unwrap();
+                        break;
+                    }
+                    frame => written.push(frame),
+                }
+            }
+            written
+        });
+
+        tokio::time::timeout(Duration::from_millis(500), inner.flush())
+            .await
+            .expect("flush should complete once the session responds")
+            .unwrap();
+        let written = handle.await.unwrap();
+        assert!(matches!(&written[..], [LinkFrame::Disposition(d)] if d.settled));
+    }
+
+    #[tokio::test]
+    async fn dispose_with_state_sends_given_state() {
+        use bytes::Bytes;
+        use fe2o3_amqp_types::{
+            definitions::{DeliveryTag, Role},
+            messaging::{
+                message::__private::Serializable, DeliveryState, Message, Modified, Received,
+            },
+        };
+
+        use crate::link::{delivery::Delivery, Receiver};
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+
+        let remote_state = DeliveryState::Received(Received {
+            section_number: 0,
+            section_offset: 0,
+        });
+        let transfer = Transfer {
+            handle: 0.into(),
+            delivery_id: Some(7),
+            delivery_tag: Some(DeliveryTag::from(vec![1u8])),
+            message_format: Some(0),
+            settled: Some(false),
+            more: false,
+            rcv_settle_mode: None,
+            state: Some(remote_state),
+            resume: false,
+            aborted: false,
+            batchable: false,
+        };
+        let payload = serde_amqp::to_vec(&Serializable(Message::from("hello"))).unwrap();
+        let delivery: Delivery<String> = inner
+            .on_complete_transfer(transfer, Bytes::from(payload))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            delivery.state(),
+            Some(DeliveryState::Received(Received {
+                section_number: 0,
+                section_offset: 0
+            }))
+        ));
+
+        let receiver = Receiver { inner };
+        let modified = Modified {
+            delivery_failed: Some(true),
+            undeliverable_here: None,
+            message_annotations: None,
+        };
+        receiver
+            .dispose_with_state(&delivery, DeliveryState::Modified(modified))
+            .await
+            .unwrap();
+
+        let disposition = match outgoing_rx.recv().await {
+            Some(LinkFrame::Disposition(disposition)) => disposition,
+            _ => panic!("Expecting a Disposition"),
+        };
+        assert_eq!(disposition.role, Role::Receiver);
+        assert_eq!(disposition.first, 7);
+        assert!(disposition.settled);
+        assert!(matches!(
+            disposition.state,
+            Some(DeliveryState::Modified(Modified {
+                delivery_failed: Some(true),
+                undeliverable_here: None,
+                message_annotations: None,
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn modify_sends_modified_outcome_in_both_settle_modes() {
+        use fe2o3_amqp_types::{
+            definitions::DeliveryTag,
+            messaging::{DeliveryState, Modified},
+            primitives::{Symbol, Value},
+        };
+
+        use crate::{
+            link::{delivery::DeliveryInfo, Receiver},
+            util::Sealed,
+        };
+
+        for (mode, settled) in [
+            (ReceiverSettleMode::First, true),
+            (ReceiverSettleMode::Second, false),
+        ] {
+            let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+            let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+            let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+            inner.link.rcv_settle_mode = mode;
+
+            let delivery_tag = DeliveryTag::from(vec![1u8]);
+            inner
+                .link
+                .unsettled
+                .write()
+                .get_or_insert(Default::default())
+                .insert(delivery_tag.clone(), None);
+            let delivery_info = DeliveryInfo {
+                delivery_id: 3,
+                delivery_tag: delivery_tag.clone(),
+                rcv_settle_mode: None,
+                _sealed: Sealed {},
+            };
+
+            let receiver = Receiver { inner };
+            let modified = Modified::failed()
+                .undeliverable_here(true)
+                .add_message_annotation("x-opt-reason", "poison");
+            receiver.modify(delivery_info, modified).await.unwrap();
+
+            let disposition = match outgoing_rx.recv().await {
+                Some(LinkFrame::Disposition(disposition)) => disposition,
+                _ => panic!("Expecting a Disposition"),
+            };
+            assert_eq!(disposition.first, 3);
+            assert_eq!(disposition.settled, settled);
+            let modified = match disposition.state {
+                Some(DeliveryState::Modified(modified)) => modified,
+                _ => panic!("Expecting Modified"),
+            };
+            assert_eq!(modified.delivery_failed, Some(true));
+            assert_eq!(modified.undeliverable_here, Some(true));
+            assert_eq!(
+                modified
+                    .message_annotations
+                    .as_ref()
+                    .and_then(|fields| fields.get(&Symbol::from("x-opt-reason"))),
+                Some(&Value::String(String::from("poison")))
+            );
+
+            // An unsettled delivery keeps the modified state in the unsettled map
+            let unsettled = receiver.inner.link.unsettled.read();
+            let local_state = unsettled.as_ref().and_then(|m| m.get(&delivery_tag));
+            match settled {
+                true => assert!(local_state.is_none()),
+                false => assert!(matches!(
+                    local_state,
+                    Some(Some(DeliveryState::Modified(_)))
+                )),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn flush_fails_if_session_is_gone() {
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        drop(outgoing_rx);
+
+        let result = inner.flush().await;
+        assert!(matches!(
+            result,
+            Err(super::DispositionError::IllegalSessionState)
+        ));
+    }
+
+    #[tokio::test]
+    async fn detach_with_error_sends_error_condition() {
+        use fe2o3_amqp_types::{
+            definitions::{self, LinkError},
+            performatives::Detach,
+        };
+
+        use crate::link::shared_inner::LinkEndpointInnerDetach;
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+
+        // Mock peer that echoes a non-closing detach
+        let handle = tokio::spawn(async move {
+            let detach = match outgoing_rx.recv().await {
+                Some(LinkFrame::Detach(detach)) => detach,
+                _ => panic!("Expecting a Detach"),
+            };
+            incoming_tx
+                .send(LinkFrame::Detach(Detach {
+                    handle: detach.handle.clone(),
+                    closed: false,
+                    error: None,
+                }))
+                .await
+                .unwrap();
+            detach
+        });
+
+        let error = definitions::Error::new(
+            LinkError::MessageSizeExceeded,
+            Some(String::from("message too large")),
+            None,
+        );
+        inner.detach_with_error(Some(error)).await.unwrap();
+
+        let detach = handle.await.unwrap();
+        assert!(!detach.closed);
+        let error = detach.error.expect("Detach should carry the error");
+        assert_eq!(
+            error.condition,
+            definitions::ErrorCondition::LinkError(LinkError::MessageSizeExceeded)
+        );
+        assert_eq!(error.description.as_deref(), Some("message too large"));
+    }
+
+    #[tokio::test]
+    async fn oversized_continuation_transfers_are_rejected() {
+        use bytes::Bytes;
+        use fe2o3_amqp_types::{
+            definitions::{ErrorCondition, LinkError},
+            messaging::DeliveryState,
+            performatives::Detach,
+            primitives::{Binary, Value},
+        };
+
+        use crate::{endpoint::InputHandle, link::RecvError};
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        inner.link.max_message_size = Some(10);
+
+        let transfer = |more: bool| Transfer {
+            handle: 0.into(),
+            delivery_id: Some(0),
+            delivery_tag: Some(Binary::from(vec![0u8])),
+            message_format: Some(0),
+            settled: Some(false),
+            more,
+            rcv_settle_mode: None,
+            state: None,
+            resume: false,
+            aborted: false,
+            batchable: false,
+        };
+        for _ in 0..3 {
+            incoming_tx
+                .send(LinkFrame::Transfer {
+                    input_handle: InputHandle(0),
+                    performative: transfer(true),
+                    payload: Bytes::from(vec![0u8; 6]),
+                })
+                .await
+                .unwrap();
+        }
+
+        // Mock peer that echoes a non-closing detach
+        let peer = tokio::spawn(async move {
+            let disposition = match outgoing_rx.recv().await {
+                Some(LinkFrame::Disposition(disposition)) => disposition,
+                _ => panic!("Expecting a Disposition"),
+            };
+            let detach = match outgoing_rx.recv().await {
+                Some(LinkFrame::Detach(detach)) => detach,
+                _ => panic!("Expecting a Detach"),
+            };
+            incoming_tx
+                .send(LinkFrame::Detach(Detach {
+                    handle: detach.handle.clone(),
+                    closed: false,
+                    error: None,
+                }))
+                .await
+                .unwrap();
+            (disposition, detach)
+        });
+
+        let result = inner.recv::<Value>().await;
+        assert!(matches!(result, Err(RecvError::MessageSizeExceeded)));
+        assert!(inner.incomplete_transfer.is_none());
+
+        let (disposition, detach) = peer.await.unwrap();
+        assert_eq!(disposition.first, 0);
+        assert!(disposition.settled);
+        match disposition.state {
+            Some(DeliveryState::Rejected(rejected)) => assert_eq!(
+                rejected.error.unwrap().condition,
+                ErrorCondition::LinkError(LinkError::MessageSizeExceeded)
+            ),
+            state => panic!("Expecting Rejected, found {:?}", state),
+        }
+        assert!(!detach.closed);
+        assert_eq!(
+            detach.error.unwrap().condition,
+            ErrorCondition::LinkError(LinkError::MessageSizeExceeded)
+        );
+    }
+
+    #[tokio::test]
+    async fn incomplete_transfer_times_out_when_continuation_never_arrives() {
+        use bytes::Bytes;
+        use fe2o3_amqp_types::primitives::{Binary, Value};
+
+        use crate::{endpoint::InputHandle, link::RecvError};
+
+        let (outgoing_tx, _outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        inner.incomplete_transfer_timeout = Some(Duration::from_millis(100));
+
+        let delivery_tag = Binary::from(vec![0u8]);
+        let transfer = Transfer {
+            handle: 0.into(),
+            delivery_id: Some(0),
+            delivery_tag: Some(delivery_tag.clone()),
+            message_format: Some(0),
+            settled: Some(false),
+            more: true,
+            rcv_settle_mode: None,
+            state: None,
+            resume: false,
+            aborted: false,
+            batchable: false,
+        };
+        incoming_tx
+            .send(LinkFrame::Transfer {
+                input_handle: InputHandle(0),
+                performative: transfer,
+                payload: Bytes::from(vec![0u8; 6]),
+            })
+            .await
+            .unwrap();
+
+        // The second fragment never arrives
+        let result = tokio::time::timeout(Duration::from_secs(1), inner.recv::<Value>())
+            .await
+            .expect("recv should time out on the incomplete transfer");
+        assert!(matches!(result, Err(RecvError::IncompleteTransferTimeout)));
+        assert!(inner.incomplete_transfer.is_none());
+        let unsettled = inner.link.unsettled.read();
+        assert!(!unsettled
+            .as_ref()
+            .map(|map| map.contains_key(&delivery_tag))
+            .unwrap_or(false));
+        drop(incoming_tx);
+    }
+
+    fn complete_transfer_frame(delivery_id: u32) -> LinkFrame {
+        use bytes::Bytes;
+        use fe2o3_amqp_types::{
+            definitions::DeliveryTag,
+            messaging::{message::__private::Serializable, Message},
+        };
+
+        use crate::endpoint::InputHandle;
+
+        let transfer = Transfer {
+            handle: 0.into(),
+            delivery_id: Some(delivery_id),
+            delivery_tag: Some(DeliveryTag::from(delivery_id.to_be_bytes().to_vec())),
+            message_format: Some(0),
+            settled: Some(false),
+            more: false,
+            rcv_settle_mode: None,
+            state: None,
+            resume: false,
+            aborted: false,
+            batchable: false,
+        };
+        let payload = serde_amqp::to_vec(&Serializable(Message::from("hello"))).unwrap();
+        LinkFrame::Transfer {
+            input_handle: InputHandle(0),
+            performative: transfer,
+            payload: Bytes::from(payload),
+        }
+    }
+
+    #[tokio::test]
+    async fn recv_with_timeout_returns_none_when_elapsed() {
+        use crate::link::Receiver;
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let mut receiver = Receiver { inner };
+
+        let result = receiver
+            .recv_with_timeout::<String>(Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert!(result.is_none());
+
+        // The timeout doesn't consume or replenish any link credit
+        assert_eq!(receiver.inner.link.flow_state.as_ref().link_credit(), 10);
+        assert!(outgoing_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn recv_with_timeout_returns_available_delivery() {
+        use crate::link::Receiver;
+
+        let (outgoing_tx, _outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let mut receiver = Receiver { inner };
+
+        incoming_tx.send(complete_transfer_frame(0)).await.unwrap();
+        let delivery = receiver
+            .recv_with_timeout::<String>(Duration::from_millis(500))
+            .await
+            .unwrap()
+            .expect("Expecting a delivery");
+        assert_eq!(delivery.body(), "hello");
+        assert_eq!(*delivery.delivery_id(), 0);
+    }
+
+    #[tokio::test]
+    async fn try_recv_returns_immediately() {
+        use crate::link::{Receiver, RecvError};
+
+        let (outgoing_tx, _outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let mut receiver = Receiver { inner };
+
+        assert!(receiver.try_recv::<String>().await.unwrap().is_none());
+
+        incoming_tx.send(complete_transfer_frame(0)).await.unwrap();
+        incoming_tx.send(complete_transfer_frame(1)).await.unwrap();
+        let delivery = receiver.try_recv::<String>().await.unwrap().unwrap();
+        assert_eq!(*delivery.delivery_id(), 0);
+        let delivery = receiver.try_recv::<String>().await.unwrap().unwrap();
+        assert_eq!(*delivery.delivery_id(), 1);
+        assert!(receiver.try_recv::<String>().await.unwrap().is_none());
+
+        drop(incoming_tx);
+        assert!(matches!(
+            receiver.try_recv::<String>().await,
+            Err(RecvError::LinkStateError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn accept_batch_coalesces_contiguous_deliveries() {
+        use fe2o3_amqp_types::messaging::DeliveryState;
+
+        use crate::link::Receiver;
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(100, outgoing_tx, incoming_rx);
+        inner.link.rcv_settle_mode = ReceiverSettleMode::Second;
+        let mut receiver = Receiver { inner };
+
+        for delivery_id in [3, 4, 5, 7, 8, 10] {
+            incoming_tx
+                .send(complete_transfer_frame(delivery_id))
+                .await
+                .unwrap();
+        }
+        let mut deliveries = Vec::new();
+        while let Some(delivery) = receiver.try_recv::<String>().await.unwrap() {
+            deliveries.push(delivery);
+        }
+        assert_eq!(deliveries.len(), 6);
+
+        // Three contiguous deliveries produce one disposition covering the range
+        receiver.accept_batch(&deliveries[..3]).await.unwrap();
+        let disposition = match outgoing_rx.try_recv() {
+            Ok(LinkFrame::Disposition(disposition)) => disposition,
+            _ => panic!("Expecting a Disposition"),
+        };
+        assert_eq!(disposition.first, 3);
+        assert_eq!(disposition.last, Some(5));
+        assert!(disposition.batchable);
+        assert!(!disposition.settled);
+        assert!(matches!(
+            disposition.state,
+            Some(DeliveryState::Accepted(_))
+        ));
+        assert!(outgoing_rx.try_recv().is_err());
+
+        // Non-contiguous deliveries produce one disposition per contiguous range
+        receiver.release_batch(&deliveries[3..]).await.unwrap();
+        let mut ranges = Vec::new();
+        while let Ok(frame) = outgoing_rx.try_recv() {
+            match frame {
+                LinkFrame::Disposition(disposition) => {
+                    assert!(disposition.batchable);
+                    assert!(matches!(
+                        disposition.state,
+                        Some(DeliveryState::Released(_))
+                    ));
+                    ranges.push((disposition.first, disposition.last));
+                }
+                _ => panic!("Expecting a Disposition"),
+            }
+        }
+        assert_eq!(ranges, vec![(7, Some(8)), (10, Some(10))]);
+    }
+
+    #[tokio::test]
+    async fn session_fatal_detach_error_ends_session() {
+        use fe2o3_amqp_types::{
+            definitions::{self, ErrorCondition, SessionError},
+            performatives::Detach,
+        };
+
+        use crate::{control::SessionControl, link::shared_inner::LinkEndpointInnerDetach};
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let (session_tx, mut session_rx) = mpsc::channel(10);
+        inner.session = session_tx;
+
+        // Mock peer that echoes a non-closing detach
+        let handle = tokio::spawn(async move {
+            let detach = match outgoing_rx.recv().await {
+                Some(LinkFrame::Detach(detach)) => detach,
+                _ => panic!("Expecting a Detach"),
+            };
+            incoming_tx
+                .send(LinkFrame::Detach(Detach {
+                    handle: detach.handle.clone(),
+                    closed: false,
+                    error: None,
+                }))
+                .await
+                .unwrap();
+            detach
+        });
+
+        let error = definitions::Error::new(SessionError::ErrantLink, None, None);
+        inner.detach_with_error(Some(error)).await.unwrap();
+
+        // The link is detached with the error
+        let detach = handle.await.unwrap();
+        assert_eq!(
+            detach.error.unwrap().condition,
+            ErrorCondition::SessionError(SessionError::ErrantLink)
+        );
+
+        // And then the session is ended with the same error
+        match session_rx.recv().await {
+            Some(SessionControl::End(Some(error))) => assert_eq!(
+                error.condition,
+                ErrorCondition::SessionError(SessionError::ErrantLink)
+            ),
+            _ => panic!("Expecting SessionControl::End"),
+        }
+    }
+
+    #[tokio::test]
+    async fn session_fatal_detach_error_does_not_wait_for_remote_detach() {
+        use fe2o3_amqp_types::definitions::{self, ErrorCondition, SessionError};
+
+        use crate::{control::SessionControl, link::shared_inner::LinkEndpointInnerDetach};
+
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(10);
+        let (_incoming_tx, incoming_rx) = mpsc::channel(10);
+        let mut inner = attached_receiver_inner(10, outgoing_tx, incoming_rx);
+        let (session_tx, mut session_rx) = mpsc::channel(10);
+        inner.session = session_tx;
+
+        // The remote peer never answers the detach
+        let error = definitions::Error::new(SessionError::ErrantLink, None, None);
+        tokio::time::timeout(
+            Duration::from_millis(500),
+            inner.detach_with_error(Some(error)),
+        )
+        .await
+        .expect("detach should not wait for the remote detach")
+        .unwrap();
+
+        assert!(matches!(
+            outgoing_rx.try_recv(),
+            Ok(LinkFrame::Detach(detach)) if detach.error.is_some()
+        ));
+        match session_rx.try_recv() {
+            Ok(SessionControl::End(Some(error))) => assert_eq!(
+                error.condition,
+                ErrorCondition::SessionError(SessionError::ErrantLink)
+            ),
+            _ => panic!("Expecting SessionControl::End"),
+        }
+    }
 }
@@ -14,6 +14,8 @@ use super::{delivery::DeliveryInfo, *};
 pub(crate) const DESCRIBED_TYPE: u8 = EncodingCodes::DescribedType as u8;
 pub(crate) const SMALL_ULONG_TYPE: u8 = EncodingCodes::SmallULong as u8;
 pub(crate) const ULONG_TYPE: u8 = EncodingCodes::ULong as u8;
+pub(crate) const VBIN8_TYPE: u8 = EncodingCodes::VBin8 as u8;
+pub(crate) const VBIN32_TYPE: u8 = EncodingCodes::VBin32 as u8;
 pub(crate) const HEADER_CODE: u8 = 0x70;
 pub(crate) const DELIV_ANNOT_CODE: u8 = 0x71;
 pub(crate) const MSG_ANNOT_CODE: u8 = 0x72;
@@ -142,7 +144,7 @@ where
 
         // ReceiverFlowState will not wait until link credit is available.
         // Will return with an error if there is not enough link credit.
-        self.flow_state.consume(1)?;
+        self.flow_state.as_ref().consume(1)?;
 
         // This only takes care of whether the message is considered
         // sett
@@ -154,6 +156,7 @@ where
             .delivery_tag
             .ok_or(Self::TransferError::DeliveryTagIsNone)?;
         let message_format = transfer.message_format;
+        let remote_state = transfer.state;
 
         let (message, mode) = if settled_by_sender {
             // If the message is pre-settled, there is no need to
@@ -217,6 +220,7 @@ where
             delivery_tag,
             message_format,
             rcv_settle_mode: mode,
+            state: remote_state,
             message,
         };
 
@@ -232,12 +236,15 @@ where
         state: DeliveryState,
         batchable: bool,
     ) -> Result<(), Self::DispositionError> {
+        let rcv_settle_mode = delivery_info
+            .rcv_settle_mode
+            .as_ref()
+            .unwrap_or(&self.rcv_settle_mode);
+        if let (ReceiverSettleMode::First, Some(false)) = (rcv_settle_mode, settled) {
+            return Err(Self::DispositionError::UnsettledInFirstMode);
+        }
         let settled = settled.unwrap_or({
-            match delivery_info
-                .rcv_settle_mode
-                .as_ref()
-                .unwrap_or(&self.rcv_settle_mode)
-            {
+            match rcv_settle_mode {
                 ReceiverSettleMode::First => {
                     // If first, this indicates that the receiver MUST settle
                     // the delivery once it has arrived without waiting
@@ -449,12 +456,15 @@ impl<T> ReceiverLink<T> {
             return Ok(());
         }
 
+        let rcv_settle_mode = consecutive_infos[0]
+            .rcv_settle_mode
+            .as_ref()
+            .unwrap_or(&self.rcv_settle_mode);
+        if let (ReceiverSettleMode::First, Some(false)) = (rcv_settle_mode, settled) {
+            return Err(DispositionError::UnsettledInFirstMode);
+        }
         let settled = settled.unwrap_or({
-            match consecutive_infos[0]
-                .rcv_settle_mode
-                .as_ref()
-                .unwrap_or(&self.rcv_settle_mode)
-            {
+            match rcv_settle_mode {
                 ReceiverSettleMode::First => true,
                 ReceiverSettleMode::Second => false,
             }
@@ -498,7 +508,7 @@ impl<T> ReceiverLink<T> {
     ) -> LinkFlow {
         match (link_credit, drain) {
             (Some(link_credit), Some(drain)) => {
-                let mut guard = self.flow_state.lock.write();
+                let mut guard = self.flow_state.as_ref().lock.write();
                 guard.link_credit = link_credit;
                 guard.drain = drain;
                 LinkFlow {
@@ -517,7 +527,7 @@ impl<T> ReceiverLink<T> {
                 }
             }
             (Some(link_credit), None) => {
-                let mut guard = self.flow_state.lock.write();
+                let mut guard = self.flow_state.as_ref().lock.write();
                 guard.link_credit = link_credit;
                 LinkFlow {
                     handle,
@@ -535,7 +545,7 @@ impl<T> ReceiverLink<T> {
                 }
             }
             (None, Some(drain)) => {
-                let mut guard = self.flow_state.lock.write();
+                let mut guard = self.flow_state.as_ref().lock.write();
                 guard.drain = drain;
                 LinkFlow {
                     handle,
@@ -553,7 +563,7 @@ impl<T> ReceiverLink<T> {
                 }
             }
             (None, None) => {
-                let guard = self.flow_state.lock.read();
+                let guard = self.flow_state.as_ref().lock.read();
                 LinkFlow {
                     handle,
                     // When the flow state is being sent from the receiver endpoint to the sender
@@ -672,6 +682,15 @@ where
             .as_ref()
             .delivery_count_mut(|_| initial_delivery_count);
 
+        self.remote_offered_capabilities = remote_attach
+            .offered_capabilities
+            .map(|capabilities| capabilities.into_inner());
+        if let Some(capability) = self.missing_required_capability() {
+            return Err(ReceiverAttachError::RequiredCapabilityNotOffered(
+                capability.clone(),
+            ));
+        }
+
         if let Some(remote_properties) = remote_attach.properties {
             self.properties_mut(|local_properties| {
                 local_properties
@@ -752,6 +771,10 @@ where
         &self.unsettled
     }
 
+    fn snd_settle_mode(&self) -> &SenderSettleMode {
+        &self.snd_settle_mode
+    }
+
     fn rcv_settle_mode(&self) -> &ReceiverSettleMode {
         &self.rcv_settle_mode
     }
@@ -761,17 +784,14 @@ where
     }
 
     fn max_message_size(&self) -> Option<u64> {
-        match self.max_message_size {
-            0 => None,
-            _ => Some(self.max_message_size),
-        }
+        self.max_message_size
     }
 
     fn properties<F, O>(&self, op: F) -> O
     where
         F: FnOnce(&Option<Fields>) -> O,
     {
-        let guard = self.flow_state.lock.read();
+        let guard = self.flow_state.as_ref().lock.read();
         op(&guard.properties)
     }
 
@@ -779,7 +799,7 @@ where
     where
         F: FnOnce(&mut Option<Fields>) -> O,
     {
-        let mut guard = self.flow_state.lock.write();
+        let mut guard = self.flow_state.as_ref().lock.write();
         op(&mut guard.properties)
     }
 
@@ -850,18 +870,18 @@ where
             }
 
             ReceiverAttachError::CoordinatorIsNotImplemented
+            | ReceiverAttachError::RequiredCapabilityNotOffered(_)
             | ReceiverAttachError::InitialDeliveryCountIsNone
             | ReceiverAttachError::SourceAddressIsNoneWhenDynamicIsTrue
             | ReceiverAttachError::TargetAddressIsSomeWhenDynamicIsTrue
-            | ReceiverAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse => {
-                match (&attach_error).try_into() {
-                    Ok(error) => match self.send_detach(writer, true, Some(error)).await {
-                        Ok(_) => recv_detach(self, reader, attach_error).await,
-                        Err(_) => ReceiverAttachError::IllegalSessionState,
-                    },
-                    Err(_) => attach_error,
-                }
-            }
+            | ReceiverAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse
+            | ReceiverAttachError::Rejected(_) => match (&attach_error).try_into() {
+                Ok(error) => match self.send_detach(writer, true, Some(error)).await {
+                    Ok(_) => recv_detach(self, reader, attach_error).await,
+                    Err(_) => ReceiverAttachError::IllegalSessionState,
+                },
+                Err(_) => attach_error,
+            },
             _ => attach_error,
         }
     }
@@ -1,47 +1,70 @@
 //! Implementation of AMQP1.0 sender
 
+use std::{
+    collections::VecDeque,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
 use async_trait::async_trait;
-use bytes::{Bytes, BytesMut};
-use tokio::sync::{mpsc, oneshot};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{ready, Sink};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    sync::{
+        mpsc::{self, error::TrySendError},
+        oneshot,
+    },
+};
 
 cfg_not_wasm32! {
+    use futures_util::future::BoxFuture;
     use std::time::Duration;
     use tokio::time::{error::Elapsed, timeout};
 }
 
 use fe2o3_amqp_types::{
-    definitions::{self, DeliveryTag, Fields, MessageFormat, SenderSettleMode},
+    definitions::{self, DeliveryTag, Fields, MessageFormat, ReceiverSettleMode, SenderSettleMode},
     messaging::{
-        message::__private::Serializable, Address, DeliveryState, Outcome, SerializableBody,
-        Source, Target,
+        message::__private::Serializable, Address, DataStream, DeliveryState, Header, Message,
+        Outcome, SerializableBody, Source, Target, MESSAGE_FORMAT,
     },
     performatives::{Attach, Detach, Transfer},
-    primitives::OrderedMap,
+    primitives::{OrderedMap, Symbol},
 };
 
 use crate::{
+    connection::{UnsettledDeliveries, UnsettledPermit},
     control::SessionControl,
     endpoint::{self, LinkAttach, LinkDetach, LinkExt, Settlement},
+    payload::TransferPayload,
     session::SessionHandle,
+    util::TryConsume,
     Payload,
 };
 
 use super::{
     builder::{self, WithSource, WithoutName, WithoutTarget},
-    delivery::{DeliveryFut, Sendable, UnsettledMessage},
+    delivery::{DeliveryFut, DeliveryOutcome, DeliveryTagGenerator, Sendable, UnsettledMessage},
     error::DetachError,
     resumption::ResumingDelivery,
     role,
     shared_inner::{
         recv_remote_detach, LinkEndpointInner, LinkEndpointInnerDetach, LinkEndpointInnerReattach,
     },
-    ArcSenderUnsettledMap, DetachThenResumeSenderError, LinkFrame, LinkRelay, LinkStateError,
-    SendError, SenderAttachError, SenderAttachExchange, SenderFlowState, SenderLink,
-    SenderResumeError, SenderResumeErrorKind,
+    ArcSenderUnsettledMap, DetachThenResumeSenderError, DuplicateDeliveryTag, FlowError,
+    IllegalRcvSettleMode, LinkFrame, LinkRelay, LinkStateError, MessageSizeExceeded,
+    RemoteFlowState, SendError, SenderAttachError, SenderAttachExchange, SenderFlowState,
+    SenderLink, SenderResumeError, SenderResumeErrorKind,
 };
 
+#[cfg(target_arch = "wasm32")]
+use futures_util::future::LocalBoxFuture;
+
 #[cfg(docsrs)]
-use fe2o3_amqp_types::messaging::{AmqpSequence, AmqpValue, Batch, Body, Data, IntoBody, Message, MESSAGE_FORMAT};
+use fe2o3_amqp_types::messaging::{AmqpSequence, AmqpValue, Batch, Body, Data, IntoBody};
 
 /// An AMQP1.0 sender
 ///
@@ -116,12 +139,39 @@ impl Sender {
         self.inner.link.name()
     }
 
-    /// Returns the `max_message_size` of the link. A value of zero indicates that the link has no
-    /// maximum message size, and thus a zero value is turned into a `None`
+    /// Returns the `max_message_size` of the link. `None` indicates that the field is not set and
+    /// `Some(0)` that there is explicitly no limit. Neither imposes a maximum message size
     pub fn max_message_size(&self) -> Option<u64> {
         self.inner.link.max_message_size()
     }
 
+    /// Returns the address of the target
+    ///
+    /// If a dynamically created node is requested, this is the address of the node created by the
+    /// remote peer
+    pub fn target_address(&self) -> Option<&Address> {
+        self.inner
+            .link
+            .target
+            .as_ref()
+            .and_then(|target| target.address.as_ref())
+    }
+
+    /// Returns the capabilities that the remote peer offered in its Attach frame
+    pub fn offered_capabilities(&self) -> Option<&[Symbol]> {
+        self.inner.link.remote_offered_capabilities.as_deref()
+    }
+
+    /// Returns the number of deliveries sent on this link that are not yet settled
+    pub fn unsettled_count(&self) -> usize {
+        self.inner.link.unsettled_count()
+    }
+
+    /// Returns the delivery tags of the deliveries sent on this link that are not yet settled
+    pub fn unsettled_tags(&self) -> Vec<DeliveryTag> {
+        self.inner.link.unsettled_tags()
+    }
+
     /// Get a reference to the link's source field
     pub fn source(&self) -> &Option<Source> {
         &self.inner.link.source
@@ -207,6 +257,10 @@ impl Sender {
     }
 
     /// Detach the link with an error
+    ///
+    /// If the error condition is a session error (eg. `amqp:session:errant-link`), the session
+    /// will also be ended with the same error right after the detach is sent, without waiting for
+    /// the remote peer to reply.
     pub async fn detach_with_error(
         mut self,
         error: impl Into<definitions::Error>,
@@ -244,6 +298,7 @@ impl Sender {
         // Re-attach the link
         self.inner.session = new_session.control.clone();
         self.inner.outgoing = new_session.outgoing.clone();
+        self.inner.unsettled_deliveries = new_session.unsettled_deliveries.clone();
         let attach_result = self.inner.resume_incoming_attach(None).await;
 
         match (detach_result, attach_result) {
@@ -261,6 +316,10 @@ impl Sender {
     }
 
     /// Detach the link with an error
+    ///
+    /// If the error condition is a session error (eg. `amqp:session:errant-link`), the session
+    /// will also be ended with the same error right after the detach is sent, without waiting for
+    /// the remote peer to reply.
     pub async fn close_with_error(
         mut self,
         error: impl Into<definitions::Error>,
@@ -396,6 +455,38 @@ impl Sender {
         fut.await
     }
 
+    /// Send a message that has already been encoded and wait for the acknowledgement
+    ///
+    /// The payload must hold the encoded sections of a message, which are sent as they are with
+    /// the [`MESSAGE_FORMAT`]. The payload is not copied, so a buffer from a pool or another
+    /// allocator can be sent by implementing [`TransferPayload`] for it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let payload: Vec<u8> = encode_message_into_pooled_buffer();
+    /// let outcome = sender.send_encoded(payload).await.unwrap();
+    /// ```
+    pub async fn send_encoded<P: TransferPayload>(
+        &mut self,
+        payload: P,
+    ) -> Result<Outcome, SendError> {
+        let fut = self
+            .inner
+            .send_payload::<SendError>(
+                payload.into_bytes(),
+                MESSAGE_FORMAT,
+                None,
+                None,
+                None,
+                None,
+                false,
+            )
+            .await
+            .map(DeliveryFut::from)?;
+        fut.await
+    }
+
     /// Send a message and wait for acknowledgement (disposition) with a timeout.
     ///
     /// This simply wraps [`send`](#method.send) inside a [`tokio::time::timeout`]
@@ -444,6 +535,107 @@ impl Sender {
             .map(DeliveryFut::from)
     }
 
+    /// Send a message without waiting for link credit or for capacity on the session outgoing
+    /// channel, and without waiting for the acknowledgement.
+    ///
+    /// [`SendError::WouldBlock`] is returned and nothing is sent if there is no link credit, if
+    /// the session outgoing channel does not have capacity, or if the connection-wide unsettled
+    /// limit is reached. The capacity of the session outgoing channel can be observed with
+    /// [`SessionHandle::capacity()`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// match sender.try_send("hello") {
+    ///     Ok(fut) => { /* the message is queued */ }
+    ///     Err(SendError::WouldBlock) => { /* try again later */ }
+    ///     Err(err) => { /* other errors */ }
+    /// }
+    /// ```
+    #[allow(clippy::result_large_err)]
+    pub fn try_send<T: SerializableBody>(
+        &mut self,
+        sendable: impl Into<Sendable<T>>,
+    ) -> Result<DeliveryFut<Result<Outcome, SendError>>, SendError> {
+        self.inner
+            .try_send(sendable.into(), false)
+            .map(DeliveryFut::from)
+    }
+
+    /// Send a message whose data section is read from a reader and wait for the acknowledgement
+    ///
+    /// The binary is read in chunks of at most 64 KiB and each chunk is sent in its own transfer
+    /// frame of the same delivery as soon as it is read, so the whole binary is never held in
+    /// memory. If reading fails or ends before `len` bytes are read, the delivery is aborted and
+    /// [`SendError::Io`] is returned.
+    ///
+    /// Because the payload is not kept, a streamed delivery that is still unsettled cannot be
+    /// resent when the link is resumed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let file = tokio::fs::File::open("large.bin").await?;
+    /// let len = file.metadata().await?.len() as usize;
+    /// let outcome = sender.send_data_stream(Message::stream_data(file, len)).await?;
+    /// ```
+    ///
+    /// # Cancel safety
+    ///
+    /// This is NOT cancel safe. Cancelling it after the first transfer frame is sent leaves an
+    /// incomplete delivery on the link.
+    pub async fn send_data_stream<R>(
+        &mut self,
+        message: Message<DataStream<R>>,
+    ) -> Result<Outcome, SendError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let fut = self
+            .inner
+            .send_data_stream(message)
+            .await
+            .map(DeliveryFut::<Result<Outcome, SendError>>::from)?;
+        fut.await
+    }
+
+    /// Turns the sender into a [`Sink`] of messages
+    ///
+    /// See [`SenderSink`] for how the sink interacts with the link credit and the settlement.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use futures_util::{stream, SinkExt, StreamExt};
+    ///
+    /// let mut sink = sender.into_sink();
+    /// let mut messages = stream::iter(["hello", "world"]).map(|body| Ok(Sendable::from(body)));
+    /// sink.send_all(&mut messages).await?;
+    /// let outcomes = sink.take_outcomes();
+    /// ```
+    pub fn into_sink<T>(self) -> SenderSink<T>
+    where
+        T: SerializableBody + Send + Sync + 'static,
+    {
+        SenderSink {
+            sender: Some(self),
+            send_fut: None,
+            unsettled: VecDeque::new(),
+            outcomes: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Request the remote receiver to echo its link flow state.
+    ///
+    /// This will send a `Flow` performative with the `echo` field set to true and wait for the
+    /// next `Flow` from the remote receiver, which carries its `link-credit`, `delivery-count` and
+    /// `available`. Multiple requests can be awaited concurrently, and each is resolved by its own
+    /// incoming `Flow` in the order the requests were made.
+    pub async fn request_flow_echo(&self) -> Result<RemoteFlowState, FlowError> {
+        self.inner.request_flow_echo().await
+    }
+
     /// Returns when the remote peer detach/close the link
     pub async fn on_detach(&mut self) -> DetachError {
         match recv_remote_detach(&mut self.inner).await {
@@ -465,6 +657,124 @@ impl Sender {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+type SendFuture = BoxFuture<'static, (Sender, Result<Settlement, SendError>)>;
+
+#[cfg(target_arch = "wasm32")]
+type SendFuture = LocalBoxFuture<'static, (Sender, Result<Settlement, SendError>)>;
+
+/// A [`Sink`] that sends messages on a [`Sender`]
+///
+/// This is created by [`Sender::into_sink`].
+///
+/// - `poll_ready` completes the transfer of the previous message, which waits for link credit,
+///   so the sink is not ready while the remote receiver withholds credit.
+/// - `start_send` starts the transfer of a message.
+/// - `poll_flush` completes the transfer of the last message and waits for all the sent
+///   deliveries to be settled. The outcomes are kept in the order the messages were sent and can
+///   be taken with [`take_outcomes`](#method.take_outcomes).
+///
+/// Pre-settled deliveries, ie. with `SenderSettleMode::Settled` or [`Sendable::settled`] set to
+/// `true`, do not wait for the receiver and are reported as accepted like with
+/// [`Sender::send`].
+///
+/// `poll_close` only flushes the sink and does not close the link. The sender can be taken back
+/// with [`into_inner`](#method.into_inner) to close the link.
+pub struct SenderSink<T> {
+    sender: Option<Sender>,
+    send_fut: Option<SendFuture>,
+    unsettled: VecDeque<DeliveryFut<Result<Outcome, SendError>>>,
+    outcomes: Vec<Outcome>,
+    marker: PhantomData<fn(T)>,
+}
+
+impl<T> std::fmt::Debug for SenderSink<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SenderSink")
+            .field("sender", &self.sender)
+            .field("sending", &self.send_fut.is_some())
+            .field("unsettled", &self.unsettled.len())
+            .field("outcomes", &self.outcomes)
+            .finish()
+    }
+}
+
+impl<T> SenderSink<T> {
+    /// Returns a mutable reference to the sender
+    ///
+    /// Returns `None` if the transfer of a message has been started but not completed by
+    /// `poll_ready` or `poll_flush`.
+    pub fn get_mut(&mut self) -> Option<&mut Sender> {
+        self.sender.as_mut()
+    }
+
+    /// Returns the sender
+    ///
+    /// Returns `None` if the transfer of a message has been started but not completed by
+    /// `poll_ready` or `poll_flush`. Deliveries that are not settled yet are still tracked by the
+    /// sender.
+    pub fn into_inner(self) -> Option<Sender> {
+        self.sender
+    }
+
+    /// Takes the outcomes of the deliveries settled so far in the order the messages were sent
+    pub fn take_outcomes(&mut self) -> Vec<Outcome> {
+        std::mem::take(&mut self.outcomes)
+    }
+
+    fn poll_send_fut(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        if let Some(send_fut) = self.send_fut.as_mut() {
+            let (sender, result) = ready!(send_fut.as_mut().poll(cx));
+            self.send_fut = None;
+            self.sender = Some(sender);
+            self.unsettled.push_back(DeliveryFut::from(result?));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> Sink<Sendable<T>> for SenderSink<T>
+where
+    T: SerializableBody + Send + Sync + 'static,
+{
+    type Error = SendError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_send_fut(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Sendable<T>) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        let mut sender = this
+            .sender
+            .take()
+            .ok_or(SendError::LinkStateError(LinkStateError::IllegalState))?;
+        this.send_fut = Some(Box::pin(async move {
+            let result = sender
+                .inner
+                .send_with_state::<T, SendError>(item, None, false)
+                .await;
+            (sender, result)
+        }));
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_fut(cx))?;
+        while let Some(delivery_fut) = this.unsettled.front_mut() {
+            let outcome = ready!(Pin::new(delivery_fut).poll(cx));
+            this.unsettled.pop_front();
+            this.outcomes.push(outcome?);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
 /// This is so that the transaction controller can re-use
 /// the sender
 #[derive(Debug)]
@@ -479,6 +789,13 @@ pub(crate) struct SenderInner<L: endpoint::SenderLink> {
     // Outgoing mpsc channel to send the Link frames
     pub(crate) outgoing: mpsc::Sender<LinkFrame>,
     pub(crate) incoming: mpsc::Receiver<LinkFrame>,
+
+    // Connection-wide unsettled deliveries
+    pub(crate) unsettled_deliveries: UnsettledDeliveries,
+
+    pub(crate) delivery_tag_generator: DeliveryTagGenerator,
+
+    pub(crate) increment_delivery_count_on_resend: bool,
 }
 
 impl<L: endpoint::SenderLink> Drop for SenderInner<L> {
@@ -622,7 +939,11 @@ where
     ) -> Result<Settlement, E>
     where
         T: SerializableBody,
-        E: From<L::TransferError> + From<serde_amqp::Error>,
+        E: From<L::TransferError>
+            + From<serde_amqp::Error>
+            + From<MessageSizeExceeded>
+            + From<IllegalRcvSettleMode>
+            + From<DuplicateDeliveryTag>,
     {
         use bytes::BufMut;
         use serde::Serialize;
@@ -632,6 +953,9 @@ where
             message,
             message_format,
             settled,
+            state: initial_state,
+            rcv_settle_mode,
+            delivery_tag,
         } = sendable;
 
         // serialize message
@@ -640,8 +964,17 @@ where
         Serializable(message).serialize(&mut serializer)?;
         let payload = payload.freeze();
 
-        self.send_payload(payload, message_format, settled, state, batchable)
-            .await
+        let state = state.or(initial_state);
+        self.send_payload(
+            payload,
+            message_format,
+            settled,
+            state,
+            rcv_settle_mode,
+            delivery_tag,
+            batchable,
+        )
+        .await
     }
 
     pub(crate) async fn send_ref_with_state<T, E>(
@@ -652,7 +985,11 @@ where
     ) -> Result<Settlement, E>
     where
         T: SerializableBody,
-        E: From<L::TransferError> + From<serde_amqp::Error>,
+        E: From<L::TransferError>
+            + From<serde_amqp::Error>
+            + From<MessageSizeExceeded>
+            + From<IllegalRcvSettleMode>
+            + From<DuplicateDeliveryTag>,
     {
         use bytes::BufMut;
         use serde::Serialize;
@@ -662,6 +999,9 @@ where
             message,
             message_format,
             settled,
+            state: initial_state,
+            rcv_settle_mode,
+            delivery_tag,
         } = sendable;
 
         // serialize message
@@ -670,21 +1010,47 @@ where
         Serializable(message).serialize(&mut serializer)?;
         let payload = payload.freeze();
 
-        self.send_payload(payload, *message_format, *settled, state, batchable)
-            .await
+        let state = state.or_else(|| initial_state.clone());
+        self.send_payload(
+            payload,
+            *message_format,
+            *settled,
+            state,
+            rcv_settle_mode.clone(),
+            delivery_tag.clone(),
+            batchable,
+        )
+        .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn send_payload<E>(
         &mut self,
         payload: Payload,
         message_format: MessageFormat,
         settled: Option<bool>,
         state: Option<DeliveryState>,
+        rcv_settle_mode: Option<ReceiverSettleMode>,
+        delivery_tag: Option<DeliveryTag>,
         batchable: bool,
     ) -> Result<Settlement, E>
     where
-        E: From<L::TransferError> + From<serde_amqp::Error>,
+        E: From<L::TransferError>
+            + From<serde_amqp::Error>
+            + From<MessageSizeExceeded>
+            + From<IllegalRcvSettleMode>
+            + From<DuplicateDeliveryTag>,
     {
+        self.check_rcv_settle_mode(&rcv_settle_mode)?;
+        self.check_message_size(payload.len())?;
+        let delivery_tag = self.resolve_delivery_tag(delivery_tag)?;
+
+        // Wait for capacity if the delivery will count towards the connection-wide limit
+        let permit = match self.will_settle(settled) {
+            true => None,
+            false => Some(self.unsettled_deliveries.acquire().await), // cancel safe
+        };
+
         // send a transfer, checking state will be implemented in SenderLink
         let detached_fut = self.incoming.recv(); // cancel safe
         let settlement = self
@@ -696,14 +1062,311 @@ where
                 message_format,
                 settled,
                 state,
+                rcv_settle_mode,
+                delivery_tag,
                 batchable,
             )
             .await?;
+
+        self.attach_unsettled_permit(&settlement, permit);
         Ok(settlement)
     }
+
+    /// If the negotiated value is first, then it is illegal to set the `rcv-settle-mode` of a
+    /// transfer to second
+    fn check_rcv_settle_mode(
+        &self,
+        rcv_settle_mode: &Option<ReceiverSettleMode>,
+    ) -> Result<(), IllegalRcvSettleMode> {
+        match (self.link.rcv_settle_mode(), rcv_settle_mode) {
+            (ReceiverSettleMode::First, Some(ReceiverSettleMode::Second)) => {
+                Err(IllegalRcvSettleMode)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// An explicit delivery tag overrides the one generated by the strategy of the sender.
+    /// `None` is returned if the delivery-count should be used as the delivery tag.
+    fn resolve_delivery_tag(
+        &mut self,
+        delivery_tag: Option<DeliveryTag>,
+    ) -> Result<Option<DeliveryTag>, DuplicateDeliveryTag> {
+        let delivery_tag = delivery_tag.or_else(|| self.delivery_tag_generator.next_tag());
+        if let Some(tag) = &delivery_tag {
+            let is_duplicate = self
+                .link
+                .unsettled()
+                .read()
+                .as_ref()
+                .map(|map| map.contains_key(tag))
+                .unwrap_or(false);
+            if is_duplicate {
+                return Err(DuplicateDeliveryTag);
+            }
+        }
+        Ok(delivery_tag)
+    }
+
+    /// If the `max-message-size` of the link is zero or unset, there is no maximum size imposed
+    fn check_message_size(&self, len: usize) -> Result<(), MessageSizeExceeded> {
+        match self.link.max_message_size() {
+            Some(max_message_size) if max_message_size > 0 && len as u64 > max_message_size => {
+                Err(MessageSizeExceeded)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn will_settle(&self, settled: Option<bool>) -> bool {
+        match self.link.snd_settle_mode() {
+            SenderSettleMode::Settled => true,
+            SenderSettleMode::Unsettled => false,
+            SenderSettleMode::Mixed => settled.unwrap_or(false),
+        }
+    }
+
+    /// The permit is released when the delivery is removed from the unsettled map
+    fn attach_unsettled_permit(&self, settlement: &Settlement, permit: Option<UnsettledPermit>) {
+        if let Settlement::Unsettled { delivery_tag, .. } = settlement {
+            if let Some(msg) = self
+                .link
+                .unsettled()
+                .write()
+                .as_mut()
+                .and_then(|m| m.get_mut(delivery_tag))
+            {
+                msg.permit = permit;
+            }
+        }
+    }
 }
 
 impl SenderInner<SenderLink<Target>> {
+    /// # Cancel safety
+    ///
+    /// This is cancel safe. A cancelled request will not consume the reply of another request.
+    pub(crate) async fn request_flow_echo(&self) -> Result<RemoteFlowState, FlowError> {
+        // Register before sending so that the echoed flow cannot be missed
+        let echoed = self.link.flow_state.state().register_echo_waiter();
+        endpoint::SenderLink::send_flow(&self.link, &self.outgoing, None, None, true).await?; // cancel safe
+
+        tokio::select! {
+            biased;
+            remote = echoed => remote.map_err(|_| FlowError::IllegalSessionState),
+            _ = self.outgoing.closed() => Err(FlowError::IllegalSessionState),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn try_send<T>(
+        &mut self,
+        sendable: Sendable<T>,
+        batchable: bool,
+    ) -> Result<Settlement, SendError>
+    where
+        T: SerializableBody,
+    {
+        use bytes::BufMut;
+        use serde::Serialize;
+        use serde_amqp::ser::Serializer;
+
+        let Sendable {
+            message,
+            message_format,
+            settled,
+            state,
+            rcv_settle_mode,
+            delivery_tag,
+        } = sendable;
+
+        // serialize message
+        let mut payload = BytesMut::new();
+        let mut serializer = Serializer::from((&mut payload).writer());
+        Serializable(message).serialize(&mut serializer)?;
+        let payload = payload.freeze();
+
+        self.try_send_payload(
+            payload,
+            message_format,
+            settled,
+            state,
+            rcv_settle_mode,
+            delivery_tag,
+            batchable,
+        )
+    }
+
+    /// Sends the payload only if it doesn't need to wait for link credit, for capacity on the
+    /// session outgoing channel or for the connection-wide unsettled limit
+    #[allow(clippy::too_many_arguments, clippy::result_large_err)]
+    pub(crate) fn try_send_payload(
+        &mut self,
+        payload: Payload,
+        message_format: MessageFormat,
+        settled: Option<bool>,
+        state: Option<DeliveryState>,
+        rcv_settle_mode: Option<ReceiverSettleMode>,
+        delivery_tag: Option<DeliveryTag>,
+        batchable: bool,
+    ) -> Result<Settlement, SendError> {
+        self.check_rcv_settle_mode(&rcv_settle_mode)?;
+        self.check_message_size(payload.len())?;
+        let delivery_tag = self.resolve_delivery_tag(delivery_tag)?;
+        // Reserve the slot first so that another link on the session cannot take it
+        let slot = self.outgoing.try_reserve().map_err(|err| match err {
+            TrySendError::Full(_) => SendError::WouldBlock,
+            TrySendError::Closed(_) => LinkStateError::IllegalSessionState.into(),
+        })?;
+
+        let permit = match self.will_settle(settled) {
+            true => None,
+            false => Some(
+                self.unsettled_deliveries
+                    .try_acquire()
+                    .ok_or(SendError::WouldBlock)?,
+            ),
+        };
+        let tag = self
+            .link
+            .flow_state
+            .try_consume(1)
+            .map_err(|_| SendError::WouldBlock)?;
+
+        let transfer = self.link.generate_non_resuming_transfer_performative(
+            delivery_tag.unwrap_or_else(|| DeliveryTag::from(tag)),
+            message_format,
+            settled,
+            state,
+            rcv_settle_mode,
+            batchable,
+        )?;
+        let settlement = self.link.send_payload_with_reserved_transfer(
+            slot,
+            message_format,
+            transfer,
+            payload,
+        )?;
+        self.attach_unsettled_permit(&settlement, permit);
+        Ok(settlement)
+    }
+
+    /// Sends the sections before the body, the header of the data section and the footer in the
+    /// first and last transfer frames, and the binary read from the reader in between
+    pub(crate) async fn send_data_stream<R>(
+        &mut self,
+        message: Message<DataStream<R>>,
+    ) -> Result<Settlement, SendError>
+    where
+        R: AsyncRead + Unpin + Send,
+    {
+        let Message {
+            header,
+            delivery_annotations,
+            message_annotations,
+            properties,
+            application_properties,
+            body: DataStream { mut reader, len },
+            footer,
+        } = message;
+        let data_len = u32::try_from(len).map_err(|_| SendError::MessageSizeExceeded)?;
+
+        let mut head = BytesMut::new();
+        encode_section(&mut head, &header)?;
+        encode_section(&mut head, &delivery_annotations)?;
+        encode_section(&mut head, &message_annotations)?;
+        encode_section(&mut head, &properties)?;
+        encode_section(&mut head, &application_properties)?;
+        // The binary is always encoded as vbin32 so that the size is known before it is read
+        head.put_slice(&[0x00, 0x53, 0x75, 0xb0]);
+        head.put_u32(data_len);
+        let mut tail = BytesMut::new();
+        encode_section(&mut tail, &footer)?;
+
+        self.check_message_size(head.len() + len + tail.len())?;
+        let delivery_tag = self.resolve_delivery_tag(None)?;
+
+        let permit = match self.will_settle(None) {
+            true => None,
+            false => Some(self.unsettled_deliveries.acquire().await),
+        };
+        let detached_fut = self.incoming.recv();
+        let tag = self
+            .link
+            .get_delivery_tag_or_detached(&self.outgoing, detached_fut)
+            .await?;
+        let delivery_tag = delivery_tag.unwrap_or_else(|| DeliveryTag::from(tag));
+        let mut transfer = self.link.generate_non_resuming_transfer_performative(
+            delivery_tag.clone(),
+            MESSAGE_FORMAT,
+            None,
+            None,
+            None,
+            false,
+        )?;
+        let settled = transfer.settled.unwrap_or(false);
+
+        // Sending the sections before the body first makes sure that the delivery can be
+        // aborted if reading fails
+        let mut remaining = len;
+        let mut chunk = head;
+        if remaining == 0 {
+            chunk.put(tail.split());
+        }
+        transfer.more = remaining > 0;
+        self.link
+            .send_transfer_fragment(&self.outgoing, transfer.clone(), chunk.split().freeze())
+            .await?;
+
+        // Only the first transfer of a multi-transfer delivery carries the delivery details
+        transfer.delivery_tag = None;
+        transfer.message_format = None;
+        transfer.settled = None;
+        transfer.rcv_settle_mode = None;
+        transfer.state = None;
+        while remaining > 0 {
+            let n = std::cmp::min(remaining, DATA_STREAM_CHUNK_SIZE);
+            chunk.resize(n, 0);
+            if let Err(err) = reader.read_exact(&mut chunk[..]).await {
+                // An aborted delivery is implicitly settled
+                transfer.more = false;
+                transfer.aborted = true;
+                self.link
+                    .send_transfer_fragment(&self.outgoing, transfer, Bytes::new())
+                    .await?;
+                return Err(SendError::Io(err));
+            }
+            remaining -= n;
+            if remaining == 0 {
+                chunk.put(tail.split());
+            }
+            transfer.more = remaining > 0;
+            self.link
+                .send_transfer_fragment(&self.outgoing, transfer.clone(), chunk.split().freeze())
+                .await?;
+        }
+
+        let settlement = match settled {
+            true => Settlement::Settled(delivery_tag),
+            false => {
+                // The payload is not kept, so the delivery cannot be resent upon resumption
+                let (tx, rx) = oneshot::channel();
+                let unsettled = UnsettledMessage::new(Bytes::new(), None, MESSAGE_FORMAT, tx);
+                self.link
+                    .unsettled
+                    .write()
+                    .get_or_insert(OrderedMap::new())
+                    .insert(delivery_tag.clone(), unsettled);
+                Settlement::Unsettled {
+                    delivery_tag,
+                    outcome: rx,
+                }
+            }
+        };
+        self.attach_unsettled_permit(&settlement, permit);
+        Ok(settlement)
+    }
+
     /// Resumes a delivery with the given state and payload.
     ///
     /// The resume operation should not replace the unsettled map entry.
@@ -744,7 +1407,7 @@ impl SenderInner<SenderLink<Target>> {
         &mut self,
         delivery_tag: DeliveryTag,
         message_format: MessageFormat,
-        sender: Option<oneshot::Sender<Option<DeliveryState>>>,
+        sender: Option<oneshot::Sender<DeliveryOutcome>>,
     ) -> Result<(), SendError> {
         let handle = self
             .link
@@ -779,7 +1442,7 @@ impl SenderInner<SenderLink<Target>> {
         match settled {
             true => {
                 if let Some(sender) = sender {
-                    let _ = sender.send(None);
+                    let _ = sender.send(Ok(None));
                 }
             }
             false => {
@@ -855,7 +1518,7 @@ impl SenderInner<SenderLink<Target>> {
         message_format: MessageFormat,
         state: DeliveryState,
         payload: Payload,
-        sender: oneshot::Sender<Option<DeliveryState>>,
+        sender: oneshot::Sender<DeliveryOutcome>,
     ) -> Result<(), SendError> {
         let handle = self
             .link
@@ -888,7 +1551,7 @@ impl SenderInner<SenderLink<Target>> {
 
         match settled {
             true => {
-                let _ = sender.send(None);
+                let _ = sender.send(Ok(None));
             }
             false => {
                 let unsettled = UnsettledMessage::new(payload, None, message_format, sender);
@@ -902,18 +1565,26 @@ impl SenderInner<SenderLink<Target>> {
         Ok(())
     }
 
-    async fn resend(&mut self, unsettled_message: UnsettledMessage) -> Result<(), SendError> {
+    async fn resend(&mut self, mut unsettled_message: UnsettledMessage) -> Result<(), SendError> {
+        if self.increment_delivery_count_on_resend {
+            unsettled_message.payload = increment_delivery_count(&unsettled_message.payload)?;
+        }
+
         let detached_fut = self.incoming.recv();
         let tag = self
             .link
             .get_delivery_tag_or_detached(&self.outgoing, detached_fut)
             .await?;
-        let new_delivery_tag = DeliveryTag::from(tag);
+        let new_delivery_tag = self
+            .delivery_tag_generator
+            .next_tag()
+            .unwrap_or_else(|| DeliveryTag::from(tag));
         let transfer = self.link.generate_non_resuming_transfer_performative(
             new_delivery_tag.clone(),
             unsettled_message.message_format,
             None,
             None,
+            None,
             false,
         )?;
 
@@ -945,18 +1616,24 @@ impl SenderInner<SenderLink<Target>> {
         &mut self,
         mut initial_remote_attach: Option<Attach>,
     ) -> Result<(), SenderResumeErrorKind> {
-        self.reallocate_output_handle().await?;
         let mut resend_buf = Vec::new();
 
         loop {
+            // The handle is released by the detach that suspends the link, so it must be
+            // allocated again on every attempt
             let attach_exchange = match initial_remote_attach.take() {
                 Some(remote_attach) => {
+                    self.reallocate_incoming_handle(remote_attach.handle.clone().into())
+                        .await?;
                     self.link
                         .send_attach(&self.outgoing, &self.session, false)
                         .await?;
                     self.link.on_incoming_attach(remote_attach)?
                 }
-                None => self.exchange_attach(false).await?,
+                None => {
+                    self.reallocate_output_handle().await?;
+                    self.exchange_attach(false).await?
+                }
             };
 
             match attach_exchange {
@@ -989,8 +1666,30 @@ impl SenderInner<SenderLink<Target>> {
     }
 }
 
+/// The max number of bytes read from the reader of a [`DataStream`] for one transfer frame
+const DATA_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Appends the encoded section if it is present
+fn encode_section<T: serde::Serialize>(
+    buf: &mut BytesMut,
+    section: &Option<T>,
+) -> Result<(), serde_amqp::Error> {
+    use serde_amqp::ser::Serializer;
+
+    if let Some(section) = section {
+        let mut serializer = Serializer::from(buf.writer());
+        section.serialize(&mut serializer)?;
+    }
+    Ok(())
+}
+
 /// A detached sender
 ///
+/// This is a suspended link. Detaching with `closed=false` keeps the link name, the unsettled
+/// map and the flow state, and resuming sends a fresh Attach with the same link name, either on
+/// the original session or on another session. Closing the link with [`Sender::close`] is
+/// terminal and the link cannot be resumed.
+///
 /// # Example
 ///
 /// Link re-attachment
@@ -1098,6 +1797,7 @@ impl DetachedSender {
         session: &SessionHandle<R>,
     ) -> Result<Sender, SenderResumeError> {
         *self.inner.session_control_mut() = session.control.clone();
+        self.inner.unsettled_deliveries = session.unsettled_deliveries.clone();
         self.resume().await
     }
 
@@ -1109,6 +1809,7 @@ impl DetachedSender {
     ) -> Result<Sender, SenderResumeError> {
         self.inner.session = session.control.clone();
         self.inner.outgoing = session.outgoing.clone();
+        self.inner.unsettled_deliveries = session.unsettled_deliveries.clone();
         self.resume_incoming_attach(remote_attach).await
     }
 
@@ -1120,6 +1821,7 @@ impl DetachedSender {
         duration: Duration,
     ) -> Result<Sender, SenderResumeError> {
         *self.inner.session_control_mut() = session.control.clone();
+        self.inner.unsettled_deliveries = session.unsettled_deliveries.clone();
         self.resume_with_timeout(duration).await
     }
 
@@ -1132,7 +1834,22 @@ impl DetachedSender {
         duration: Duration,
     ) -> Result<Sender, SenderResumeError> {
         *self.inner.session_control_mut() = session.control.clone();
+        self.inner.unsettled_deliveries = session.unsettled_deliveries.clone();
         self.resume_incoming_attach_with_timeout(remote_attach, duration)
             .await
     }
 }
+
+/// Increment the `delivery-count` in the header of an encoded message. A header is prepended if
+/// the message doesn't carry one
+pub(crate) fn increment_delivery_count(payload: &Payload) -> Result<Payload, serde_amqp::Error> {
+    let (mut header, consumed) =
+        serde_amqp::from_slice_prefix::<Header>(payload).unwrap_or_default();
+    header.delivery_count = header.delivery_count.wrapping_add(1);
+
+    let encoded = serde_amqp::to_vec(&header)?;
+    let mut buf = BytesMut::with_capacity(encoded.len() + payload.len() - consumed);
+    buf.put_slice(&encoded);
+    buf.put_slice(&payload[consumed..]);
+    Ok(buf.freeze())
+}
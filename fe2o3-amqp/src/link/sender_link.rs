@@ -23,9 +23,42 @@ where
     pub(crate) async fn send_transfer_without_modifying_unsettled_map(
         &mut self,
         writer: &mpsc::Sender<LinkFrame>,
-        mut transfer: Transfer,
-        mut payload: Payload,
+        transfer: Transfer,
+        payload: Payload,
     ) -> Result<bool, LinkStateError> {
+        let (settled, input_handle, transfer) = self.prepare_single_transfer(transfer)?;
+        send_transfer(writer, input_handle, transfer, payload).await?; // cancel safe
+
+        Ok(settled)
+    }
+
+    /// Sends the payload in a single transfer through a slot that is already reserved on the
+    /// outgoing channel, so that sending never waits
+    pub(crate) fn send_payload_with_reserved_transfer(
+        &mut self,
+        slot: mpsc::Permit<'_, LinkFrame>,
+        message_format: MessageFormat,
+        transfer: Transfer,
+        payload: Payload,
+    ) -> Result<Settlement, LinkStateError> {
+        let delivery_tag = transfer
+            .delivery_tag
+            .clone()
+            .ok_or(LinkStateError::IllegalState)?;
+        let (settled, input_handle, transfer) = self.prepare_single_transfer(transfer)?;
+        slot.send(LinkFrame::Transfer {
+            input_handle,
+            performative: transfer,
+            payload: payload.clone(),
+        });
+        Ok(self.track_settlement(delivery_tag, settled, payload, message_format))
+    }
+
+    /// Returns whether the delivery is settled along with the handle to send the transfer with
+    fn prepare_single_transfer(
+        &self,
+        mut transfer: Transfer,
+    ) -> Result<(bool, InputHandle, Transfer), LinkStateError> {
         let settled = transfer.settled.unwrap_or(match self.snd_settle_mode {
             SenderSettleMode::Settled => true,
             SenderSettleMode::Unsettled => false,
@@ -36,38 +69,57 @@ where
             .clone()
             .ok_or(LinkStateError::IllegalState)?;
 
-        // Check message size
-        // If this field is zero or unset, there is no maximum size imposed by the link endpoint.
-        let more = (self.max_message_size != 0) && (payload.len() as u64 > self.max_message_size);
-        if !more {
-            transfer.more = false;
-            send_transfer(writer, input_handle, transfer, payload.clone()).await?;
-        // cancel safe
-        } else {
-            // Send the first frame
-            let partial = payload.split_to(self.max_message_size as usize);
-            transfer.more = true;
-            send_transfer(writer, input_handle.clone(), transfer.clone(), partial).await?; // cancel safe
-
-            // Send the transfers in the middle
-            while payload.len() > self.max_message_size as usize {
-                let partial = payload.split_to(self.max_message_size as usize);
-                transfer.delivery_tag = None;
-                transfer.message_format = None;
-                transfer.settled = None;
-                send_transfer(writer, input_handle.clone(), transfer.clone(), partial).await?;
-                // cancel safe
-            }
+        // The transport splits the transfer into multiple frames if it exceeds the max frame size
+        transfer.more = false;
+        Ok((settled, input_handle, transfer))
+    }
+
+    /// Inserts an unsettled delivery into the unsettled map
+    fn track_settlement(
+        &mut self,
+        delivery_tag: DeliveryTag,
+        settled: bool,
+        payload: Payload,
+        message_format: MessageFormat,
+    ) -> Settlement {
+        match settled {
+            true => Settlement::Settled(delivery_tag),
+            // If not set on the first (or only) transfer for a (multi-transfer)
+            // delivery, then the settled flag MUST be interpreted as being false.
+            false => {
+                let (tx, rx) = oneshot::channel();
+                let unsettled = UnsettledMessage::new(payload, None, message_format, tx);
+                {
+                    let mut guard = self.unsettled.write();
+                    guard
+                        .get_or_insert(OrderedMap::new())
+                        .insert(delivery_tag.clone(), unsettled);
+                }
 
-            // Send the last transfer
-            // For messages that are too large to fit within the maximum frame size, additional
-            // data MAY be trans- ferred in additional transfer frames by setting the more flag on
-            // all but the last transfer frame
-            transfer.more = false;
-            send_transfer(writer, input_handle, transfer, payload).await?; // cancel safe
+                Settlement::Unsettled {
+                    delivery_tag,
+                    outcome: rx,
+                }
+            }
         }
+    }
 
-        Ok(settled)
+    /// Sends one transfer frame of a multi-transfer delivery without modifying the `more` flag
+    ///
+    /// # Cancel safety
+    ///
+    /// This is cancel safe because all internal `.await` are cancel safe
+    pub(crate) async fn send_transfer_fragment(
+        &mut self,
+        writer: &mpsc::Sender<LinkFrame>,
+        transfer: Transfer,
+        payload: Payload,
+    ) -> Result<(), LinkStateError> {
+        let input_handle = self
+            .input_handle
+            .clone()
+            .ok_or(LinkStateError::IllegalState)?;
+        send_transfer(writer, input_handle, transfer, payload).await // cancel safe
     }
 
     pub(crate) async fn get_delivery_tag_or_detached<Fut>(
@@ -121,6 +173,7 @@ where
         message_format: MessageFormat,
         settled: Option<bool>,
         state: Option<DeliveryState>,
+        rcv_settle_mode: Option<ReceiverSettleMode>,
         batchable: bool,
     ) -> Result<Transfer, LinkStateError> {
         let handle = self
@@ -151,7 +204,7 @@ where
 
             // If not set, this value is defaulted to the value negotiated
             // on link attach.
-            rcv_settle_mode: None,
+            rcv_settle_mode,
             state,
             resume,
             aborted: false,
@@ -204,6 +257,8 @@ where
         message_format: MessageFormat,
         settled: Option<bool>,
         state: Option<DeliveryState>,
+        rcv_settle_mode: Option<ReceiverSettleMode>,
+        delivery_tag: Option<DeliveryTag>,
         batchable: bool,
     ) -> Result<Settlement, Self::TransferError>
     where
@@ -211,13 +266,14 @@ where
     {
         let tag = self.get_delivery_tag_or_detached(writer, detached).await?;
         // Delivery count is incremented when consuming credit
-        let delivery_tag = DeliveryTag::from(tag);
+        let delivery_tag = delivery_tag.unwrap_or_else(|| DeliveryTag::from(tag));
 
         let transfer = self.generate_non_resuming_transfer_performative(
             delivery_tag,
             message_format,
             settled,
             state,
+            rcv_settle_mode,
             batchable,
         )?;
 
@@ -245,26 +301,7 @@ where
         let settled = self
             .send_transfer_without_modifying_unsettled_map(writer, transfer, payload)
             .await?;
-        match settled {
-            true => Ok(Settlement::Settled(delivery_tag)),
-            // If not set on the first (or only) transfer for a (multi-transfer)
-            // delivery, then the settled flag MUST be interpreted as being false.
-            false => {
-                let (tx, rx) = oneshot::channel();
-                let unsettled = UnsettledMessage::new(payload_copy, None, message_format, tx);
-                {
-                    let mut guard = self.unsettled.write();
-                    guard
-                        .get_or_insert(OrderedMap::new())
-                        .insert(delivery_tag.clone(), unsettled);
-                }
-
-                Ok(Settlement::Unsettled {
-                    delivery_tag,
-                    outcome: rx,
-                })
-            }
-        }
+        Ok(self.track_settlement(delivery_tag, settled, payload_copy, message_format))
     }
 
     async fn dispose(
@@ -657,6 +694,15 @@ where
         self.max_message_size =
             get_max_message_size(self.max_message_size, remote_attach.max_message_size);
 
+        self.remote_offered_capabilities = remote_attach
+            .offered_capabilities
+            .map(|capabilities| capabilities.into_inner());
+        if let Some(capability) = self.missing_required_capability() {
+            return Err(SenderAttachError::RequiredCapabilityNotOffered(
+                capability.clone(),
+            ));
+        }
+
         if let Some(remote_properties) = remote_attach.properties {
             self.properties_mut(|local_properties| {
                 local_properties
@@ -733,6 +779,10 @@ where
         &self.unsettled
     }
 
+    fn snd_settle_mode(&self) -> &SenderSettleMode {
+        &self.snd_settle_mode
+    }
+
     fn rcv_settle_mode(&self) -> &ReceiverSettleMode {
         &self.rcv_settle_mode
     }
@@ -742,10 +792,7 @@ where
     }
 
     fn max_message_size(&self) -> Option<u64> {
-        match self.max_message_size {
-            0 => None,
-            _ => Some(self.max_message_size),
-        }
+        self.max_message_size
     }
 
     fn properties<F, O>(&self, op: F) -> O
@@ -828,9 +875,11 @@ where
             }
 
             SenderAttachError::CoordinatorIsNotImplemented
+            | SenderAttachError::RequiredCapabilityNotOffered(_)
             | SenderAttachError::SourceAddressIsSomeWhenDynamicIsTrue
             | SenderAttachError::TargetAddressIsNoneWhenDynamicIsTrue
-            | SenderAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse => {
+            | SenderAttachError::DynamicNodePropertiesIsSomeWhenDynamicIsFalse
+            | SenderAttachError::Rejected(_) => {
                 try_detach_with_error(self, attach_error, writer, reader).await
             }
             #[cfg(feature = "transaction")]
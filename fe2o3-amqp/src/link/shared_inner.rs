@@ -4,11 +4,11 @@ use tokio::sync::mpsc;
 
 use crate::{
     control::SessionControl,
-    endpoint::{self, LinkAttach, LinkDetach, LinkExt},
+    endpoint::{self, InputHandle, LinkAttach, LinkDetach, LinkExt},
     session::{self, AllocLinkError},
 };
 
-use super::{state::LinkState, DetachError, LinkFrame, LinkRelay};
+use super::{error::ErrorScope, state::LinkState, DetachError, LinkFrame, LinkRelay};
 
 #[async_trait]
 pub(crate) trait LinkEndpointInner
@@ -65,6 +65,31 @@ where
         *self.link_mut().output_handle_mut() = Some(handle);
         Ok(())
     }
+
+    /// Same as `reallocate_output_handle` but for resuming with an Attach that is already
+    /// received from the remote peer, so the relay is registered under the remote handle as well
+    ///
+    /// # Cancel safety
+    ///
+    /// This should be cancel safe if oneshot channel is cancel safe
+    async fn reallocate_incoming_handle(
+        &mut self,
+        input_handle: InputHandle,
+    ) -> Result<(), <Self::Link as LinkAttach>::AttachError> {
+        let (tx, incoming) = mpsc::channel(self.buffer_size());
+        let link_relay = self.as_new_link_relay(tx);
+        *self.reader_mut() = incoming;
+        let link_name = self.link().name().to_string();
+        let handle = session::allocate_incoming_link(
+            self.session_control(),
+            link_name,
+            link_relay,
+            input_handle,
+        )
+        .await?;
+        *self.link_mut().output_handle_mut() = Some(handle);
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -129,57 +154,13 @@ where
         &mut self,
         error: Option<definitions::Error>,
     ) -> Result<(), <Self::Link as LinkDetach>::DetachError> {
-        match self.link().local_state() {
-            LinkState::Unattached
-            | LinkState::AttachSent
-            | LinkState::IncompleteAttachSent
-            | LinkState::IncompleteAttachReceived
-            | LinkState::IncompleteAttachExchanged
-            | LinkState::AttachReceived
-            | LinkState::Attached => {
-                // Send a non-closing detach
-                self.send_detach(false, error).await?;
-
-                let remote_detach = recv_remote_detach(self).await?;
-                if remote_detach.closed {
-                    // Note that one peer MAY send a closing detach while its partner is
-                    // sending a non-closing detach. In this case, the partner MUST
-                    // signal that it has closed the link by reattaching and then sending
-                    // a closing detach.
-                    reattach_and_then_close(self).await?;
-
-                    // A peer closes a link by sending the detach frame with the handle for the
-                    // specified link, and the closed flag set to true. The partner will destroy
-                    // the corresponding link endpoint, and reply with its own detach frame with
-                    // the closed flag set to true.
-                    Err(DetachError::ClosedByRemote)
-                } else {
-                    self.link_mut().on_incoming_detach(remote_detach)
-                }
-            }
-            LinkState::DetachSent => {
-                let remote_detach = recv_remote_detach(self).await?;
-                if remote_detach.closed {
-                    reattach_and_then_close(self).await?;
-                    Err(DetachError::ClosedByRemote)
-                } else {
-                    self.link_mut().on_incoming_detach(remote_detach)
-                }
+        match error_to_escalate(&error) {
+            Some(escalated) => {
+                let result = send_detach_before_escalating(self, false, error).await;
+                escalate_to_session(self, escalated).await;
+                result
             }
-            LinkState::DetachReceived => self.send_detach(false, error).await,
-            LinkState::Detached => Ok(()),
-            LinkState::CloseSent => {
-                // This should be impossible.
-                // FIXME: treat it as if remote closed
-                let _remote_detach = recv_remote_detach(self).await?;
-                reattach_and_then_close(self).await?;
-                Err(DetachError::ClosedByRemote)
-            }
-            LinkState::CloseReceived => {
-                self.send_detach(true, error).await?;
-                Err(DetachError::ClosedByRemote)
-            }
-            LinkState::Closed => Err(DetachError::ClosedByRemote),
+            None => detach_link_with_error(self, error).await,
         }
     }
 
@@ -190,57 +171,208 @@ where
         &mut self,
         error: Option<definitions::Error>,
     ) -> Result<(), <Self::Link as LinkDetach>::DetachError> {
-        match self.link().local_state() {
-            LinkState::Unattached
-            | LinkState::AttachSent
-            | LinkState::IncompleteAttachSent
-            | LinkState::IncompleteAttachReceived
-            | LinkState::IncompleteAttachExchanged
-            | LinkState::AttachReceived
-            | LinkState::Attached => {
-                // Send detach with closed=true and wait for remote closing detach
-                // The sender will be dropped after close
-                self.send_detach(true, error)
-                    .await // cancel safe
-                    .map_err(|_| DetachError::IllegalSessionState)?;
-
-                // Wait for remote detach
-                let remote_detach = recv_remote_detach(self).await?; // cancel safe
-                if remote_detach.closed {
-                    // If the remote detach contains an error, the error will be propagated
-                    // back by `on_incoming_detach`
-                    self.link_mut().on_incoming_detach(remote_detach)
-                } else {
-                    reattach_and_then_close(self).await // FIXME: cancel safe? if oneshot channel is cancel safe
-                }
+        match error_to_escalate(&error) {
+            Some(escalated) => {
+                let result = send_detach_before_escalating(self, true, error).await;
+                escalate_to_session(self, escalated).await;
+                result
+            }
+            None => close_link_with_error(self, error).await,
+        }
+    }
+}
+
+/// Sends the detach carrying an error that is fatal to the session
+///
+/// The reply of the remote peer is not waited for because ending the session detaches the link
+/// anyway, and the remote peer may never reply to a link that it considers errant.
+///
+/// # Cancel safety
+///
+/// This is cancel safe because it only `.await` on sending over `tokio::mpsc::Sender`
+async fn send_detach_before_escalating<T>(
+    link_inner: &mut T,
+    closed: bool,
+    error: Option<definitions::Error>,
+) -> Result<(), DetachError>
+where
+    T: LinkEndpointInner + Send + Sync,
+    T::Link: LinkDetach<DetachError = DetachError>,
+    <T::Link as LinkAttach>::AttachError: From<AllocLinkError> + Sync,
+{
+    match link_inner.link().local_state() {
+        // The detach has already been sent
+        LinkState::DetachSent | LinkState::Detached | LinkState::CloseSent | LinkState::Closed => {
+            Ok(())
+        }
+        _ => link_inner.send_detach(closed, error).await, // cancel safe
+    }
+}
+
+/// Returns the error if it must also end the session
+fn error_to_escalate(error: &Option<definitions::Error>) -> Option<definitions::Error> {
+    error
+        .as_ref()
+        .filter(|error| ErrorScope::from(&error.condition) == ErrorScope::Session)
+        .cloned()
+}
+
+/// Ends the session with the error that is fatal to the session
+///
+/// A failure to reach the session is only logged so that it does not mask the result of the
+/// detach.
+///
+/// # Cancel safety
+///
+/// This is cancel safe because it only `.await` on sending over `tokio::mpsc::Sender`
+async fn escalate_to_session<T>(link_inner: &mut T, error: definitions::Error)
+where
+    T: LinkEndpointInner,
+    <T::Link as LinkAttach>::AttachError: From<AllocLinkError> + Send + Sync,
+{
+    #[cfg(feature = "tracing")]
+    tracing::debug!(escalating = ?error);
+    #[cfg(feature = "log")]
+    log::debug!("escalating = {:?}", error);
+    if link_inner
+        .session_control()
+        .send(SessionControl::End(Some(error)))
+        .await
+        .is_err()
+    {
+        #[cfg(feature = "tracing")]
+        tracing::error!("Failed to escalate the error to the session");
+        #[cfg(feature = "log")]
+        log::error!("Failed to escalate the error to the session");
+    }
+}
+
+async fn detach_link_with_error<T>(
+    link_inner: &mut T,
+    error: Option<definitions::Error>,
+) -> Result<(), DetachError>
+where
+    T: LinkEndpointInner + LinkEndpointInnerReattach + Send + Sync,
+    T::Link: LinkDetach<DetachError = DetachError>,
+    <T::Link as LinkAttach>::AttachError: From<AllocLinkError> + Sync,
+{
+    match link_inner.link().local_state() {
+        LinkState::Unattached
+        | LinkState::AttachSent
+        | LinkState::IncompleteAttachSent
+        | LinkState::IncompleteAttachReceived
+        | LinkState::IncompleteAttachExchanged
+        | LinkState::AttachReceived
+        | LinkState::Attached => {
+            // Send a non-closing detach
+            link_inner.send_detach(false, error).await?;
+
+            let remote_detach = recv_remote_detach(link_inner).await?;
+            if remote_detach.closed {
+                // Note that one peer MAY send a closing detach while its partner is
+                // sending a non-closing detach. In this case, the partner MUST
+                // signal that it has closed the link by reattaching and then sending
+                // a closing detach.
+                reattach_and_then_close(link_inner).await?;
+
+                // A peer closes a link by sending the detach frame with the handle for the
+                // specified link, and the closed flag set to true. The partner will destroy
+                // the corresponding link endpoint, and reply with its own detach frame with
+                // the closed flag set to true.
+                Err(DetachError::ClosedByRemote)
+            } else {
+                link_inner.link_mut().on_incoming_detach(remote_detach)
             }
-            LinkState::DetachSent => {
-                // FIXME: this should be impossible
-                // Wait for remote detach
-                let _remote_detach = recv_remote_detach(self).await?; // cancel safe
-                reattach_and_then_close(self).await?; // FIXME: cancel safe? if oneshot channel is cancel safe
-                Err(DetachError::DetachedByRemote)
+        }
+        LinkState::DetachSent => {
+            let remote_detach = recv_remote_detach(link_inner).await?;
+            if remote_detach.closed {
+                reattach_and_then_close(link_inner).await?;
+                Err(DetachError::ClosedByRemote)
+            } else {
+                link_inner.link_mut().on_incoming_detach(remote_detach)
             }
-            LinkState::DetachReceived => self
+        }
+        LinkState::DetachReceived => link_inner.send_detach(false, error).await,
+        LinkState::Detached => Ok(()),
+        LinkState::CloseSent => {
+            // This should be impossible.
+            // FIXME: treat it as if remote closed
+            let _remote_detach = recv_remote_detach(link_inner).await?;
+            reattach_and_then_close(link_inner).await?;
+            Err(DetachError::ClosedByRemote)
+        }
+        LinkState::CloseReceived => {
+            link_inner.send_detach(true, error).await?;
+            Err(DetachError::ClosedByRemote)
+        }
+        LinkState::Closed => Err(DetachError::ClosedByRemote),
+    }
+}
+
+/// # Cancel safety
+///
+/// This should be cancel safe if oneshot channel is cancel safe
+async fn close_link_with_error<T>(
+    link_inner: &mut T,
+    error: Option<definitions::Error>,
+) -> Result<(), DetachError>
+where
+    T: LinkEndpointInner + LinkEndpointInnerReattach + Send + Sync,
+    T::Link: LinkDetach<DetachError = DetachError>,
+    <T::Link as LinkAttach>::AttachError: From<AllocLinkError> + Sync,
+{
+    match link_inner.link().local_state() {
+        LinkState::Unattached
+        | LinkState::AttachSent
+        | LinkState::IncompleteAttachSent
+        | LinkState::IncompleteAttachReceived
+        | LinkState::IncompleteAttachExchanged
+        | LinkState::AttachReceived
+        | LinkState::Attached => {
+            // Send detach with closed=true and wait for remote closing detach
+            // The sender will be dropped after close
+            link_inner
                 .send_detach(true, error)
                 .await // cancel safe
-                .map_err(|_| DetachError::IllegalSessionState),
-            LinkState::Detached => reattach_and_then_close(self).await, // FIXME: cancel safe? if oneshot channel is cancel safe
-            LinkState::CloseSent => {
-                // Wait for remote detach
-                let remote_detach = recv_remote_detach(self).await?; // cancel safe
-                if remote_detach.closed {
-                    self.link_mut().on_incoming_detach(remote_detach)
-                } else {
-                    reattach_and_then_close(self).await // FIXME: cancel safe? if oneshot channel is cancel safe
-                }
+                .map_err(|_| DetachError::IllegalSessionState)?;
+
+            // Wait for remote detach
+            let remote_detach = recv_remote_detach(link_inner).await?; // cancel safe
+            if remote_detach.closed {
+                // If the remote detach contains an error, the error will be propagated
+                // back by `on_incoming_detach`
+                link_inner.link_mut().on_incoming_detach(remote_detach)
+            } else {
+                reattach_and_then_close(link_inner).await // FIXME: cancel safe? if oneshot channel is cancel safe
             }
-            LinkState::CloseReceived => self
-                .send_detach(true, error)
-                .await // cancel safe
-                .map_err(|_| DetachError::IllegalSessionState),
-            LinkState::Closed => Ok(()),
         }
+        LinkState::DetachSent => {
+            // FIXME: this should be impossible
+            // Wait for remote detach
+            let _remote_detach = recv_remote_detach(link_inner).await?; // cancel safe
+            reattach_and_then_close(link_inner).await?; // FIXME: cancel safe? if oneshot channel is cancel safe
+            Err(DetachError::DetachedByRemote)
+        }
+        LinkState::DetachReceived => link_inner
+            .send_detach(true, error)
+            .await // cancel safe
+            .map_err(|_| DetachError::IllegalSessionState),
+        LinkState::Detached => reattach_and_then_close(link_inner).await, // FIXME: cancel safe? if oneshot channel is cancel safe
+        LinkState::CloseSent => {
+            // Wait for remote detach
+            let remote_detach = recv_remote_detach(link_inner).await?; // cancel safe
+            if remote_detach.closed {
+                link_inner.link_mut().on_incoming_detach(remote_detach)
+            } else {
+                reattach_and_then_close(link_inner).await // FIXME: cancel safe? if oneshot channel is cancel safe
+            }
+        }
+        LinkState::CloseReceived => link_inner
+            .send_detach(true, error)
+            .await // cancel safe
+            .map_err(|_| DetachError::IllegalSessionState),
+        LinkState::Closed => Ok(()),
     }
 }
 
@@ -1,10 +1,11 @@
 //! Link state and link flow state
 
-use std::{marker::PhantomData, sync::Arc};
+use std::{collections::VecDeque, marker::PhantomData, sync::Arc};
 
 use async_trait::async_trait;
 use fe2o3_amqp_types::definitions::{Fields, SequenceNo};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::oneshot;
 
 use crate::{
     endpoint::{LinkFlow, OutputHandle},
@@ -82,10 +83,39 @@ impl LinkFlowStateInner {
     }
 }
 
+/// The link flow state of the remote peer as carried by a `Flow` frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFlowState {
+    /// The `delivery-count` reported by the remote peer
+    pub delivery_count: Option<SequenceNo>,
+
+    /// The `link-credit` reported by the remote peer
+    pub link_credit: Option<u32>,
+
+    /// The `available` field reported by the remote peer
+    pub available: Option<u32>,
+
+    /// The `drain` field reported by the remote peer
+    pub drain: bool,
+}
+
+impl From<&LinkFlow> for RemoteFlowState {
+    fn from(flow: &LinkFlow) -> Self {
+        Self {
+            delivery_count: flow.delivery_count,
+            link_credit: flow.link_credit,
+            available: flow.available,
+            drain: flow.drain,
+        }
+    }
+}
+
 /// The Sender and Receiver handle link flow control differently
 #[derive(Debug)]
 pub(crate) struct LinkFlowState<R> {
     pub(crate) lock: RwLock<LinkFlowStateInner>,
+    /// Pending echo requests, each resolved in order by the next incoming flow
+    echo_waiters: Mutex<VecDeque<oneshot::Sender<RemoteFlowState>>>,
     role: PhantomData<R>,
 }
 
@@ -93,9 +123,28 @@ impl<R> LinkFlowState<R> {
     pub(crate) fn new(inner: LinkFlowStateInner) -> Self {
         Self {
             lock: RwLock::new(inner),
+            echo_waiters: Mutex::new(VecDeque::new()),
             role: PhantomData,
         }
     }
+
+    /// Registers a waiter that will be resolved with the flow state carried by the next
+    /// incoming flow that is not claimed by an earlier waiter
+    pub(crate) fn register_echo_waiter(&self) -> oneshot::Receiver<RemoteFlowState> {
+        let (tx, rx) = oneshot::channel();
+        self.echo_waiters.lock().push_back(tx);
+        rx
+    }
+
+    fn resolve_echo_waiter(&self, remote: RemoteFlowState) {
+        let mut waiters = self.echo_waiters.lock();
+        while let Some(waiter) = waiters.pop_front() {
+            // Skip waiters whose request has been dropped
+            if waiter.send(remote.clone()).is_ok() {
+                break;
+            }
+        }
+    }
 }
 
 impl LinkFlowState<role::SenderMarker> {
@@ -194,9 +243,10 @@ impl LinkFlowState<role::ReceiverMarker> {
         // value from the sender and any subsequent messages received on the link. Note that,
         // despite its name, the delivery-count is not a count but a sequence number
         // initialized at an arbitrary point by the sender.
-        if let Some(delivery_count) = flow.delivery_count {
-            state.delivery_count = delivery_count;
-        }
+        //
+        // The transfers that precede the flow may still be queued for the link, so the
+        // delivery-count of the sender is applied with `on_sender_delivery_count()` once the
+        // link has consumed them
 
         // link credit
         //
@@ -223,12 +273,38 @@ impl LinkFlowState<role::ReceiverMarker> {
         // consuming all link-credit, and send the flow state to the receiver. Only the
         // receiver can independently modify this field. The sender’s value is always the
         // last known value indicated by the receiver.
+        //
+        // The sender replies to a drain request with its flow state once all link-credit has
+        // been consumed, which marks the end of the drain cycle
+        if state.drain && flow.drain && matches!(flow.link_credit, Some(0)) {
+            state.drain = false;
+        }
 
         match flow.echo {
             true => Some(state.as_link_flow(output_handle, false)),
             false => None,
         }
     }
+
+    /// Updates the delivery-count and the link-credit with the delivery-count of the sender and
+    /// returns the link-credit consumed by the sender without sending a delivery
+    ///
+    /// This must only be called once the transfers that precede the flow from the sender have
+    /// been consumed
+    pub(crate) fn on_sender_delivery_count(&self, delivery_count: SequenceNo) -> u32 {
+        let mut state = self.lock.write();
+
+        // The sender may advance the delivery-count without sending any delivery (eg. when
+        // draining), which consumes the link-credit up to the same delivery-limit
+        let link_credit = state
+            .delivery_count
+            .saturating_add(state.link_credit)
+            .saturating_sub(delivery_count);
+        let consumed = state.link_credit.saturating_sub(link_credit);
+        state.link_credit = link_credit;
+        state.delivery_count = delivery_count;
+        consumed
+    }
 }
 
 impl<R> LinkFlowState<R> {
@@ -284,7 +360,24 @@ impl ProducerState for Arc<LinkFlowState<role::SenderMarker>> {
 
     #[inline]
     fn update_state(&mut self, (flow, output_handle): Self::Item) -> Self::Outcome {
-        self.on_incoming_flow(flow, output_handle)
+        let remote = RemoteFlowState::from(&flow);
+        let outcome = self.on_incoming_flow(flow, output_handle);
+        self.resolve_echo_waiter(remote);
+        outcome
+    }
+}
+
+impl ProducerState for Arc<LinkFlowState<role::ReceiverMarker>> {
+    type Item = (LinkFlow, OutputHandle);
+    // If echo is requested, a Some(LinkFlow) will be returned
+    type Outcome = Option<LinkFlow>;
+
+    #[inline]
+    fn update_state(&mut self, (flow, output_handle): Self::Item) -> Self::Outcome {
+        let remote = RemoteFlowState::from(&flow);
+        let outcome = self.on_incoming_flow(flow, output_handle);
+        self.resolve_echo_waiter(remote);
+        outcome
     }
 }
 
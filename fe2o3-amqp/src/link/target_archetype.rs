@@ -158,12 +158,18 @@ impl VariantOfTargetArchetype for TargetArchetype {
 
 pub trait DynamicTarget {
     fn is_dynamic(&self) -> Option<bool>;
+
+    fn has_address(&self) -> bool;
 }
 
 impl DynamicTarget for Target {
     fn is_dynamic(&self) -> Option<bool> {
         Some(self.dynamic)
     }
+
+    fn has_address(&self) -> bool {
+        self.address.is_some()
+    }
 }
 
 #[cfg(feature = "transaction")]
@@ -171,6 +177,10 @@ impl DynamicTarget for Coordinator {
     fn is_dynamic(&self) -> Option<bool> {
         None
     }
+
+    fn has_address(&self) -> bool {
+        false
+    }
 }
 
 impl DynamicTarget for TargetArchetype {
@@ -181,6 +191,14 @@ impl DynamicTarget for TargetArchetype {
             TargetArchetype::Coordinator(t) => t.is_dynamic(),
         }
     }
+
+    fn has_address(&self) -> bool {
+        match self {
+            TargetArchetype::Target(t) => t.has_address(),
+            #[cfg(feature = "transaction")]
+            TargetArchetype::Coordinator(t) => t.has_address(),
+        }
+    }
 }
 
 /// Extension trait for TargetArchetypes
@@ -0,0 +1,83 @@
+//! Buffers that can be used as the payload of a transfer
+
+use bytes::Bytes;
+
+/// A buffer holding an encoded message that can be sent as the payload of a transfer
+///
+/// The payload is handed to the link and the transport without copying the data, so a buffer
+/// from a pool or another allocator can be sent as is. [`Bytes`] is the payload type used
+/// internally, and any other buffer is wrapped with [`Bytes::from_owner`], which keeps the buffer
+/// alive until the last transfer frame carrying a part of it has been written.
+///
+/// # Example
+///
+/// ```rust
+/// use fe2o3_amqp::payload::TransferPayload;
+///
+/// struct PooledBuf(Vec<u8>);
+///
+/// impl AsRef<[u8]> for PooledBuf {
+///     fn as_ref(&self) -> &[u8] {
+///         &self.0
+///     }
+/// }
+///
+/// impl TransferPayload for PooledBuf {}
+/// ```
+pub trait TransferPayload: AsRef<[u8]> + Send + 'static {
+    /// Converts the buffer into [`Bytes`] without copying the data
+    fn into_bytes(self) -> Bytes
+    where
+        Self: Sized,
+    {
+        Bytes::from_owner(self)
+    }
+}
+
+impl TransferPayload for Bytes {
+    fn into_bytes(self) -> Bytes {
+        self
+    }
+}
+
+impl TransferPayload for Vec<u8> {
+    fn into_bytes(self) -> Bytes {
+        Bytes::from(self)
+    }
+}
+
+impl TransferPayload for Box<[u8]> {
+    fn into_bytes(self) -> Bytes {
+        Bytes::from(self)
+    }
+}
+
+impl TransferPayload for &'static [u8] {
+    fn into_bytes(self) -> Bytes {
+        Bytes::from_static(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransferPayload;
+
+    struct PooledBuf(Vec<u8>);
+
+    impl AsRef<[u8]> for PooledBuf {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl TransferPayload for PooledBuf {}
+
+    #[test]
+    fn custom_payload_is_not_copied() {
+        let buf = PooledBuf(vec![1, 2, 3]);
+        let ptr = buf.0.as_ptr();
+        let bytes = buf.into_bytes();
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(&bytes[..], &[1, 2, 3]);
+    }
+}
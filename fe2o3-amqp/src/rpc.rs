@@ -0,0 +1,555 @@
+//! Request/response over a pair of sender and receiver links
+//!
+//! [`RpcClient`] sends requests to a service node and receives the replies on a dynamically
+//! created node. The `reply-to` of each request is set to the address of the reply node, and the
+//! `message-id` is generated unless it is already set. A reply is matched to its request by its
+//! `correlation-id`, which allows multiple calls to be in flight at the same time.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use fe2o3_amqp::{rpc::RpcClient, types::messaging::Message};
+//!
+//! let client = RpcClient::attach(&mut session, "service").await.unwrap();
+//! let reply = client.call(Message::builder().value("ping").build()).await.unwrap();
+//! client.close().await.unwrap();
+//! ```
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use fe2o3_amqp_types::{
+    messaging::{Body, Message, MessageId, Outcome, Properties, SerializableBody},
+    primitives::Value,
+};
+use parking_lot::Mutex;
+use tokio::{
+    sync::{oneshot, Mutex as AsyncMutex},
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+use crate::{
+    link::{
+        delivery::{DeliveryFut, Sendable},
+        DetachError, ReceiverAttachError, SendError, SenderAttachError,
+    },
+    session::SessionHandle,
+    Receiver, Sender,
+};
+
+/// The default time to wait for a call to complete
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default name of the client, which is used as the prefix of the link names
+pub const DEFAULT_CLIENT_NAME: &str = "rpc-client";
+
+/// A reply received by [`RpcClient`]
+pub type Reply = Message<Body<Value>>;
+
+type PendingCalls = Arc<Mutex<HashMap<MessageId, oneshot::Sender<Reply>>>>;
+
+/// Set by the dispatcher while holding the lock on the pending calls once it stops receiving
+/// replies
+type ReplyLinkClosed = Arc<AtomicBool>;
+
+/// Error attaching a [`RpcClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum RpcAttachError {
+    /// Error attaching the sender link
+    #[error(transparent)]
+    Sender(#[from] SenderAttachError),
+
+    /// Error attaching the receiver link
+    #[error(transparent)]
+    Receiver(#[from] ReceiverAttachError),
+
+    /// The remote peer did not assign an address to the dynamically created reply node
+    #[error("The address of the reply node is not assigned")]
+    ReplyAddressNotAssigned,
+}
+
+/// Error with a call made by [`RpcClient`]
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    /// Error sending the request
+    #[error(transparent)]
+    Send(#[from] SendError),
+
+    /// The request is not accepted by the remote peer
+    #[error("The request is not accepted {0:?}")]
+    NotAccepted(Outcome),
+
+    /// Another call with the same `message-id` is still waiting for its reply
+    #[error("A call with the same message-id is pending")]
+    DuplicateMessageId,
+
+    /// The call did not complete within the timeout
+    #[error("The call timed out")]
+    Timeout,
+
+    /// The reply link is closed before the reply is received
+    #[error("The reply link is closed")]
+    ReplyLinkClosed,
+}
+
+/// A client that makes request/response calls over a sender and a receiver link
+#[derive(Debug)]
+pub struct RpcClient {
+    sender: AsyncMutex<Sender>,
+    reply_to: String,
+    timeout: Duration,
+    next_id: AtomicU64,
+    pending: PendingCalls,
+    closed: ReplyLinkClosed,
+    shutdown: oneshot::Sender<()>,
+    dispatcher: JoinHandle<Receiver>,
+}
+
+impl RpcClient {
+    /// Creates a builder for [`RpcClient`]
+    pub fn builder() -> RpcClientBuilder {
+        RpcClientBuilder::default()
+    }
+
+    /// Attach a client with the default configuration that sends requests to the `target`
+    /// address
+    pub async fn attach<R>(
+        session: &mut SessionHandle<R>,
+        target: impl Into<String>,
+    ) -> Result<Self, RpcAttachError> {
+        Self::builder().attach(session, target).await
+    }
+
+    /// The address of the node that receives the replies
+    pub fn reply_to(&self) -> &str {
+        &self.reply_to
+    }
+
+    /// Send a request and wait for the reply
+    ///
+    /// The `reply-to` of the request is overwritten with the address of the reply node, and a
+    /// `message-id` is generated if it is not set. The reply is the message whose
+    /// `correlation-id` equals the `message-id` of the request.
+    ///
+    /// The call fails immediately with [`RpcError::ReplyLinkClosed`] if the reply link has
+    /// already stopped receiving.
+    pub async fn call<T>(&self, mut request: Message<T>) -> Result<Reply, RpcError>
+    where
+        T: SerializableBody,
+    {
+        let properties = request.properties.get_or_insert_with(Properties::default);
+        let message_id = properties
+            .message_id
+            .get_or_insert_with(|| MessageId::ULong(self.next_id.fetch_add(1, Ordering::Relaxed)))
+            .clone();
+        properties.reply_to = Some(self.reply_to.clone());
+
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock();
+            if self.closed.load(Ordering::Acquire) {
+                return Err(RpcError::ReplyLinkClosed);
+            }
+            if pending.contains_key(&message_id) {
+                return Err(RpcError::DuplicateMessageId);
+            }
+            pending.insert(message_id.clone(), tx);
+        }
+        // Removes the pending call if the call fails or is cancelled
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            message_id,
+        };
+
+        match tokio::time::timeout(self.timeout, self.send_and_wait(request, rx)).await {
+            Ok(result) => result,
+            Err(_) => Err(RpcError::Timeout),
+        }
+    }
+
+    async fn send_and_wait<T>(
+        &self,
+        request: Message<T>,
+        rx: oneshot::Receiver<Reply>,
+    ) -> Result<Reply, RpcError>
+    where
+        T: SerializableBody,
+    {
+        // Only hold the lock while sending so that the outcomes are awaited concurrently
+        let fut: DeliveryFut<Result<Outcome, SendError>> = {
+            let mut sender = self.sender.lock().await;
+            sender
+                .inner
+                .send_with_state::<T, SendError>(Sendable::from(request), None, false)
+                .await
+                .map(DeliveryFut::from)?
+        };
+        let outcome = fut.await?;
+        outcome.accepted_or_else(RpcError::NotAccepted)?;
+        rx.await.map_err(|_| RpcError::ReplyLinkClosed)
+    }
+
+    /// Close both links
+    ///
+    /// Calls that are still waiting for their replies fail with [`RpcError::ReplyLinkClosed`]
+    pub async fn close(self) -> Result<(), DetachError> {
+        let _ = self.shutdown.send(());
+        self.sender.into_inner().close().await?;
+        if let Ok(receiver) = self.dispatcher.await {
+            receiver.close().await?;
+        }
+        Ok(())
+    }
+}
+
+struct PendingGuard<'a> {
+    pending: &'a PendingCalls,
+    message_id: MessageId,
+}
+
+impl<'a> Drop for PendingGuard<'a> {
+    fn drop(&mut self) {
+        self.pending.lock().remove(&self.message_id);
+    }
+}
+
+/// Receives the replies and hands them over to the pending calls until the client is closed or
+/// dropped, or the receiver fails
+async fn dispatch_replies(
+    mut receiver: Receiver,
+    pending: PendingCalls,
+    closed: ReplyLinkClosed,
+    mut shutdown: oneshot::Receiver<()>,
+) -> Receiver {
+    loop {
+        let delivery = tokio::select! {
+            _ = &mut shutdown => break,
+            result = receiver.recv::<Body<Value>>() => match result {
+                Ok(delivery) => delivery,
+                Err(_error) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(?_error);
+                    #[cfg(feature = "log")]
+                    log::error!("{:?}", _error);
+                    break;
+                }
+            },
+        };
+
+        if receiver.accept(&delivery).await.is_err() {
+            break;
+        }
+
+        let reply = delivery.into_message();
+        let tx = reply
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.correlation_id.as_ref())
+            .and_then(|correlation_id| pending.lock().remove(correlation_id));
+        if let Some(tx) = tx {
+            // The call may have been cancelled
+            let _ = tx.send(reply);
+        } else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("Dropping reply without a pending call");
+            #[cfg(feature = "log")]
+            log::debug!("Dropping reply without a pending call");
+        }
+    }
+
+    // Dropping the senders fails the pending calls, and new calls are rejected from now on
+    let mut pending = pending.lock();
+    closed.store(true, Ordering::Release);
+    pending.clear();
+    drop(pending);
+    receiver
+}
+
+/// Builder for [`RpcClient`]
+#[derive(Debug, Clone)]
+pub struct RpcClientBuilder {
+    name: String,
+    timeout: Duration,
+}
+
+impl Default for RpcClientBuilder {
+    fn default() -> Self {
+        Self {
+            name: String::from(DEFAULT_CLIENT_NAME),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl RpcClientBuilder {
+    /// Set the name of the client, which is used as the prefix of the link names
+    ///
+    /// The link names are `"<name>-<uuid>-receiver"` and `"<name>-<uuid>-sender"`, where
+    /// `<uuid>` is generated for each client so that multiple clients can be attached to the
+    /// same session.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set the time to wait for a call to complete
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Attach the client to a session and send requests to the `target` address
+    ///
+    /// The receiver link asks the remote peer to create a node for the replies.
+    pub async fn attach<R>(
+        self,
+        session: &mut SessionHandle<R>,
+        target: impl Into<String>,
+    ) -> Result<RpcClient, RpcAttachError> {
+        let id = Uuid::new_v4();
+        let receiver = Receiver::builder()
+            .name(format!("{}-{}-receiver", self.name, id))
+            .dynamic(true)
+            .attach(session)
+            .await?;
+        let reply_to = match receiver.source_address() {
+            Some(address) => address.clone(),
+            None => {
+                let _ = receiver.close().await;
+                return Err(RpcAttachError::ReplyAddressNotAssigned);
+            }
+        };
+
+        let sender = match Sender::builder()
+            .name(format!("{}-{}-sender", self.name, id))
+            .target(target.into())
+            .attach(session)
+            .await
+        {
+            Ok(sender) => sender,
+            Err(error) => {
+                let _ = receiver.close().await;
+                return Err(error.into());
+            }
+        };
+
+        let pending = PendingCalls::default();
+        let closed = ReplyLinkClosed::default();
+        let (shutdown, shutdown_rx) = oneshot::channel();
+        let dispatcher = tokio::spawn(dispatch_replies(
+            receiver,
+            pending.clone(),
+            closed.clone(),
+            shutdown_rx,
+        ));
+
+        Ok(RpcClient {
+            sender: AsyncMutex::new(sender),
+            reply_to,
+            timeout: self.timeout,
+            next_id: AtomicU64::new(0),
+            pending,
+            closed,
+            shutdown,
+            dispatcher,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "acceptor"))]
+mod tests {
+    use std::time::Duration;
+
+    use fe2o3_amqp_types::{
+        messaging::{AmqpValue, Body, Message, Properties},
+        primitives::Value,
+    };
+
+    use crate::{acceptor::LinkAcceptor, test_util};
+
+    use super::{RpcClient, RpcError};
+
+    const REPLY_ADDRESS: &str = "reply-node-1";
+
+    #[tokio::test]
+    async fn concurrent_calls_are_correlated_with_replies() {
+        let (client, server) = tokio::io::duplex(4096);
+
+        // An echo responder that replies to two requests in the reverse order
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .on_dynamic_source(|mut source| {
+                    source.address = Some(REPLY_ADDRESS.into());
+                    Some(source)
+                })
+                .build();
+            let mut reply_sender = test_util::accept_sender(&link_acceptor, &mut session).await;
+            let mut request_receiver =
+                test_util::accept_receiver(&link_acceptor, &mut session).await;
+
+            let mut requests = Vec::new();
+            for _ in 0..2 {
+                let delivery = request_receiver.recv::<Value>().await.unwrap();
+                request_receiver.accept(&delivery).await.unwrap();
+                requests.push(delivery.into_message());
+            }
+
+            for request in requests.into_iter().rev() {
+                let properties = request.properties.unwrap();
+                assert_eq!(properties.reply_to.as_deref(), Some(REPLY_ADDRESS));
+                let reply = Message::builder()
+                    .properties(
+                        Properties::builder()
+                            .correlation_id(properties.message_id.unwrap())
+                            .build(),
+                    )
+                    .value(request.body)
+                    .build();
+                reply_sender.send(reply).await.unwrap();
+            }
+
+            let _ = request_receiver.recv::<Value>().await;
+            let _ = request_receiver.close().await;
+            let _ = reply_sender.on_detach().await;
+            let _ = reply_sender.close().await;
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let client = RpcClient::builder()
+            .timeout(Duration::from_secs(5))
+            .attach(&mut session, "echo")
+            .await
+            .unwrap();
+        assert_eq!(client.reply_to(), REPLY_ADDRESS);
+
+        let (first, second) = tokio::join!(
+            client.call(Message::builder().value("first").build()),
+            client.call(Message::builder().value("second").build()),
+        );
+        assert_eq!(
+            first.unwrap().body,
+            Body::Value(AmqpValue(Value::from("first")))
+        );
+        assert_eq!(
+            second.unwrap().body,
+            Body::Value(AmqpValue(Value::from("second")))
+        );
+
+        client.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn call_times_out_without_reply() {
+        let (client, server) = tokio::io::duplex(4096);
+
+        // A responder that accepts the request but never replies
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .on_dynamic_source(|mut source| {
+                    source.address = Some(REPLY_ADDRESS.into());
+                    Some(source)
+                })
+                .build();
+            let mut reply_sender = test_util::accept_sender(&link_acceptor, &mut session).await;
+            let mut request_receiver =
+                test_util::accept_receiver(&link_acceptor, &mut session).await;
+
+            let delivery = request_receiver.recv::<Value>().await.unwrap();
+            request_receiver.accept(&delivery).await.unwrap();
+
+            let _ = request_receiver.recv::<Value>().await;
+            let _ = request_receiver.close().await;
+            let _ = reply_sender.on_detach().await;
+            let _ = reply_sender.close().await;
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let client = RpcClient::builder()
+            .timeout(Duration::from_millis(100))
+            .attach(&mut session, "echo")
+            .await
+            .unwrap();
+
+        let result = client.call(Message::builder().value("ping").build()).await;
+        assert!(matches!(result, Err(RpcError::Timeout)));
+        assert!(client.pending.lock().is_empty());
+
+        client.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn call_fails_once_reply_link_is_closed() {
+        let (client, server) = tokio::io::duplex(4096);
+        let (detached_tx, detached_rx) = tokio::sync::oneshot::channel();
+
+        // A responder that closes the reply link right after attaching
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::builder()
+                .on_dynamic_source(|mut source| {
+                    source.address = Some(REPLY_ADDRESS.into());
+                    Some(source)
+                })
+                .build();
+            let reply_sender = test_util::accept_sender(&link_acceptor, &mut session).await;
+            let mut request_receiver =
+                test_util::accept_receiver(&link_acceptor, &mut session).await;
+            reply_sender.close().await.unwrap();
+            detached_tx.send(()).unwrap();
+
+            let _ = request_receiver.recv::<Value>().await;
+            let _ = request_receiver.close().await;
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let client = RpcClient::builder()
+            .timeout(Duration::from_secs(5))
+            .attach(&mut session, "echo")
+            .await
+            .unwrap();
+        detached_rx.await.unwrap();
+
+        // Wait for the dispatcher to observe the detach
+        let _ = tokio::time::timeout(Duration::from_secs(1), async {
+            while !client.dispatcher.is_finished() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await;
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            client.call(Message::builder().value("ping").build()),
+        )
+        .await
+        .expect("The call should fail before the timeout");
+        assert!(matches!(result, Err(RpcError::ReplyLinkClosed)));
+        assert!(client.pending.lock().is_empty());
+
+        let _ = client.close().await;
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+}
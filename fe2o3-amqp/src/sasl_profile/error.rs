@@ -1,3 +1,5 @@
+use fe2o3_amqp_types::primitives::Symbol;
+
 #[cfg(feature = "scram")]
 use crate::auth::error::ScramErrorKind;
 
@@ -8,6 +10,15 @@ pub enum Error {
     #[error("Not implemented {0:?}")]
     NotImplemented(Option<String>),
 
+    /// None of the preferred mechanisms is offered by the server
+    #[error("None of {preferred:?} is offered by the server, which offers {offered:?}")]
+    NoMatchingMechanism {
+        /// Mechanisms offered by the server
+        offered: Vec<Symbol>,
+        /// Mechanisms of the negotiated profile in order of preference
+        preferred: Vec<Symbol>,
+    },
+
     /// Error with SCRAM
     #[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
     #[cfg(feature = "scram")]
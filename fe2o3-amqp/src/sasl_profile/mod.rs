@@ -15,12 +15,17 @@ use crate::auth::error::ScramErrorKind;
 mod error;
 pub use error::Error;
 
+mod trace;
+pub use trace::{SaslDirection, SaslTrace, SaslTraceEntry};
+
 #[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
 #[cfg(feature = "scram")]
 pub mod scram;
 
 #[cfg(feature = "scram")]
-pub use self::scram::{SaslScramSha1, SaslScramSha256, SaslScramSha512};
+pub use self::scram::{
+    SaslScramSha1, SaslScramSha256, SaslScramSha256Plus, SaslScramSha512, SaslScramSha512Plus,
+};
 
 // pub const EXTERN: Symbol = Symbol::from("EXTERNAL");
 pub(crate) const ANONYMOUS: &str = "ANONYMOUS";
@@ -35,6 +40,12 @@ pub(crate) const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
 #[cfg(feature = "scram")]
 pub(crate) const SCRAM_SHA_512: &str = "SCRAM-SHA-512";
 
+#[cfg(feature = "scram")]
+pub(crate) const SCRAM_SHA_256_PLUS: &str = "SCRAM-SHA-256-PLUS";
+
+#[cfg(feature = "scram")]
+pub(crate) const SCRAM_SHA_512_PLUS: &str = "SCRAM-SHA-512-PLUS";
+
 #[cfg_attr(not(feature = "scram"), allow(dead_code))]
 pub(crate) enum Negotiation {
     Init(SaslInit),
@@ -70,6 +81,22 @@ pub enum SaslProfile {
     #[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
     #[cfg(feature = "scram")]
     ScramSha512(SaslScramSha512),
+
+    /// SASL-SCRAM-SHA-256-PLUS
+    #[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
+    #[cfg(feature = "scram")]
+    ScramSha256Plus(SaslScramSha256Plus),
+
+    /// SASL-SCRAM-SHA-512-PLUS
+    #[cfg_attr(docsrs, doc(cfg(feature = "scram")))]
+    #[cfg(feature = "scram")]
+    ScramSha512Plus(SaslScramSha512Plus),
+
+    /// Selects the first profile, in order of preference, whose mechanism is advertised by the
+    /// server
+    ///
+    /// The negotiation fails if none of the mechanisms is advertised by the server
+    Negotiated(Vec<SaslProfile>),
 }
 
 impl<T1, T2> From<(T1, T2)> for SaslProfile
@@ -108,8 +135,78 @@ impl<'a> TryFrom<&'a Url> for SaslProfile {
 }
 
 impl SaslProfile {
-    pub(crate) fn mechanism(&self) -> Symbol {
+    /// Creates a profile that selects the first profile in `preferences` whose mechanism is
+    /// advertised by the server
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let profile = SaslProfile::negotiated(vec![
+    ///     SaslScramSha512::new("user", "password").into(),
+    ///     SaslScramSha256::new("user", "password").into(),
+    ///     SaslProfile::Plain {
+    ///         username: "user".into(),
+    ///         password: "password".into(),
+    ///     },
+    /// ]);
+    /// ```
+    pub fn negotiated(preferences: Vec<SaslProfile>) -> Self {
+        Self::Negotiated(preferences)
+    }
+
+    /// Selects the first profile whose mechanism is in `server_mechanisms`
+    fn select(self, server_mechanisms: &[Symbol]) -> Option<Self> {
+        match self {
+            SaslProfile::Negotiated(preferences) => preferences
+                .into_iter()
+                .find_map(|profile| profile.select(server_mechanisms)),
+            profile => {
+                let is_offered = profile
+                    .mechanism()
+                    .filter(|mechanism| server_mechanisms.contains(mechanism))
+                    .is_some();
+                (profile.has_channel_binding() && is_offered).then_some(profile)
+            }
+        }
+    }
+
+    /// Binds the `-PLUS` profiles to the DER encoded certificate of the TLS server
+    #[cfg(any(
+        feature = "rustls",
+        all(feature = "native-tls", not(target_arch = "wasm32"))
+    ))]
+    #[cfg_attr(not(feature = "scram"), allow(unused_variables))]
+    pub(crate) fn bind_tls_server_certificate(&mut self, certificate: &[u8]) {
+        match self {
+            #[cfg(feature = "scram")]
+            SaslProfile::ScramSha256Plus(SaslScramSha256Plus { client })
+            | SaslProfile::ScramSha512Plus(SaslScramSha512Plus { client }) => {
+                scram::bind_tls_server_certificate(client, certificate)
+            }
+            SaslProfile::Negotiated(preferences) => preferences
+                .iter_mut()
+                .for_each(|profile| profile.bind_tls_server_certificate(certificate)),
+            _ => {}
+        }
+    }
+
+    /// Returns false if this is a `-PLUS` profile without channel binding data
+    fn has_channel_binding(&self) -> bool {
+        match self {
+            #[cfg(feature = "scram")]
+            SaslProfile::ScramSha256Plus(SaslScramSha256Plus { client })
+            | SaslProfile::ScramSha512Plus(SaslScramSha512Plus { client }) => !matches!(
+                client.channel_binding(),
+                crate::auth::scram::channel_binding::ChannelBinding::None
+            ),
+            _ => true,
+        }
+    }
+
+    /// Returns `None` for [`SaslProfile::Negotiated`] as no mechanism is selected yet
+    pub(crate) fn mechanism(&self) -> Option<Symbol> {
         let value = match self {
+            SaslProfile::Negotiated(_) => return None,
             SaslProfile::Anonymous => ANONYMOUS,
             SaslProfile::Plain {
                 username: _,
@@ -121,13 +218,28 @@ impl SaslProfile {
             SaslProfile::ScramSha256(_) => SCRAM_SHA_256,
             #[cfg(feature = "scram")]
             SaslProfile::ScramSha512(_) => SCRAM_SHA_512,
+            #[cfg(feature = "scram")]
+            SaslProfile::ScramSha256Plus(_) => SCRAM_SHA_256_PLUS,
+            #[cfg(feature = "scram")]
+            SaslProfile::ScramSha512Plus(_) => SCRAM_SHA_512_PLUS,
         };
-        Symbol::from(value)
+        Some(Symbol::from(value))
+    }
+
+    /// Returns the mechanisms in order of preference
+    fn preferences(&self) -> Vec<Symbol> {
+        match self {
+            SaslProfile::Negotiated(preferences) => preferences
+                .iter()
+                .flat_map(|profile| profile.preferences())
+                .collect(),
+            profile => profile.mechanism().into_iter().collect(),
+        }
     }
 
     pub(crate) fn initial_response(&mut self) -> Option<Binary> {
         match self {
-            SaslProfile::Anonymous => None,
+            SaslProfile::Anonymous | SaslProfile::Negotiated(_) => None,
             SaslProfile::Plain { username, password } => {
                 let username = username.as_bytes();
                 let password = password.as_bytes();
@@ -150,6 +262,11 @@ impl SaslProfile {
             SaslProfile::ScramSha512(scram_sha512) => Some(Binary::from(
                 scram_sha512.client.compute_client_first_message().to_vec(),
             )),
+            #[cfg(feature = "scram")]
+            SaslProfile::ScramSha256Plus(SaslScramSha256Plus { client })
+            | SaslProfile::ScramSha512Plus(SaslScramSha512Plus { client }) => {
+                Some(Binary::from(client.compute_client_first_message().to_vec()))
+            }
         }
     }
 
@@ -164,7 +281,26 @@ impl SaslProfile {
 
         match frame {
             Frame::Mechanisms(mechanisms) => {
-                let mechanism = self.mechanism();
+                if let SaslProfile::Negotiated(_) = self {
+                    let preferred = self.preferences();
+                    let profile = std::mem::replace(self, SaslProfile::Negotiated(Vec::new()));
+                    let server_mechanisms = &mechanisms.sasl_server_mechanisms.0;
+                    *self = profile.select(server_mechanisms).ok_or_else(|| {
+                        Error::NoMatchingMechanism {
+                            offered: server_mechanisms.clone(),
+                            preferred,
+                        }
+                    })?;
+                }
+
+                #[cfg(feature = "scram")]
+                if !self.has_channel_binding() {
+                    return Err(ScramErrorKind::ChannelBindingUnavailable.into());
+                }
+
+                let mechanism = self.mechanism().ok_or_else(|| {
+                    Error::NotImplemented(Some("No mechanism is selected".to_string()))
+                })?;
                 if mechanisms.sasl_server_mechanisms.0.contains(&mechanism) {
                     let init = SaslInit {
                         mechanism,
@@ -183,10 +319,15 @@ impl SaslProfile {
                 SaslProfile::Anonymous | SaslProfile::Plain { .. } => Err(Error::NotImplemented(
                     Some("SASL Challenge is not implemented for ANONYMOUS or PLAIN.".to_string()),
                 )),
+                SaslProfile::Negotiated(_) => Err(Error::NotImplemented(Some(
+                    "SASL Challenge received before a mechanism is selected.".to_string(),
+                ))),
                 #[cfg(feature = "scram")]
                 SaslProfile::ScramSha1(SaslScramSha1 { client })
                 | SaslProfile::ScramSha256(SaslScramSha256 { client })
-                | SaslProfile::ScramSha512(SaslScramSha512 { client }) => {
+                | SaslProfile::ScramSha512(SaslScramSha512 { client })
+                | SaslProfile::ScramSha256Plus(SaslScramSha256Plus { client })
+                | SaslProfile::ScramSha512Plus(SaslScramSha512Plus { client }) => {
                     let server_first = std::str::from_utf8(&challenge.challenge)
                         .map_err(ScramErrorKind::Utf8Error)?;
                     let client_final = client.compute_client_final_message(server_first)?;
@@ -199,11 +340,15 @@ impl SaslProfile {
             },
             Frame::Outcome(outcome) => {
                 match self {
-                    SaslProfile::Anonymous | SaslProfile::Plain { .. } => {}
+                    SaslProfile::Anonymous
+                    | SaslProfile::Plain { .. }
+                    | SaslProfile::Negotiated(_) => {}
                     #[cfg(feature = "scram")]
                     SaslProfile::ScramSha1(SaslScramSha1 { client })
                     | SaslProfile::ScramSha256(SaslScramSha256 { client })
-                    | SaslProfile::ScramSha512(SaslScramSha512 { client }) => {
+                    | SaslProfile::ScramSha512(SaslScramSha512 { client })
+                    | SaslProfile::ScramSha256Plus(SaslScramSha256Plus { client })
+                    | SaslProfile::ScramSha512Plus(SaslScramSha512Plus { client }) => {
                         if matches!(outcome.code, fe2o3_amqp_types::sasl::SaslCode::Ok) {
                             let server_final = outcome
                                 .additional_data
@@ -225,9 +370,12 @@ impl SaslProfile {
 
 #[cfg(test)]
 mod tests {
+    use fe2o3_amqp_types::{primitives::Symbol, sasl::SaslMechanisms};
     use url::Url;
 
-    use super::SaslProfile;
+    use crate::frames::sasl::Frame;
+
+    use super::{Error, Negotiation, SaslProfile, ANONYMOUS, PLAIN};
 
     #[test]
     fn test_try_from_address() {
@@ -261,4 +409,135 @@ mod tests {
         let response = profile.initial_response();
         println!("{:?}", response);
     }
+
+    #[test]
+    fn negotiated_selects_first_offered_mechanism() {
+        let mut profile = SaslProfile::negotiated(vec![
+            SaslProfile::Plain {
+                username: String::from("user"),
+                password: String::from("example"),
+            },
+            SaslProfile::Anonymous,
+        ]);
+        let mechanisms = SaslMechanisms {
+            sasl_server_mechanisms: vec![Symbol::from(ANONYMOUS), Symbol::from(PLAIN)].into(),
+        };
+        match profile.on_frame(Frame::Mechanisms(mechanisms), None) {
+            Ok(Negotiation::Init(init)) => assert_eq!(init.mechanism, Symbol::from(PLAIN)),
+            _ => panic!("Expecting sasl-init"),
+        }
+        assert!(matches!(profile, SaslProfile::Plain { .. }));
+    }
+
+    #[cfg(feature = "scram")]
+    #[test]
+    fn negotiated_prefers_strongest_offered_scram() {
+        use super::{SaslScramSha256, SaslScramSha512, SCRAM_SHA_256};
+
+        let mut profile = SaslProfile::negotiated(vec![
+            SaslScramSha512::new("user", "example").into(),
+            SaslScramSha256::new("user", "example").into(),
+            SaslProfile::Plain {
+                username: String::from("user"),
+                password: String::from("example"),
+            },
+        ]);
+        let mechanisms = SaslMechanisms {
+            sasl_server_mechanisms: vec![Symbol::from(PLAIN), Symbol::from(SCRAM_SHA_256)].into(),
+        };
+        match profile.on_frame(Frame::Mechanisms(mechanisms), None) {
+            Ok(Negotiation::Init(init)) => {
+                assert_eq!(init.mechanism, Symbol::from(SCRAM_SHA_256));
+                assert!(init.initial_response.is_some());
+            }
+            _ => panic!("Expecting sasl-init"),
+        }
+        assert!(matches!(profile, SaslProfile::ScramSha256(_)));
+    }
+
+    #[test]
+    fn negotiated_fails_if_no_mechanism_is_offered() {
+        let mut profile = SaslProfile::negotiated(vec![SaslProfile::Plain {
+            username: String::from("user"),
+            password: String::from("example"),
+        }]);
+        let mechanisms = SaslMechanisms {
+            sasl_server_mechanisms: vec![Symbol::from(ANONYMOUS)].into(),
+        };
+        let result = profile.on_frame(Frame::Mechanisms(mechanisms), None);
+        match result {
+            Err(Error::NoMatchingMechanism { offered, preferred }) => {
+                assert_eq!(offered, vec![Symbol::from(ANONYMOUS)]);
+                assert_eq!(preferred, vec![Symbol::from(PLAIN)]);
+            }
+            _ => panic!("Expecting Error::NoMatchingMechanism"),
+        }
+    }
+
+    /// A certificate signed with sha256WithRSAEncryption that only has the structure needed to
+    /// find the signature algorithm
+    #[cfg(feature = "scram")]
+    const CERTIFICATE: &[u8] = &[
+        0x30, 0x12, 0x30, 0x00, 0x30, 0x0b, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01,
+        0x01, 0x0b, 0x03, 0x01, 0x00,
+    ];
+
+    #[cfg(feature = "scram")]
+    #[test]
+    fn scram_plus_sends_channel_binding_gs2_header() {
+        use super::{SaslScramSha256Plus, SCRAM_SHA_256_PLUS};
+
+        let mut profile = SaslProfile::from(
+            SaslScramSha256Plus::new("user", "example").tls_server_certificate(CERTIFICATE),
+        );
+        let mechanisms = SaslMechanisms {
+            sasl_server_mechanisms: vec![Symbol::from(SCRAM_SHA_256_PLUS)].into(),
+        };
+        match profile.on_frame(Frame::Mechanisms(mechanisms), None) {
+            Ok(Negotiation::Init(init)) => {
+                assert_eq!(init.mechanism, Symbol::from(SCRAM_SHA_256_PLUS));
+                let initial_response = init.initial_response.unwrap();
+                assert!(initial_response.starts_with(b"p=tls-server-end-point,,n=user,r="));
+            }
+            _ => panic!("Expecting sasl-init"),
+        }
+    }
+
+    #[cfg(feature = "scram")]
+    #[test]
+    fn scram_plus_requires_channel_binding() {
+        use super::{SaslScramSha256Plus, SCRAM_SHA_256, SCRAM_SHA_256_PLUS};
+        use crate::auth::scram::ScramErrorKind;
+
+        let mut profile = SaslProfile::from(SaslScramSha256Plus::new("user", "example"));
+        let mechanisms = SaslMechanisms {
+            sasl_server_mechanisms: vec![Symbol::from(SCRAM_SHA_256_PLUS)].into(),
+        };
+        let result = profile.on_frame(Frame::Mechanisms(mechanisms), None);
+        assert!(matches!(
+            result,
+            Err(Error::ScramError(ScramErrorKind::ChannelBindingUnavailable))
+        ));
+
+        // Falls back to the non-PLUS mechanism without channel binding
+        let mut profile = SaslProfile::negotiated(vec![
+            SaslScramSha256Plus::new("user", "example").into(),
+            super::SaslScramSha256::new("user", "example").into(),
+        ]);
+        let mechanisms = SaslMechanisms {
+            sasl_server_mechanisms: vec![
+                Symbol::from(SCRAM_SHA_256_PLUS),
+                Symbol::from(SCRAM_SHA_256),
+            ]
+            .into(),
+        };
+        match profile.on_frame(Frame::Mechanisms(mechanisms), None) {
+            Ok(Negotiation::Init(init)) => {
+                assert_eq!(init.mechanism, Symbol::from(SCRAM_SHA_256));
+                let initial_response = init.initial_response.unwrap();
+                assert!(initial_response.starts_with(b"n,,n=user,r="));
+            }
+            _ => panic!("Expecting sasl-init"),
+        }
+    }
 }
@@ -1,6 +1,7 @@
-//! SASL-SCRAM-SHA-1, SASL-SCRAM-SHA-256, and SASL-SCRAM-SHA-512
+//! SASL-SCRAM-SHA-1, SASL-SCRAM-SHA-256, and SASL-SCRAM-SHA-512, and the channel binding
+//! SASL-SCRAM-SHA-256-PLUS and SASL-SCRAM-SHA-512-PLUS
 
-use crate::auth::scram::{client::ScramClient, ScramVersion};
+use crate::auth::scram::{channel_binding::ChannelBinding, client::ScramClient, ScramVersion};
 
 use super::SaslProfile;
 
@@ -106,3 +107,97 @@ impl From<SaslScramSha512> for SaslProfile {
         Self::ScramSha512(sha1)
     }
 }
+
+/// SASL-SCRAM-SHA-256-PLUS
+///
+/// SCRAM with `tls-server-end-point` channel binding, which binds the authentication to the
+/// certificate of the TLS server. The channel binding data is taken from the TLS stream when the
+/// connection is opened with an `"amqps"` url. If the TLS stream is established outside of the
+/// builder, the server certificate must be supplied with
+/// [`tls_server_certificate`](#method.tls_server_certificate).
+///
+/// # Example
+///
+/// ```rust
+/// use fe2o3_amqp::{Connection, sasl_profile::SaslScramSha256Plus};
+///
+/// let mut connection = Connection::builder()
+///     .container_id("connection-1")
+///     .sasl_profile(SaslScramSha256Plus::new("username", "password"))
+///     .open("amqps://localhost:5671")
+///     .await
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SaslScramSha256Plus {
+    pub(crate) client: ScramClient,
+}
+
+impl SaslScramSha256Plus {
+    /// Creates a [`SaslScramSha256Plus`]
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        let client = ScramClient::new(username, password, ScramVersion::Sha256);
+        Self { client }
+    }
+
+    /// Binds to the DER encoded certificate of the TLS server
+    pub fn tls_server_certificate(mut self, certificate: &[u8]) -> Self {
+        bind_tls_server_certificate(&mut self.client, certificate);
+        self
+    }
+}
+
+impl From<SaslScramSha256Plus> for SaslProfile {
+    fn from(sha256_plus: SaslScramSha256Plus) -> Self {
+        Self::ScramSha256Plus(sha256_plus)
+    }
+}
+
+/// SASL-SCRAM-SHA-512-PLUS
+///
+/// See [`SaslScramSha256Plus`] for how the channel binding data is obtained.
+///
+/// # Example
+///
+/// ```rust
+/// use fe2o3_amqp::{Connection, sasl_profile::SaslScramSha512Plus};
+///
+/// let mut connection = Connection::builder()
+///     .container_id("connection-1")
+///     .sasl_profile(SaslScramSha512Plus::new("username", "password"))
+///     .open("amqps://localhost:5671")
+///     .await
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SaslScramSha512Plus {
+    pub(crate) client: ScramClient,
+}
+
+impl SaslScramSha512Plus {
+    /// Creates a [`SaslScramSha512Plus`]
+    pub fn new(username: impl Into<String>, password: impl Into<String>) -> Self {
+        let client = ScramClient::new(username, password, ScramVersion::Sha512);
+        Self { client }
+    }
+
+    /// Binds to the DER encoded certificate of the TLS server
+    pub fn tls_server_certificate(mut self, certificate: &[u8]) -> Self {
+        bind_tls_server_certificate(&mut self.client, certificate);
+        self
+    }
+}
+
+impl From<SaslScramSha512Plus> for SaslProfile {
+    fn from(sha512_plus: SaslScramSha512Plus) -> Self {
+        Self::ScramSha512Plus(sha512_plus)
+    }
+}
+
+/// The channel binding is left unset if the signature algorithm of the certificate is not
+/// supported, and the negotiation then fails with `ScramErrorKind::ChannelBindingUnavailable`
+pub(crate) fn bind_tls_server_certificate(client: &mut ScramClient, certificate: &[u8]) {
+    if let Some(channel_binding) = ChannelBinding::tls_server_end_point(certificate) {
+        client.set_channel_binding(channel_binding);
+    }
+}
@@ -0,0 +1,308 @@
+//! Capture of the SASL exchange for debugging
+
+use fe2o3_amqp_types::{primitives::Symbol, sasl::SaslCode};
+
+use crate::frames::sasl;
+
+use super::{ANONYMOUS, PLAIN};
+
+#[cfg(feature = "scram")]
+use super::{SCRAM_SHA_1, SCRAM_SHA_256, SCRAM_SHA_256_PLUS, SCRAM_SHA_512, SCRAM_SHA_512_PLUS};
+
+const REDACTED: &str = "<redacted>";
+
+/// Direction of a captured SASL frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaslDirection {
+    /// Frame sent by the client
+    Sent,
+
+    /// Frame received from the server
+    Received,
+}
+
+/// A SASL frame captured in a [`SaslTrace`]
+///
+/// Binary payloads are rendered as UTF-8 text with secrets (ie. the PLAIN password and the
+/// SCRAM client proof) replaced by `<redacted>`. The payloads of mechanisms other than ANONYMOUS,
+/// PLAIN and SCRAM are replaced by `<redacted>` entirely. Payloads that are not valid UTF-8 are
+/// rendered as their length in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaslTraceEntry {
+    /// sasl-mechanisms received from the server
+    Mechanisms(Vec<Symbol>),
+
+    /// sasl-init sent by the client
+    Init {
+        /// Selected security mechanism
+        mechanism: Symbol,
+        /// Redacted initial response
+        initial_response: Option<String>,
+        /// Hostname
+        hostname: Option<String>,
+    },
+
+    /// sasl-challenge received from the server
+    Challenge(String),
+
+    /// sasl-response sent by the client
+    Response(String),
+
+    /// sasl-outcome received from the server
+    Outcome {
+        /// Outcome code
+        code: SaslCode,
+        /// Redacted additional data
+        additional_data: Option<String>,
+    },
+}
+
+impl SaslTraceEntry {
+    /// Direction of the captured frame
+    pub fn direction(&self) -> SaslDirection {
+        match self {
+            SaslTraceEntry::Init { .. } | SaslTraceEntry::Response(_) => SaslDirection::Sent,
+            SaslTraceEntry::Mechanisms(_)
+            | SaslTraceEntry::Challenge(_)
+            | SaslTraceEntry::Outcome { .. } => SaslDirection::Received,
+        }
+    }
+}
+
+/// Frames exchanged during SASL negotiation, in the order they were sent or received
+///
+/// This is only captured if enabled with
+/// [`Builder::sasl_trace`](crate::connection::Builder::sasl_trace) and is returned with
+/// [`OpenError::SaslTraced`](crate::connection::OpenError::SaslTraced) if the connect fails
+/// during SASL negotiation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaslTrace {
+    mechanism: Option<Symbol>,
+    entries: Vec<SaslTraceEntry>,
+}
+
+impl SaslTrace {
+    pub(crate) fn new(mechanism: Option<Symbol>) -> Self {
+        Self {
+            mechanism,
+            entries: Vec::new(),
+        }
+    }
+
+    /// The mechanism of the local SASL profile
+    ///
+    /// For [`SaslProfile::Negotiated`](super::SaslProfile::Negotiated), this is the mechanism
+    /// selected in the sasl-init frame, or `None` if none was selected
+    pub fn mechanism(&self) -> Option<&Symbol> {
+        self.mechanism.as_ref()
+    }
+
+    /// Captured frames
+    pub fn entries(&self) -> &[SaslTraceEntry] {
+        &self.entries
+    }
+
+    pub(crate) fn record(&mut self, frame: &sasl::Frame) {
+        if let sasl::Frame::Init(init) = frame {
+            self.mechanism = Some(init.mechanism.clone());
+        }
+
+        let entry = match frame {
+            sasl::Frame::Mechanisms(mechanisms) => {
+                SaslTraceEntry::Mechanisms(mechanisms.sasl_server_mechanisms.0.clone())
+            }
+            sasl::Frame::Init(init) => SaslTraceEntry::Init {
+                mechanism: init.mechanism.clone(),
+                initial_response: init.initial_response.as_ref().map(|data| self.redact(data)),
+                hostname: init.hostname.clone(),
+            },
+            sasl::Frame::Challenge(challenge) => {
+                SaslTraceEntry::Challenge(self.redact(&challenge.challenge))
+            }
+            sasl::Frame::Response(response) => {
+                SaslTraceEntry::Response(self.redact(&response.response))
+            }
+            sasl::Frame::Outcome(outcome) => SaslTraceEntry::Outcome {
+                code: outcome.code.clone(),
+                additional_data: outcome
+                    .additional_data
+                    .as_ref()
+                    .map(|data| self.redact(data)),
+            },
+        };
+        self.entries.push(entry);
+    }
+
+    fn redact(&self, data: &[u8]) -> String {
+        let text = match std::str::from_utf8(data) {
+            Ok(text) => text,
+            Err(_) => return format!("<{} bytes>", data.len()),
+        };
+
+        match self.mechanism.as_ref().map(Symbol::as_str) {
+            Some(ANONYMOUS) => text.to_string(),
+            Some(PLAIN) => {
+                // authzid NUL authcid NUL passwd
+                match text.rfind('\0') {
+                    Some(index) => format!("{}{}", &text[..=index], REDACTED),
+                    None => REDACTED.to_string(),
+                }
+            }
+            #[cfg(feature = "scram")]
+            Some(
+                SCRAM_SHA_1 | SCRAM_SHA_256 | SCRAM_SHA_512 | SCRAM_SHA_256_PLUS
+                | SCRAM_SHA_512_PLUS,
+            ) => redact_scram_client_proof(text),
+            // Where the secrets are is unknown for any other mechanism
+            _ => REDACTED.to_string(),
+        }
+    }
+}
+
+/// The client proof is the only secret in a SCRAM exchange. It follows the channel binding in the
+/// client-final message, whereas the `p=` at the start of the client-first message is the GS2
+/// channel binding flag.
+#[cfg(feature = "scram")]
+fn redact_scram_client_proof(text: &str) -> String {
+    let mut is_client_final = false;
+    text.split(',')
+        .map(|attr| {
+            if attr.starts_with("c=") {
+                is_client_final = true;
+            }
+            match is_client_final && attr.starts_with("p=") {
+                true => format!("p={}", REDACTED),
+                false => attr.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl std::fmt::Display for SaslTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            let arrow = match entry.direction() {
+                SaslDirection::Sent => "->",
+                SaslDirection::Received => "<-",
+            };
+            writeln!(f, "{} {:?}", arrow, entry)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe2o3_amqp_types::{
+        primitives::{Binary, Symbol},
+        sasl::{SaslChallenge, SaslCode, SaslInit, SaslMechanisms, SaslOutcome, SaslResponse},
+    };
+
+    use crate::{frames::sasl, sasl_profile::SaslProfile};
+
+    use super::{SaslDirection, SaslTrace, SaslTraceEntry};
+
+    #[test]
+    fn plain_password_is_redacted() {
+        let mut profile = SaslProfile::Plain {
+            username: String::from("user"),
+            password: String::from("secret"),
+        };
+        let mut trace = SaslTrace::new(profile.mechanism());
+
+        trace.record(&sasl::Frame::Mechanisms(SaslMechanisms {
+            sasl_server_mechanisms: vec![Symbol::from("PLAIN")].into(),
+        }));
+        trace.record(&sasl::Frame::Init(SaslInit {
+            mechanism: profile.mechanism().unwrap(),
+            initial_response: profile.initial_response(),
+            hostname: None,
+        }));
+        trace.record(&sasl::Frame::Outcome(SaslOutcome {
+            code: SaslCode::Auth,
+            additional_data: Some(Binary::from(vec![0xff, 0xfe])),
+        }));
+
+        let entries = trace.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].direction(), SaslDirection::Received);
+        assert_eq!(
+            entries[1],
+            SaslTraceEntry::Init {
+                mechanism: Symbol::from("PLAIN"),
+                initial_response: Some(String::from("\0user\0<redacted>")),
+                hostname: None,
+            }
+        );
+        assert_eq!(
+            entries[2],
+            SaslTraceEntry::Outcome {
+                code: SaslCode::Auth,
+                additional_data: Some(String::from("<2 bytes>")),
+            }
+        );
+        assert!(!trace.to_string().contains("secret"));
+    }
+
+    #[test]
+    fn unknown_mechanism_is_redacted_entirely() {
+        let mut trace = SaslTrace::new(None);
+
+        trace.record(&sasl::Frame::Init(SaslInit {
+            mechanism: Symbol::from("GSSAPI"),
+            initial_response: Some(Binary::from(b"token".to_vec())),
+            hostname: None,
+        }));
+        trace.record(&sasl::Frame::Challenge(SaslChallenge {
+            challenge: Binary::from(b"challenge".to_vec()),
+        }));
+
+        let entries = trace.entries();
+        assert_eq!(
+            entries[0],
+            SaslTraceEntry::Init {
+                mechanism: Symbol::from("GSSAPI"),
+                initial_response: Some(String::from("<redacted>")),
+                hostname: None,
+            }
+        );
+        assert_eq!(
+            entries[1],
+            SaslTraceEntry::Challenge(String::from("<redacted>"))
+        );
+    }
+
+    #[cfg(feature = "scram")]
+    #[test]
+    fn only_scram_client_proof_is_redacted() {
+        let mut trace = SaslTrace::new(Some(Symbol::from("SCRAM-SHA-256-PLUS")));
+
+        trace.record(&sasl::Frame::Init(SaslInit {
+            mechanism: Symbol::from("SCRAM-SHA-256-PLUS"),
+            initial_response: Some(Binary::from(
+                b"p=tls-server-end-point,,n=user,r=nonce".to_vec(),
+            )),
+            hostname: None,
+        }));
+        trace.record(&sasl::Frame::Response(SaslResponse {
+            response: Binary::from(b"c=cD10bHMtc2VydmVyLWVuZC1wb2ludCws,r=nonce,p=proof".to_vec()),
+        }));
+
+        let entries = trace.entries();
+        assert_eq!(
+            entries[0],
+            SaslTraceEntry::Init {
+                mechanism: Symbol::from("SCRAM-SHA-256-PLUS"),
+                initial_response: Some(String::from("p=tls-server-end-point,,n=user,r=nonce")),
+                hostname: None,
+            }
+        );
+        assert_eq!(
+            entries[1],
+            SaslTraceEntry::Response(String::from(
+                "c=cD10bHMtc2VydmVyLWVuZC1wb2ludCws,r=nonce,p=<redacted>"
+            ))
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! Session builder
 
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
 
 use fe2o3_amqp_types::definitions::{Fields, Handle, TransferNumber};
 use serde_amqp::primitives::Symbol;
@@ -49,10 +52,25 @@ pub struct Builder {
     /// that are used by links attached to the session
     pub buffer_size: usize,
 
+    /// Buffer size of the channel that carries outgoing frames from the links to the session.
+    /// This bounds how many frames can be queued before senders experience backpressure.
+    /// Falls back to `buffer_size` if not set
+    pub outgoing_buffer_size: Option<usize>,
+
+    /// Timeout of the best-effort end that is performed when the session handle is dropped
+    /// without being ended. `None` means the end is only attempted if it can be queued
+    /// immediately
+    pub end_on_drop: Option<Duration>,
+
     /// Acceptor for incoming transaction control links
     #[cfg(not(target_arch = "wasm32"))]
     #[cfg(all(feature = "transaction", feature = "acceptor"))]
     pub(crate) control_link_acceptor: Option<ControlLinkAcceptor>,
+
+    /// Inspects the Begin of an incoming session before it is accepted
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "acceptor")]
+    pub(crate) on_incoming_begin: Option<crate::acceptor::session::BeginHook>,
 }
 
 impl Default for Builder {
@@ -66,10 +84,16 @@ impl Default for Builder {
             desired_capabilities: None,
             properties: None,
             buffer_size: DEFAULT_SESSION_MUX_BUFFER_SIZE,
+            outgoing_buffer_size: None,
+            end_on_drop: None,
 
             #[cfg(not(target_arch = "wasm32"))]
             #[cfg(all(feature = "transaction", feature = "acceptor"))]
             control_link_acceptor: None,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            #[cfg(feature = "acceptor")]
+            on_incoming_begin: None,
         }
     }
 }
@@ -96,8 +120,10 @@ cfg_transaction! {
                     local_state,
                     initial_outgoing_id: Constant::new(self.next_outgoing_id),
                     next_outgoing_id: self.next_outgoing_id,
+                    initial_incoming_window: Constant::new(self.incoming_window),
                     incoming_window: self.incoming_window,
                     outgoing_window: self.outgoing_window,
+                    agreed_handle_max: self.handle_max.0,
                     handle_max: self.handle_max,
                     incoming_channel: None,
                     next_incoming_id: 0,
@@ -141,8 +167,10 @@ impl Builder {
             local_state,
             initial_outgoing_id: Constant::new(self.next_outgoing_id),
             next_outgoing_id: self.next_outgoing_id,
+            initial_incoming_window: Constant::new(self.incoming_window),
             incoming_window: self.incoming_window,
             outgoing_window: self.outgoing_window,
+            agreed_handle_max: self.handle_max.0,
             handle_max: self.handle_max,
             incoming_channel: None,
             next_incoming_id: 0,
@@ -167,17 +195,26 @@ impl Builder {
     }
 
     /// The initial incoming-window of the sender
+    ///
+    /// The session sends a flow to reopen the window once half of it has been consumed, and ends
+    /// with a `window-violation` error if the peer sends beyond it
     pub fn incoming_window(mut self, value: TransferNumber) -> Self {
         self.incoming_window = value;
         self
     }
 
     /// The initial outgoing-window of the sender
-    pub fn outgoing_widnow(mut self, value: TransferNumber) -> Self {
+    pub fn outgoing_window(mut self, value: TransferNumber) -> Self {
         self.outgoing_window = value;
         self
     }
 
+    /// The initial outgoing-window of the sender
+    #[deprecated = "Since 0.8.15, please use `outgoing_window` instead"]
+    pub fn outgoing_widnow(self, value: TransferNumber) -> Self {
+        self.outgoing_window(value)
+    }
+
     /// The maximum handle value that can be used on the session
     pub fn handle_max(mut self, value: impl Into<Handle>) -> Self {
         self.handle_max = value.into();
@@ -227,6 +264,27 @@ impl Builder {
         self
     }
 
+    /// Buffer size of the channel that carries outgoing frames from the links to the session.
+    /// Falls back to [`buffer_size`](#method.buffer_size) if not set
+    pub fn outgoing_buffer_size(mut self, outgoing_buffer_size: usize) -> Self {
+        self.outgoing_buffer_size = Some(outgoing_buffer_size);
+        self
+    }
+
+    /// End the session on a best-effort basis if the handle is dropped without being ended
+    ///
+    /// The End frame is sent from a detached task that gives up after `timeout`. Without this
+    /// option, dropping the handle only attempts the end if it can be queued immediately
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn end_on_drop(mut self, timeout: Duration) -> Self {
+        self.end_on_drop = Some(timeout);
+        self
+    }
+
+    pub(crate) fn outgoing_buffer_size_or_default(&self) -> usize {
+        self.outgoing_buffer_size.unwrap_or(self.buffer_size)
+    }
+
     // TODO
     // /// Enable handling remotely initiated control link and transaction by setting the
     // /// `control_link_acceptor` field
@@ -259,7 +317,9 @@ impl Builder {
         let (session_control_tx, session_control_rx) =
             mpsc::channel::<SessionControl>(DEFAULT_SESSION_CONTROL_BUFFER_SIZE);
         let (incoming_tx, incoming_rx) = mpsc::channel(self.buffer_size);
-        let (outgoing_tx, outgoing_rx) = mpsc::channel(self.buffer_size);
+        let outgoing_buffer_size = self.outgoing_buffer_size_or_default();
+        let end_on_drop = self.end_on_drop;
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(outgoing_buffer_size);
 
         // create session in connection::Engine
         let outgoing_channel = match connection.allocate_session(incoming_tx).await {
@@ -274,7 +334,7 @@ impl Builder {
         };
 
         #[cfg(not(all(feature = "transaction", feature = "acceptor")))]
-        let engine_handle = {
+        let (handle_max, engine_handle) = {
             let session = self.into_session(outgoing_channel, local_state);
             let engine = SessionEngine::begin_client_session(
                 connection.control.clone(),
@@ -285,11 +345,11 @@ impl Builder {
                 outgoing_rx,
             )
             .await?;
-            engine.spawn()
+            (engine.agreed_handle_max(), engine.spawn())
         };
 
         #[cfg(all(feature = "transaction", feature = "acceptor"))]
-        let engine_handle = {
+        let (handle_max, engine_handle) = {
             let mut this = self;
             match this.control_link_acceptor.take() {
                 Some(control_link_acceptor) => {
@@ -309,7 +369,7 @@ impl Builder {
                         outgoing_rx,
                     )
                     .await?;
-                    engine.spawn()
+                    (engine.agreed_handle_max(), engine.spawn())
                 }
                 None => {
                     let session = this.into_session(outgoing_channel, local_state);
@@ -322,7 +382,7 @@ impl Builder {
                         outgoing_rx,
                     )
                     .await?;
-                    engine.spawn()
+                    (engine.agreed_handle_max(), engine.spawn())
                 }
             }
         };
@@ -331,8 +391,12 @@ impl Builder {
             is_ended: false,
             control: session_control_tx,
             engine_handle,
+            handle_max,
             outgoing: outgoing_tx,
+            outgoing_buffer_size,
             link_listener: (),
+            unsettled_deliveries: connection.unsettled_deliveries.clone(),
+            end_on_drop,
         };
         Ok(handle)
     }
@@ -358,7 +422,9 @@ impl Builder {
         let (session_control_tx, session_control_rx) =
             mpsc::channel::<SessionControl>(DEFAULT_SESSION_CONTROL_BUFFER_SIZE);
         let (incoming_tx, incoming_rx) = mpsc::channel(self.buffer_size);
-        let (outgoing_tx, outgoing_rx) = mpsc::channel(self.buffer_size);
+        let outgoing_buffer_size = self.outgoing_buffer_size_or_default();
+        let end_on_drop = self.end_on_drop;
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(outgoing_buffer_size);
 
         // create session in connection::Engine
         let outgoing_channel = match connection.allocate_session(incoming_tx).await {
@@ -372,7 +438,7 @@ impl Builder {
             },
         };
 
-        let engine_handle = {
+        let (handle_max, engine_handle) = {
             let session = self.into_session(outgoing_channel, local_state);
             let engine = SessionEngine::begin_client_session(
                 connection.control.clone(),
@@ -383,15 +449,19 @@ impl Builder {
                 outgoing_rx,
             )
             .await?;
-            engine.spawn_local(local_set)
+            (engine.agreed_handle_max(), engine.spawn_local(local_set))
         };
 
         let handle = SessionHandle {
             is_ended: false,
             control: session_control_tx,
             engine_handle,
+            handle_max,
             outgoing: outgoing_tx,
+            outgoing_buffer_size,
             link_listener: (),
+            unsettled_deliveries: connection.unsettled_deliveries.clone(),
+            end_on_drop,
         };
         Ok(handle)
     }
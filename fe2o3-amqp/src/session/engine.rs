@@ -101,6 +101,10 @@ where
         engine.session.on_incoming_begin(channel, remote_begin)?;
         Ok(engine)
     }
+
+    pub(crate) fn agreed_handle_max(&self) -> u32 {
+        self.session.agreed_handle_max()
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -157,9 +161,28 @@ where
                 performative,
                 payload,
             } => {
-                self.session
+                self.session.consume_incoming_window()?;
+
+                // A transactional resource may need to inform the controller of the presumptive
+                // outcome of a transactionally posted transfer
+                if let Some(disposition) = self
+                    .session
                     .on_incoming_transfer(performative, payload)
-                    .await?;
+                    .await?
+                {
+                    let disposition = self.session.on_outgoing_disposition(disposition)?;
+                    self.outgoing
+                        .send(disposition)
+                        .await
+                        .map_err(|_| SessionInnerError::IllegalConnectionState)?;
+                }
+
+                if let Some(flow) = self.session.replenish_incoming_window() {
+                    self.outgoing
+                        .send(flow)
+                        .await
+                        .map_err(|_| SessionInnerError::IllegalConnectionState)?;
+                }
             }
             SessionFrameBody::Disposition(disposition) => {
                 if let Some(dispositions) = self.session.on_incoming_disposition(disposition)? {
@@ -190,6 +213,9 @@ where
                 }
                 result?;
             }
+            SessionFrameBody::Flush(_) => {
+                unreachable!("SessionFrameBody::Flush should not appear in incoming session frames")
+            }
         }
 
         match self.session.local_state() {
@@ -213,7 +239,11 @@ where
                     self.on_outgoing_link_frames(frame).await?;
                 }
 
-                self.session.send_end(&self.outgoing, error).await?;
+                // Wait for the remote End so that dispositions sent by the remote peer
+                // before ending are still applied, and then detach all links
+                let running = self.end_session(error.clone()).await;
+                self.session.detach_all_links(error);
+                return running;
             }
             SessionControl::AllocateLink {
                 link_name,
@@ -333,6 +363,10 @@ where
             LinkFrame::Detach(detach) => Some(SessionOutgoingItem::SingleFrame(
                 self.session.on_outgoing_detach(detach),
             )),
+            LinkFrame::Flush(resp) => Some(SessionOutgoingItem::SingleFrame(SessionFrame::new(
+                self.session.outgoing_channel(),
+                SessionFrameBody::Flush(resp),
+            ))),
 
             #[cfg(feature = "transaction")]
             LinkFrame::Acquisition(_) => {
@@ -376,6 +410,10 @@ where
                 let error = Error::new(SessionError::HandleInUse, None, None);
                 self.end_session(Some(error)).await
             }
+            SessionInnerError::WindowViolation => {
+                let error = Error::new(SessionError::WindowViolation, None, None);
+                self.end_session(Some(error)).await
+            }
             SessionInnerError::IllegalState => {
                 let error = Error::new(AmqpError::IllegalState, None, None);
                 self.end_session(Some(error)).await
@@ -45,6 +45,11 @@ pub enum BeginError {
     /// Channel max reached
     #[error("Local channel-max reached")]
     LocalChannelMaxReached,
+
+    /// The incoming session is rejected by the `on_incoming_begin` hook of the session acceptor.
+    /// The session has been ended with the error returned by the hook
+    #[error("Incoming session is rejected {:?}", .0)]
+    Rejected(definitions::Error),
 }
 
 impl From<SessionStateError> for BeginError {
@@ -72,6 +77,10 @@ pub(crate) enum SessionInnerError {
     #[error("An attach was received using a handle that is already in use for an attached link.")]
     HandleInUse,
 
+    /// The peer sent more transfers than the incoming-window allows
+    #[error("The peer violated the incoming-window of the session")]
+    WindowViolation,
+
     /// Illegal sesesion state
     #[error("Illegal session state")]
     IllegalState,
@@ -135,6 +144,10 @@ pub enum Error {
     #[error("An attach was received using a handle that is already in use for an attached link.")]
     HandleInUse,
 
+    /// The peer sent more transfers than the incoming-window allows
+    #[error("The peer violated the incoming-window of the session")]
+    WindowViolation,
+
     /// Illegal sesesion state
     #[error("Illegal session state")]
     IllegalState,
@@ -173,6 +186,7 @@ impl From<SessionInnerError> for Error {
                 Self::RemoteAttachingLinkNameNotFound
             }
             SessionInnerError::HandleInUse => Self::HandleInUse,
+            SessionInnerError::WindowViolation => Self::WindowViolation,
             SessionInnerError::IllegalState => Self::IllegalState,
             SessionInnerError::IllegalConnectionState => Self::IllegalConnectionState,
             SessionInnerError::TransferFrameToSender => Self::TransferFrameToSender,
@@ -215,4 +229,7 @@ pub(crate) enum AllocLinkError {
 
     #[error("Link name must be unique")]
     DuplicatedLinkName,
+
+    #[error("Reached session handle max")]
+    HandleMaxReached,
 }
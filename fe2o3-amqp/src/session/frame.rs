@@ -1,4 +1,5 @@
 use fe2o3_amqp_types::performatives::{Attach, Begin, Detach, Disposition, End, Flow, Transfer};
+use tokio::sync::oneshot;
 
 use crate::Payload;
 
@@ -38,6 +39,10 @@ pub(crate) enum SessionFrameBody {
     Disposition(Disposition),
     Detach(Detach),
 
+    /// Not an actual frame. The connection event loop responds once all previously
+    /// queued frames have been written to the transport
+    Flush(oneshot::Sender<()>),
+
     // Frames handled by Session
     Begin(Begin),
     End(End),
@@ -58,6 +63,7 @@ impl std::fmt::Debug for SessionFrameBody {
                 .finish(),
             Self::Disposition(arg0) => f.debug_tuple("Disposition").field(arg0).finish(),
             Self::Detach(arg0) => f.debug_tuple("Detach").field(arg0).finish(),
+            Self::Flush(_) => f.debug_tuple("Flush").finish(),
             Self::Begin(arg0) => f.debug_tuple("Begin").field(arg0).finish(),
             Self::End(arg0) => f.debug_tuple("End").field(arg0).finish(),
         }
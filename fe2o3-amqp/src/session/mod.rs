@@ -1,6 +1,10 @@
 //! Implements AMQP1.0 Session
 
-use std::collections::{HashMap, VecDeque};
+use std::{
+    cmp::min,
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use fe2o3_amqp_types::{
@@ -21,9 +25,10 @@ use tokio::{
 };
 
 use crate::{
+    connection::UnsettledDeliveries,
     control::SessionControl,
     endpoint::{self, IncomingChannel, InputHandle, LinkFlow, OutgoingChannel, OutputHandle},
-    link::{LinkFrame, LinkRelay},
+    link::{DetachError, LinkFrame, LinkRelay},
     util::{is_consecutive, Constant},
     Payload,
 };
@@ -61,10 +66,18 @@ pub struct SessionHandle<R> {
     pub(crate) is_ended: bool,
     pub(crate) control: mpsc::Sender<SessionControl>,
     pub(crate) engine_handle: JoinHandle<Result<(), Error>>,
+    pub(crate) handle_max: u32,
 
     // outgoing for Link
     pub(crate) outgoing: mpsc::Sender<LinkFrame>,
+    pub(crate) outgoing_buffer_size: usize,
     pub(crate) link_listener: R,
+
+    // unsettled outgoing deliveries shared with the connection
+    pub(crate) unsettled_deliveries: UnsettledDeliveries,
+
+    // how long a best-effort end may take when the handle is dropped without being ended
+    pub(crate) end_on_drop: Option<Duration>,
 }
 
 impl<R> std::fmt::Debug for SessionHandle<R> {
@@ -75,6 +88,18 @@ impl<R> std::fmt::Debug for SessionHandle<R> {
 
 impl<R> Drop for SessionHandle<R> {
     fn drop(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(timeout), false) = (self.end_on_drop, self.is_ended) {
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                let control = self.control.clone();
+                runtime.spawn(async move {
+                    let end = control.send(SessionControl::End(None));
+                    let _ = tokio::time::timeout(timeout, end).await;
+                });
+                return;
+            }
+        }
+
         let _ = self.control.try_send(SessionControl::End(None));
     }
 }
@@ -88,9 +113,36 @@ impl<R> SessionHandle<R> {
         }
     }
 
+    /// Number of frames that can currently be queued on the channel shared by all links on the
+    /// session before sending starts to wait for the session to catch up
+    ///
+    /// This can be used together with [`Sender::try_send`](crate::Sender::try_send) for
+    /// admission control
+    pub fn capacity(&self) -> usize {
+        self.outgoing.capacity()
+    }
+
+    /// Total size of the channel shared by all links on the session for outgoing frames
+    pub fn max_capacity(&self) -> usize {
+        self.outgoing_buffer_size
+    }
+
+    /// The handle-max in effect for the session, which is the smaller of the local and the remote
+    /// handle-max
+    ///
+    /// Attaching a link beyond this limit fails with `HandleMaxReached`
+    pub fn handle_max(&self) -> u32 {
+        self.handle_max
+    }
+
     cfg_not_wasm32! {
         /// End the session
         ///
+        /// Frames already queued by the links are sent before the `End` frame. Once the remote
+        /// peer replies with its `End`, all links on the session are detached and the
+        /// [`DeliveryFut`](crate::link::delivery::DeliveryFut)s of deliveries that are still
+        /// unsettled resolve with `LinkStateError::IllegalSessionState`.
+        ///
         /// An `Error::IllegalState` will be returned if called after any of [`end`](#method.end),
         /// [`end_with_error`](#method.end_with_error), [`on_end`](#on_end) has beend executed. This
         /// will cause the JoinHandle to be polled after completion, which causes a panic.
@@ -116,6 +168,8 @@ impl<R> SessionHandle<R> {
     
         /// End the session with an error
         ///
+        /// See [`end`](#method.end) for how the links on the session are handled.
+        ///
         /// An `Error::IllegalState` will be returned if called after any of [`end`](#method.end),
         /// [`end_with_error`](#method.end_with_error), [`on_end`](#on_end) has beend executed.    
         /// This will cause the JoinHandle to be polled after completion, which causes a panic.
@@ -191,6 +245,39 @@ pub(crate) async fn allocate_link(
         .map_err(|_| AllocLinkError::IllegalSessionState)?
 }
 
+/// Allocate a link that is attached by the remote peer
+///
+/// Unlike [`allocate_link`], the relay is registered under the input handle right away because
+/// the remote Attach has already been received.
+pub(crate) async fn allocate_incoming_link(
+    control: &mpsc::Sender<SessionControl>,
+    link_name: String,
+    link_relay: LinkRelay<()>,
+    input_handle: InputHandle,
+) -> Result<OutputHandle, AllocLinkError> {
+    let (responder, resp_rx) = oneshot::channel();
+
+    control
+        .send(SessionControl::AllocateIncomingLink {
+            link_name,
+            link_relay,
+            input_handle,
+            responder,
+        })
+        .await // cancel safe
+        // The `SendError` could only happen when the receiving half is
+        // dropped, meaning the `SessionEngine::event_loop` has stopped.
+        // This would also mean the `Session` is Unmapped, and thus it
+        // may be treated as illegal state
+        .map_err(|_| AllocLinkError::IllegalSessionState)?;
+    resp_rx
+        .await
+        // The error could only occur when the sending half is dropped,
+        // indicating the `SessionEngine::even_loop` has stopped or
+        // unmapped. Thus it could be considered as illegal state
+        .map_err(|_| AllocLinkError::IllegalSessionState)?
+}
+
 /// AMQP1.0 Session
 ///
 /// # Begin a new Session with default configuration
@@ -233,9 +320,14 @@ pub struct Session {
     pub(crate) local_state: SessionState,
     pub(crate) initial_outgoing_id: Constant<TransferNumber>,
     pub(crate) next_outgoing_id: TransferNumber,
+    // The incoming-window configured on the builder, which the current incoming-window is reset
+    // to once half of it has been consumed
+    pub(crate) initial_incoming_window: Constant<TransferNumber>,
     pub(crate) incoming_window: TransferNumber,
     pub(crate) outgoing_window: TransferNumber,
     pub(crate) handle_max: Handle,
+    // the smaller of the local and the remote handle-max
+    pub(crate) agreed_handle_max: u32,
 
     // remote amqp states
     pub(crate) incoming_channel: Option<IncomingChannel>,
@@ -472,6 +564,10 @@ impl endpoint::Session for Session {
         self.outgoing_channel
     }
 
+    fn agreed_handle_max(&self) -> u32 {
+        self.agreed_handle_max
+    }
+
     fn allocate_link(
         &mut self,
         link_name: String,
@@ -489,6 +585,9 @@ impl endpoint::Session for Session {
 
         // get a new entry index
         let entry = self.link_name_by_output_handle.vacant_entry();
+        if entry.key() > self.agreed_handle_max as usize {
+            return Err(AllocLinkError::HandleMaxReached);
+        }
         let handle = OutputHandle(entry.key() as u32);
 
         entry.insert(link_name.clone());
@@ -523,6 +622,27 @@ impl endpoint::Session for Session {
         }
     }
 
+    fn detach_all_links(&mut self, error: Option<definitions::Error>) {
+        self.link_name_by_output_handle.clear();
+        self.delivery_tag_by_id.clear();
+        let relays = self
+            .link_by_name
+            .drain()
+            .filter_map(|(_, relay)| relay)
+            .chain(self.link_by_input_handle.drain().map(|(_, relay)| relay));
+        for relay in relays {
+            // The pending `DeliveryFut`s resolve with `DetachError::SessionEnded`, and dropping
+            // the relay closes the incoming channel of the link
+            if let LinkRelay::Sender { unsettled, .. } = relay {
+                if let Some(unsettled) = unsettled.write().take() {
+                    for message in unsettled.into_values() {
+                        message.detach(DetachError::SessionEnded(error.clone()));
+                    }
+                }
+            }
+        }
+    }
+
     fn on_incoming_begin(
         &mut self,
         channel: IncomingChannel,
@@ -538,6 +658,7 @@ impl endpoint::Session for Session {
         self.next_incoming_id = begin.next_outgoing_id;
         self.remote_incoming_window = begin.incoming_window;
         self.remote_outgoing_window = begin.outgoing_window;
+        self.agreed_handle_max = min(self.handle_max.0, begin.handle_max.0);
 
         Ok(())
     }
@@ -616,8 +737,6 @@ impl endpoint::Session for Session {
         self.next_incoming_id = self.next_incoming_id.wrapping_add(1);
         self.remote_outgoing_window = self.remote_outgoing_window.saturating_sub(1);
 
-        // TODO: allow user to define whether the incoming window should be decremented
-
         let input_handle = InputHandle::from(transfer.handle.clone());
         match self.link_by_input_handle.get_mut(&input_handle) {
             Some(link_relay) => {
@@ -635,6 +754,42 @@ impl endpoint::Session for Session {
         Ok(None)
     }
 
+    fn consume_incoming_window(&mut self) -> Result<(), Self::Error> {
+        // A transfer that arrives while the incoming-window is zero exceeds the window that was
+        // advertised to the peer
+        self.incoming_window = self
+            .incoming_window
+            .checked_sub(1)
+            .ok_or(SessionInnerError::WindowViolation)?;
+        Ok(())
+    }
+
+    fn replenish_incoming_window(&mut self) -> Option<SessionFrame> {
+        let initial_incoming_window = *self.initial_incoming_window.value();
+        if self.incoming_window > initial_incoming_window / 2 {
+            return None;
+        }
+
+        // The peer only learns about the new incoming-window from a flow frame, so a session
+        // flow is sent even if none of the links has anything to update
+        self.incoming_window = initial_incoming_window;
+        let flow = Flow {
+            next_incoming_id: Some(self.next_incoming_id),
+            incoming_window: self.incoming_window,
+            next_outgoing_id: self.next_outgoing_id,
+            outgoing_window: self.outgoing_window,
+            handle: None,
+            delivery_count: None,
+            link_credit: None,
+            available: None,
+            drain: false,
+            echo: false,
+            properties: None,
+        };
+        let body = SessionFrameBody::Flow(flow);
+        Some(SessionFrame::new(self.outgoing_channel, body))
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     fn on_incoming_disposition(
         &mut self,
@@ -993,4 +1148,448 @@ mod tests {
         let count = num_messages_settled_by_disposition(first, last);
         assert_eq!(count, 1);
     }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn try_send_would_block_when_outgoing_channel_is_full() {
+        use tokio::sync::oneshot;
+
+        use crate::{
+            acceptor::LinkAcceptor,
+            link::{receiver::CreditMode, SendError},
+            test_util, Sender, Session,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+        let (credit_tx, credit_rx) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let mut link_acceptor = LinkAcceptor::new();
+            link_acceptor.local_receiver_acceptor.credit_mode = CreditMode::Manual;
+            let mut receiver = test_util::accept_receiver(&link_acceptor, &mut session).await;
+
+            credit_rx.await.unwrap();
+            receiver.set_credit(2).await.unwrap();
+            for _ in 0..2 {
+                let delivery = receiver.recv::<String>().await.unwrap();
+                receiver.accept(&delivery).await.unwrap();
+            }
+
+            // The client closes the link, session and connection
+            assert!(receiver.recv::<String>().await.is_err());
+            assert!(session.on_end().await.is_err());
+            assert!(connection.on_close().await.is_err());
+        });
+
+        let mut connection = test_util::open_connection(client).await;
+        let mut session = Session::builder()
+            .outgoing_buffer_size(1)
+            .begin(&mut connection)
+            .await
+            .unwrap();
+        assert_eq!(session.max_capacity(), 1);
+        assert_eq!(session.capacity(), 1);
+        let mut sender = Sender::attach(&mut session, "sender", "q1").await.unwrap();
+
+        // No link credit yet
+        assert!(matches!(
+            sender.try_send("first"),
+            Err(SendError::WouldBlock)
+        ));
+
+        credit_tx.send(()).unwrap();
+        sender
+            .send("first")
+            .await
+            .unwrap()
+            .accepted_or("Not accepted")
+            .unwrap();
+
+        // Hold the only slot of the outgoing channel
+        let permit = session.outgoing.reserve().await.unwrap();
+        assert_eq!(session.capacity(), 0);
+        assert!(matches!(
+            sender.try_send("second"),
+            Err(SendError::WouldBlock)
+        ));
+
+        drop(permit);
+        assert_eq!(session.capacity(), 1);
+        // The slot is reserved up front so this never waits on the outgoing channel
+        let second = sender.try_send("second").unwrap();
+        second.await.unwrap().accepted_or("Not accepted").unwrap();
+
+        // The link credit is used up
+        assert!(matches!(
+            sender.try_send("third"),
+            Err(SendError::WouldBlock)
+        ));
+
+        sender.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn dropping_session_with_end_on_drop_sends_end() {
+        use std::time::Duration;
+
+        use tokio::sync::oneshot;
+
+        use crate::{control::SessionControl, test_util, Session};
+
+        let (client, server) = tokio::io::duplex(4096);
+        let (ended_tx, ended_rx) = oneshot::channel();
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let ended = tokio::time::timeout(Duration::from_secs(1), session.on_end())
+                .await
+                .expect("Expecting an end before the timeout");
+            assert!(matches!(ended, Err(super::Error::RemoteEnded)));
+            ended_tx.send(()).unwrap();
+            let _ = connection.on_close().await;
+        });
+
+        let mut connection = test_util::open_connection(client).await;
+        let session = Session::builder()
+            .end_on_drop(Duration::from_millis(500))
+            .begin(&mut connection)
+            .await
+            .unwrap();
+
+        // The event loop cannot drain the control channel until the test yields, so the End can
+        // only be queued by waiting for capacity
+        while session
+            .control
+            .try_send(SessionControl::GetMaxFrameSize(oneshot::channel().0))
+            .is_ok()
+        {}
+        drop(session);
+
+        ended_rx.await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn begin_is_rejected_by_hook() {
+        use fe2o3_amqp_types::definitions::{self, AmqpError};
+
+        use crate::{
+            acceptor::SessionAcceptor,
+            session::{BeginError, Error},
+            test_util,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            let session_acceptor = SessionAcceptor::builder()
+                .on_incoming_begin(|begin| match begin.properties {
+                    Some(_) => Ok(()),
+                    None => Err(definitions::Error::new(
+                        AmqpError::NotAllowed,
+                        "session properties are required".to_string(),
+                        None,
+                    )),
+                })
+                .build();
+            let result = session_acceptor.accept(&mut connection).await;
+            assert!(matches!(result, Err(BeginError::Rejected(_))));
+
+            let _ = connection.on_close().await;
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        match session.on_end().await {
+            Err(Error::RemoteEndedWithError(error)) => {
+                assert_eq!(error.condition, AmqpError::NotAllowed.into());
+            }
+            other => panic!("Expecting the session to be ended, found {:?}", other),
+        }
+
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn attach_beyond_agreed_handle_max_is_rejected() {
+        use crate::{
+            acceptor::{LinkAcceptor, SessionAcceptor},
+            link::SenderAttachError,
+            test_util, Sender, Session,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            let mut session = SessionAcceptor::builder()
+                .handle_max(5)
+                .build()
+                .accept(&mut connection)
+                .await
+                .unwrap();
+            assert_eq!(session.handle_max(), 1);
+            let link_acceptor = LinkAcceptor::new();
+            let _first = link_acceptor.accept(&mut session).await.unwrap();
+            let _second = link_acceptor.accept(&mut session).await.unwrap();
+
+            // The client ends the session and closes the connection
+            assert!(session.on_end().await.is_err());
+            assert!(connection.on_close().await.is_err());
+        });
+
+        let mut connection = test_util::open_connection(client).await;
+        let mut session = Session::builder()
+            .handle_max(1)
+            .begin(&mut connection)
+            .await
+            .unwrap();
+        assert_eq!(session.handle_max(), 1);
+
+        let _first = Sender::attach(&mut session, "first", "q1").await.unwrap();
+        let _second = Sender::attach(&mut session, "second", "q1").await.unwrap();
+        let result = Sender::attach(&mut session, "third", "q1").await;
+        assert!(matches!(result, Err(SenderAttachError::HandleMaxReached)));
+
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn end_resolves_unsettled_deliveries_of_all_links() {
+        use tokio::sync::oneshot;
+
+        use crate::{
+            acceptor::LinkAcceptor,
+            link::{DetachError, SendError},
+            test_util, Sender,
+        };
+
+        let (client, server) = tokio::io::duplex(4096);
+        let (received_tx, received_rx) = oneshot::channel::<()>();
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let link_acceptor = LinkAcceptor::new();
+            let mut receivers = Vec::new();
+            for _ in 0..2 {
+                receivers.push(test_util::accept_receiver(&link_acceptor, &mut session).await);
+            }
+
+            // Receive without settling
+            for receiver in &mut receivers {
+                let _ = receiver.recv::<String>().await.unwrap();
+            }
+            received_tx.send(()).unwrap();
+
+            assert!(matches!(
+                session.on_end().await,
+                Err(super::Error::RemoteEnded)
+            ));
+            drop(receivers);
+            assert!(connection.on_close().await.is_err());
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let mut sender1 = Sender::attach(&mut session, "sender-1", "q1")
+            .await
+            .unwrap();
+        let mut sender2 = Sender::attach(&mut session, "sender-2", "q2")
+            .await
+            .unwrap();
+
+        let fut1 = sender1.send_batchable("first").await.unwrap();
+        let fut2 = sender2.send_batchable("second").await.unwrap();
+        received_rx.await.unwrap();
+
+        session.end().await.unwrap();
+        assert!(matches!(
+            fut1.await,
+            Err(SendError::Detached(DetachError::SessionEnded(None)))
+        ));
+        assert!(matches!(
+            fut2.await,
+            Err(SendError::Detached(DetachError::SessionEnded(None)))
+        ));
+
+        drop(sender1);
+        drop(sender2);
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    fn mapped_session(builder: super::Builder, remote_incoming_window: u32) -> super::Session {
+        use fe2o3_amqp_types::performatives::Begin;
+
+        use crate::endpoint::{IncomingChannel, OutgoingChannel, Session as _};
+
+        let mut session = builder.into_session(OutgoingChannel(0), super::SessionState::BeginSent);
+        let begin = Begin {
+            remote_channel: Some(0),
+            next_outgoing_id: 0,
+            incoming_window: remote_incoming_window,
+            outgoing_window: super::DEFAULT_WINDOW,
+            handle_max: Default::default(),
+            offered_capabilities: None,
+            desired_capabilities: None,
+            properties: None,
+        };
+        session
+            .on_incoming_begin(IncomingChannel(0), begin)
+            .unwrap();
+        session
+    }
+
+    #[tokio::test]
+    async fn transfers_beyond_remote_incoming_window_are_held_until_flow() {
+        use fe2o3_amqp_types::performatives::{Flow, Transfer};
+
+        use crate::{
+            endpoint::{InputHandle, Session as _},
+            session::frame::{SessionFrameBody, SessionOutgoingItem},
+            Payload,
+        };
+
+        let mut session = mapped_session(super::Builder::new(), 2);
+        let transfer = |tag: u8| Transfer {
+            handle: 0.into(),
+            delivery_id: None,
+            delivery_tag: Some(vec![tag].into()),
+            message_format: Some(0),
+            settled: Some(true),
+            more: false,
+            rcv_settle_mode: None,
+            state: None,
+            resume: false,
+            aborted: false,
+            batchable: false,
+        };
+
+        for tag in 0..2 {
+            let item = session
+                .on_outgoing_transfer(InputHandle(0), transfer(tag), Payload::new())
+                .unwrap();
+            assert!(matches!(item, Some(SessionOutgoingItem::SingleFrame(_))));
+        }
+
+        // The remote incoming-window is exhausted
+        for tag in 2..5 {
+            let item = session
+                .on_outgoing_transfer(InputHandle(0), transfer(tag), Payload::new())
+                .unwrap();
+            assert!(item.is_none());
+        }
+        assert_eq!(session.remote_incoming_window_exhausted_buffer.len(), 3);
+
+        // A session flow that only reopens the window by two releases two of the held transfers
+        let flow = Flow {
+            next_incoming_id: Some(2),
+            incoming_window: 2,
+            next_outgoing_id: 0,
+            outgoing_window: super::DEFAULT_WINDOW,
+            handle: None,
+            delivery_count: None,
+            link_credit: None,
+            available: None,
+            drain: false,
+            echo: false,
+            properties: None,
+        };
+        let frames = match session.on_incoming_flow(flow).await.unwrap() {
+            Some(SessionOutgoingItem::MultipleFrames(frames)) => frames,
+            _ => panic!("Expecting the held transfers"),
+        };
+        let delivery_ids: Vec<_> = frames
+            .into_iter()
+            .map(|frame| match frame.body {
+                SessionFrameBody::Transfer { performative, .. } => performative.delivery_id,
+                _ => panic!("Expecting a transfer"),
+            })
+            .collect();
+        assert_eq!(delivery_ids, [Some(2), Some(3)]);
+        assert_eq!(session.remote_incoming_window, 0);
+        assert_eq!(session.remote_incoming_window_exhausted_buffer.len(), 1);
+    }
+
+    #[test]
+    fn incoming_window_is_replenished_and_enforced() {
+        use crate::{
+            endpoint::Session as _,
+            session::{frame::SessionFrameBody, SessionInnerError},
+        };
+
+        let mut session = mapped_session(super::Builder::new().incoming_window(4), 0);
+
+        session.consume_incoming_window().unwrap();
+        assert!(session.replenish_incoming_window().is_none());
+        session.consume_incoming_window().unwrap();
+        let frame = session.replenish_incoming_window().unwrap();
+        match frame.body {
+            SessionFrameBody::Flow(flow) => {
+                assert!(flow.handle.is_none());
+                assert_eq!(flow.incoming_window, 4);
+            }
+            _ => panic!("Expecting a session flow"),
+        }
+
+        for _ in 0..4 {
+            session.consume_incoming_window().unwrap();
+        }
+        assert!(matches!(
+            session.consume_incoming_window(),
+            Err(SessionInnerError::WindowViolation)
+        ));
+    }
+
+    #[cfg(feature = "acceptor")]
+    #[tokio::test]
+    async fn small_incoming_window_does_not_stall_receiver() {
+        use crate::{acceptor::LinkAcceptor, test_util, Receiver, Session};
+
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let (mut connection, mut session) = test_util::accept_session(server).await;
+            let mut sender = test_util::accept_sender(&LinkAcceptor::new(), &mut session).await;
+
+            for i in 0..10 {
+                sender.send(format!("message {}", i)).await.unwrap();
+            }
+            sender.close().await.unwrap();
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+        });
+
+        let mut connection = test_util::open_connection(client).await;
+        let mut session = Session::builder()
+            .incoming_window(2)
+            .begin(&mut connection)
+            .await
+            .unwrap();
+        let mut receiver = Receiver::attach(&mut session, "receiver", "q1")
+            .await
+            .unwrap();
+
+        for i in 0..10 {
+            let delivery = receiver.recv::<String>().await.unwrap();
+            receiver.accept(&delivery).await.unwrap();
+            assert_eq!(delivery.body(), &format!("message {}", i));
+        }
+
+        receiver.close().await.unwrap();
+        session.close().await.unwrap();
+        connection.close().await.unwrap();
+        server.await.unwrap();
+    }
 }
@@ -0,0 +1,96 @@
+//! Helpers for the unit tests that talk to an acceptor over an in-memory stream
+
+use fe2o3_amqp_types::messaging::{Source, Target};
+use tokio::io::DuplexStream;
+
+use crate::{
+    acceptor::{
+        ConnectionAcceptor, LinkAcceptor, LinkEndpoint, ListenerConnectionHandle,
+        ListenerSessionHandle, SessionAcceptor,
+    },
+    connection::ConnectionHandle,
+    session::SessionHandle,
+    Connection, Receiver, Sender, Session,
+};
+
+/// Opens a connection with the container id `"client"`
+pub(crate) async fn open_connection(stream: DuplexStream) -> ConnectionHandle<()> {
+    Connection::builder()
+        .container_id("client")
+        .open_with_stream(stream)
+        .await
+        .unwrap()
+}
+
+/// Opens a connection with the container id `"client"` and begins a session on it
+pub(crate) async fn begin_session(
+    stream: DuplexStream,
+) -> (ConnectionHandle<()>, SessionHandle<()>) {
+    let mut connection = open_connection(stream).await;
+    let session = Session::begin(&mut connection).await.unwrap();
+    (connection, session)
+}
+
+/// Accepts a connection with the container id `"server"`
+pub(crate) async fn accept_connection(stream: DuplexStream) -> ListenerConnectionHandle {
+    ConnectionAcceptor::new("server")
+        .accept(stream)
+        .await
+        .unwrap()
+}
+
+/// Accepts a connection with the container id `"server"` and then a session on it
+pub(crate) async fn accept_session(
+    stream: DuplexStream,
+) -> (ListenerConnectionHandle, ListenerSessionHandle) {
+    let mut connection = accept_connection(stream).await;
+    let session = SessionAcceptor::new()
+        .accept(&mut connection)
+        .await
+        .unwrap();
+    (connection, session)
+}
+
+/// Accepts the next link on the session, which must be attached by a remote sender
+pub(crate) async fn accept_receiver<FS, FT>(
+    link_acceptor: &LinkAcceptor<FS, FT>,
+    session: &mut ListenerSessionHandle,
+) -> Receiver
+where
+    FS: Fn(Source) -> Option<Source>,
+    FT: Fn(Target) -> Option<Target>,
+{
+    match link_acceptor.accept(session).await.unwrap() {
+        LinkEndpoint::Receiver(receiver) => receiver,
+        LinkEndpoint::Sender(_) => panic!("Expecting a receiver"),
+    }
+}
+
+/// Accepts the next link on the session, which must be attached by a remote receiver
+pub(crate) async fn accept_sender<FS, FT>(
+    link_acceptor: &LinkAcceptor<FS, FT>,
+    session: &mut ListenerSessionHandle,
+) -> Sender
+where
+    FS: Fn(Source) -> Option<Source>,
+    FT: Fn(Target) -> Option<Target>,
+{
+    match link_acceptor.accept(session).await.unwrap() {
+        LinkEndpoint::Sender(sender) => sender,
+        LinkEndpoint::Receiver(_) => panic!("Expecting a sender"),
+    }
+}
+
+/// Accepts a connection, a session and then a single link attached by a remote sender
+pub(crate) async fn accept_single_link<FS, FT>(
+    stream: DuplexStream,
+    link_acceptor: &LinkAcceptor<FS, FT>,
+) -> (ListenerConnectionHandle, ListenerSessionHandle, Receiver)
+where
+    FS: Fn(Source) -> Option<Source>,
+    FT: Fn(Target) -> Option<Target>,
+{
+    let (connection, mut session) = accept_session(stream).await;
+    let receiver = accept_receiver(link_acceptor, &mut session).await;
+    (connection, session, receiver)
+}
@@ -59,7 +59,7 @@ where
     pub async fn cleanup(&mut self) -> Result<(), FlowError> {
         // clear txn-id
         {
-            let mut writer = self.recver.inner.link.flow_state.lock.write();
+            let mut writer = self.recver.inner.link.flow_state.as_ref().lock.write();
             writer.properties.as_mut().map(|map| map.remove(TXN_ID_KEY));
         }
 
@@ -151,7 +151,7 @@ where
         if !self.txn.is_discharged() {
             // clear txn-id from the link's properties
             {
-                let mut writer = self.recver.inner.link.flow_state.lock.write();
+                let mut writer = self.recver.inner.link.flow_state.as_ref().lock.write();
                 writer
                     .properties
                     .as_mut()
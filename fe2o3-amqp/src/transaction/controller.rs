@@ -10,6 +10,7 @@ use crate::{
     link::{
         self,
         builder::{WithSource, WithoutName, WithoutTarget},
+        delivery::DeliveryOutcome,
         role,
         sender::SenderInner,
         shared_inner::LinkEndpointInnerDetach,
@@ -48,7 +49,7 @@ pub struct Controller {
 async fn send_on_control_link<T>(
     sender: &mut SenderInner<ControlLink>,
     sendable: Sendable<T>,
-) -> Result<oneshot::Receiver<Option<DeliveryState>>, link::SendError>
+) -> Result<oneshot::Receiver<DeliveryOutcome>, link::SendError>
 where
     T: SerializableBody,
 {
@@ -135,6 +136,7 @@ impl Controller {
             .await?
             .await
             .map_err(|_| LinkStateError::IllegalSessionState)?
+            .map_err(LinkStateError::from)?
             .ok_or(ControllerSendError::NonTerminalDeliveryState)?
             .declared_or_else(|state| {
                 if let DeliveryState::Rejected(rejected) = state {
@@ -163,6 +165,7 @@ impl Controller {
             .await?
             .await
             .map_err(|_| LinkStateError::IllegalSessionState)?
+            .map_err(LinkStateError::from)?
             .ok_or(ControllerSendError::NonTerminalDeliveryState)?
             .accepted_or_else(|state| {
                 if let DeliveryState::Rejected(rejected) = state {
@@ -53,6 +53,8 @@ impl Default for ControlLinkAcceptor {
                 credit_mode: Default::default(),
                 target_capabilities: None,
                 auto_accept: false,
+                #[cfg(not(target_arch = "wasm32"))]
+                incomplete_transfer_timeout: None,
                 on_dynamic_target: unreachable_dynamic_coordinator,
                 target_marker: std::marker::PhantomData,
             },
@@ -169,12 +171,13 @@ impl TxnCoordinator {
                     tracing::error!(?error);
                     #[cfg(feature = "log")]
                     log::error!("error = {:?}", error);
-                    let error = definitions::Error::new(AmqpError::IllegalState, None, None);
+                    let error = definitions::Error::from(AmqpError::IllegalState);
                     // TODO: detach instead of closing
                     let _ = self.inner.close_with_error(Some(error)).await;
                     Running::Stop
                 }
-                crate::link::LinkStateError::IllegalSessionState => {
+                crate::link::LinkStateError::IllegalSessionState
+                | crate::link::LinkStateError::SessionEnded(_) => {
                     #[cfg(feature = "tracing")]
                     tracing::error!(?error);
                     #[cfg(feature = "log")]
@@ -212,23 +215,34 @@ impl TxnCoordinator {
                 tracing::error!(?error);
                 #[cfg(feature = "log")]
                 log::error!("error = {:?}", error);
-                let error = definitions::Error::new(LinkError::TransferLimitExceeded, None, None);
+                let error = definitions::Error::from(LinkError::TransferLimitExceeded);
                 // TODO: detach instead of closing
                 let _ = self.inner.close_with_error(Some(error)).await;
                 Running::Stop
             }
+            RecvError::MessageSizeExceeded => {
+                // The link is already detached with an error
+                #[cfg(feature = "tracing")]
+                tracing::error!(?error);
+                #[cfg(feature = "log")]
+                log::error!("error = {:?}", error);
+                Running::Stop
+            }
             RecvError::DeliveryIdIsNone
             | RecvError::DeliveryTagIsNone
             | RecvError::MessageDecodeError
             | RecvError::IllegalRcvSettleModeInTransfer
             | RecvError::InconsistentFieldInMultiFrameDelivery
-            | RecvError::TransactionalAcquisitionIsNotImeplemented => {
+            | RecvError::TransactionalAcquisitionIsNotImeplemented
+            | RecvError::IncompleteTransferTimeout
+            | RecvError::DeliveryAborted
+            | RecvError::Io(_) => {
                 #[cfg(feature = "tracing")]
                 tracing::error!(?error);
                 #[cfg(feature = "log")]
                 log::error!("error = {:?}", error);
-                let error =
-                    definitions::Error::new(AmqpError::NotAllowed, format!("{:?}", error), None);
+                let error = definitions::Error::from_condition(AmqpError::NotAllowed)
+                    .with_description(format!("{:?}", error));
                 // TODO: detach instead of closing
                 let _ = self.inner.close_with_error(Some(error)).await;
                 Running::Stop
@@ -278,8 +292,9 @@ impl TxnCoordinator {
         match disposition_result {
             Ok(_) => Running::Continue,
             Err(disposition_error) => match disposition_error {
-                IllegalLinkStateError::IllegalState => {
-                    let error = definitions::Error::new(AmqpError::IllegalState, None, None);
+                IllegalLinkStateError::IllegalState
+                | IllegalLinkStateError::UnsettledInFirstMode => {
+                    let error = definitions::Error::from(AmqpError::IllegalState);
                     // TODO: detach instead of closing
                     let _ = self.inner.close_with_error(Some(error)).await;
                     Running::Stop
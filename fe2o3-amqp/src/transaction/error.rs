@@ -4,8 +4,9 @@ use fe2o3_amqp_types::{
 };
 
 use crate::link::{
-    delivery::{FromDeliveryState, FromOneshotRecvError, FromPreSettled},
-    DetachError, IllegalLinkStateError, LinkStateError, SendError, SenderAttachError,
+    delivery::{FromDeliveryState, FromDetachError, FromOneshotRecvError, FromPreSettled},
+    DetachError, DuplicateDeliveryTag, IllegalLinkStateError, IllegalRcvSettleMode, LinkStateError,
+    MessageSizeExceeded, SendError, SenderAttachError,
 };
 
 /// Errors with allocation of new transacation ID
@@ -113,6 +114,23 @@ pub enum ControllerSendError {
     /// Error serializing message
     #[error("Error encoding message")]
     MessageEncodeError,
+
+    /// A non-blocking send could not be completed without waiting
+    #[error("Send would block")]
+    WouldBlock,
+
+    /// The serialized message exceeds the `max-message-size` of the link. Nothing is sent.
+    #[error("Message exceeds the max message size of the link")]
+    MessageSizeExceeded,
+
+    /// The delivery asked for `ReceiverSettleMode::Second` while the negotiated value is
+    /// `ReceiverSettleMode::First`. Nothing is sent.
+    #[error("Negotiated value is first. Setting mode to second is illegal")]
+    IllegalRcvSettleModeInTransfer,
+
+    /// The delivery tag is already used by an unsettled delivery of the sender. Nothing is sent.
+    #[error("The delivery tag is already used by an unsettled delivery")]
+    DuplicateDeliveryTag,
 }
 
 impl From<SendError> for ControllerSendError {
@@ -123,6 +141,12 @@ impl From<SendError> for ControllerSendError {
             SendError::NonTerminalDeliveryState => Self::NonTerminalDeliveryState,
             SendError::IllegalDeliveryState => Self::IllegalDeliveryState,
             SendError::MessageEncodeError => Self::MessageEncodeError,
+            SendError::WouldBlock => Self::WouldBlock,
+            SendError::MessageSizeExceeded => Self::MessageSizeExceeded,
+            SendError::IllegalRcvSettleModeInTransfer => Self::IllegalRcvSettleModeInTransfer,
+            SendError::DuplicateDeliveryTag => Self::DuplicateDeliveryTag,
+            // Control messages are never streamed
+            SendError::Io(_) => Self::MessageEncodeError,
         }
     }
 }
@@ -201,6 +225,19 @@ pub enum PostError {
     /// Error serializing message
     #[error("Error encoding message")]
     MessageEncodeError,
+
+    /// The serialized message exceeds the `max-message-size` of the link. Nothing is sent.
+    #[error("Message exceeds the max message size of the link")]
+    MessageSizeExceeded,
+
+    /// The delivery asked for `ReceiverSettleMode::Second` while the negotiated value is
+    /// `ReceiverSettleMode::First`. Nothing is sent.
+    #[error("Negotiated value is first. Setting mode to second is illegal")]
+    IllegalRcvSettleModeInTransfer,
+
+    /// The delivery tag is already used by an unsettled delivery of the sender. Nothing is sent.
+    #[error("The delivery tag is already used by an unsettled delivery")]
+    DuplicateDeliveryTag,
 }
 
 impl From<serde_amqp::Error> for PostError {
@@ -215,10 +252,30 @@ impl From<DetachError> for PostError {
     }
 }
 
+impl From<MessageSizeExceeded> for PostError {
+    fn from(_: MessageSizeExceeded) -> Self {
+        Self::MessageSizeExceeded
+    }
+}
+
+impl From<IllegalRcvSettleMode> for PostError {
+    fn from(_: IllegalRcvSettleMode) -> Self {
+        Self::IllegalRcvSettleModeInTransfer
+    }
+}
+
+impl From<DuplicateDeliveryTag> for PostError {
+    fn from(_: DuplicateDeliveryTag) -> Self {
+        Self::DuplicateDeliveryTag
+    }
+}
+
 impl From<IllegalLinkStateError> for PostError {
     fn from(value: IllegalLinkStateError) -> Self {
         match value {
-            IllegalLinkStateError::IllegalState => LinkStateError::IllegalState.into(),
+            IllegalLinkStateError::IllegalState | IllegalLinkStateError::UnsettledInFirstMode => {
+                LinkStateError::IllegalState.into()
+            }
             IllegalLinkStateError::IllegalSessionState => {
                 LinkStateError::IllegalSessionState.into()
             }
@@ -265,3 +322,9 @@ impl FromOneshotRecvError for PostResult {
         ))
     }
 }
+
+impl FromDetachError for PostResult {
+    fn from_detach_error(err: DetachError) -> Self {
+        Err(PostError::Detached(err))
+    }
+}
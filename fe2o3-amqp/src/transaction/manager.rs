@@ -8,9 +8,10 @@ use fe2o3_amqp_types::{
     messaging::{Accepted, DeliveryState, Outcome},
     performatives::{Attach, Disposition, Transfer},
     primitives::OrderedMap,
-    transaction::{TransactionId, TransactionalState},
+    transaction::{TransactionError, TransactionId, TransactionalState},
 };
 use tokio::sync::mpsc;
+use uuid::Uuid;
 
 use crate::{link::LinkFrame, Payload};
 
@@ -42,6 +43,38 @@ impl TransactionManager {
             control_link_acceptor: Arc::new(control_link_acceptor),
         }
     }
+
+    /// Allocates a new transaction ID that is not used by any pending transaction and registers
+    /// an empty transaction under it
+    pub(crate) fn allocate_transaction_id(&mut self) -> TransactionId {
+        let mut txn_id = TransactionId::from(Uuid::new_v4().into_bytes());
+        while self.txns.contains_key(&txn_id) {
+            txn_id = TransactionId::from(Uuid::new_v4().into_bytes());
+        }
+
+        let _ = self.txns.insert(txn_id.clone(), ResourceTransaction::new());
+        txn_id
+    }
+
+    /// Removes a pending transaction and returns the work that should be applied
+    ///
+    /// If `fail` is true, the transaction is rolled back and the work is discarded, so the
+    /// returned `Vec` is empty. An error is returned if the transaction ID is unknown.
+    pub(crate) fn discharge(
+        &mut self,
+        txn_id: &TransactionId,
+        fail: bool,
+    ) -> Result<Vec<TxnWorkFrame>, TransactionError> {
+        let txn = self
+            .txns
+            .remove(txn_id)
+            .ok_or(TransactionError::UnknownId)?;
+        if fail {
+            Ok(Vec::new())
+        } else {
+            Ok(txn.frames)
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -97,9 +130,17 @@ impl ResourceTransaction {
 
 #[cfg(test)]
 mod tests {
-    use fe2o3_amqp_types::transaction::TransactionId;
+    use fe2o3_amqp_types::{
+        performatives::Transfer,
+        transaction::{TransactionError, TransactionId},
+    };
+    use tokio::sync::mpsc;
     use uuid::Uuid;
 
+    use crate::transaction::coordinator::ControlLinkAcceptor;
+
+    use super::TransactionManager;
+
     #[test]
     fn test_recover_key_from_txn_id() {
         let uuid = Uuid::new_v4();
@@ -107,4 +148,56 @@ mod tests {
         let uuid2 = Uuid::from_slice(txn_id.as_ref()).unwrap();
         assert_eq!(uuid, uuid2);
     }
+
+    #[test]
+    fn declares_allocate_distinct_pending_txn_ids() {
+        let (outgoing, _outgoing_rx) = mpsc::channel(1);
+        let mut manager = TransactionManager::new(outgoing, ControlLinkAcceptor::default());
+
+        let first = manager.allocate_transaction_id();
+        let second = manager.allocate_transaction_id();
+
+        assert_ne!(first, second);
+        assert_eq!(manager.txns.len(), 2);
+        assert!(manager.txns.contains_key(&first));
+        assert!(manager.txns.contains_key(&second));
+    }
+
+    #[test]
+    fn discharge_removes_pending_txn() {
+        let (outgoing, _outgoing_rx) = mpsc::channel(1);
+        let mut manager = TransactionManager::new(outgoing, ControlLinkAcceptor::default());
+
+        let committed = manager.allocate_transaction_id();
+        let rolled_back = manager.allocate_transaction_id();
+        for txn_id in [&committed, &rolled_back] {
+            let transfer = Transfer {
+                handle: 0.into(),
+                delivery_id: Some(0),
+                delivery_tag: Some(vec![0].into()),
+                message_format: Some(0),
+                settled: Some(true),
+                more: false,
+                rcv_settle_mode: None,
+                state: None,
+                resume: false,
+                aborted: false,
+                batchable: false,
+            };
+            let _ = manager.txns.get_mut(txn_id).unwrap().on_incoming_post(
+                txn_id.clone(),
+                transfer,
+                Default::default(),
+            );
+        }
+
+        let work = manager.discharge(&committed, false).unwrap();
+        assert_eq!(work.len(), 1);
+        let work = manager.discharge(&rolled_back, true).unwrap();
+        assert!(work.is_empty());
+        assert!(manager.txns.is_empty());
+
+        let err = manager.discharge(&committed, false).unwrap_err();
+        assert!(matches!(err, TransactionError::UnknownId));
+    }
 }
@@ -193,6 +193,13 @@ pub trait TransactionExt: TransactionDischarge + TransactionalRetirement {
 /// control link for declaring and discharging of multiple transactions. [`OwnedTransaction`]
 /// is an alternative that holds the ownership of a control link.
 ///
+/// The `txn-id` is scoped to the controller rather than to any particular link, so the same
+/// [`Transaction`] can post to or retire from any number of senders and receivers on the session.
+/// The borrow of the [`Controller`] lasts until the transaction is committed, rolled back, or
+/// dropped (which rolls it back). Discharging a transaction never closes the control link, so
+/// other transactions declared on the same [`Controller`] are unaffected, and the controller can
+/// only be closed once all of its transactions are gone.
+///
 /// # Examples
 ///
 /// Please note that only transactional posting has been tested.
@@ -220,6 +227,23 @@ pub trait TransactionExt: TransactionDischarge + TransactionalRetirement {
 /// sender.close().await.unwrap();
 /// ```
 ///
+/// ## Posting via multiple senders
+///
+/// ```rust
+/// let controller = Controller::attach(&mut session, "controller").await.unwrap();
+/// let mut sender1 = Sender::attach(&mut session, "rust-sender-link-1", "q1")
+///     .await
+///     .unwrap();
+/// let mut sender2 = Sender::attach(&mut session, "rust-sender-link-2", "q2")
+///     .await
+///     .unwrap();
+///
+/// let txn = Transaction::declare(&controller, None).await.unwrap();
+/// txn.post(&mut sender1, "hello").await.unwrap();
+/// txn.post(&mut sender2, "world").await.unwrap();
+/// txn.commit().await.unwrap();
+/// ```
+///
 /// ## Transactional retirement
 ///
 /// ```rust
@@ -432,7 +456,7 @@ impl<'t> Transaction<'t> {
     ) -> Result<TxnAcquisition<'r, Transaction<'t>>, FlowError> {
         let value = Value::Binary(self.declared.txn_id.clone());
         {
-            let mut writer = recver.inner.link.flow_state.lock.write();
+            let mut writer = recver.inner.link.flow_state.as_ref().lock.write();
             match &mut writer.properties {
                 Some(fields) => {
                     if fields.contains_key(TXN_ID_KEY) {
@@ -456,7 +480,7 @@ impl<'t> Transaction<'t> {
         {
             Ok(_) => Ok(TxnAcquisition { txn: self, recver }),
             Err(error) => {
-                let mut writer = recver.inner.link.flow_state.lock.write();
+                let mut writer = recver.inner.link.flow_state.as_ref().lock.write();
                 if let Some(fields) = &mut writer.properties {
                     fields.remove(TXN_ID_KEY);
                 }
@@ -572,7 +596,7 @@ impl<'t> Drop for Transaction<'t> {
                             .insert(delivery_tag, unsettled);
                     }
                     match rx.blocking_recv() {
-                        Ok(Some(state)) => match state {
+                        Ok(Ok(Some(state))) => match state {
                             DeliveryState::Accepted(_) => {}
                             _ => {
                                 #[cfg(feature = "tracing")]
@@ -581,12 +605,18 @@ impl<'t> Drop for Transaction<'t> {
                                 log::error!("error = {:?}", state);
                             }
                         },
-                        Ok(None) => {
+                        Ok(Ok(None)) => {
                             #[cfg(feature = "tracing")]
                             tracing::error!(error = ?ControllerSendError::IllegalDeliveryState);
                             #[cfg(feature = "log")]
                             log::error!("error = {:?}", ControllerSendError::IllegalDeliveryState);
                         }
+                        Ok(Err(_error)) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!(error = ?_error);
+                            #[cfg(feature = "log")]
+                            log::error!("error = {:?}", _error);
+                        }
                         Err(_error) => {
                             #[cfg(feature = "tracing")]
                             tracing::error!(error = ?_error);
@@ -605,3 +635,80 @@ impl<'t> Drop for Transaction<'t> {
         }
     }
 }
+
+#[cfg(all(test, feature = "acceptor"))]
+mod tests {
+    use crate::{
+        acceptor::{LinkAcceptor, SessionAcceptor},
+        test_util,
+        transaction::{
+            coordinator::ControlLinkAcceptor, Controller, Transaction, TransactionDischarge,
+        },
+        Sender,
+    };
+
+    #[tokio::test]
+    async fn one_transaction_posts_via_two_senders() {
+        let (client, server) = tokio::io::duplex(4096);
+
+        let server = tokio::spawn(async move {
+            let mut connection = test_util::accept_connection(server).await;
+            let mut session = SessionAcceptor::builder()
+                .control_link_acceptor(ControlLinkAcceptor::default())
+                .build()
+                .accept(&mut connection)
+                .await
+                .unwrap();
+            let link_acceptor = LinkAcceptor::new();
+            let mut receivers = Vec::new();
+            for _ in 0..2 {
+                receivers.push(test_util::accept_receiver(&link_acceptor, &mut session).await);
+            }
+
+            let mut bodies = Vec::new();
+            for receiver in &mut receivers {
+                let delivery = receiver.recv::<String>().await.unwrap();
+                receiver.accept(&delivery).await.unwrap();
+                bodies.push(delivery.into_body());
+            }
+
+            // The work posted in the rolled back transaction is never delivered
+            for receiver in &mut receivers {
+                assert!(receiver.recv::<String>().await.is_err());
+            }
+            let _ = session.on_end().await;
+            let _ = connection.on_close().await;
+            bodies
+        });
+
+        let (mut connection, mut session) = test_util::begin_session(client).await;
+        let controller = Controller::attach(&mut session, "controller")
+            .await
+            .unwrap();
+        let mut sender1 = Sender::attach(&mut session, "sender-1", "q1")
+            .await
+            .unwrap();
+        let mut sender2 = Sender::attach(&mut session, "sender-2", "q2")
+            .await
+            .unwrap();
+
+        let txn = Transaction::declare(&controller, None).await.unwrap();
+        txn.post(&mut sender1, "hello").await.unwrap();
+        txn.post(&mut sender2, "world").await.unwrap();
+        txn.commit().await.unwrap();
+
+        // Discharging a transaction leaves the shared controller usable
+        let txn = Transaction::declare(&controller, None).await.unwrap();
+        txn.post(&mut sender1, "foo").await.unwrap();
+        txn.rollback().await.unwrap();
+
+        sender1.close().await.unwrap();
+        sender2.close().await.unwrap();
+        controller.close().await.unwrap();
+        session.end().await.unwrap();
+        connection.close().await.unwrap();
+
+        let bodies = server.await.unwrap();
+        assert_eq!(bodies, vec![String::from("hello"), String::from("world")]);
+    }
+}
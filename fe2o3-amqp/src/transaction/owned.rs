@@ -282,7 +282,7 @@ impl OwnedTransaction {
         credit: SequenceNo,
     ) -> Result<TxnAcquisition<'_, OwnedTransaction>, FlowError> {
         {
-            let mut writer = recver.inner.link.flow_state.lock.write();
+            let mut writer = recver.inner.link.flow_state.as_ref().lock.write();
             let value = Value::Binary(self.declared.txn_id.clone());
             match &mut writer.properties {
                 Some(fields) => {
@@ -307,7 +307,7 @@ impl OwnedTransaction {
         {
             Ok(_) => Ok(TxnAcquisition { txn: self, recver }),
             Err(error) => {
-                let mut writer = recver.inner.link.flow_state.lock.write();
+                let mut writer = recver.inner.link.flow_state.as_ref().lock.write();
                 if let Some(fields) = &mut writer.properties {
                     fields.remove(TXN_ID_KEY);
                 }
@@ -8,7 +8,6 @@ use fe2o3_amqp_types::{
     transaction::{TransactionError, TransactionId},
 };
 use tokio::sync::{mpsc, oneshot};
-use uuid::Uuid;
 
 use crate::{
     control::SessionControl,
@@ -23,7 +22,7 @@ use crate::{
 
 use super::{
     frame::TxnWorkFrame,
-    manager::{HandleControlLink, ResourceTransaction, TransactionManager},
+    manager::{HandleControlLink, TransactionManager},
     AllocTxnIdError, DischargeError,
 };
 
@@ -123,16 +122,7 @@ where
     S: endpoint::Session<Error = session::SessionInnerError> + endpoint::SessionExt + Send + Sync,
 {
     fn allocate_transaction_id(&mut self) -> Result<TransactionId, AllocTxnIdError> {
-        let mut txn_id = TransactionId::from(Uuid::new_v4().into_bytes());
-        while self.txn_manager.txns.contains_key(&txn_id) {
-            txn_id = TransactionId::from(Uuid::new_v4().into_bytes());
-        }
-
-        let _ = self
-            .txn_manager
-            .txns
-            .insert(txn_id.clone(), ResourceTransaction::new());
-        Ok(txn_id)
+        Ok(self.txn_manager.allocate_transaction_id())
     }
 }
 
@@ -146,12 +136,12 @@ where
         &mut self,
         txn_id: TransactionId,
     ) -> Result<Result<Accepted, TransactionError>, Self::Error> {
-        let txn = match self.txn_manager.txns.remove(&txn_id) {
-            Some(txn) => txn,
-            None => return Ok(Err(TransactionError::UnknownId)),
+        let work_frames = match self.txn_manager.discharge(&txn_id, false) {
+            Ok(work_frames) => work_frames,
+            Err(error) => return Ok(Err(error)),
         };
 
-        for work_frame in txn.frames {
+        for work_frame in work_frames {
             match work_frame {
                 TxnWorkFrame::Post {
                     mut transfer,
@@ -200,13 +190,11 @@ where
         &mut self,
         txn_id: TransactionId,
     ) -> Result<Result<Accepted, TransactionError>, Self::Error> {
-        match self.txn_manager.txns.remove(&txn_id) {
-            Some(_) => {
-                // TODO: Simply drop the frames?
-                Ok(Ok(Accepted {}))
-            }
-            None => Ok(Err(TransactionError::UnknownId)),
-        }
+        // The posted transfers and retired deliveries are simply dropped
+        Ok(self
+            .txn_manager
+            .discharge(&txn_id, true)
+            .map(|_| Accepted {}))
     }
 }
 
@@ -231,6 +219,10 @@ where
         self.session.outgoing_channel()
     }
 
+    fn agreed_handle_max(&self) -> u32 {
+        self.session.agreed_handle_max()
+    }
+
     // Allocate new local handle for new Link
     fn allocate_link(
         &mut self,
@@ -254,6 +246,10 @@ where
         self.session.deallocate_link(output_handle)
     }
 
+    fn detach_all_links(&mut self, error: Option<definitions::Error>) {
+        self.session.detach_all_links(error)
+    }
+
     fn on_incoming_begin(
         &mut self,
         channel: IncomingChannel,
@@ -308,6 +304,14 @@ where
         Ok(txn.on_incoming_post(txn_id, transfer, payload))
     }
 
+    fn consume_incoming_window(&mut self) -> Result<(), Self::Error> {
+        self.session.consume_incoming_window()
+    }
+
+    fn replenish_incoming_window(&mut self) -> Option<SessionFrame> {
+        self.session.replenish_incoming_window()
+    }
+
     fn on_incoming_disposition(
         &mut self,
         disposition: Disposition,
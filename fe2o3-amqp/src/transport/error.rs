@@ -1,7 +1,10 @@
 use std::io;
 
 use bytes::Bytes;
-use fe2o3_amqp_types::{primitives::Binary, sasl::SaslCode};
+use fe2o3_amqp_types::{
+    primitives::{Binary, Symbol},
+    sasl::SaslCode,
+};
 
 use crate::{frames, sasl_profile};
 
@@ -68,6 +71,12 @@ pub enum NegotiationError {
     #[error("Not implemented")]
     NotImplemented(Option<String>),
 
+    #[error("None of {preferred:?} is offered by the server, which offers {offered:?}")]
+    NoMatchingMechanism {
+        offered: Vec<Symbol>,
+        preferred: Vec<Symbol>,
+    },
+
     #[error("Illegal state")]
     IllegalState,
 
@@ -99,6 +108,9 @@ impl From<sasl_profile::Error> for NegotiationError {
     fn from(err: sasl_profile::Error) -> Self {
         match err {
             sasl_profile::Error::NotImplemented(msg) => Self::NotImplemented(msg),
+            sasl_profile::Error::NoMatchingMechanism { offered, preferred } => {
+                Self::NoMatchingMechanism { offered, preferred }
+            }
 
             #[cfg(feature = "scram")]
             sasl_profile::Error::ScramError(scram_error) => Self::ScramError(scram_error),
@@ -14,7 +14,7 @@ use fe2o3_amqp_types::{
     states::ConnectionState,
 };
 
-use std::{io, marker::PhantomData, task::Poll, time::Duration};
+use std::{io, marker::PhantomData, sync::Arc, task::Poll, time::Duration};
 
 use bytes::BytesMut;
 use futures_util::{Future, Sink, SinkExt, Stream, StreamExt};
@@ -23,6 +23,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
 use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite, LengthDelimitedCodec};
 
 use crate::{
+    connection::{ConnectionMetrics, FrameDirection, FrameEvent, FrameHook},
     frames::{amqp, sasl},
     util::IdleTimeout,
 };
@@ -47,6 +48,11 @@ pin_project! {
 
         #[pin]
         idle_timeout: Option<IdleTimeout>,
+
+        frame_hook: Option<FrameHook>,
+
+        metrics: Option<Arc<dyn ConnectionMetrics>>,
+
         // frame type
         ftype: PhantomData<Ftype>,
     }
@@ -97,6 +103,8 @@ where
             framed_write,
             framed_read,
             idle_timeout,
+            frame_hook: None,
+            metrics: None,
             ftype: PhantomData,
         }
     }
@@ -156,6 +164,26 @@ where
             NegotiationError::Io(io::Error::new(io::ErrorKind::Other, format!("{:?}", e)))
         })
     }
+
+    /// DER encoded certificate of the TLS server
+    #[cfg(feature = "rustls")]
+    pub(crate) fn rustls_server_certificate(
+        stream: &tokio_rustls::client::TlsStream<Io>,
+    ) -> Option<Vec<u8>> {
+        let (_, connection) = stream.get_ref();
+        connection
+            .peer_certificates()?
+            .first()
+            .map(|certificate| certificate.0.clone())
+    }
+
+    /// DER encoded certificate of the TLS server
+    #[cfg(all(feature = "native-tls", not(target_arch = "wasm32")))]
+    pub(crate) fn native_tls_server_certificate(
+        stream: &tokio_native_tls::TlsStream<Io>,
+    ) -> Option<Vec<u8>> {
+        stream.get_ref().peer_certificate().ok()??.to_der().ok()
+    }
 }
 
 impl<Io> Transport<Io, sasl::Frame>
@@ -271,6 +299,49 @@ where
         self.idle_timeout = idle_timeout;
         self
     }
+
+    /// Set a hook that is called for every AMQP frame sent or received on the transport
+    pub fn set_frame_hook(&mut self, hook: Option<FrameHook>) -> &mut Self {
+        self.frame_hook = hook;
+        self
+    }
+
+    /// Set the metrics that count the AMQP frames and bytes sent or received on the transport
+    pub fn set_metrics(&mut self, metrics: Option<Arc<dyn ConnectionMetrics>>) -> &mut Self {
+        self.metrics = metrics;
+        self
+    }
+}
+
+/// Passes a frame sent or received on the transport to the frame hook and the metrics
+fn observe_frame(
+    frame_hook: &Option<FrameHook>,
+    metrics: &Option<Arc<dyn ConnectionMetrics>>,
+    event: FrameEvent<'_>,
+) {
+    if let Some(hook) = frame_hook {
+        hook.call(&event);
+    }
+    if let Some(metrics) = metrics {
+        match event.direction() {
+            FrameDirection::Incoming => metrics.on_frame_received(event.kind()),
+            FrameDirection::Outgoing => metrics.on_frame_sent(event.kind()),
+        }
+    }
+}
+
+fn report_error(metrics: &Option<Arc<dyn ConnectionMetrics>>, error: Error) -> Error {
+    if let Some(metrics) = metrics {
+        metrics.on_error(&error);
+    }
+    error
+}
+
+fn report_bytes_sent(metrics: &Option<Arc<dyn ConnectionMetrics>>, len: usize) {
+    if let Some(metrics) = metrics {
+        // The length field is written by the length delimited codec
+        metrics.on_bytes_sent(len + 4);
+    }
 }
 
 /// Creates a LengthDelimitedCodec that can handle the AMQP and SASL frames
@@ -437,21 +508,38 @@ where
     ) -> Result<(), Self::Error> {
         use std::pin::Pin;
 
+        observe_frame(
+            &self.frame_hook,
+            &self.metrics,
+            FrameEvent::new(FrameDirection::Outgoing, item.channel, &item.body),
+        );
+
         let mut bytesmut = BytesMut::new();
         let max_frame_size = self.framed_write.encoder().max_frame_length();
         let mut encoder = amqp::FrameEncoder::new(max_frame_size);
-        encoder.encode(item, &mut bytesmut)?;
+        if let Err(err) = encoder.encode(item, &mut bytesmut) {
+            return Err(report_error(&self.metrics, err.into()));
+        }
 
         while bytesmut.len() > max_frame_size {
             let partial = bytesmut.split_to(max_frame_size);
+            let len = partial.len();
             let writer = Pin::new(&mut self.framed_write);
-            writer.start_send(partial.freeze())?;
+            if let Err(err) = writer.start_send(partial.freeze()) {
+                return Err(report_error(&self.metrics, err.into()));
+            }
+            report_bytes_sent(&self.metrics, len);
         }
 
+        let len = bytesmut.len();
         let writer = Pin::new(&mut self.framed_write);
-        writer
-            .start_send(bytesmut.freeze()) // Result<_, std::io::Error>
-            .map_err(Into::into)
+        match writer.start_send(bytesmut.freeze()) {
+            Ok(()) => {
+                report_bytes_sent(&self.metrics, len);
+                Ok(())
+            }
+            Err(err) => Err(report_error(&self.metrics, err.into())),
+        }
     }
 
     fn poll_flush(
@@ -498,11 +586,36 @@ where
                     Some(item) => {
                         let mut src = match item {
                             Ok(b) => b,
-                            Err(err) => return Poll::Ready(Some(Err(err.into()))),
+                            Err(err) => {
+                                return Poll::Ready(Some(Err(report_error(
+                                    this.metrics,
+                                    err.into(),
+                                ))))
+                            }
                         };
                         // tracing::debug!("raw bytes {:#x?}", &src[..]);
+                        if let Some(metrics) = this.metrics.as_ref() {
+                            // The length field is not part of the decoded bytes
+                            metrics.on_bytes_received(src.len() + 4);
+                        }
                         let mut decoder = amqp::FrameDecoder {};
-                        Poll::Ready(decoder.decode(&mut src).map_err(Into::into).transpose())
+                        let frame = decoder.decode(&mut src);
+                        if let Ok(Some(frame)) = &frame {
+                            observe_frame(
+                                this.frame_hook,
+                                this.metrics,
+                                FrameEvent::new(
+                                    FrameDirection::Incoming,
+                                    frame.channel,
+                                    &frame.body,
+                                ),
+                            );
+                        }
+                        Poll::Ready(
+                            frame
+                                .map_err(|err| report_error(this.metrics, err.into()))
+                                .transpose(),
+                        )
                     }
                     None => Poll::Ready(None),
                 }
@@ -511,10 +624,13 @@ where
                 // check if idle timeout has exceeded
                 if let Some(delay) = this.idle_timeout.as_pin_mut() {
                     match delay.poll(cx) {
-                        Poll::Ready(result) => match result {
-                            Ok(_) => return Poll::Ready(Some(Err(Error::IdleTimeoutElapsed))),
-                            Err(err) => return Poll::Ready(Some(Err(err.into()))),
-                        },
+                        Poll::Ready(result) => {
+                            let err = match result {
+                                Ok(_) => Error::IdleTimeoutElapsed,
+                                Err(err) => err.into(),
+                            };
+                            return Poll::Ready(Some(Err(report_error(this.metrics, err))));
+                        }
                         Poll::Pending => return Poll::Pending,
                     }
                 }
@@ -605,7 +721,10 @@ where
 #[cfg(test)]
 mod tests {
     use bytes::{Bytes, BytesMut};
-    use fe2o3_amqp_types::{performatives::Open, states::ConnectionState};
+    use fe2o3_amqp_types::{
+        performatives::{Open, Transfer},
+        states::ConnectionState,
+    };
     use futures_util::{SinkExt, StreamExt};
     use tokio_test::io::Builder;
     use tokio_util::codec::{Encoder, FramedRead, FramedWrite, LengthDelimitedCodec};
@@ -737,4 +856,68 @@ mod tests {
 
         transport.send(frame).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn transfer_larger_than_max_frame_size_is_fragmented() {
+        const MAX_FRAME_SIZE: usize = 512;
+
+        let (client, server) = tokio::io::duplex(16 * MAX_FRAME_SIZE);
+        let mut sender: Transport<_, Frame> = Transport::bind(client, MAX_FRAME_SIZE, None);
+        let mut receiver: Transport<_, Frame> = Transport::bind(server, MAX_FRAME_SIZE, None);
+
+        let payload: Bytes = (0..3 * MAX_FRAME_SIZE).map(|i| i as u8).collect();
+        let transfer = Transfer {
+            handle: 0.into(),
+            delivery_id: Some(7),
+            delivery_tag: Some(vec![1, 2, 3, 4].into()),
+            message_format: Some(0),
+            settled: Some(false),
+            more: false,
+            rcv_settle_mode: None,
+            state: None,
+            resume: false,
+            aborted: false,
+            batchable: false,
+        };
+        let frame = Frame::new(
+            0u16,
+            FrameBody::Transfer {
+                performative: transfer,
+                payload: payload.clone(),
+            },
+        );
+        sender.send(frame).await.unwrap();
+        drop(sender);
+
+        let mut transfers = Vec::new();
+        while let Some(frame) = receiver.next().await {
+            match frame.unwrap().body {
+                FrameBody::Transfer {
+                    performative,
+                    payload,
+                } => transfers.push((performative, payload)),
+                body => panic!("Expecting transfer, found {:?}", body),
+            }
+        }
+
+        // Each frame carries less than `MAX_FRAME_SIZE` bytes of payload
+        assert_eq!(transfers.len(), 4);
+        let (last, continuations) = transfers.split_last().unwrap();
+        assert!(continuations.iter().all(|(transfer, _)| transfer.more));
+        assert!(!last.0.more);
+
+        let (first, _) = &transfers[0];
+        assert_eq!(first.delivery_id, Some(7));
+        assert_eq!(first.delivery_tag, Some(vec![1, 2, 3, 4].into()));
+        for (transfer, _) in &transfers[1..] {
+            assert!(transfer.delivery_id.is_none());
+            assert!(transfer.delivery_tag.is_none());
+        }
+
+        let received: Vec<u8> = transfers
+            .iter()
+            .flat_map(|(_, payload)| payload.iter().copied())
+            .collect();
+        assert_eq!(received, payload);
+    }
 }
@@ -1,66 +1,335 @@
 //! Deserializer implementation
 
 use serde::{
-    de::{self},
+    de::{self, IntoDeserializer},
     Deserialize,
 };
-use std::convert::TryInto;
+use std::{collections::HashSet, convert::TryInto};
 
 use crate::{
     __constants::{
         ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIBED_LIST, DESCRIBED_MAP,
-        DESCRIPTOR, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID, VALUE,
+        DESCRIPTOR, RAW, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID, VALUE,
     },
-    descriptor::PeekDescriptor,
+    descriptor::{Descriptor, PeekDescriptor},
     error::Error,
     fixed_width::{DECIMAL128_WIDTH, DECIMAL32_WIDTH, DECIMAL64_WIDTH, UUID_WIDTH},
     format::{
         OFFSET_ARRAY32, OFFSET_ARRAY8, OFFSET_LIST32, OFFSET_LIST8, OFFSET_MAP32, OFFSET_MAP8,
     },
     format_code::EncodingCodes,
+    primitives::symbol::with_interning,
     read::{IoReader, Read, SliceReader},
     util::{EnumType, NewType, PeekTypeCode, StructEncoding},
+    Value,
 };
 
 /// Deserialize an instance of type T from an IO stream
 pub fn from_reader<T: de::DeserializeOwned>(reader: impl std::io::Read) -> Result<T, Error> {
+    from_reader_with_config(reader, DeserializerConfig::default())
+}
+
+/// Deserialize an instance of type T from an IO stream with the given [`DeserializerConfig`]
+pub fn from_reader_with_config<T: de::DeserializeOwned>(
+    reader: impl std::io::Read,
+    config: DeserializerConfig,
+) -> Result<T, Error> {
     let reader = IoReader::new(reader);
-    let mut de = Deserializer::new(reader);
+    let mut de = Deserializer::with_config(reader, config);
     T::deserialize(&mut de)
 }
 
 /// Deserialize and instance of type T from a bytes slice
 pub fn from_slice<'de, T: de::Deserialize<'de>>(slice: &'de [u8]) -> Result<T, Error> {
+    from_slice_with_config(slice, DeserializerConfig::default())
+}
+
+/// Deserialize and instance of type T from a bytes slice with the given [`DeserializerConfig`]
+pub fn from_slice_with_config<'de, T: de::Deserialize<'de>>(
+    slice: &'de [u8],
+    config: DeserializerConfig,
+) -> Result<T, Error> {
     let reader = SliceReader::new(slice);
-    let mut de = Deserializer::new(reader);
-    T::deserialize(&mut de)
+    let mut de = Deserializer::with_config(reader, config);
+    T::deserialize(&mut de).map_err(|err| de.error_at_offset(err))
+}
+
+/// Deserialize an instance of type T from the beginning of a bytes slice and return it along with
+/// the number of bytes consumed
+///
+/// Unlike [`from_slice`], trailing bytes after the value are left untouched, which allows decoding
+/// successive values from a buffer holding several concatenated values.
+pub fn from_slice_prefix<'de, T: de::Deserialize<'de>>(
+    slice: &'de [u8],
+) -> Result<(T, usize), Error> {
+    from_slice_prefix_with_config(slice, DeserializerConfig::default())
+}
+
+/// Deserialize an instance of type T from the beginning of a bytes slice with the given
+/// [`DeserializerConfig`] and return it along with the number of bytes consumed
+pub fn from_slice_prefix_with_config<'de, T: de::Deserialize<'de>>(
+    slice: &'de [u8],
+    config: DeserializerConfig,
+) -> Result<(T, usize), Error> {
+    let reader = SliceReader::new(slice);
+    let mut de = Deserializer::with_config(reader, config);
+    let value = T::deserialize(&mut de).map_err(|err| de.error_at_offset(err))?;
+    Ok((value, de.reader.position()))
+}
+
+/// Configuration of a [`Deserializer`]
+///
+/// The default configuration is lenient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializerConfig {
+    /// Reject described types that are not claimed by a concrete type
+    ///
+    /// Types with a known descriptor (eg. types deriving `DeserializeComposite`) already reject a
+    /// mismatching descriptor. Otherwise, a described type is accepted as a
+    /// [`Value::Described`](crate::Value::Described) regardless of its descriptor. If this is
+    /// `true`, deserializing a described type into a [`Value`](crate::Value) fails with
+    /// [`Error::UnrecognizedDescriptor`] instead. [`Described<T>`](crate::described::Described)
+    /// still accepts any descriptor.
+    pub strict_descriptors: bool,
+
+    /// Maximum number of elements in a list, array or map
+    ///
+    /// The count of a map includes both the keys and the values. An encoded count exceeding this
+    /// limit fails with [`Error::ContainerTooLarge`] before any element is deserialized.
+    pub max_container_len: usize,
+
+    /// Maximum nesting depth of lists, arrays and maps
+    ///
+    /// Exceeding this limit fails with [`Error::DepthLimitExceeded`].
+    pub max_depth: usize,
+
+    /// Reject maps that contain the same key more than once
+    ///
+    /// AMQP maps must not contain duplicate keys. By default, the value of the last occurrence of
+    /// a key is kept. If this is `true`, decoding a map fails with [`Error::DuplicateMapKey`] on
+    /// the first repeated key instead. Keys are compared by value, so the same string encoded
+    /// as `str8` and `str32` is a duplicate.
+    pub strict_map_keys: bool,
+
+    /// Reuse interned symbols when deserializing a [`Symbol`](crate::primitives::Symbol)
+    ///
+    /// If this is `true`, a decoded symbol that has been registered with
+    /// [`Symbol::intern`](crate::primitives::Symbol::intern) (or is a descriptor name of the core
+    /// specification) shares the backing storage of the interned symbol instead of allocating a
+    /// new one. This takes a read lock on the process-wide interner for every decoded symbol, so
+    /// it is disabled by default.
+    pub intern_symbols: bool,
+}
+
+impl DeserializerConfig {
+    /// Default maximum number of elements in a list, array or map
+    pub const DEFAULT_MAX_CONTAINER_LEN: usize = 16 * 1024 * 1024;
+
+    /// Default maximum nesting depth of lists, arrays and maps
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+}
+
+impl Default for DeserializerConfig {
+    fn default() -> Self {
+        Self {
+            strict_descriptors: false,
+            max_container_len: Self::DEFAULT_MAX_CONTAINER_LEN,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            strict_map_keys: false,
+            intern_symbols: false,
+        }
+    }
 }
 
 /// A structure that deserializes AMQP1.0 binary encoded values into rust types
 #[derive(Debug)]
 pub struct Deserializer<R> {
     reader: R,
+    config: DeserializerConfig,
+    depth: usize,
     new_type: NewType,
     enum_type: EnumType,
     struct_encoding: StructEncoding,
     elem_format_code: Option<EncodingCodes>,
+    elem_descriptor: Option<Descriptor>,
+    last_format_code: Option<u8>,
+}
+
+impl<'de> Deserializer<SliceReader<'de>> {
+    /// Wraps truncation and invalid format code errors with the position in the slice
+    fn error_at_offset(&self, err: Error) -> Error {
+        match &err {
+            Error::InvalidFormatCode => {}
+            _ if err.is_unexpected_eof() => {}
+            _ => return err,
+        }
+        Error::AtOffset {
+            offset: self.reader.position(),
+            found: self.last_format_code,
+            source: Box::new(err),
+        }
+    }
 }
 
 impl<'de, R: Read<'de>> Deserializer<R> {
     /// Creates a new AMQP1.0 (crate)deserializer
     pub fn new(reader: R) -> Self {
+        Self::with_config(reader, DeserializerConfig::default())
+    }
+
+    /// Creates a new AMQP1.0 deserializer with the given [`DeserializerConfig`]
+    pub fn with_config(reader: R, config: DeserializerConfig) -> Self {
         Self {
             reader,
+            config,
+            depth: 0,
             new_type: Default::default(),
             enum_type: Default::default(),
             struct_encoding: StructEncoding::None,
             elem_format_code: None,
+            elem_descriptor: None,
+            last_format_code: None,
+        }
+    }
+
+    /// Returns an error if a described type is about to be deserialized into a `Value` in strict
+    /// mode
+    fn check_unrecognized_descriptor(&mut self) -> Result<(), Error> {
+        if !self.config.strict_descriptors {
+            return Ok(());
+        }
+        if let Some(descriptor) = &self.elem_descriptor {
+            return Err(Error::UnrecognizedDescriptor(descriptor.clone()));
+        }
+        match self.get_elem_code_or_peek_byte() {
+            Some(Ok(code)) if code == EncodingCodes::DescribedType as u8 => {
+                let descriptor = Descriptor::deserialize(&mut *self)?;
+                Err(Error::UnrecognizedDescriptor(descriptor))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the encoded element count and the nesting depth against the [`DeserializerConfig`]
+    /// before entering a list, array or map
+    fn enter_container(&mut self, count: usize) -> Result<(), Error> {
+        if count > self.config.max_container_len {
+            return Err(Error::ContainerTooLarge(count));
+        }
+        if self.depth >= self.config.max_depth {
+            return Err(Error::DepthLimitExceeded);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn leave_container(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Reads the complete encoding of the next value, including its constructor, without
+    /// interpreting it
+    fn read_raw_value(&mut self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        if self.elem_format_code.is_some() {
+            // The constructor of an array element is shared and not part of the element
+            return Err(Error::InvalidValue);
+        }
+
+        let code = self
+            .reader
+            .next()
+            .ok_or_else(|| Error::unexpected_eof("Expecting format code"))?;
+        buf.push(code);
+        let width = match raw_width(code.try_into()?) {
+            RawWidth::Described => {
+                self.enter_container(0)?;
+                let result = self
+                    .read_raw_value(buf)
+                    .and_then(|_| self.read_raw_value(buf));
+                self.leave_container();
+                return result;
+            }
+            RawWidth::Fixed(width) => width,
+            RawWidth::Variable8 => {
+                let width = self
+                    .reader
+                    .next()
+                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                buf.push(width);
+                width as usize
+            }
+            RawWidth::Variable32 => {
+                let width_bytes: [u8; 4] = self
+                    .reader
+                    .read_const_bytes()
+                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                buf.extend_from_slice(&width_bytes);
+                u32::from_be_bytes(width_bytes) as usize
+            }
+        };
+        let bytes = self
+            .reader
+            .read_bytes(width)
+            .ok_or_else(|| Error::unexpected_eof(""))?;
+        buf.extend_from_slice(&bytes);
+        Ok(())
+    }
+
+    /// Returns the length of the complete encoding of the value that starts `offset` bytes ahead,
+    /// including its constructor, without consuming it
+    fn peek_raw_value_len(&mut self, offset: usize) -> Result<usize, Error> {
+        let code = self.peek_bytes_at(offset, 1)?[0];
+        let len = match raw_width(code.try_into()?) {
+            RawWidth::Described => {
+                self.enter_container(0)?;
+                let result = self
+                    .peek_raw_value_len(offset + 1)
+                    .and_then(|descriptor_len| {
+                        self.peek_raw_value_len(offset + 1 + descriptor_len)
+                            .map(|value_len| 1 + descriptor_len + value_len)
+                    });
+                self.leave_container();
+                return result;
+            }
+            RawWidth::Fixed(width) => 1 + width,
+            RawWidth::Variable8 => 2 + self.peek_bytes_at(offset + 1, 1)?[0] as usize,
+            RawWidth::Variable32 => {
+                let mut width_bytes = [0u8; 4];
+                width_bytes.copy_from_slice(self.peek_bytes_at(offset + 1, 4)?);
+                5 + u32::from_be_bytes(width_bytes) as usize
+            }
+        };
+        Ok(len)
+    }
+
+    fn peek_bytes_at(&mut self, offset: usize, n: usize) -> Result<&[u8], Error> {
+        self.reader
+            .peek_bytes(offset + n)
+            .map(|bytes| &bytes[offset..])
+            .ok_or_else(|| Error::unexpected_eof(""))
+    }
+
+    /// Decodes the next map key without consuming it, failing if the same key has already been
+    /// found in the map
+    fn check_duplicate_map_key(&mut self, keys: &mut HashSet<Value>) -> Result<(), Error> {
+        if self.elem_format_code.is_some() {
+            return Ok(());
+        }
+        let len = self.peek_raw_value_len(0)?;
+        let config = self.config;
+        let bytes = self.peek_bytes_at(0, len)?;
+        let key: Value = from_slice_with_config(bytes, config)?;
+        if keys.contains(&key) {
+            return Err(Error::DuplicateMapKey(key));
         }
+        keys.insert(key);
+        Ok(())
     }
 
     fn read_format_code(&mut self) -> Option<Result<EncodingCodes, Error>> {
         let code = self.reader.next();
         let code = code?;
+        self.last_format_code = Some(code);
         Some(code.try_into())
     }
 
@@ -71,6 +340,26 @@ impl<'de, R: Read<'de>> Deserializer<R> {
         }
     }
 
+    /// Reads the element constructor of an array, which is preceded by a descriptor if the
+    /// elements are of a described type
+    fn read_array_elem_constructor(&mut self) -> Result<(), Error> {
+        self.elem_format_code = None;
+        self.elem_descriptor = match self
+            .reader
+            .peek()
+            .ok_or_else(|| Error::unexpected_eof("Expecting format code"))?
+            .try_into()?
+        {
+            EncodingCodes::DescribedType => Some(Descriptor::deserialize(&mut *self)?),
+            _ => None,
+        };
+        let format_code = self
+            .read_format_code()
+            .ok_or_else(|| Error::unexpected_eof("Expecting format code"))??;
+        self.elem_format_code = Some(format_code);
+        Ok(())
+    }
+
     fn get_elem_code_or_peek_byte(&mut self) -> Option<Result<u8, Error>> {
         match &self.elem_format_code {
             Some(c) => Some(Ok(c.clone() as u8)),
@@ -708,7 +997,11 @@ where
                 // Leave symbol as visit_string because serde(untagged)
                 // on descriptor will visit String instead of str
                 self.new_type = NewType::None;
-                visitor.visit_string(self.parse_symbol()?)
+                let symbol = self.parse_symbol()?;
+                match self.config.intern_symbols {
+                    true => with_interning(|| visitor.visit_string(symbol)),
+                    false => visitor.visit_string(symbol),
+                }
             }
             _ => visitor.visit_string(self.parse_string()?),
         }
@@ -858,6 +1151,10 @@ where
         } else if name == TRANSPARENT_VEC {
             self.new_type = NewType::TransparentVec;
             visitor.visit_seq(TransparentVecAccess::new(self))
+        } else if name == RAW {
+            let mut buf = Vec::new();
+            self.read_raw_value(&mut buf)?;
+            visitor.visit_byte_buf(buf)
         } else {
             visitor.visit_newtype_struct(self)
         }
@@ -881,16 +1178,19 @@ where
                     .reader
                     .next()
                     .ok_or_else(|| Error::unexpected_eof(""))? as usize;
-                let format_code = self
-                    .read_format_code()
-                    .ok_or_else(|| Error::unexpected_eof("Expecting format code"))??;
-                self.elem_format_code = Some(format_code);
+                // An empty array written without the element constructor only has the count
+                if count > 0 || len > OFFSET_ARRAY8 - 1 {
+                    self.read_array_elem_constructor()?;
+                }
 
                 // Account for offset
-                let len = len - OFFSET_ARRAY8;
+                let len = len.saturating_sub(OFFSET_ARRAY8);
                 // let buf = self.reader.read_bytes(len)?;
 
-                visitor.visit_seq(ArrayAccess::new(self, len, count))
+                self.enter_container(count)?;
+                let result = visitor.visit_seq(ArrayAccess::new(self, len, count));
+                self.leave_container();
+                result
             }
             EncodingCodes::Array32 => {
                 // Read "header" bytes
@@ -902,52 +1202,64 @@ where
                     .reader
                     .read_const_bytes()
                     .ok_or_else(|| Error::unexpected_eof(""))?;
-                let format_code = self
-                    .read_format_code()
-                    .ok_or_else(|| Error::unexpected_eof("Expecting format code"))??;
-                self.elem_format_code = Some(format_code);
 
                 // Conversion
                 let len = u32::from_be_bytes(len_bytes) as usize;
                 let count = u32::from_be_bytes(count_bytes) as usize;
 
+                // An empty array written without the element constructor only has the count
+                if count > 0 || len > OFFSET_ARRAY32 - 1 {
+                    self.read_array_elem_constructor()?;
+                }
+
                 // Account for offset
-                let len = len - OFFSET_ARRAY32;
+                let len = len.saturating_sub(OFFSET_ARRAY32);
                 // let buf = self.reader.read_bytes(len)?;
 
-                visitor.visit_seq(ArrayAccess::new(self, len, count))
+                self.enter_container(count)?;
+                let result = visitor.visit_seq(ArrayAccess::new(self, len, count));
+                self.leave_container();
+                result
             }
             EncodingCodes::List0 => {
                 let len = 0;
                 let count = 0;
-                visitor.visit_seq(ListAccess::new(self, len, count))
+                self.enter_container(count)?;
+                let result = visitor.visit_seq(ListAccess::new(self, len, count));
+                self.leave_container();
+                result
             }
             EncodingCodes::List8 => {
                 let len = self
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))? as usize;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list size"))?
+                    as usize;
                 let count = self
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))? as usize;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list count"))?
+                    as usize;
 
                 // Account for offset
                 let len = len - OFFSET_LIST8;
 
                 // Make sure there is no other element format code
                 self.elem_format_code = None;
-                visitor.visit_seq(ListAccess::new(self, len, count))
+                self.enter_container(count)?;
+                let result = visitor.visit_seq(ListAccess::new(self, len, count));
+                self.leave_container();
+                result
             }
             EncodingCodes::List32 => {
                 let len_bytes = self
                     .reader
                     .read_const_bytes()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list size"))?;
                 let count_bytes = self
                     .reader
                     .read_const_bytes()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list count"))?;
                 let len = u32::from_be_bytes(len_bytes) as usize;
                 let count = u32::from_be_bytes(count_bytes) as usize;
 
@@ -956,7 +1268,10 @@ where
 
                 // Make sure there is no other element format code
                 self.elem_format_code = None;
-                visitor.visit_seq(ListAccess::new(self, len, count))
+                self.enter_container(count)?;
+                let result = visitor.visit_seq(ListAccess::new(self, len, count));
+                self.leave_container();
+                result
             }
             _ => Err(Error::InvalidFormatCode),
         }
@@ -981,11 +1296,13 @@ where
                 let size = self
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))? as usize;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list size"))?
+                    as usize;
                 let count = self
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))? as usize;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list count"))?
+                    as usize;
 
                 // Account for offset
                 let size = size - OFFSET_LIST8;
@@ -1016,11 +1333,16 @@ where
             _ => return Err(Error::InvalidFormatCode),
         };
 
-        if count != len {
+        // List0 is left to the visitor so that a struct with all default fields can be
+        // deserialized from an empty list
+        if count != len && !matches!(code, EncodingCodes::List0) {
             return Err(Error::SequenceLengthMismatch);
         }
 
-        visitor.visit_seq(ListAccess::new(self, size, count))
+        self.enter_container(count)?;
+        let result = visitor.visit_seq(ListAccess::new(self, size, count));
+        self.leave_container();
+        result
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -1069,7 +1391,10 @@ where
 
         // // AMQP map count includes both key and value, should be halfed
         // let count = count / 2;
-        visitor.visit_map(MapAccess::new(self, size, count))
+        self.enter_container(count)?;
+        let result = visitor.visit_map(MapAccess::new(self, size, count));
+        self.leave_container();
+        result
     }
 
     fn deserialize_tuple_struct<V>(
@@ -1152,7 +1477,14 @@ where
 
         let curr_enum_type = self.enum_type.clone();
 
+        if name == DESCRIPTOR {
+            if let Some(descriptor) = self.elem_descriptor.take() {
+                return visitor.visit_enum(ElemDescriptorAccess { descriptor });
+            }
+        }
+
         let result = if name == VALUE {
+            self.check_unrecognized_descriptor()?;
             self.enum_type = EnumType::Value;
             visitor.visit_enum(VariantAccess::new(self))
         } else if name == DESCRIPTOR {
@@ -1350,11 +1682,18 @@ impl<'a, 'de, R: Read<'de>> de::SeqAccess<'de> for ArrayAccess<'a, R> {
         match self.count {
             0 => {
                 self.de.elem_format_code = None;
+                self.de.elem_descriptor = None;
                 Ok(None)
             }
             _ => {
                 self.count -= 1;
-                seed.deserialize(self.as_mut()).map(Some)
+                // Compound elements consume the element constructor
+                let elem_format_code = self.de.elem_format_code.clone();
+                let elem_descriptor = self.de.elem_descriptor.clone();
+                let result = seed.deserialize(self.as_mut()).map(Some);
+                self.de.elem_format_code = elem_format_code;
+                self.de.elem_descriptor = elem_descriptor;
+                result
             }
         }
     }
@@ -1458,20 +1797,94 @@ impl<'a, 'de, R: Read<'de>> de::SeqAccess<'de> for TransparentVecAccess<'a, R> {
     }
 }
 
+/// How the width of an encoded value follows from its format code
+enum RawWidth {
+    /// A descriptor followed by a value
+    Described,
+
+    /// Fixed number of bytes after the format code
+    Fixed(usize),
+
+    /// A one byte width after the format code
+    Variable8,
+
+    /// A four byte width after the format code
+    Variable32,
+}
+
+fn raw_width(code: EncodingCodes) -> RawWidth {
+    match code {
+        EncodingCodes::DescribedType => RawWidth::Described,
+        EncodingCodes::Null
+        | EncodingCodes::BooleanTrue
+        | EncodingCodes::BooleanFalse
+        | EncodingCodes::UInt0
+        | EncodingCodes::ULong0
+        | EncodingCodes::List0 => RawWidth::Fixed(0),
+        EncodingCodes::Boolean
+        | EncodingCodes::UByte
+        | EncodingCodes::Byte
+        | EncodingCodes::SmallUInt
+        | EncodingCodes::SmallULong
+        | EncodingCodes::SmallInt
+        | EncodingCodes::SmallLong => RawWidth::Fixed(1),
+        EncodingCodes::UShort | EncodingCodes::Short => RawWidth::Fixed(2),
+        EncodingCodes::UInt
+        | EncodingCodes::Int
+        | EncodingCodes::Float
+        | EncodingCodes::Char
+        | EncodingCodes::Decimal32 => RawWidth::Fixed(4),
+        EncodingCodes::ULong
+        | EncodingCodes::Long
+        | EncodingCodes::Double
+        | EncodingCodes::Timestamp
+        | EncodingCodes::Decimal64 => RawWidth::Fixed(8),
+        EncodingCodes::Decimal128 | EncodingCodes::Uuid => RawWidth::Fixed(16),
+        EncodingCodes::VBin8
+        | EncodingCodes::Str8
+        | EncodingCodes::Sym8
+        | EncodingCodes::List8
+        | EncodingCodes::Map8
+        | EncodingCodes::Array8 => RawWidth::Variable8,
+        EncodingCodes::VBin32
+        | EncodingCodes::Str32
+        | EncodingCodes::Sym32
+        | EncodingCodes::List32
+        | EncodingCodes::Map32
+        | EncodingCodes::Array32 => RawWidth::Variable32,
+    }
+}
+
 /// Accessor for map type
 #[derive(Debug)]
 pub struct MapAccess<'a, R> {
     de: &'a mut Deserializer<R>,
     _size: usize,
     count: usize,
+    // Only tracked if `DeserializerConfig::strict_map_keys` is set
+    keys: Option<HashSet<Value>>,
 }
 
 impl<'a, R> MapAccess<'a, R> {
     pub(crate) fn new(de: &'a mut Deserializer<R>, size: usize, count: usize) -> Self {
+        let keys = match de.config.strict_map_keys {
+            true => Some(HashSet::new()),
+            false => None,
+        };
         Self {
             de,
             _size: size,
             count,
+            keys,
+        }
+    }
+}
+
+impl<'a, 'de, R: Read<'de>> MapAccess<'a, R> {
+    fn check_duplicate_key(&mut self) -> Result<(), Error> {
+        match &mut self.keys {
+            Some(keys) => self.de.check_duplicate_map_key(keys),
+            None => Ok(()),
         }
     }
 }
@@ -1493,6 +1906,7 @@ impl<'a, 'de, R: Read<'de>> de::MapAccess<'de> for MapAccess<'a, R> {
             0 => Ok(None),
             _ => {
                 self.count -= 1;
+                self.check_duplicate_key()?;
                 seed.deserialize(self.as_mut()).map(Some)
             }
         }
@@ -1520,6 +1934,7 @@ impl<'a, 'de, R: Read<'de>> de::MapAccess<'de> for MapAccess<'a, R> {
             _ => {
                 // AMQP map count includes both key and value
                 self.count -= 2;
+                self.check_duplicate_key()?;
                 let key = kseed.deserialize(self.as_mut())?;
                 let val = vseed.deserialize(self.as_mut())?;
                 Ok(Some((key, val)))
@@ -1528,6 +1943,68 @@ impl<'a, 'de, R: Read<'de>> de::MapAccess<'de> for MapAccess<'a, R> {
     }
 }
 
+/// Accessor for the descriptor of an array element, which has been read from the element
+/// constructor of the array
+#[derive(Debug)]
+struct ElemDescriptorAccess {
+    descriptor: Descriptor,
+}
+
+impl<'de> de::EnumAccess<'de> for ElemDescriptorAccess {
+    type Error = Error;
+
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        // The variant of a descriptor is identified by the format code of its value
+        let code = match &self.descriptor {
+            Descriptor::Name(_) => EncodingCodes::Sym32,
+            Descriptor::Code(_) => EncodingCodes::ULong,
+        };
+        let val = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(code as u8))?;
+        Ok((val, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for ElemDescriptorAccess {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(Error::InvalidValue)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.descriptor {
+            Descriptor::Name(name) => seed.deserialize(name.into_inner().into_deserializer()),
+            Descriptor::Code(code) => seed.deserialize(code.into_deserializer()),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::InvalidValue)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::InvalidValue)
+    }
+}
+
 /// Accessor for enum variant
 #[derive(Debug)]
 pub struct VariantAccess<'a, R> {
@@ -1627,6 +2104,14 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
         }
     }
 
+    /// The descriptor of an array element is taken from the element constructor of the array
+    fn peek_code(&mut self) -> Option<Result<EncodingCodes, Error>> {
+        match &self.de.elem_descriptor {
+            Some(_) if self.counter == 0 => Some(Ok(EncodingCodes::DescribedType)),
+            _ => self.de.reader.peek().map(TryInto::try_into),
+        }
+    }
+
     pub(crate) fn consume_list_header(&mut self) -> Result<u32, Error> {
         // consume the list headers if
         match self
@@ -1640,12 +2125,12 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
                     .as_mut()
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list size"))?;
                 let count = self
                     .as_mut()
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list count"))?;
                 Ok(count as u32)
             }
             EncodingCodes::List32 => {
@@ -1653,13 +2138,13 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
                     .as_mut()
                     .reader
                     .read_const_bytes()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list size"))?;
                 let _size = u32::from_be_bytes(bytes);
                 let bytes = self
                     .as_mut()
                     .reader
                     .read_const_bytes()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting list count"))?;
                 let count = u32::from_be_bytes(bytes);
                 Ok(count)
             }
@@ -1679,12 +2164,12 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
                     .as_mut()
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting map size"))?;
                 let count = self
                     .as_mut()
                     .reader
                     .next()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting map count"))?;
                 Ok(count as u32)
             }
             EncodingCodes::Map32 => {
@@ -1692,13 +2177,13 @@ impl<'a, 'de, R: Read<'de>> DescribedAccess<'a, R> {
                     .as_mut()
                     .reader
                     .read_const_bytes()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting map size"))?;
                 let _size = u32::from_be_bytes(bytes);
                 let bytes = self
                     .as_mut()
                     .reader
                     .read_const_bytes()
-                    .ok_or_else(|| Error::unexpected_eof(""))?;
+                    .ok_or_else(|| Error::unexpected_eof("Expecting map count"))?;
                 let count = u32::from_be_bytes(bytes);
                 Ok(count)
             }
@@ -1723,11 +2208,10 @@ impl<'a, 'de, R: Read<'de>> de::SeqAccess<'de> for DescribedAccess<'a, R> {
         if self.counter >= self.field_count {
             return Ok(None);
         }
-        let byte = match self.de.reader.peek() {
-            Some(b) => b,
+        let code = match self.peek_code() {
+            Some(code) => code?,
             None => return Ok(None),
         };
-        let code = byte.try_into()?;
         let result = match code {
             EncodingCodes::DescribedType => {
                 let result = seed.deserialize(self.as_mut()).map(Some);
@@ -1737,6 +2221,7 @@ impl<'a, 'de, R: Read<'de>> de::SeqAccess<'de> for DescribedAccess<'a, R> {
                 if self.counter == 0 {
                     if let StructEncoding::DescribedList = self.de.struct_encoding {
                         self.field_count += self.consume_list_header()?;
+                        self.de.elem_format_code = None;
                     }
                 }
                 result
@@ -1761,11 +2246,10 @@ impl<'a, 'de, R: Read<'de>> de::MapAccess<'de> for DescribedAccess<'a, R> {
         if self.counter >= self.field_count {
             return Ok(None);
         }
-        let byte = match self.de.reader.peek() {
-            Some(b) => b,
+        let code = match self.peek_code() {
+            Some(code) => code?,
             None => return Ok(None),
         };
-        let code = byte.try_into()?;
         let result = match code {
             EncodingCodes::Null => {
                 let _ = self.de.reader.next(); // consume the Null byte
@@ -1777,6 +2261,7 @@ impl<'a, 'de, R: Read<'de>> de::MapAccess<'de> for DescribedAccess<'a, R> {
                 if self.counter == 0 {
                     if let StructEncoding::DescribedMap = self.de.struct_encoding {
                         self.field_count += self.consume_map_header()?;
+                        self.de.elem_format_code = None;
                     }
                 }
                 result
@@ -2062,6 +2547,37 @@ mod tests {
         assert_eq_from_reader_vs_expected(&buf, expected);
     }
 
+    #[test]
+    fn test_deserialize_empty_array() {
+        use std::collections::BTreeMap;
+
+        use crate::primitives::{Array, Symbol};
+        use crate::ser::to_vec;
+
+        let expected: Array<Symbol> = Array::from(vec![]);
+        let buf = to_vec(&expected).unwrap();
+        assert_eq_from_reader_vs_expected(&buf, expected.clone());
+
+        // An empty array without the element constructor followed by another field
+        let buf = &[
+            EncodingCodes::List8 as u8,
+            5,
+            2,
+            EncodingCodes::Array8 as u8,
+            1,
+            0,
+            EncodingCodes::Map8 as u8,
+            1,
+            0,
+        ];
+        let (array, map): (Array<Symbol>, BTreeMap<Symbol, i32>) = from_slice(buf).unwrap();
+        assert_eq!(array, expected);
+        assert!(map.is_empty());
+
+        let buf = &[EncodingCodes::Array32 as u8, 0, 0, 0, 4, 0, 0, 0, 0];
+        assert_eq_from_slice_vs_expected(buf, expected);
+    }
+
     #[test]
     fn test_deserialize_list() {
         // List0
@@ -2505,4 +3021,252 @@ mod tests {
         let buf = to_vec(&expected).unwrap();
         assert_eq_from_reader_vs_expected(&buf, expected);
     }
+
+    #[test]
+    fn test_deserialize_list0_into_empty_collections() {
+        #[derive(Debug, Default, Deserialize, PartialEq)]
+        #[serde(default)]
+        struct Foo {
+            a: Option<i32>,
+            b: bool,
+        }
+
+        let buf = [EncodingCodes::List0 as u8];
+
+        let vec: Vec<i32> = from_slice(&buf).unwrap();
+        assert!(vec.is_empty());
+        assert_eq_from_reader_vs_expected(&buf, Vec::<i32>::new());
+
+        let tuple: [i32; 0] = from_slice(&buf).unwrap();
+        assert_eq!(tuple, [0i32; 0]);
+
+        let foo: Foo = from_slice(&buf).unwrap();
+        assert_eq!(foo, Foo::default());
+        assert_eq_from_reader_vs_expected(&buf, Foo::default());
+    }
+
+    #[test]
+    fn test_from_reader_over_cursor_matches_from_slice() {
+        use serde::Serialize;
+        use serde_bytes::ByteBuf;
+        use std::io::{BufReader, Cursor};
+
+        use crate::{ser::to_vec, Value};
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Blob {
+            name: String,
+            payload: ByteBuf,
+            tags: Vec<String>,
+            value: Value,
+        }
+
+        let blob = Blob {
+            name: "a".repeat(300),
+            payload: ByteBuf::from(vec![7u8; 300]),
+            tags: vec![String::from("large"), String::from("file")],
+            value: Value::List(vec![Value::Symbol("sym".into()), Value::Long(-1)]),
+        };
+        let buf = to_vec(&blob).unwrap();
+
+        let from_slice: Blob = from_slice(&buf).unwrap();
+        let from_cursor: Blob = from_reader(Cursor::new(buf.clone())).unwrap();
+        let from_buf_reader: Blob =
+            from_reader(BufReader::with_capacity(16, Cursor::new(buf))).unwrap();
+        assert_eq!(from_cursor, from_slice);
+        assert_eq!(from_buf_reader, from_slice);
+        assert_eq!(from_cursor, blob);
+    }
+
+    #[test]
+    fn test_strict_descriptors_rejects_unrecognized_descriptor() {
+        use serde::Serialize;
+
+        use crate::{
+            de::{from_slice_with_config, DeserializerConfig},
+            described::Described,
+            descriptor::Descriptor,
+            ser::to_vec,
+            Error, Value,
+        };
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Envelope {
+            id: u32,
+            body: Value,
+        }
+
+        let described = Described {
+            descriptor: Descriptor::Code(0x0000_beef_0000_0001),
+            value: Value::String(String::from("unexpected")),
+        };
+        let envelope = Envelope {
+            id: 1,
+            body: Value::Described(Box::new(described)),
+        };
+        let buf = to_vec(&envelope).unwrap();
+
+        let lenient: Envelope = from_slice(&buf).unwrap();
+        assert_eq!(lenient, envelope);
+
+        let config = DeserializerConfig {
+            strict_descriptors: true,
+            ..Default::default()
+        };
+        let err = from_slice_with_config::<Envelope>(&buf, config).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::UnrecognizedDescriptor(Descriptor::Code(0x0000_beef_0000_0001))
+        ));
+        assert!(err.to_string().contains("0x0000beef:0x00000001"));
+
+        // Values without a descriptor are not affected
+        let buf = to_vec(&Value::Long(-1)).unwrap();
+        let value: Value = from_slice_with_config(&buf, config).unwrap();
+        assert_eq!(value, Value::Long(-1));
+    }
+
+    #[test]
+    fn test_container_limits() {
+        use crate::{
+            de::{from_slice_with_config, DeserializerConfig},
+            primitives::OrderedMap,
+            Error, Value,
+        };
+
+        // Map32 header claiming a billion entries followed by a single entry
+        let mut buf = vec![EncodingCodes::Map32 as u8];
+        buf.extend_from_slice(&u32::MAX.to_be_bytes());
+        buf.extend_from_slice(&1_000_000_000u32.to_be_bytes());
+        buf.extend_from_slice(&[EncodingCodes::Null as u8, EncodingCodes::Null as u8]);
+        let err = from_slice::<OrderedMap<Value, Value>>(&buf).unwrap_err();
+        assert!(matches!(err, Error::ContainerTooLarge(1_000_000_000)));
+        let err = from_slice::<Value>(&buf).unwrap_err();
+        assert!(matches!(err, Error::ContainerTooLarge(1_000_000_000)));
+
+        let config = DeserializerConfig {
+            max_container_len: 2,
+            ..Default::default()
+        };
+        let buf = [EncodingCodes::List8 as u8, 4, 3, 0x50, 1, 0x50, 2, 0x50, 3];
+        let list: Vec<u8> = from_slice(&buf).unwrap();
+        assert_eq!(list, vec![1, 2, 3]);
+        let err = from_slice_with_config::<Vec<u8>>(&buf, config).unwrap_err();
+        assert!(matches!(err, Error::ContainerTooLarge(3)));
+
+        // Three nested lists
+        let buf = [
+            EncodingCodes::List8 as u8,
+            3,
+            1,
+            EncodingCodes::List8 as u8,
+            1,
+            1,
+            EncodingCodes::List0 as u8,
+        ];
+        let config = DeserializerConfig {
+            max_depth: 3,
+            ..Default::default()
+        };
+        let value: Value = from_slice_with_config(&buf, config).unwrap();
+        assert_eq!(
+            value,
+            Value::List(vec![Value::List(vec![Value::List(vec![])])])
+        );
+        let config = DeserializerConfig {
+            max_depth: 2,
+            ..Default::default()
+        };
+        let err = from_slice_with_config::<Value>(&buf, config).unwrap_err();
+        assert!(matches!(err, Error::DepthLimitExceeded));
+    }
+
+    #[test]
+    fn test_strict_map_keys() {
+        use crate::{
+            de::{from_reader_with_config, from_slice_with_config, DeserializerConfig},
+            primitives::OrderedMap,
+            to_vec, Error, Value,
+        };
+
+        // Map8 of {"a": 1, "b": 2, "a": 3} where the repeated key is encoded as a str32
+        let buf = [
+            EncodingCodes::Map8 as u8,
+            19,
+            6,
+            EncodingCodes::Str8 as u8,
+            1,
+            b'a',
+            EncodingCodes::UByte as u8,
+            1,
+            EncodingCodes::Str8 as u8,
+            1,
+            b'b',
+            EncodingCodes::UByte as u8,
+            2,
+            EncodingCodes::Str32 as u8,
+            0,
+            0,
+            0,
+            1,
+            b'a',
+            EncodingCodes::UByte as u8,
+            3,
+        ];
+
+        // The last value wins by default
+        let map: OrderedMap<String, u8> = from_slice(&buf).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&3));
+        assert_eq!(map.get("b"), Some(&2));
+
+        let config = DeserializerConfig {
+            strict_map_keys: true,
+            ..Default::default()
+        };
+        let err = from_slice_with_config::<OrderedMap<String, u8>>(&buf, config).unwrap_err();
+        assert!(matches!(err, Error::DuplicateMapKey(Value::String(ref key)) if key == "a"));
+        let err = from_slice_with_config::<Value>(&buf, config).unwrap_err();
+        assert!(matches!(err, Error::DuplicateMapKey(_)));
+        let err = from_reader_with_config::<OrderedMap<String, u8>>(&buf[..], config).unwrap_err();
+        assert!(matches!(err, Error::DuplicateMapKey(_)));
+
+        // A map without repeated keys is not affected
+        let mut map = OrderedMap::new();
+        map.insert(String::from("a"), 1u8);
+        map.insert(String::from("b"), 2u8);
+        let buf = to_vec(&map).unwrap();
+        let decoded: OrderedMap<String, u8> = from_slice_with_config(&buf, config).unwrap();
+        assert_eq!(decoded, map);
+    }
+
+    #[test]
+    fn test_error_at_offset() {
+        use crate::{de::from_slice_prefix, Error};
+
+        // A list of two ubytes where the second element has an invalid format code
+        let buf = [EncodingCodes::List8 as u8, 5, 2, 0x50, 1, 0xff, 2];
+        let err = from_slice::<Vec<u8>>(&buf).unwrap_err();
+        assert_eq!(err.offset(), Some(6));
+        assert!(matches!(
+            err,
+            Error::AtOffset {
+                found: Some(0xff),
+                ref source,
+                ..
+            } if matches!(**source, Error::InvalidFormatCode)
+        ));
+
+        // Truncated in the middle of a ulong
+        let buf = [EncodingCodes::ULong as u8, 0, 0, 0];
+        let err = from_slice_prefix::<u64>(&buf).unwrap_err();
+        assert_eq!(err.offset(), Some(1));
+        assert!(err.is_unexpected_eof());
+
+        // Errors that are not about malformed input are not wrapped
+        let buf = [EncodingCodes::Str8 as u8, 1, 0xff];
+        let err = from_slice::<String>(&buf).unwrap_err();
+        assert!(matches!(err, Error::InvalidUtf8Encoding));
+        assert_eq!(err.offset(), None);
+    }
 }
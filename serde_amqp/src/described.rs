@@ -5,9 +5,9 @@ use std::marker::PhantomData;
 use serde::{de, ser};
 
 use crate::{
-    __constants::{DESCRIBED_BASIC, DESCRIPTOR},
-    descriptor::Descriptor,
-    Value,
+    __constants::{DESCRIBED_BASIC, DESCRIPTOR, RAW},
+    descriptor::{peek_descriptor, Descriptor},
+    Error, Value,
 };
 
 /// Contains a descriptor and a wrapped value T.
@@ -100,6 +100,94 @@ impl<'de, T: de::Deserialize<'de>> de::Deserialize<'de> for Described<T> {
     }
 }
 
+/// A described type kept in its original encoding
+///
+/// Deserializing into [`Described<Value>`] and serializing it again does not necessarily give
+/// back the same bytes, because the serializer always picks the most compact encoding. The
+/// following are normalized:
+///
+/// - `uint`, `ulong`, `int` and `long` (eg. `0x70` may become `smalluint` or `uint0`)
+/// - `boolean` (`0x56` becomes `true` or `false`)
+/// - the 8 and 32 bit width forms of `binary`, `string`, `symbol`, `list`, `map` and `array`
+/// - `list0` and lists with only null elements
+///
+/// `RawDescribed` holds the complete encoding of the described type, including the descriptor,
+/// and serializes it back byte-identically. This is useful for forwarding described types that
+/// are not known to the application.
+///
+/// `RawDescribed` cannot be an element of an AMQP array because the constructor of an array
+/// element is shared by all elements.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawDescribed {
+    descriptor: Descriptor,
+    bytes: Vec<u8>,
+}
+
+impl RawDescribed {
+    /// Creates a `RawDescribed` from the complete encoding of a described type
+    ///
+    /// Only the descriptor is validated
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        let descriptor = peek_descriptor(&bytes)?;
+        Ok(Self { descriptor, bytes })
+    }
+
+    /// Descriptor of the described type
+    pub fn descriptor(&self) -> &Descriptor {
+        &self.descriptor
+    }
+
+    /// The complete encoding of the described type
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consumes `self` and returns the complete encoding of the described type
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Deserializes the described type into a [`Described<Value>`]
+    pub fn to_described_value(&self) -> Result<Described<Value>, Error> {
+        crate::from_slice(&self.bytes)
+    }
+}
+
+impl ser::Serialize for RawDescribed {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(RAW, serde_bytes::Bytes::new(&self.bytes))
+    }
+}
+
+struct RawDescribedVisitor {}
+
+impl<'de> de::Visitor<'de> for RawDescribedVisitor {
+    type Value = RawDescribed;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("struct RawDescribed")
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        RawDescribed::from_bytes(v).map_err(de::Error::custom)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for RawDescribed {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(RAW, RawDescribedVisitor {})
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "derive")]
@@ -176,4 +264,67 @@ mod tests {
         println!("{:?}", &deserialized);
         assert_eq!(test, deserialized);
     }
+
+    #[test]
+    fn test_described_value_round_trip() {
+        use crate::Value;
+
+        // A described list with a symbol descriptor and elements in their most compact encoding
+        let buf = vec![
+            0x00, 0xa3, 0x07, b'f', b'o', b'o', b':', b'b', b'a', b'r', // descriptor
+            0xc0, 0x0e, 0x05, // list8
+            0x41, // true
+            0x52, 0x05, // smalluint
+            0x44, // ulong0
+            0xa1, 0x02, b'h', b'i', // str8
+            0xc1, 0x03, 0x02, 0x43, 0x40, // map8
+        ];
+        let value: Value = from_slice(&buf).unwrap();
+        let described = match &value {
+            Value::Described(described) => described,
+            _ => panic!("Expecting a described value"),
+        };
+        assert_eq!(described.descriptor, Descriptor::Name("foo:bar".into()));
+        assert!(matches!(&described.value, Value::List(list) if list.len() == 5));
+        assert_eq!(to_vec(&value).unwrap(), buf);
+    }
+
+    #[test]
+    fn test_raw_described_round_trip() {
+        use super::RawDescribed;
+        use crate::Value;
+
+        // The same described list but with non-compact encodings which are normalized by `Value`
+        let buf = vec![
+            0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x99, // ulong descriptor
+            0xd0, 0x00, 0x00, 0x00, 0x16, 0x00, 0x00, 0x00, 0x04, // list32
+            0x56, 0x01, // boolean
+            0x70, 0x00, 0x00, 0x00, 0x05, // uint
+            0xb1, 0x00, 0x00, 0x00, 0x02, b'h', b'i', // str32
+            0x00, 0x53, 0x01, 0x45, // nested described list0
+        ];
+        let value: Value = from_slice(&buf).unwrap();
+        assert_ne!(to_vec(&value).unwrap(), buf);
+
+        let raw: RawDescribed = from_slice(&buf).unwrap();
+        assert_eq!(raw.descriptor(), &Descriptor::Code(0x99));
+        assert_eq!(raw.as_bytes(), &buf[..]);
+        assert_eq!(to_vec(&raw).unwrap(), buf);
+        assert_eq!(crate::serialized_size(&raw).unwrap(), buf.len());
+        assert_eq!(
+            Value::Described(Box::new(raw.to_described_value().unwrap())),
+            value
+        );
+        assert_eq!(crate::to_value(&raw).unwrap(), value);
+
+        // Forwarded as part of another type
+        let forwarded = (1u8, raw, Some(2u8));
+        let forwarded_buf = to_vec(&forwarded).unwrap();
+        let (a, raw, b): (u8, RawDescribed, Option<u8>) = from_slice(&forwarded_buf).unwrap();
+        assert_eq!((a, b), (1, Some(2)));
+        assert_eq!(raw.into_bytes(), buf);
+
+        // Not a described type
+        assert!(from_slice::<RawDescribed>(&[0x40]).is_err());
+    }
 }
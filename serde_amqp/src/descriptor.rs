@@ -1,7 +1,7 @@
 //! Definition of `Descriptor` type.
 
 use crate::__constants::DESCRIPTOR;
-use crate::primitives::Symbol;
+use crate::primitives::{symbol::interned_on_decode, Symbol};
 
 /// Descriptor of a described type
 ///
@@ -32,7 +32,10 @@ use std::convert::TryInto;
 use serde::de::{self, VariantAccess};
 use serde::ser::Serialize;
 
+use crate::de::Deserializer;
 use crate::format_code::EncodingCodes;
+use crate::read::SliceReader;
+use crate::Error;
 
 impl Serialize for Descriptor {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -130,6 +133,35 @@ impl<'de> de::Deserialize<'de> for Descriptor {
     }
 }
 
+/// Reads only the descriptor of the described type at the beginning of `bytes`
+///
+/// The value that follows the descriptor is not deserialized and is left for the caller to
+/// interpret. An error will be returned if `bytes` does not begin with a described type.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_amqp::{peek_descriptor, descriptor::Descriptor, described::Described, to_vec};
+///
+/// let described = Described {
+///     descriptor: Descriptor::Code(0x70),
+///     value: true,
+/// };
+/// let buf = to_vec(&described).unwrap();
+/// assert_eq!(peek_descriptor(&buf).unwrap(), Descriptor::Code(0x70));
+/// ```
+pub fn peek_descriptor(bytes: &[u8]) -> Result<Descriptor, Error> {
+    match bytes.first() {
+        Some(&code) if code == EncodingCodes::DescribedType as u8 => {}
+        Some(_) => return Err(Error::InvalidFormatCode),
+        None => return Err(Error::unexpected_eof("Expecting a described type")),
+    }
+
+    let reader = SliceReader::new(bytes);
+    let mut deserializer = Deserializer::new(reader);
+    de::Deserialize::deserialize(&mut deserializer)
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum PeekDescriptor {
     /// A name descriptor
@@ -151,14 +183,18 @@ impl<'de> de::Visitor<'de> for PeekDescriptorVisitor {
     where
         E: de::Error,
     {
-        Ok(PeekDescriptor::Name(Symbol::from(v)))
+        Ok(PeekDescriptor::Name(
+            interned_on_decode(v).unwrap_or_else(|| Symbol::from(v)),
+        ))
     }
 
     fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        Ok(PeekDescriptor::Name(Symbol::from(v)))
+        Ok(PeekDescriptor::Name(
+            interned_on_decode(&v).unwrap_or_else(|| Symbol::from(v)),
+        ))
     }
 
     fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
@@ -247,4 +283,27 @@ mod tests {
         let expected = PeekDescriptor::Name(Symbol::from("test:name"));
         assert_eq!(peek, expected);
     }
+
+    #[test]
+    fn test_peek_descriptor_fn() {
+        use crate::{described::Described, descriptor::peek_descriptor, ser::to_vec, Error};
+
+        let described = Described {
+            descriptor: Descriptor::Name(Symbol::from("test:name")),
+            value: vec![1u32, 2, 3],
+        };
+        let buf = to_vec(&described).unwrap();
+        let descriptor = peek_descriptor(&buf).unwrap();
+        assert_eq!(descriptor, Descriptor::Name(Symbol::from("test:name")));
+
+        // Not a described type
+        let buf = to_vec(&13u32).unwrap();
+        assert!(matches!(
+            peek_descriptor(&buf),
+            Err(Error::InvalidFormatCode)
+        ));
+
+        // Empty buffer
+        assert!(matches!(peek_descriptor(&[]), Err(Error::Io(_))));
+    }
 }
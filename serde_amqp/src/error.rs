@@ -3,6 +3,8 @@
 use serde::{de, ser};
 use std::fmt::Display;
 
+use crate::{descriptor::Descriptor, Value};
+
 // pub type Result<T> = core::result::Result<T, Error>;
 
 /// Custom serialization/deserialization errors
@@ -39,9 +41,73 @@ pub enum Error {
     /// Length is invalid
     #[error("Invalid length")]
     InvalidLength,
+
+    /// A described type that is not claimed by a concrete type is found in strict mode
+    #[error("Unrecognized descriptor {}", display_descriptor(.0))]
+    UnrecognizedDescriptor(Descriptor),
+
+    /// The encoded element count of a list, array or map exceeds the configured maximum
+    #[error("Container too large: {0} elements")]
+    ContainerTooLarge(usize),
+
+    /// Lists, arrays or maps are nested deeper than the configured maximum
+    #[error("Nesting depth limit exceeded")]
+    DepthLimitExceeded,
+
+    /// A map contains the same key more than once while `DeserializerConfig::strict_map_keys` is
+    /// set
+    #[error("Duplicate map key {0:?}")]
+    DuplicateMapKey(Value),
+
+    /// The input is truncated or has an invalid format code at a byte offset
+    ///
+    /// This is only returned when deserializing from a slice, which wraps truncation and invalid
+    /// format code errors with the position at which they occurred.
+    #[error("{source} at byte offset {offset}{}", display_found(.found))]
+    AtOffset {
+        /// Number of bytes consumed from the input when the error occurred
+        offset: usize,
+
+        /// The last format code read before the error occurred
+        found: Option<u8>,
+
+        /// The underlying error, which describes what was expected
+        source: Box<Error>,
+    },
+}
+
+fn display_descriptor(descriptor: &Descriptor) -> String {
+    match descriptor {
+        Descriptor::Name(name) => name.to_string(),
+        Descriptor::Code(code) => format!("0x{:08x}:0x{:08x}", code >> 32, code & 0xffff_ffff),
+    }
+}
+
+fn display_found(found: &Option<u8>) -> String {
+    match found {
+        Some(code) => format!(" (last format code 0x{:02x})", code),
+        None => String::new(),
+    }
 }
 
 impl Error {
+    /// Returns the byte offset in the input at which the error occurred, if known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::AtOffset { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the error is caused by the input ending before a value is complete
+    pub fn is_unexpected_eof(&self) -> bool {
+        match self {
+            Error::Io(err) => err.kind() == std::io::ErrorKind::UnexpectedEof,
+            Error::AtOffset { source, .. } => source.is_unexpected_eof(),
+            _ => false,
+        }
+    }
+
     pub(crate) fn too_long() -> Self {
         let io_err = std::io::Error::new(std::io::ErrorKind::Other, "Too long");
         Self::Io(io_err)
@@ -0,0 +1,140 @@
+//! Adapter that serializes a `Result<T, E>` as either one of two described types
+//!
+//! By default, `Result<T, E>` is treated as an enum by serde and is thus encoded as a map of one
+//! key-value pair (see the [`enum` section](crate#warning-enum)). This adapter instead encodes
+//! `Ok(T)` and `Err(E)` as the bare `T` or `E`, which is useful when `T` and `E` are described
+//! types with different descriptors (for example a field that holds either one of two outcomes).
+//!
+//! When deserializing, the value is first decoded as `T`, and `E` is only tried if that fails.
+//! `T` and `E` should therefore be distinguishable, ie. having different descriptors.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::{Serialize, Deserialize};
+//! use serde_amqp::{SerializeComposite, DeserializeComposite, to_vec, from_slice};
+//!
+//! #[derive(Debug, PartialEq, SerializeComposite, DeserializeComposite)]
+//! #[amqp_contract(name = "example:success:list", encoding = "list")]
+//! struct Success {
+//!     id: u32,
+//! }
+//!
+//! #[derive(Debug, PartialEq, SerializeComposite, DeserializeComposite)]
+//! #[amqp_contract(name = "example:failure:list", encoding = "list")]
+//! struct Failure {
+//!     reason: String,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Reply {
+//!     #[serde(with = "serde_amqp::extensions::described_result")]
+//!     outcome: Result<Success, Failure>,
+//! }
+//!
+//! let reply = Reply { outcome: Err(Failure { reason: String::from("not found") }) };
+//! let buf = to_vec(&reply).unwrap();
+//! let decoded: Reply = from_slice(&buf).unwrap();
+//! assert_eq!(decoded, reply);
+//! ```
+
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{from_slice, to_vec, Value};
+
+/// Serializes `Ok(T)` as `T` and `Err(E)` as `E`
+pub fn serialize<T, E, S>(value: &Result<T, E>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    E: Serialize,
+    S: Serializer,
+{
+    match value {
+        Ok(value) => value.serialize(serializer),
+        Err(error) => error.serialize(serializer),
+    }
+}
+
+/// Deserializes into `Ok(T)` if the value can be decoded as `T`, otherwise into `Err(E)`
+pub fn deserialize<'de, T, E, D>(deserializer: D) -> Result<Result<T, E>, D::Error>
+where
+    T: DeserializeOwned,
+    E: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    // The described value is buffered and re-encoded because the value deserializer does not
+    // support described composite types
+    let value = Value::deserialize(deserializer)?;
+    let buf = to_vec(&value).map_err(de::Error::custom)?;
+    match from_slice::<T>(&buf) {
+        Ok(value) => Ok(Ok(value)),
+        Err(_) => from_slice::<E>(&buf).map(Err).map_err(de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_amqp_derive::{DeserializeComposite, SerializeComposite};
+
+    use crate::{described::Described, descriptor::Descriptor, from_slice, to_vec, value::Value};
+
+    use crate as serde_amqp;
+
+    #[derive(Debug, PartialEq, SerializeComposite, DeserializeComposite)]
+    #[amqp_contract(code = "0x0000_0000:0x0000_0001", encoding = "list")]
+    struct Success {
+        id: u32,
+    }
+
+    #[derive(Debug, PartialEq, SerializeComposite, DeserializeComposite)]
+    #[amqp_contract(code = "0x0000_0000:0x0000_0002", encoding = "list")]
+    struct Failure {
+        reason: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Reply {
+        #[serde(with = "super")]
+        outcome: Result<Success, Failure>,
+    }
+
+    #[test]
+    fn ok_serializes_as_first_described_type() {
+        let reply = Reply {
+            outcome: Ok(Success { id: 7 }),
+        };
+        let buf = to_vec(&reply).unwrap();
+        let value: Value = from_slice(&buf).unwrap();
+        let expected = Value::List(vec![Value::from(Described {
+            descriptor: Descriptor::Code(0x01),
+            value: Value::List(vec![Value::UInt(7)]),
+        })]);
+        assert_eq!(value, expected);
+
+        let decoded: Reply = from_slice(&buf).unwrap();
+        assert_eq!(decoded, reply);
+    }
+
+    #[test]
+    fn err_serializes_as_second_described_type() {
+        let reply = Reply {
+            outcome: Err(Failure {
+                reason: String::from("not found"),
+            }),
+        };
+        let buf = to_vec(&reply).unwrap();
+        let value: Value = from_slice(&buf).unwrap();
+        let expected = Value::List(vec![Value::from(Described {
+            descriptor: Descriptor::Code(0x02),
+            value: Value::List(vec![Value::String(String::from("not found"))]),
+        })]);
+        assert_eq!(value, expected);
+
+        let decoded: Reply = from_slice(&buf).unwrap();
+        assert_eq!(decoded, reply);
+    }
+}
@@ -2,3 +2,5 @@
 
 mod transparent_vec;
 pub use transparent_vec::*;
+
+pub mod described_result;
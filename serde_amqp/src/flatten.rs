@@ -0,0 +1,33 @@
+//! Traits implemented by the derive macros to support `#[amqp_contract(flatten)]`
+//!
+//! The fields of a struct that is flattened are inlined into the list of the parent struct. The
+//! derive macros implement these traits for structs with the `"list"` encoding and without a
+//! descriptor.
+
+use serde::{de::SeqAccess, ser::SerializeStruct};
+
+/// Serialize the fields of a struct into the list of the struct it is flattened into
+pub trait SerializeFlattened {
+    /// Number of items inlined into the parent list
+    const FIELD_COUNT: usize;
+
+    /// Serialize the fields into the parent list
+    ///
+    /// The null fields are buffered in `nulls` so that the parent can still omit the trailing
+    /// null fields
+    fn serialize_flattened<S>(
+        &self,
+        state: &mut S,
+        nulls: &mut Vec<&'static str>,
+    ) -> Result<(), S::Error>
+    where
+        S: SerializeStruct;
+}
+
+/// Deserialize the fields of a struct from the list of the struct it is flattened into
+pub trait DeserializeFlattened<'de>: Sized {
+    /// Read the fields from the parent list, leaving the remaining items untouched
+    fn deserialize_flattened<A>(seq: A) -> Result<Self, A::Error>
+    where
+        A: SeqAccess<'de>;
+}
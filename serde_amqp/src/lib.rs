@@ -14,12 +14,14 @@
 //! Serialization:
 //!
 //! - [`to_vec`]
+//! - [`to_vec_canonical`], which sorts map entries by their encoded keys
 //! - [`serialized_size`]
 //!
 //! Deserialization:
 //!
 //! - [`from_slice`]
 //! - [`from_reader`]
+//! - [`from_slice_with_config`] and [`from_reader_with_config`] with a [`DeserializerConfig`]
 //!
 //! # Primitive types
 //!
@@ -244,6 +246,8 @@
 //!
 //! 1. `TransparentVec` - a thin wrapper around `Vec` that is serialized/deserialized as a sequence
 //!    of elements `Vec` is treated as an AMQP `List` in the core spec
+//! 2. `described_result` - an adapter to be used with `#[serde(with = "...")]` that serializes a
+//!    `Result<T, E>` as either the described type `T` or the described type `E`
 
 // Public mods
 pub mod de;
@@ -268,6 +272,11 @@ pub mod extensions;
 #[path = "constants.rs"]
 pub mod __constants;
 
+// Private mod but is used by derive macros to flatten structs
+#[doc(hidden)]
+#[path = "flatten.rs"]
+pub mod __flatten;
+
 // Private mods
 mod util;
 
@@ -276,9 +285,13 @@ mod format;
 
 pub use serde;
 
-pub use de::{from_reader, from_slice};
+pub use de::{
+    from_reader, from_reader_with_config, from_slice, from_slice_prefix,
+    from_slice_prefix_with_config, from_slice_with_config, DeserializerConfig,
+};
+pub use descriptor::peek_descriptor;
 pub use error::Error;
-pub use ser::to_vec;
+pub use ser::{to_vec, to_vec_canonical};
 pub use size_ser::serialized_size;
 pub use value::{de::from_value, ser::to_value, Value};
 
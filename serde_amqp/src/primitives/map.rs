@@ -3,12 +3,30 @@ use std::{hash::Hash, marker::PhantomData, ops::RangeBounds};
 use indexmap::{Equivalent, IndexMap};
 use serde::{de, ser::SerializeMap, Deserialize, Serialize};
 
-pub use indexmap::map::{Drain, IntoKeys, IntoValues, Iter, IterMut, Keys, Values, ValuesMut};
+pub use indexmap::map::{
+    Drain, Entry, IntoKeys, IntoValues, Iter, IterMut, Keys, OccupiedEntry, VacantEntry, Values,
+    ValuesMut,
+};
 
 /// A wrapper around [`IndexMap`] with custom implementation of [`PartialEq`], [`Eq`],
 /// [`PartialOrd`], [`Ord`], [`Hash`], [`Serialize`], and [`Deserialize`].
 ///
 /// Only a selected list of methods are re-exported for convenience.
+///
+/// # Ordering
+///
+/// Iteration (including [`IntoIterator`], [`iter`](#method.iter), [`keys`](#method.keys) and
+/// [`values`](#method.values)) and serialization always yield the entries in insertion order, and
+/// deserialization keeps the order in which the entries appear in the encoded map. Re-serializing
+/// a decoded map therefore produces the same key order, which makes the encoding deterministic
+/// (eg. for signing or hashing the payload).
+///
+/// - Inserting a key that is already present updates the value in place without changing its
+///   position.
+/// - [`shift_remove`](#method.shift_remove) and [`shift_remove_entry`](#method.shift_remove_entry)
+///   keep the order of the remaining entries. [`remove`](#method.remove),
+///   [`remove_entry`](#method.remove_entry) and [`swap_remove`](#method.swap_remove) are faster
+///   but move the last entry into the position of the removed one.
 #[derive(Debug, Clone, Default)]
 pub struct OrderedMap<K, V>(IndexMap<K, V>);
 
@@ -124,11 +142,23 @@ where
 {
     /// Insert a key-value pair in the map.
     ///
+    /// If an equivalent key already exists in the map, the value is updated in place without
+    /// changing the position of the entry, and the old value is returned.
+    ///
     /// Calls [`IndexMap::insert`] internally
     pub fn insert(&mut self, key: K, value: V) -> Option<V> {
         self.0.insert(key, value)
     }
 
+    /// Get the given key's corresponding entry in the map for in-place manipulation.
+    ///
+    /// A vacant entry is inserted at the end of the map, and an occupied entry keeps its position.
+    ///
+    /// Calls [`IndexMap::entry`] internally
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        self.0.entry(key)
+    }
+
     /// Calls [`IndexMap::get`] internally
     pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<&V>
     where
@@ -147,6 +177,9 @@ where
 
     /// Remove the key-value pair equivalent to key and return its value.
     ///
+    /// The last entry is moved into the position of the removed entry. Use
+    /// [`shift_remove`](#method.shift_remove) to preserve the order of the remaining entries.
+    ///
     /// Calls [`IndexMap::remove`] internally
     pub fn remove<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
     where
@@ -157,6 +190,10 @@ where
 
     /// Remove and return the key-value pair equivalent to key.
     ///
+    /// The last entry is moved into the position of the removed entry. Use
+    /// [`shift_remove_entry`](#method.shift_remove_entry) to preserve the order of the remaining
+    /// entries.
+    ///
     /// Calls [`IndexMap::remove_entry`] internally
     pub fn remove_entry<Q: ?Sized>(&mut self, key: &Q) -> Option<(K, V)>
     where
@@ -165,6 +202,42 @@ where
         self.0.remove_entry(key)
     }
 
+    /// Remove the key-value pair equivalent to key and return its value.
+    ///
+    /// The order of the remaining entries is preserved. Computes in O(n) time.
+    ///
+    /// Calls [`IndexMap::shift_remove`] internally
+    pub fn shift_remove<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K>,
+    {
+        self.0.shift_remove(key)
+    }
+
+    /// Remove and return the key-value pair equivalent to key.
+    ///
+    /// The order of the remaining entries is preserved. Computes in O(n) time.
+    ///
+    /// Calls [`IndexMap::shift_remove_entry`] internally
+    pub fn shift_remove_entry<Q: ?Sized>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        Q: Hash + Equivalent<K>,
+    {
+        self.0.shift_remove_entry(key)
+    }
+
+    /// Remove the key-value pair equivalent to key and return its value.
+    ///
+    /// The last entry is moved into the position of the removed entry. Computes in O(1) time.
+    ///
+    /// Calls [`IndexMap::swap_remove`] internally
+    pub fn swap_remove<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: Hash + Equivalent<K>,
+    {
+        self.0.swap_remove(key)
+    }
+
     /// Return true if an equivalent to key exists in the map.
     ///
     /// Calls [`IndexMap::contains_key`] internally
@@ -349,3 +422,49 @@ where
         Self(index_map)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{from_slice, to_vec};
+
+    use super::OrderedMap;
+
+    #[test]
+    fn entry_updates_in_place() {
+        let mut map = OrderedMap::new();
+        map.insert("c", 1);
+        map.insert("a", 2);
+        map.insert("b", 3);
+
+        *map.entry("a").or_insert(0) += 10;
+        map.entry("d").or_insert(4);
+        map.insert("c", 5);
+
+        let entries: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(entries, [("c", 5), ("a", 12), ("b", 3), ("d", 4)]);
+    }
+
+    #[test]
+    fn shift_remove_preserves_order() {
+        let mut map: OrderedMap<_, _> = [("c", 1), ("a", 2), ("b", 3), ("d", 4)]
+            .into_iter()
+            .collect();
+        assert_eq!(map.shift_remove("a"), Some(2));
+        let keys: Vec<_> = map.into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["c", "b", "d"]);
+    }
+
+    #[test]
+    fn reserializing_decoded_map_preserves_key_order() {
+        let mut map = OrderedMap::new();
+        for key in ["zeta", "alpha", "mu", "beta"] {
+            map.insert(String::from(key), key.len() as u32);
+        }
+        let buf = to_vec(&map).unwrap();
+
+        let decoded: OrderedMap<String, u32> = from_slice(&buf).unwrap();
+        let keys: Vec<_> = decoded.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["zeta", "alpha", "mu", "beta"]);
+        assert_eq!(to_vec(&decoded).unwrap(), buf);
+    }
+}
@@ -4,7 +4,7 @@ mod array;
 mod binary_ref;
 mod decimal;
 mod map;
-mod symbol;
+pub(crate) mod symbol;
 mod timestamp;
 mod uuid;
 
@@ -1,6 +1,10 @@
 use std::{
     borrow::Borrow,
-    ops::{Deref, DerefMut},
+    cell::Cell,
+    collections::HashSet,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{Arc, RwLock},
 };
 
 use serde::{
@@ -135,63 +139,271 @@ impl<'de> de::Deserialize<'de> for SymbolRef<'de> {
 /// to cache all the distinct values. Symbols are encoded as ASCII characters.
 ///
 /// Symbol should only contain ASCII characters. The implementation, however, wraps
-/// over a String. `AmqpNetLite` also wraps around a String, which in c# is utf-16.
-#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Symbol(pub String);
+/// over a `str`. `AmqpNetLite` also wraps around a String, which in c# is utf-16.
+///
+/// # Interning
+///
+/// The string is either borrowed from a `&'static str` or stored in an `Arc<str>` so that symbols
+/// can share their backing storage. [`Symbol::from_static`] wraps a static string without
+/// allocating, which is how the descriptor names of the core specification are represented.
+/// [`Symbol::intern`] registers a symbol in a process-wide interner, which is pre-populated with
+/// the descriptor names defined in the core specification. If
+/// [`DeserializerConfig::intern_symbols`](crate::de::DeserializerConfig::intern_symbols) is set,
+/// deserializing a symbol that has been interned returns a clone of the interned symbol instead of
+/// allocating a new one. Equality, ordering and hashing only depend on the string content.
+#[derive(Clone)]
+pub struct Symbol(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Static(&'static str),
+    Shared(Arc<str>),
+}
+
+impl Default for Symbol {
+    fn default() -> Self {
+        Self::from_static("")
+    }
+}
 
 impl Symbol {
     /// Creates a new [`Symbol`]
     pub fn new(val: impl Into<String>) -> Self {
-        Self(val.into())
+        Self(Repr::Shared(Arc::from(val.into())))
+    }
+
+    /// Creates a [`Symbol`] that borrows a static string without allocating
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_amqp::primitives::Symbol;
+    ///
+    /// const ACCEPTED: Symbol = Symbol::from_static("amqp:accepted:list");
+    /// assert_eq!(ACCEPTED, Symbol::from("amqp:accepted:list"));
+    /// ```
+    pub const fn from_static(val: &'static str) -> Self {
+        Self(Repr::Static(val))
+    }
+
+    /// Returns the interned [`Symbol`] with the same value, registering `val` in the interner if
+    /// it is not interned yet
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_amqp::primitives::Symbol;
+    ///
+    /// let a = Symbol::intern("com.example:my-type");
+    /// let b = Symbol::intern("com.example:my-type");
+    /// assert!(Symbol::ptr_eq(&a, &b));
+    /// ```
+    pub fn intern(val: &str) -> Self {
+        if let Some(symbol) = Self::interned(val) {
+            return symbol;
+        }
+
+        let mut guard = INTERNER.write().unwrap_or_else(|e| e.into_inner());
+        let set = guard.get_or_insert_with(spec_symbols);
+        match set.get(val) {
+            Some(symbol) => symbol.clone(),
+            None => {
+                let symbol = Symbol::from(val);
+                set.insert(symbol.clone());
+                symbol
+            }
+        }
+    }
+
+    /// Returns the interned [`Symbol`] with the same value without registering it
+    pub fn interned(val: &str) -> Option<Self> {
+        {
+            let guard = INTERNER.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(set) = guard.as_ref() {
+                return set.get(val).cloned();
+            }
+        }
+
+        let mut guard = INTERNER.write().unwrap_or_else(|e| e.into_inner());
+        guard.get_or_insert_with(spec_symbols).get(val).cloned()
+    }
+
+    /// Returns true if both symbols share the same backing storage
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        std::ptr::eq(this.as_str(), other.as_str())
+    }
+
+    /// Returns true if the symbol borrows a static string
+    pub fn is_static(&self) -> bool {
+        matches!(self.0, Repr::Static(_))
     }
 
     /// Consume the wrapper into the inner string
     pub fn into_inner(self) -> String {
-        self.0
+        self.as_str().to_string()
     }
 
     /// Returns the inner value as str
     pub fn as_str(&self) -> &str {
-        &self.0[..]
+        match &self.0 {
+            Repr::Static(val) => val,
+            Repr::Shared(val) => val,
+        }
+    }
+}
+
+impl std::fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Symbol").field(&self.as_str()).finish()
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+/// Process-wide symbol interner. This is lazily populated with [`SPEC_SYMBOLS`]
+static INTERNER: RwLock<Option<HashSet<Symbol>>> = RwLock::new(None);
+
+thread_local! {
+    /// Whether symbols decoded on this thread are looked up in [`INTERNER`]. This is only set by
+    /// [`with_interning`]
+    static INTERN_ON_DECODE: Cell<bool> = Cell::new(false);
+}
+
+/// Runs `f` with the lookup of decoded symbols in the interner enabled on the current thread
+pub(crate) fn with_interning<T>(f: impl FnOnce() -> T) -> T {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            INTERN_ON_DECODE.with(|flag| flag.set(self.0));
+        }
+    }
+
+    let _reset = Reset(INTERN_ON_DECODE.with(|flag| flag.replace(true)));
+    f()
+}
+
+/// Returns the interned [`Symbol`] if the lookup of decoded symbols is enabled on the current
+/// thread. This doesn't take the lock on [`INTERNER`] otherwise
+pub(crate) fn interned_on_decode(val: &str) -> Option<Symbol> {
+    match INTERN_ON_DECODE.with(Cell::get) {
+        true => Symbol::interned(val),
+        false => None,
     }
 }
 
+/// Descriptor names defined in the core specification
+const SPEC_SYMBOLS: &[&str] = &[
+    // Transport performatives
+    "amqp:open:list",
+    "amqp:begin:list",
+    "amqp:attach:list",
+    "amqp:flow:list",
+    "amqp:transfer:list",
+    "amqp:disposition:list",
+    "amqp:detach:list",
+    "amqp:end:list",
+    "amqp:close:list",
+    "amqp:error:list",
+    // Message format
+    "amqp:header:list",
+    "amqp:delivery-annotations:map",
+    "amqp:message-annotations:map",
+    "amqp:properties:list",
+    "amqp:application-properties:map",
+    "amqp:data:binary",
+    "amqp:amqp-sequence:list",
+    "amqp:amqp-value:*",
+    "amqp:footer:map",
+    // Delivery states
+    "amqp:received:list",
+    "amqp:accepted:list",
+    "amqp:rejected:list",
+    "amqp:released:list",
+    "amqp:modified:list",
+    // Terminus
+    "amqp:source:list",
+    "amqp:target:list",
+    "amqp:delete-on-close:list",
+    "amqp:delete-on-no-links:list",
+    "amqp:delete-on-no-messages:list",
+    "amqp:delete-on-no-links-or-messages:list",
+    // Transactions
+    "amqp:coordinator:list",
+    "amqp:declare:list",
+    "amqp:discharge:list",
+    "amqp:declared:list",
+    "amqp:transactional-state:list",
+    // Security
+    "amqp:sasl-mechanisms:list",
+    "amqp:sasl-init:list",
+    "amqp:sasl-challenge:list",
+    "amqp:sasl-response:list",
+    "amqp:sasl-outcome:list",
+];
+
+fn spec_symbols() -> HashSet<Symbol> {
+    SPEC_SYMBOLS
+        .iter()
+        .copied()
+        .map(Symbol::from_static)
+        .collect()
+}
+
 impl From<String> for Symbol {
     fn from(val: String) -> Self {
-        Self(val)
+        Self::new(val)
     }
 }
 
 impl From<&str> for Symbol {
     fn from(val: &str) -> Self {
-        Self(val.into())
+        Self(Repr::Shared(Arc::from(val)))
     }
 }
 
 impl<'a> From<SymbolRef<'a>> for Symbol {
     fn from(value: SymbolRef<'a>) -> Self {
-        Self(value.0.into())
+        Self::from(value.0)
     }
 }
 
 impl Deref for Symbol {
-    type Target = String;
+    type Target = str;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        self.as_str()
     }
 }
 
-impl DerefMut for Symbol {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
-
-/// The `Ord` and `Hash` is exactly the same as wrapped `String`, which is the same as `&str`
+/// The `Ord` and `Hash` is exactly the same as wrapped `str`
 impl Borrow<str> for Symbol {
     fn borrow(&self) -> &str {
-        &self.0[..]
+        self.as_str()
     }
 }
 
@@ -200,7 +412,7 @@ impl Serialize for Symbol {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_newtype_struct(SYMBOL, &self.0)
+        serializer.serialize_newtype_struct(SYMBOL, self.as_str())
     }
 }
 
@@ -217,14 +429,14 @@ impl<'de> Visitor<'de> for SymbolVisitor {
     where
         E: de::Error,
     {
-        Ok(Symbol::from(v))
+        Ok(interned_on_decode(v).unwrap_or_else(|| Symbol::from(v)))
     }
 
     fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        Ok(Symbol::from(v))
+        Ok(interned_on_decode(&v).unwrap_or_else(|| Symbol::from(v)))
     }
 
     fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
@@ -232,7 +444,7 @@ impl<'de> Visitor<'de> for SymbolVisitor {
         D: serde::Deserializer<'de>,
     {
         let val: String = de::Deserialize::deserialize(deserializer)?;
-        Ok(Symbol::new(val))
+        self.visit_string(val)
     }
 }
 
@@ -247,10 +459,22 @@ impl<'de> de::Deserialize<'de> for Symbol {
 
 #[cfg(test)]
 mod tests {
-    use crate::{from_slice, primitives::OrderedMap, to_vec};
+    use crate::{
+        de::{from_slice_with_config, DeserializerConfig},
+        from_slice,
+        primitives::OrderedMap,
+        to_vec,
+    };
 
     use super::{Symbol, SymbolRef};
 
+    fn intern_symbols() -> DeserializerConfig {
+        DeserializerConfig {
+            intern_symbols: true,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_serialize_symbol_ref() {
         let val = "hello AMQP";
@@ -286,4 +510,84 @@ mod tests {
         let val = map.get(&Symbol::from("hello"));
         assert_eq!(val, Some(&Value::String(String::from("world"))));
     }
+
+    #[test]
+    fn intern_returns_shared_symbol() {
+        let a = Symbol::intern("test:intern-shared");
+        let b = Symbol::intern("test:intern-shared");
+        assert!(Symbol::ptr_eq(&a, &b));
+        assert!(Symbol::interned("test:not-interned").is_none());
+
+        // Interning doesn't change equality or hashing
+        let c = Symbol::from("test:intern-shared");
+        assert!(!Symbol::ptr_eq(&a, &c));
+        assert_eq!(a, c);
+        let mut map = OrderedMap::new();
+        map.insert(c, 1);
+        assert_eq!(map.get(&a), Some(&1));
+        assert_eq!(map.get("test:intern-shared"), Some(&1));
+    }
+
+    #[test]
+    fn deserialized_spec_symbols_share_backing_storage() {
+        use crate::{described::Described, descriptor::Descriptor, Value};
+
+        let interned = Symbol::interned("amqp:application-properties:map").unwrap();
+        assert!(interned.is_static());
+        let described = Described {
+            descriptor: Descriptor::Name(interned.clone()),
+            value: Value::Map(OrderedMap::new()),
+        };
+        let buf = to_vec(&described).unwrap();
+
+        let deserialized: Vec<Described<Value>> = (0..1000)
+            .map(|_| from_slice_with_config(&buf, intern_symbols()).unwrap())
+            .collect();
+        for described in &deserialized {
+            match &described.descriptor {
+                Descriptor::Name(name) => assert!(Symbol::ptr_eq(name, &interned)),
+                Descriptor::Code(_) => panic!("Expecting a name"),
+            }
+        }
+    }
+
+    #[test]
+    fn user_interned_symbol_is_used_when_deserializing() {
+        let buf = to_vec(&Symbol::from("test:user-registered")).unwrap();
+        let before: Symbol = from_slice_with_config(&buf, intern_symbols()).unwrap();
+        let interned = Symbol::intern("test:user-registered");
+        assert!(!Symbol::ptr_eq(&before, &interned));
+
+        let after: Symbol = from_slice_with_config(&buf, intern_symbols()).unwrap();
+        assert!(Symbol::ptr_eq(&after, &interned));
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn interned_symbol_is_not_used_by_default() {
+        let interned = Symbol::intern("test:not-used-by-default");
+        let buf = to_vec(&interned).unwrap();
+
+        let deserialized: Symbol = from_slice(&buf).unwrap();
+        assert!(!Symbol::ptr_eq(&deserialized, &interned));
+        assert_eq!(deserialized, interned);
+    }
+
+    #[test]
+    fn static_symbol_borrows_without_allocating() {
+        const NAME: &str = "amqp:accepted:list";
+        const STATIC: Symbol = Symbol::from_static(NAME);
+
+        // The symbol points at the static string itself instead of a heap copy
+        assert!(STATIC.is_static());
+        assert!(std::ptr::eq(STATIC.as_str(), NAME));
+        assert!(!Symbol::from(NAME).is_static());
+
+        let owned = Symbol::from(String::from(NAME));
+        assert_eq!(STATIC, owned);
+        assert_eq!(to_vec(&STATIC).unwrap(), to_vec(&owned).unwrap());
+        assert_eq!(to_vec(&STATIC).unwrap(), to_vec(&SymbolRef(NAME)).unwrap());
+        let deserialized: Symbol = from_slice(&to_vec(&STATIC).unwrap()).unwrap();
+        assert_eq!(deserialized, STATIC);
+    }
 }
@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime};
+
 use serde::de;
 use serde::ser;
 
@@ -72,6 +74,61 @@ impl<'de> de::Deserialize<'de> for Timestamp {
     }
 }
 
+/// Error returned when a point in time cannot be converted to or from a [`Timestamp`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum TimestampOutOfRange {
+    /// The point in time is earlier than the earliest value representable by the target type
+    #[error("Point in time is before the earliest representable value")]
+    Underflow,
+
+    /// The point in time is later than the latest value representable by the target type
+    #[error("Point in time is after the latest representable value")]
+    Overflow,
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TimestampOutOfRange;
+
+    /// Points in time before the unix epoch are converted to negative timestamps, and
+    /// sub-millisecond precision is truncated towards the past.
+    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+        match value.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(since_epoch) => i64::try_from(since_epoch.as_millis())
+                .map(Self)
+                .map_err(|_| TimestampOutOfRange::Overflow),
+            Err(err) => {
+                let before_epoch = err.duration();
+                let mut millis = -(before_epoch.as_millis() as i128);
+                if before_epoch.subsec_nanos() % 1_000_000 != 0 {
+                    millis -= 1;
+                }
+                i64::try_from(millis)
+                    .map(Self)
+                    .map_err(|_| TimestampOutOfRange::Underflow)
+            }
+        }
+    }
+}
+
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = TimestampOutOfRange;
+
+    /// The conversion fails if the timestamp is outside of the range supported by
+    /// [`SystemTime`] on the current platform.
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        let offset = Duration::from_millis(value.0.unsigned_abs());
+        if value.0 >= 0 {
+            SystemTime::UNIX_EPOCH
+                .checked_add(offset)
+                .ok_or(TimestampOutOfRange::Overflow)
+        } else {
+            SystemTime::UNIX_EPOCH
+                .checked_sub(offset)
+                .ok_or(TimestampOutOfRange::Underflow)
+        }
+    }
+}
+
 /// Please note that this conversion does NOT check for overflow
 #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
 #[cfg(feature = "time")]
@@ -199,3 +256,95 @@ impl From<Timestamp> for Option<chrono::DateTime<chrono::Utc>> {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{Timestamp, TimestampOutOfRange};
+
+    // 2022-01-01T00:00:00Z
+    const KNOWN_DATE_MILLIS: i64 = 1_640_995_200_000;
+
+    #[test]
+    fn system_time_at_epoch() {
+        let timestamp = Timestamp::try_from(SystemTime::UNIX_EPOCH).unwrap();
+        assert_eq!(timestamp.milliseconds(), 0);
+        assert_eq!(
+            SystemTime::try_from(timestamp).unwrap(),
+            SystemTime::UNIX_EPOCH
+        );
+    }
+
+    #[test]
+    fn system_time_at_known_date() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(KNOWN_DATE_MILLIS as u64);
+        let timestamp = Timestamp::try_from(time).unwrap();
+        assert_eq!(timestamp.milliseconds(), KNOWN_DATE_MILLIS);
+        assert_eq!(SystemTime::try_from(timestamp).unwrap(), time);
+
+        // Sub-millisecond precision is truncated
+        let timestamp = Timestamp::try_from(time + Duration::from_micros(999)).unwrap();
+        assert_eq!(timestamp.milliseconds(), KNOWN_DATE_MILLIS);
+    }
+
+    #[test]
+    fn system_time_before_epoch() {
+        let time = SystemTime::UNIX_EPOCH - Duration::from_millis(1500);
+        let timestamp = Timestamp::try_from(time).unwrap();
+        assert_eq!(timestamp.milliseconds(), -1500);
+        assert_eq!(SystemTime::try_from(timestamp).unwrap(), time);
+
+        // Truncated towards the past rather than towards the epoch
+        let time = SystemTime::UNIX_EPOCH - Duration::from_micros(1500);
+        let timestamp = Timestamp::try_from(time).unwrap();
+        assert_eq!(timestamp.milliseconds(), -2);
+    }
+
+    #[test]
+    fn system_time_overflow_boundary() {
+        let max = Duration::from_millis(i64::MAX as u64);
+        let time = match SystemTime::UNIX_EPOCH.checked_add(max) {
+            Some(time) => time,
+            None => return, // Not representable on this platform
+        };
+        let timestamp = Timestamp::try_from(time).unwrap();
+        assert_eq!(timestamp.milliseconds(), i64::MAX);
+
+        if let Some(time) = time.checked_add(Duration::from_millis(1)) {
+            assert_eq!(
+                Timestamp::try_from(time),
+                Err(TimestampOutOfRange::Overflow)
+            );
+        }
+        if let Some(time) = SystemTime::UNIX_EPOCH.checked_sub(max + Duration::from_millis(2)) {
+            assert_eq!(
+                Timestamp::try_from(time),
+                Err(TimestampOutOfRange::Underflow)
+            );
+        }
+    }
+
+    #[cfg(feature = "chrono-preview")]
+    #[test]
+    fn chrono_date_time_conversions() {
+        use chrono::{DateTime, TimeZone, Utc};
+
+        let epoch = Utc.timestamp_millis_opt(0).unwrap();
+        assert_eq!(Timestamp::from(epoch).milliseconds(), 0);
+
+        let date = Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(Timestamp::from(date).milliseconds(), KNOWN_DATE_MILLIS);
+        let converted = DateTime::<Utc>::try_from(Timestamp::from(KNOWN_DATE_MILLIS)).unwrap();
+        assert_eq!(converted, date);
+
+        let before_epoch = DateTime::<Utc>::try_from(Timestamp::from(-1500)).unwrap();
+        assert_eq!(before_epoch.timestamp_millis(), -1500);
+
+        let out_of_range = Timestamp::from(i64::MAX);
+        assert_eq!(
+            DateTime::<Utc>::try_from(out_of_range.clone()),
+            Err(out_of_range)
+        );
+    }
+}
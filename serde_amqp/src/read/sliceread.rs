@@ -8,12 +8,18 @@ use super::{private, Read};
 #[derive(Debug)]
 pub struct SliceReader<'s> {
     slice: &'s [u8],
+    position: usize,
 }
 
 impl<'s> SliceReader<'s> {
     /// Creates a new slice reader
     pub fn new(slice: &'s [u8]) -> Self {
-        Self { slice }
+        Self { slice, position: 0 }
+    }
+
+    /// Returns the number of bytes consumed from the original slice
+    pub fn position(&self) -> usize {
+        self.position
     }
 
     /// Return a slice of the given length. If the internal slice doesn't have
@@ -24,6 +30,7 @@ impl<'s> SliceReader<'s> {
         }
         let (read_slice, remaining) = self.slice.split_at(n);
         self.slice = remaining;
+        self.position += n;
         Ok(read_slice)
     }
 }
@@ -119,6 +126,30 @@ mod tests {
         assert!(next_none.is_none());
     }
 
+    #[test]
+    fn test_position() {
+        let slice = LONG_BUFFER;
+        let mut reader = SliceReader::new(slice);
+        assert_eq!(reader.position(), 0);
+
+        reader.peek().expect("Should not return error");
+        assert_eq!(reader.position(), 0);
+
+        reader.next().expect("Should not return error");
+        assert_eq!(reader.position(), 1);
+
+        let mut buf = [0u8; 5];
+        reader
+            .read_exact(&mut buf)
+            .expect("Should not return error");
+        assert_eq!(reader.position(), 6);
+
+        reader
+            .read_const_bytes::<4>()
+            .expect("Should not return error");
+        assert_eq!(reader.position(), 10);
+    }
+
     #[test]
     fn test_read_const_bytes_without_peek() {
         let slice = LONG_BUFFER;
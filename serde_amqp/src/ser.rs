@@ -11,12 +11,13 @@ use serde::{
 use crate::{
     __constants::{
         ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIBED_LIST, DESCRIBED_MAP,
-        DESCRIPTOR, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID,
+        DESCRIPTOR, RAW, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID,
     },
     error::Error,
     format::{OFFSET_LIST32, OFFSET_LIST8, OFFSET_MAP32, OFFSET_MAP8},
     format_code::EncodingCodes,
     util::{FieldRole, IsArrayElement, NewType, StructEncoding},
+    Value,
 };
 
 pub(crate) const U8_MAX: usize = u8::MAX as usize;
@@ -42,6 +43,63 @@ where
     Ok(writer)
 }
 
+/// Serializes the given value into a byte vector with a canonical encoding
+///
+/// Unlike [`to_vec`], which preserves the insertion order of maps, the entries of every map
+/// (including maps nested in lists, arrays and described types) are sorted by the encoded bytes of
+/// their keys. Two maps with the same entries therefore produce identical bytes regardless of the
+/// order in which the entries were inserted, which is useful when the encoding needs to be signed
+/// or hashed. The order of the elements of lists and arrays is left as-is.
+///
+/// The value is first converted into a [`Value`] with [`to_value`](crate::to_value).
+pub fn to_vec_canonical<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+    T: Serialize,
+{
+    let value = canonicalize(crate::to_value(value)?)?;
+    to_vec(&value)
+}
+
+fn canonicalize(value: Value) -> Result<Value, Error> {
+    let value = match value {
+        Value::Described(mut described) => {
+            let inner = std::mem::take(&mut described.value);
+            described.value = canonicalize(inner)?;
+            Value::Described(described)
+        }
+        Value::List(list) => Value::List(
+            list.into_iter()
+                .map(canonicalize)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Array(array) => Value::Array(
+            array
+                .into_iter()
+                .map(canonicalize)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(map) => {
+            let mut entries = map
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = canonicalize(key)?;
+                    let encoded = to_vec(&key)?;
+                    Ok((encoded, key, canonicalize(value)?))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(_, key, value)| (key, value))
+                    .collect(),
+            )
+        }
+        value => value,
+    };
+    Ok(value)
+}
+
 /// A struct for serializing Rust structs/values into AMQP1.0 wire format
 #[derive(Debug)]
 pub struct Serializer<W> {
@@ -571,6 +629,13 @@ impl<'a, W: Write + 'a> ser::Serializer for &'a mut Serializer<W> {
                 }
                 self.new_type = NewType::None;
             }
+            // The raw bytes already contain the constructor, which cannot be elided in an array
+            NewType::Raw => {
+                if let IsArrayElement::OtherElement = self.is_array_elem {
+                    return Err(Error::InvalidValue);
+                }
+                self.new_type = NewType::None;
+            }
             // Timestamp should be handled by i64
             NewType::Timestamp
             | NewType::Array
@@ -654,6 +719,8 @@ impl<'a, W: Write + 'a> ser::Serializer for &'a mut Serializer<W> {
             self.new_type = NewType::Uuid;
         } else if name == TRANSPARENT_VEC {
             self.new_type = NewType::TransparentVec;
+        } else if name == RAW {
+            self.new_type = NewType::Raw;
         }
         value.serialize(self)
     }
@@ -674,9 +741,21 @@ impl<'a, W: Write + 'a> ser::Serializer for &'a mut Serializer<W> {
         if name == DESCRIPTOR
         // || name == VALUE || name == AMQP_ERROR || name == CONNECTION_ERROR || name == SESSION_ERROR || name == LINK_ERROR
         {
-            let code = [EncodingCodes::DescribedType as u8];
-            self.writer.write_all(&code)?;
-            value.serialize(self)
+            match self.is_array_elem {
+                IsArrayElement::False => {
+                    let code = [EncodingCodes::DescribedType as u8];
+                    self.writer.write_all(&code)?;
+                    value.serialize(self)
+                }
+                // The descriptor is part of the element constructor shared by all elements
+                IsArrayElement::FirstElement => {
+                    let code = [EncodingCodes::DescribedType as u8];
+                    self.writer.write_all(&code)?;
+                    let mut serializer = Serializer::new(&mut self.writer);
+                    value.serialize(&mut serializer)
+                }
+                IsArrayElement::OtherElement => Ok(()),
+            }
         } else {
             let mut state = self.serialize_map(Some(1))?;
             state.serialize_entry(&variant_index, value)?;
@@ -872,7 +951,8 @@ impl<'a, W: Write + 'a> ser::SerializeSeq for SeqSerializer<'a, W> {
             | NewType::Symbol
             | NewType::SymbolRef
             | NewType::Timestamp
-            | NewType::Uuid => unreachable!(),
+            | NewType::Uuid
+            | NewType::Raw => unreachable!(),
         }
 
         self.num += 1;
@@ -892,7 +972,8 @@ impl<'a, W: Write + 'a> ser::SerializeSeq for SeqSerializer<'a, W> {
             | NewType::Symbol
             | NewType::SymbolRef
             | NewType::Timestamp
-            | NewType::Uuid => unreachable!(),
+            | NewType::Uuid
+            | NewType::Raw => unreachable!(),
         }
     }
 }
@@ -903,6 +984,14 @@ fn write_array<'a, W: Write + 'a>(
     buf: &'a [u8],
     ext_is_array_elem: &IsArrayElement,
 ) -> Result<(), Error> {
+    // The element constructor is mandatory even if the array is empty. The element type of an
+    // empty array is unknown, so the null constructor is used
+    let empty_elem_constructor = [EncodingCodes::Null as u8];
+    let buf = if buf.is_empty() {
+        &empty_elem_constructor[..]
+    } else {
+        buf
+    };
     let len = buf.len();
 
     match len {
@@ -1023,6 +1112,72 @@ fn write_list<'a, W: Write + 'a>(
     Ok(())
 }
 
+/// The list of a described type in an array is always encoded as `list32` so that all elements
+/// share the same constructor
+fn write_described_list<'a, W: Write + 'a>(
+    writer: W,
+    num: usize,
+    buf: &'a [u8],
+    ext_is_array_elem: &IsArrayElement,
+) -> Result<(), Error> {
+    match ext_is_array_elem {
+        IsArrayElement::False => write_list(writer, num, buf, ext_is_array_elem),
+        IsArrayElement::FirstElement | IsArrayElement::OtherElement => write_compound32(
+            writer,
+            EncodingCodes::List32,
+            OFFSET_LIST32,
+            num,
+            buf,
+            ext_is_array_elem,
+        ),
+    }
+}
+
+/// The map of a described type in an array is always encoded as `map32` so that all elements
+/// share the same constructor
+fn write_described_map<'a, W: Write + 'a>(
+    writer: W,
+    num: usize,
+    buf: &'a [u8],
+    ext_is_array_elem: &IsArrayElement,
+) -> Result<(), Error> {
+    match ext_is_array_elem {
+        IsArrayElement::False => write_map(writer, num, buf, ext_is_array_elem),
+        IsArrayElement::FirstElement | IsArrayElement::OtherElement => write_compound32(
+            writer,
+            EncodingCodes::Map32,
+            OFFSET_MAP32,
+            num,
+            buf,
+            ext_is_array_elem,
+        ),
+    }
+}
+
+fn write_compound32<'a, W: Write + 'a>(
+    mut writer: W,
+    code: EncodingCodes,
+    offset: usize,
+    num: usize,
+    buf: &'a [u8],
+    ext_is_array_elem: &IsArrayElement,
+) -> Result<(), Error> {
+    let len = buf.len();
+    if len > U32_MAX_MINUS_4 {
+        return Err(Error::too_long());
+    }
+    if let IsArrayElement::False | IsArrayElement::FirstElement = ext_is_array_elem {
+        writer.write_all(&[code as u8])?;
+    }
+    // Length including the four bytes taken by `num`
+    let len = ((len + offset) as u32).to_be_bytes();
+    let num = (num as u32).to_be_bytes();
+    writer.write_all(&len)?;
+    writer.write_all(&num)?;
+    writer.write_all(buf)?;
+    Ok(())
+}
+
 /// Serializer for map types
 #[derive(Debug)]
 pub struct MapSerializer<'a, W: 'a> {
@@ -1225,11 +1380,11 @@ impl<'a, W: Write + 'a> ser::SerializeTupleStruct for TupleStructSerializer<'a,
             }
             StructEncoding::DescribedList => {
                 self.se.struct_encoding.pop();
-                write_list(
+                write_described_list(
                     &mut self.se.writer,
                     self.count,
                     &self.buf,
-                    &IsArrayElement::False,
+                    &self.se.is_array_elem,
                 )
             }
             StructEncoding::DescribedMap => {
@@ -1319,7 +1474,7 @@ impl<'a, W: Write + 'a> ser::SerializeStruct for StructSerializer<'a, W> {
             // The wrapper of value is always the `Described` struct. `Described` constructor is handled elsewhere
             StructEncoding::DescribedList => {
                 self.se.struct_encoding.pop();
-                write_list(
+                write_described_list(
                     &mut self.se.writer,
                     self.count,
                     &self.buf,
@@ -1329,7 +1484,7 @@ impl<'a, W: Write + 'a> ser::SerializeStruct for StructSerializer<'a, W> {
             // The wrapper of value is always the `Described` struct. `Described` constructor is handled elsewhere
             StructEncoding::DescribedMap => {
                 self.se.struct_encoding.pop();
-                write_map(
+                write_described_map(
                     &mut self.se.writer,
                     self.count * 2,
                     &self.buf,
@@ -1842,6 +1997,18 @@ mod test {
         assert_eq_on_serialized_vs_expected(val, &expected);
     }
 
+    #[test]
+    fn test_serialize_empty_array() {
+        let val: Array<Symbol> = Array::from(vec![]);
+        let expected = vec![
+            EncodingCodes::Array8 as u8,
+            2, // length including `count` and element constructor
+            0, // count
+            EncodingCodes::Null as u8,
+        ];
+        assert_eq_on_serialized_vs_expected(val, &expected);
+    }
+
     #[test]
     fn test_serialzie_slice_as_list() {
         // slice will call `serialize_tuple`
@@ -1955,6 +2122,47 @@ mod test {
         assert_eq_on_serialized_vs_expected(val, &expected);
     }
 
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct WithBytes {
+        #[serde(with = "serde_bytes")]
+        payload: Vec<u8>,
+    }
+
+    #[test]
+    fn test_serialize_serde_bytes_field_as_vbin() {
+        // vbin8
+        let val = WithBytes {
+            payload: vec![1, 2, 3],
+        };
+        let expected = vec![
+            EncodingCodes::List8 as u8,
+            1 + 2 + 3, // 1 for count, 2 for vbin8 constructor and width
+            1,         // count
+            EncodingCodes::VBin8 as u8,
+            3,
+            1,
+            2,
+            3,
+        ];
+        assert_eq_on_serialized_vs_expected(&val, &expected);
+        let deserialized: WithBytes = crate::from_slice(&expected).unwrap();
+        assert_eq!(deserialized, val);
+
+        // vbin32
+        let val = WithBytes {
+            payload: vec![7; 256],
+        };
+        let mut expected = vec![EncodingCodes::List32 as u8];
+        expected.extend_from_slice(&(4 + 1 + 4 + 256u32).to_be_bytes()); // size
+        expected.extend_from_slice(&1u32.to_be_bytes()); // count
+        expected.push(EncodingCodes::VBin32 as u8);
+        expected.extend_from_slice(&256u32.to_be_bytes());
+        expected.extend_from_slice(&[7; 256]);
+        assert_eq_on_serialized_vs_expected(&val, &expected);
+        let deserialized: WithBytes = crate::from_slice(&expected).unwrap();
+        assert_eq!(deserialized, val);
+    }
+
     #[cfg(feature = "serde_amqp_derive")]
     #[test]
     fn test_serialize_described_macro() {
@@ -2401,4 +2609,35 @@ mod test {
         let buf = to_vec(&data).unwrap();
         println!("{:#x?}", buf);
     }
+
+    #[test]
+    fn canonical_map_encoding_is_independent_of_insertion_order() {
+        use crate::primitives::OrderedMap;
+
+        let mut nested = OrderedMap::new();
+        nested.insert(Value::from("y"), Value::Int(2));
+        nested.insert(Value::from("x"), Value::Int(1));
+
+        let mut first = OrderedMap::new();
+        first.insert(Value::from("b"), Value::Map(nested.clone()));
+        first.insert(Value::from("a"), Value::UInt(1));
+        first.insert(Value::Symbol(Symbol::from("c")), Value::Null);
+
+        let nested: OrderedMap<Value, Value> = nested.into_iter().rev().collect();
+        let mut second = OrderedMap::new();
+        second.insert(Value::Symbol(Symbol::from("c")), Value::Null);
+        second.insert(Value::from("a"), Value::UInt(1));
+        second.insert(Value::from("b"), Value::Map(nested));
+
+        let first = Value::Map(first);
+        let second = Value::Map(second);
+        assert_ne!(to_vec(&first).unwrap(), to_vec(&second).unwrap());
+
+        let canonical = to_vec_canonical(&first).unwrap();
+        assert_eq!(canonical, to_vec_canonical(&second).unwrap());
+
+        // The canonical bytes decode into a map that is already in canonical order
+        let decoded: Value = crate::from_slice(&canonical).unwrap();
+        assert_eq!(to_vec(&decoded).unwrap(), canonical);
+    }
 }
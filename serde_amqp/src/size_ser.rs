@@ -6,7 +6,7 @@ use crate::{
     Error,
     __constants::{
         ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIBED_LIST, DESCRIBED_MAP,
-        DESCRIPTOR, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID,
+        DESCRIPTOR, RAW, SYMBOL, SYMBOL_REF, TIMESTAMP, TRANSPARENT_VEC, UUID,
     },
     ser::{U32_MAX_MINUS_4, U8_MAX, U8_MAX_MINUS_1, U8_MAX_PLUS_1},
     util::{FieldRole, IsArrayElement, NewType, StructEncoding},
@@ -266,6 +266,11 @@ impl<'a> ser::Serializer for &'a mut SizeSerializer {
                 IsArrayElement::FirstElement => Ok(1 + l),
                 IsArrayElement::OtherElement => Ok(l),
             },
+            NewType::Raw => match self.is_array_element {
+                IsArrayElement::False => Ok(l),
+                IsArrayElement::FirstElement => Ok(l),
+                IsArrayElement::OtherElement => Err(Error::InvalidValue),
+            },
             NewType::Timestamp
             | NewType::Array
             | NewType::Symbol
@@ -328,6 +333,8 @@ impl<'a> ser::Serializer for &'a mut SizeSerializer {
             self.new_type = NewType::Uuid;
         } else if name == TRANSPARENT_VEC {
             self.new_type = NewType::TransparentVec;
+        } else if name == RAW {
+            self.new_type = NewType::Raw;
         }
         value.serialize(self)
     }
@@ -343,7 +350,15 @@ impl<'a> ser::Serializer for &'a mut SizeSerializer {
         T: serde::Serialize,
     {
         if name == DESCRIPTOR {
-            value.serialize(self).map(|len| len + 1)
+            match self.is_array_element {
+                IsArrayElement::False => value.serialize(self).map(|len| len + 1),
+                // The descriptor is part of the element constructor shared by all elements
+                IsArrayElement::FirstElement => {
+                    let mut serializer = SizeSerializer::new();
+                    value.serialize(&mut serializer).map(|len| len + 1)
+                }
+                IsArrayElement::OtherElement => Ok(0),
+            }
         } else {
             let mut state = self.serialize_map(Some(1))?;
             state.serialize_entry(&variant_index, value)?;
@@ -472,7 +487,8 @@ impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
             | NewType::Symbol
             | NewType::SymbolRef
             | NewType::Timestamp
-            | NewType::Uuid => unreachable!(),
+            | NewType::Uuid
+            | NewType::Raw => unreachable!(),
         }
 
         self.idx += 1;
@@ -495,7 +511,8 @@ impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
             | NewType::Symbol
             | NewType::SymbolRef
             | NewType::Timestamp
-            | NewType::Uuid => unreachable!(),
+            | NewType::Uuid
+            | NewType::Raw => unreachable!(),
         }
     }
 }
@@ -517,7 +534,29 @@ fn list_size(len: usize, is_array_element: &IsArrayElement) -> Result<usize, usi
     }
 }
 
+/// The list of a described type in an array is always encoded as `list32`
+fn described_list_size(len: usize, is_array_element: &IsArrayElement) -> Result<usize, usize> {
+    match is_array_element {
+        IsArrayElement::False => list_size(len, is_array_element),
+        IsArrayElement::FirstElement | IsArrayElement::OtherElement => {
+            compound32_size(len, is_array_element)
+        }
+    }
+}
+
+fn compound32_size(len: usize, is_array_element: &IsArrayElement) -> Result<usize, usize> {
+    match len {
+        0..=U32_MAX_MINUS_4 => match is_array_element {
+            IsArrayElement::False | IsArrayElement::FirstElement => Ok(1 + 4 + 4 + len),
+            IsArrayElement::OtherElement => Ok(4 + 4 + len),
+        },
+        _ => Err(len),
+    }
+}
+
 fn array_size(len: usize, is_array_element: &IsArrayElement) -> Result<usize, usize> {
+    // An empty array still carries the null element constructor
+    let len = std::cmp::max(len, 1);
     let out = match len {
         0..=U8_MAX_MINUS_1 => match is_array_element {
             IsArrayElement::False => 1 + 2 + len,
@@ -647,6 +686,16 @@ fn map_size(len: usize, is_array_element: &IsArrayElement) -> Result<usize, usiz
     }
 }
 
+/// The map of a described type in an array is always encoded as `map32`
+fn described_map_size(len: usize, is_array_element: &IsArrayElement) -> Result<usize, usize> {
+    match is_array_element {
+        IsArrayElement::False => map_size(len, is_array_element),
+        IsArrayElement::FirstElement | IsArrayElement::OtherElement => {
+            compound32_size(len, is_array_element)
+        }
+    }
+}
+
 /// SeqSerializer that calculates the size of serialized data without actually allocating `Vec<u8>`
 #[derive(Debug)]
 pub struct TupleStructSerializer<'a> {
@@ -717,7 +766,7 @@ impl<'a> ser::SerializeTupleStruct for TupleStructSerializer<'a> {
                 .map_err(|_| Error::too_long()),
             StructEncoding::DescribedList => {
                 let _ = self.se.struct_encoding.pop();
-                list_size(self.cumulated_size, &self.se.is_array_element)
+                described_list_size(self.cumulated_size, &self.se.is_array_element)
                     .map_err(|_| Error::too_long())
             }
             StructEncoding::DescribedBasic => {
@@ -791,12 +840,12 @@ impl<'a> ser::SerializeStruct for StructSerializer<'a> {
                 .map_err(|_| Error::too_long()),
             StructEncoding::DescribedList => {
                 let _ = self.se.struct_encoding.pop();
-                list_size(self.cumulated_size, &self.se.is_array_element)
+                described_list_size(self.cumulated_size, &self.se.is_array_element)
                     .map_err(|_| Error::too_long())
             }
             StructEncoding::DescribedMap => {
                 let _ = self.se.struct_encoding.pop();
-                map_size(self.cumulated_size, &self.se.is_array_element)
+                described_map_size(self.cumulated_size, &self.se.is_array_element)
                     .map_err(|_| Error::too_long())
             }
             StructEncoding::DescribedBasic => {
@@ -1172,6 +1221,11 @@ mod tests {
 
     #[test]
     fn serialized_size_of_array() {
+        let value: Array<u8> = Array::from(vec![]);
+        let ssize = serialized_size(&value).unwrap();
+        let buf = to_vec(&value).unwrap();
+        assert_eq!(ssize, buf.len());
+
         let value = Array::from(vec![1u8]);
         let ssize = serialized_size(&value).unwrap();
         let buf = to_vec(&value).unwrap();
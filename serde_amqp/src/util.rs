@@ -12,6 +12,7 @@ pub(crate) enum NewType {
     Timestamp,
     Uuid,
     TransparentVec,
+    Raw,
 }
 
 impl Default for NewType {
@@ -747,7 +747,7 @@ impl<'de> de::Deserializer<'de> for Deserializer {
                 _ => Err(Error::InvalidValue),
             },
             NewType::Symbol => match self.value {
-                Value::Symbol(v) => visitor.visit_string(v.into_inner()),
+                Value::Symbol(v) => visitor.visit_str(v.as_str()),
                 _ => Err(Error::InvalidValue),
             },
             _ => Err(Error::InvalidValue),
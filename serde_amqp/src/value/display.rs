@@ -0,0 +1,171 @@
+//! Indented tree view of a [`Value`]
+
+use std::fmt;
+
+use crate::descriptor::Descriptor;
+
+use super::Value;
+
+const INDENT: &str = "  ";
+
+/// Renders a [`Value`] as an indented tree with each node labeled by its AMQP type name
+///
+/// This is created by [`Value::display_tree()`].
+///
+/// # Example
+///
+/// ```rust
+/// use serde_amqp::{described::Described, descriptor::Descriptor, primitives::Symbol, Value};
+///
+/// let value = Value::from(Described {
+///     descriptor: Descriptor::Code(0x70),
+///     value: Value::List(vec![Value::UInt(0), Value::Symbol(Symbol::from("x"))]),
+/// });
+/// let expected = "\
+/// described(0x70)
+///   list[2]
+///     uint(0)
+///     symbol(\"x\")";
+/// assert_eq!(value.display_tree().to_string(), expected);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayTree<'a> {
+    value: &'a Value,
+}
+
+impl<'a> DisplayTree<'a> {
+    pub(crate) fn new(value: &'a Value) -> Self {
+        Self { value }
+    }
+}
+
+impl<'a> fmt::Display for DisplayTree<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_node(f, self.value, 0, "")
+    }
+}
+
+fn fmt_node(f: &mut fmt::Formatter<'_>, value: &Value, depth: usize, label: &str) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str(INDENT)?;
+    }
+    f.write_str(label)?;
+
+    match value {
+        Value::Described(described) => {
+            match &described.descriptor {
+                Descriptor::Code(code) => write!(f, "described(0x{:x})", code)?,
+                Descriptor::Name(name) => write!(f, "described({:?})", name.as_str())?,
+            }
+            f.write_str("\n")?;
+            fmt_node(f, &described.value, depth + 1, "")
+        }
+        Value::Null => f.write_str("null"),
+        Value::Bool(v) => write!(f, "bool({})", v),
+        Value::UByte(v) => write!(f, "ubyte({})", v),
+        Value::UShort(v) => write!(f, "ushort({})", v),
+        Value::UInt(v) => write!(f, "uint({})", v),
+        Value::ULong(v) => write!(f, "ulong({})", v),
+        Value::Byte(v) => write!(f, "byte({})", v),
+        Value::Short(v) => write!(f, "short({})", v),
+        Value::Int(v) => write!(f, "int({})", v),
+        Value::Long(v) => write!(f, "long({})", v),
+        Value::Float(v) => write!(f, "float({})", v),
+        Value::Double(v) => write!(f, "double({})", v),
+        Value::Decimal32(v) => fmt_bytes(f, "decimal32", &v.clone().into_inner()),
+        Value::Decimal64(v) => fmt_bytes(f, "decimal64", &v.clone().into_inner()),
+        Value::Decimal128(v) => fmt_bytes(f, "decimal128", &v.clone().into_inner()),
+        Value::Char(v) => write!(f, "char({:?})", v),
+        Value::Timestamp(v) => write!(f, "timestamp({})", v.milliseconds()),
+        Value::Uuid(v) => fmt_bytes(f, "uuid", v.as_inner()),
+        Value::Binary(v) => {
+            write!(f, "binary[{}]", v.len())?;
+            fmt_bytes(f, "", v)
+        }
+        Value::String(v) => write!(f, "string({:?})", v),
+        Value::Symbol(v) => write!(f, "symbol({:?})", v.as_str()),
+        Value::List(list) => {
+            write!(f, "list[{}]", list.len())?;
+            for item in list {
+                f.write_str("\n")?;
+                fmt_node(f, item, depth + 1, "")?;
+            }
+            Ok(())
+        }
+        Value::Map(map) => {
+            write!(f, "map{{{}}}", map.len())?;
+            for (key, value) in map.iter() {
+                f.write_str("\n")?;
+                fmt_node(f, key, depth + 1, "key: ")?;
+                f.write_str("\n")?;
+                fmt_node(f, value, depth + 1, "value: ")?;
+            }
+            Ok(())
+        }
+        Value::Array(array) => {
+            write!(f, "array[{}]", array.0.len())?;
+            for item in &array.0 {
+                f.write_str("\n")?;
+                fmt_node(f, item, depth + 1, "")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn fmt_bytes(f: &mut fmt::Formatter<'_>, name: &str, bytes: &[u8]) -> fmt::Result {
+    write!(f, "{}(", name)?;
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    f.write_str(")")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        described::Described,
+        descriptor::Descriptor,
+        primitives::{OrderedMap, Symbol},
+        Value,
+    };
+
+    #[test]
+    fn display_tree_of_described_list() {
+        let mut properties = OrderedMap::new();
+        properties.insert(Value::Symbol(Symbol::from("product")), Value::from("fe2o3"));
+        let value = Value::from(Described {
+            descriptor: Descriptor::Code(0x10),
+            value: Value::List(vec![
+                Value::String(String::from("container-1")),
+                Value::Null,
+                Value::UInt(65536),
+                Value::Map(properties),
+            ]),
+        });
+
+        let expected = "\
+described(0x10)
+  list[4]
+    string(\"container-1\")
+    null
+    uint(65536)
+    map{1}
+      key: symbol(\"product\")
+      value: string(\"fe2o3\")";
+        assert_eq!(value.display_tree().to_string(), expected);
+    }
+
+    #[test]
+    fn display_tree_of_named_descriptor_and_binary() {
+        let value = Value::from(Described {
+            descriptor: Descriptor::Name(Symbol::from("amqp:data:binary")),
+            value: Value::Binary(vec![0x0a, 0xff].into()),
+        });
+
+        let expected = "\
+described(\"amqp:data:binary\")
+  binary[2](0aff)";
+        assert_eq!(value.display_tree().to_string(), expected);
+    }
+}
@@ -18,14 +18,21 @@ use crate::{
 };
 
 pub(crate) mod de;
+mod display;
 pub(crate) mod ser;
 
+pub use display::DisplayTree;
+
 /// Primitive type definitions
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Value {
     /// Described type
     ///
     /// Box is used to reduce the memory size of the Value type.
+    ///
+    /// Re-encoding a described value may normalize the encoding. Use
+    /// [`RawDescribed`](crate::described::RawDescribed) to forward a described type
+    /// byte-identically.
     Described(Box<Described<Value>>),
 
     /// Indicates an empty value
@@ -312,6 +319,14 @@ impl Value {
         };
         code as u8
     }
+
+    /// Returns an object that renders the value as an indented tree, with each node labeled by
+    /// its AMQP type name, eg. `symbol("x")`, `list[3]`, `map{2}` or `described(0x70)`
+    ///
+    /// This is meant for debugging and does not replace the `Debug` impl.
+    pub fn display_tree(&self) -> DisplayTree<'_> {
+        DisplayTree::new(self)
+    }
 }
 
 macro_rules! impl_from_for_value {
@@ -9,7 +9,7 @@ use serde_bytes::ByteBuf;
 use crate::{
     __constants::{
         ARRAY, DECIMAL128, DECIMAL32, DECIMAL64, DESCRIBED_BASIC, DESCRIBED_LIST, DESCRIBED_MAP,
-        DESCRIPTOR, SYMBOL, SYMBOL_REF, TIMESTAMP, UUID,
+        DESCRIPTOR, RAW, SYMBOL, SYMBOL_REF, TIMESTAMP, UUID,
     },
     described::Described,
     descriptor::Descriptor,
@@ -202,6 +202,10 @@ impl<'a> ser::Serializer for &'a mut Serializer {
             NewType::Symbol => Err(Error::InvalidValue),
             NewType::SymbolRef => Err(Error::InvalidValue),
             NewType::TransparentVec => Err(Error::InvalidValue),
+            NewType::Raw => {
+                self.new_type = NewType::None;
+                crate::from_slice(v)
+            }
         }
     }
 
@@ -250,6 +254,8 @@ impl<'a> ser::Serializer for &'a mut Serializer {
             self.new_type = NewType::Timestamp
         } else if name == UUID {
             self.new_type = NewType::Uuid
+        } else if name == RAW {
+            self.new_type = NewType::Raw
         }
         value.serialize(self)
     }
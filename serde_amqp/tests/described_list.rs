@@ -480,3 +480,87 @@ fn single_custom_struct() {
     let decoded: Single<CustomStruct> = from_slice(&buf).unwrap();
     assert_eq!(decoded, value);
 }
+
+#[cfg(feature = "derive")]
+#[test]
+fn array_of_described_list() {
+    use serde_amqp::primitives::Array;
+
+    let value = Array::from(vec![Single { a: 1u32 }, Single { a: 1u32 }, Single { a: 1u32 }]);
+    let buf = to_vec(&value).unwrap();
+    // The descriptor and the list32 constructor are shared by all elements
+    let expected = [
+        0xe0, 0x2a, 0x3, 0x0, 0x80, 0x0, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0, 0x1, 0xd0, 0x0, 0x0, 0x0,
+        0x6, 0x0, 0x0, 0x0, 0x1, 0x52, 0x1, 0x0, 0x0, 0x0, 0x6, 0x0, 0x0, 0x0, 0x1, 0x52, 0x1,
+        0x0, 0x0, 0x0, 0x6, 0x0, 0x0, 0x0, 0x1, 0x52, 0x1,
+    ];
+    assert_eq!(buf, expected);
+    assert_eq!(serde_amqp::serialized_size(&value).unwrap(), buf.len());
+
+    let decoded: Array<Single<u32>> = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Mode(u8);
+
+#[cfg(feature = "derive")]
+impl Default for Mode {
+    fn default() -> Self {
+        Self(2)
+    }
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(
+    name = "test:optional:list",
+    code = "0x0000_0001:0000_0002",
+    encoding = "list"
+)]
+struct AllOptional {
+    name: Option<String>,
+    count: Option<u32>,
+    #[amqp_contract(default)]
+    mode: Mode,
+    #[amqp_contract(default)]
+    flag: bool,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn all_optional_fields_from_list0() {
+    // descriptor 0x0000_0001:0000_0002 followed by list0
+    let buf = [0x0, 0x80, 0x0, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0, 0x2, 0x45];
+    let decoded: AllOptional = from_slice(&buf).unwrap();
+    assert_eq!(decoded.name, None);
+    assert_eq!(decoded.count, None);
+    assert_eq!(decoded.mode, Mode(2));
+    assert!(!decoded.flag);
+
+    // A composite with all fields absent is also encoded as list0
+    assert_eq!(to_vec(&decoded).unwrap(), buf);
+
+    // An empty list8 is treated the same
+    let list8 = [
+        0x0, 0x80, 0x0, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0, 0x2, 0xc0, 0x01, 0x00,
+    ];
+    let decoded_from_list8: AllOptional = from_slice(&list8).unwrap();
+    assert_eq!(decoded_from_list8, decoded);
+
+    // The same with a symbolic descriptor
+    let mut buf = vec![0x0, 0xa3, 0x12];
+    buf.extend_from_slice(b"test:optional:list");
+    buf.push(0x45);
+    let decoded: AllOptional = from_slice(&buf).unwrap();
+    assert_eq!(
+        decoded,
+        AllOptional {
+            name: None,
+            count: None,
+            mode: Mode::default(),
+            flag: false,
+        }
+    );
+}
@@ -0,0 +1,67 @@
+#[cfg(feature = "derive")]
+use serde_amqp::{from_slice, to_vec, DeserializeComposite, SerializeComposite};
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(
+    name = "test:foo:list",
+    code = "0x0000_0000:0x0000_0001",
+    encoding = "list",
+    descriptor = "symbolic"
+)]
+struct Symbolic {
+    a: i32,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(
+    name = "test:foo:list",
+    code = "0x0000_0000:0x0000_0001",
+    encoding = "list",
+    descriptor = "numeric"
+)]
+struct Numeric {
+    a: i32,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(
+    name = "test:foo:list",
+    code = "0x0000_0000:0x0000_0001",
+    encoding = "list"
+)]
+struct DefaultForm {
+    a: i32,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn symbolic_descriptor_writes_name() {
+    let value = Symbolic { a: 1 };
+    let buf = to_vec(&value).unwrap();
+    let mut expected = vec![0x00, 0xa3, 0x0d];
+    expected.extend_from_slice(b"test:foo:list");
+    expected.extend_from_slice(&[0xc0, 0x03, 0x01, 0x54, 0x01]);
+    assert_eq!(buf, expected);
+
+    let decoded: Symbolic = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+
+    // The numeric form is still accepted when deserializing
+    let decoded: Symbolic = from_slice(&to_vec(&Numeric { a: 1 }).unwrap()).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn numeric_descriptor_is_the_default() {
+    let expected = [0x00, 0x53, 0x01, 0xc0, 0x03, 0x01, 0x54, 0x01];
+    assert_eq!(to_vec(&Numeric { a: 1 }).unwrap(), expected);
+    assert_eq!(to_vec(&DefaultForm { a: 1 }).unwrap(), expected);
+
+    // The symbolic form is still accepted when deserializing
+    let decoded: Numeric = from_slice(&to_vec(&Symbolic { a: 1 }).unwrap()).unwrap();
+    assert_eq!(decoded, Numeric { a: 1 });
+}
@@ -0,0 +1,131 @@
+#[cfg(feature = "derive")]
+use serde_amqp::{from_slice, to_vec, DeserializeComposite, SerializeComposite};
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(encoding = "list", no_descriptor)]
+struct Base {
+    id: u32,
+    name: Option<String>,
+    #[amqp_contract(default)]
+    durable: bool,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(code = "0x0000_0000:0x0000_0001", encoding = "list")]
+struct Flattened {
+    kind: u8,
+    #[amqp_contract(flatten)]
+    base: Base,
+    extra: Option<i32>,
+}
+
+/// Same layout as `Flattened` with the fields of `Base` written out
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(code = "0x0000_0000:0x0000_0001", encoding = "list")]
+struct Inlined {
+    kind: u8,
+    id: u32,
+    name: Option<String>,
+    #[amqp_contract(default)]
+    durable: bool,
+    extra: Option<i32>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(encoding = "list", no_descriptor)]
+struct NestedFlattened {
+    #[amqp_contract(flatten)]
+    base: Base,
+    tag: Option<String>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(code = "0x0000_0000:0x0000_0002", encoding = "list")]
+struct Outer {
+    #[amqp_contract(flatten)]
+    nested: NestedFlattened,
+    last: u8,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn flattened_fields_are_inlined_in_declaration_order() {
+    let value = Flattened {
+        kind: 1,
+        base: Base {
+            id: 2,
+            name: Some(String::from("a")),
+            durable: true,
+        },
+        extra: Some(3),
+    };
+    let inlined = Inlined {
+        kind: 1,
+        id: 2,
+        name: Some(String::from("a")),
+        durable: true,
+        extra: Some(3),
+    };
+    let buf = to_vec(&value).unwrap();
+    assert_eq!(buf, to_vec(&inlined).unwrap());
+
+    let decoded: Flattened = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn trailing_null_fields_of_flattened_struct_are_skipped() {
+    let value = Flattened {
+        kind: 1,
+        base: Base {
+            id: 2,
+            name: None,
+            durable: false,
+        },
+        extra: None,
+    };
+    let buf = to_vec(&value).unwrap();
+    let expected = [0x00, 0x53, 0x01, 0xc0, 0x05, 0x02, 0x50, 0x01, 0x52, 0x02];
+    assert_eq!(buf, expected);
+
+    let decoded: Flattened = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn nested_flattened_struct_round_trip() {
+    let value = Outer {
+        nested: NestedFlattened {
+            base: Base {
+                id: 7,
+                name: None,
+                durable: false,
+            },
+            tag: Some(String::from("t")),
+        },
+        last: 9,
+    };
+    let buf = to_vec(&value).unwrap();
+    let expected = [
+        0x00, 0x53, 0x02, 0xc0, 0x0a, 0x05, 0x52, 0x07, 0x40, 0x40, 0xa1, 0x01, b't', 0x50, 0x09,
+    ];
+    assert_eq!(buf, expected);
+
+    let decoded: Outer = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn missing_mandatory_flattened_field_is_rejected() {
+    // Only `kind` is present
+    let buf = [0x00, 0x53, 0x01, 0xc0, 0x03, 0x01, 0x50, 0x01];
+    assert!(from_slice::<Flattened>(&buf).is_err());
+}
@@ -0,0 +1,122 @@
+#[cfg(feature = "derive")]
+use serde_amqp::{from_slice, to_vec, DeserializeComposite, SerializeComposite};
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(encoding = "list", no_descriptor)]
+struct BareList {
+    a: i32,
+    b: Option<String>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(encoding = "map", rename_all = "kebab-case", no_descriptor)]
+struct BareMap {
+    field_a: i32,
+    field_b: Option<bool>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(encoding = "list", no_descriptor)]
+struct BareTuple(u8, Option<bool>);
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(
+    name = "test:outer:list",
+    code = "0x0000_0001:0x0000_0002",
+    encoding = "list"
+)]
+struct Outer {
+    inner: BareList,
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn list_round_trip_without_descriptor() {
+    let value = BareList {
+        a: 1,
+        b: Some(String::from("b")),
+    };
+    let buf = to_vec(&value).unwrap();
+    let expected = [0xc0, 0x06, 0x02, 0x54, 0x01, 0xa1, 0x01, b'b'];
+    assert_eq!(buf, expected);
+
+    let decoded: BareList = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn list_without_descriptor_omits_trailing_none() {
+    let value = BareList { a: 1, b: None };
+    let buf = to_vec(&value).unwrap();
+    let expected = [0xc0, 0x03, 0x01, 0x54, 0x01];
+    assert_eq!(buf, expected);
+
+    let decoded: BareList = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn map_round_trip_without_descriptor() {
+    let value = BareMap {
+        field_a: 7,
+        field_b: Some(true),
+    };
+    let buf = to_vec(&value).unwrap();
+    assert_eq!(buf[0], 0xc1);
+
+    let decoded: BareMap = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn tuple_round_trip_without_descriptor() {
+    let value = BareTuple(3, Some(false));
+    let buf = to_vec(&value).unwrap();
+    let expected = [0xc0, 0x04, 0x02, 0x50, 0x03, 0x42];
+    assert_eq!(buf, expected);
+
+    let decoded: BareTuple = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn nested_without_descriptor() {
+    let value = Outer {
+        inner: BareList { a: 1, b: None },
+    };
+    let buf = to_vec(&value).unwrap();
+    let expected = [
+        0x00, 0x80, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0xc0, 0x06, 0x01, 0xc0, 0x03,
+        0x01, 0x54, 0x01,
+    ];
+    assert_eq!(buf, expected);
+
+    let decoded: Outer = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn field_layout_is_validated_without_descriptor() {
+    // missing mandatory field
+    let buf = [0x45];
+    assert!(from_slice::<BareList>(&buf).is_err());
+
+    // too many items
+    let buf = [0xc0, 0x07, 0x03, 0x54, 0x01, 0x40, 0x54, 0x02];
+    assert!(from_slice::<BareList>(&buf).is_err());
+
+    // described list is not a bare list
+    let described = [
+        0x00, 0x53, 0x01, 0xc0, 0x06, 0x02, 0x54, 0x01, 0xa1, 0x01, b'b',
+    ];
+    assert!(from_slice::<BareList>(&described).is_err());
+}
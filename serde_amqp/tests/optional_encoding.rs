@@ -0,0 +1,126 @@
+#[cfg(feature = "derive")]
+use serde_amqp::{from_slice, to_vec, DeserializeComposite, SerializeComposite};
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(code = "0x0000_0000:0x0000_0001", encoding = "list")]
+struct SkipTrailing {
+    a: i32,
+    b: Option<String>,
+    #[amqp_contract(default)]
+    c: bool,
+    d: Option<u8>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(
+    code = "0x0000_0000:0x0000_0001",
+    encoding = "list",
+    optional_encoding = "skip"
+)]
+struct ExplicitSkipTrailing {
+    a: i32,
+    b: Option<String>,
+    #[amqp_contract(default)]
+    c: bool,
+    d: Option<u8>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(
+    code = "0x0000_0000:0x0000_0001",
+    encoding = "list",
+    optional_encoding = "null"
+)]
+struct NullTrailing {
+    a: i32,
+    b: Option<String>,
+    #[amqp_contract(default)]
+    c: bool,
+    d: Option<u8>,
+}
+
+#[cfg(feature = "derive")]
+#[derive(Debug, SerializeComposite, DeserializeComposite, PartialEq)]
+#[amqp_contract(encoding = "list", no_descriptor, optional_encoding = "null")]
+struct NullTrailingTuple(u8, Option<bool>, Option<bool>);
+
+#[cfg(feature = "derive")]
+#[test]
+fn trailing_none_fields_are_skipped_by_default() {
+    let value = SkipTrailing {
+        a: 1,
+        b: None,
+        c: false,
+        d: None,
+    };
+    let buf = to_vec(&value).unwrap();
+    let expected = [0x00, 0x53, 0x01, 0xc0, 0x03, 0x01, 0x54, 0x01];
+    assert_eq!(buf, expected);
+
+    let explicit = ExplicitSkipTrailing {
+        a: 1,
+        b: None,
+        c: false,
+        d: None,
+    };
+    assert_eq!(to_vec(&explicit).unwrap(), expected);
+
+    let decoded: SkipTrailing = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn trailing_none_fields_are_encoded_as_null() {
+    let value = NullTrailing {
+        a: 1,
+        b: None,
+        c: false,
+        d: None,
+    };
+    let buf = to_vec(&value).unwrap();
+    let expected = [
+        0x00, 0x53, 0x01, 0xc0, 0x06, 0x04, 0x54, 0x01, 0x40, 0x40, 0x40,
+    ];
+    assert_eq!(buf, expected);
+
+    let decoded: NullTrailing = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn null_encoding_only_changes_trailing_fields() {
+    let value = NullTrailing {
+        a: 1,
+        b: None,
+        c: true,
+        d: Some(2),
+    };
+    let skipped = SkipTrailing {
+        a: 1,
+        b: None,
+        c: true,
+        d: Some(2),
+    };
+    let buf = to_vec(&value).unwrap();
+    assert_eq!(buf, to_vec(&skipped).unwrap());
+
+    let decoded: NullTrailing = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn trailing_none_tuple_fields_are_encoded_as_null() {
+    let value = NullTrailingTuple(1, None, None);
+    let buf = to_vec(&value).unwrap();
+    let expected = [0xc0, 0x05, 0x03, 0x50, 0x01, 0x40, 0x40];
+    assert_eq!(buf, expected);
+
+    let decoded: NullTrailingTuple = from_slice(&buf).unwrap();
+    assert_eq!(decoded, value);
+}
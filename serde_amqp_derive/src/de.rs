@@ -47,6 +47,16 @@ fn expand_deserialize_on_datastruct(
         },
     };
 
+    if attr.no_descriptor {
+        if let EncodingType::Basic = attr.encoding {
+            let span = get_span_of("encoding", ctx).unwrap_or_else(|| ident.span());
+            return Err(syn::Error::new(
+                span,
+                "Basic encoding is not supported with no_descriptor",
+            ));
+        }
+    }
+
     let evaluate_descriptor = quote! {
         match __descriptor {
             serde_amqp::descriptor::Descriptor::Name(__symbol) => {
@@ -57,13 +67,17 @@ fn expand_deserialize_on_datastruct(
             #evaluate_code
         }
     };
+    let evaluate_descriptor = match attr.no_descriptor {
+        true => None,
+        false => Some(&evaluate_descriptor),
+    };
 
     match &data.fields {
         Fields::Named(fields) => Ok(expand_deserialize_struct(
             ident,
             generics,
             &expecting,
-            &evaluate_descriptor,
+            evaluate_descriptor,
             &attr.encoding,
             &attr.rename_field,
             fields,
@@ -73,7 +87,7 @@ fn expand_deserialize_on_datastruct(
             ident,
             generics,
             name,
-            &evaluate_descriptor,
+            evaluate_descriptor,
             &attr.encoding,
             fields,
             ctx,
@@ -81,7 +95,7 @@ fn expand_deserialize_on_datastruct(
         Fields::Unit => Ok(expand_deserialize_unit_struct(
             ident,
             &expecting,
-            &evaluate_descriptor,
+            evaluate_descriptor,
             &attr.encoding,
             ctx,
         )?),
@@ -90,19 +104,17 @@ fn expand_deserialize_on_datastruct(
 
 fn impl_visit_seq_for_unit_struct(
     ident: &syn::Ident,
-    evaluate_descriptor: &proc_macro2::TokenStream,
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
 ) -> proc_macro2::TokenStream {
+    let (read_descriptor, check_no_remaining) = impl_read_descriptor_from_seq(evaluate_descriptor);
     quote! {
         fn visit_seq<A>(self, mut __seq: A) -> Result<Self::Value, A::Error>
         where
             A: serde_amqp::serde::de::SeqAccess<'de>,
         {
-            let __descriptor: serde_amqp::descriptor::Descriptor = match __seq.next_element()? {
-                Some(__val) => __val,
-                None => return Err(serde_amqp::serde::de::Error::custom("Expecting descriptor"))
-            };
+            #read_descriptor
 
-            #evaluate_descriptor
+            #check_no_remaining
 
             Ok( #ident )
         }
@@ -112,7 +124,7 @@ fn impl_visit_seq_for_unit_struct(
 fn expand_deserialize_unit_struct(
     ident: &syn::Ident,
     expecting: &str,
-    evaluate_descriptor: &proc_macro2::TokenStream,
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
     encoding: &EncodingType,
     ctx: &DeriveInput,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
@@ -135,6 +147,18 @@ fn expand_deserialize_unit_struct(
     };
     let visit_seq = impl_visit_seq_for_unit_struct(ident, evaluate_descriptor);
     let len = 0usize;
+    let deserialize = match evaluate_descriptor {
+        Some(_) => quote! {
+            // DESCRIPTOR is included here for compatibility with other deserializer
+            deserializer.deserialize_tuple_struct(
+                #struct_name,
+                #len,
+                Visitor{}
+            )
+        },
+        // Without a descriptor, the struct is a bare list
+        None => quote! { deserializer.deserialize_any(Visitor{}) },
+    };
 
     let token = quote! {
         #[automatically_derived]
@@ -154,12 +178,7 @@ fn expand_deserialize_unit_struct(
                     #visit_seq
                 }
 
-                // DESCRIPTOR is included here for compatibility with other deserializer
-                deserializer.deserialize_tuple_struct(
-                    #struct_name,
-                    #len,
-                    Visitor{}
-                )
+                #deserialize
             }
         }
     };
@@ -170,28 +189,26 @@ fn impl_visit_seq_for_tuple_struct(
     ident: &syn::Ident,
     field_idents: &Vec<syn::Ident>,
     field_types: &Vec<&syn::Type>,
-    evaluate_descriptor: &proc_macro2::TokenStream,
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
 ) -> proc_macro2::TokenStream {
     let unwrap_or_none = match field_idents.len() {
         0 => quote! {},
         _ => macro_rules_unwrap_or_none(),
     };
+    let (read_descriptor, check_no_remaining) = impl_read_descriptor_from_seq(evaluate_descriptor);
     quote! {
         fn visit_seq<A>(self, mut __seq: A) -> Result<Self::Value, A::Error>
         where
             A: serde_amqp::serde::de::SeqAccess<'de>,
         {
-            let __descriptor: serde_amqp::descriptor::Descriptor = match __seq.next_element()? {
-                Some(val) => val,
-                None => return Err(serde_amqp::serde::de::Error::custom("Expecting descriptor"))
-            };
-
-            #evaluate_descriptor
+            #read_descriptor
 
             #unwrap_or_none
 
             #( unwrap_or_none!(#field_idents, __seq.next_element()?, #field_types); )*
 
+            #check_no_remaining
+
             Ok( #ident( #(#field_idents, )* ) )
         }
     }
@@ -201,7 +218,7 @@ fn expand_deserialize_tuple_struct(
     ident: &syn::Ident,
     generics: &syn::Generics,
     expecting: &str,
-    evaluate_descriptor: &proc_macro2::TokenStream,
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
     encoding: &EncodingType,
     fields: &syn::FieldsUnnamed,
     ctx: &DeriveInput,
@@ -239,6 +256,18 @@ fn expand_deserialize_tuple_struct(
     let visit_seq =
         impl_visit_seq_for_tuple_struct(ident, &field_idents, &field_types, evaluate_descriptor);
     let len = field_idents.len();
+    let deserialize = match evaluate_descriptor {
+        Some(_) => quote! {
+            // DESCRIPTOR is included here for compatibility with other deserializer
+            deserializer.deserialize_tuple_struct(
+                #struct_name,
+                #len + 1, // descriptor also takes one
+                Visitor::new()
+            )
+        },
+        // Without a descriptor, the struct is a bare list
+        None => quote! { deserializer.deserialize_any(Visitor::new()) },
+    };
 
     let gen_params = &generics.params;
     let visitor = generic_visitor(generics);
@@ -263,12 +292,7 @@ fn expand_deserialize_tuple_struct(
                     #visit_seq
                 }
 
-                // DESCRIPTOR is included here for compatibility with other deserializer
-                deserializer.deserialize_tuple_struct(
-                    #struct_name,
-                    #len + 1, // descriptor also takes one
-                    Visitor::new()
-                )
+                #deserialize
             }
         }
     };
@@ -280,7 +304,7 @@ fn expand_deserialize_struct(
     ident: &syn::Ident,
     generics: &syn::Generics,
     expecting: &str,
-    evaluate_descriptor: &proc_macro2::TokenStream,
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
     encoding: &EncodingType,
     rename_all: &str,
     fields: &syn::FieldsNamed,
@@ -321,8 +345,21 @@ fn expand_deserialize_struct(
         .collect();
     let field_types: Vec<&syn::Type> = fields.named.iter().map(|f| &f.ty).collect();
     let field_attrs = parse_named_field_attrs(fields.named.iter());
+    let has_flatten = field_attrs.iter().any(|attr| attr.flatten);
+    if has_flatten && !matches!(encoding, EncodingType::List) {
+        let span = get_span_of("encoding", ctx).unwrap_or_else(|| ident.span());
+        return Err(syn::Error::new(
+            span,
+            "flatten is only supported with the list encoding",
+        ));
+    }
 
-    let deserialize_field = impl_deserialize_for_field(&field_idents, &field_names);
+    // The flattened fields cannot be looked up by name, so a struct with flattened fields
+    // can only be deserialized from a list
+    let deserialize_field = match has_flatten {
+        true => quote! {},
+        false => impl_deserialize_for_field(&field_idents, &field_names),
+    };
 
     let visit_seq = impl_visit_seq_for_struct(
         ident,
@@ -331,8 +368,8 @@ fn expand_deserialize_struct(
         &field_attrs,
         evaluate_descriptor,
     );
-    let visit_map = match len {
-        0 => quote! {},
+    let visit_map = match (len, has_flatten) {
+        (0, _) | (_, true) => quote! {},
         _ => impl_visit_map(
             ident,
             &field_idents,
@@ -345,7 +382,7 @@ fn expand_deserialize_struct(
 
     let mut n_true: u32 = 0;
     let mut n_false: u32 = 0;
-    field_attrs.iter().for_each(|a| {
+    field_attrs.iter().filter(|a| !a.flatten).for_each(|a| {
         if a.default {
             n_true += 1;
         } else {
@@ -361,14 +398,50 @@ fn expand_deserialize_struct(
         0 => quote! {},
         _ => macro_rules_unwrap_or_none(),
     };
+    let deserialize = match evaluate_descriptor {
+        Some(_) => quote! {
+            // DESCRIPTOR is included here for compatibility with other deserializer
+            const FIELDS: &'static [&'static str] = &[serde_amqp::__constants::DESCRIPTOR, #(#field_names,)*];
+            deserializer.deserialize_struct(
+                #struct_name,
+                FIELDS,
+                Visitor::new()
+            )
+        },
+        // Without a descriptor, the struct is either a bare list or a bare map
+        None => quote! { deserializer.deserialize_any(Visitor::new()) },
+    };
     let gen_params = &generics.params;
     let visitor = generic_visitor(generics);
     let where_clause = where_deserialize(generics);
 
+    // A list without a descriptor can be flattened into another struct
+    let impl_deserialize_flattened = match (encoding, evaluate_descriptor) {
+        (EncodingType::List, None) => {
+            let field_impls = impl_read_fields_from_seq(&field_idents, &field_types, &field_attrs);
+            quote! {
+                #[automatically_derived]
+                impl<'de, #gen_params> serde_amqp::__flatten::DeserializeFlattened<'de> for #ident<#gen_params> #where_clause {
+                    fn deserialize_flattened<_A>(mut __seq: _A) -> Result<Self, _A::Error>
+                    where
+                        _A: serde_amqp::serde::de::SeqAccess<'de>,
+                    {
+                        #( #field_impls; )*
+
+                        Ok( #ident{ #(#field_idents, )* } )
+                    }
+                }
+            }
+        }
+        _ => quote! {},
+    };
+
     let token = quote! {
         #unwrap_or_default
         #unwrap_or_none
 
+        #impl_deserialize_flattened
+
         #[automatically_derived]
         impl<'de, #gen_params> serde_amqp::serde::de::Deserialize<'de> for #ident<#gen_params> #where_clause {
             fn deserialize<_D>(deserializer: _D) -> Result<Self, _D::Error>
@@ -391,13 +464,7 @@ fn expand_deserialize_struct(
                     #visit_map
                 }
 
-                // DESCRIPTOR is included here for compatibility with other deserializer
-                const FIELDS: &'static [&'static str] = &[serde_amqp::__constants::DESCRIPTOR, #(#field_names,)*];
-                deserializer.deserialize_struct(
-                    #struct_name,
-                    FIELDS,
-                    Visitor::new()
-                )
+                #deserialize
             }
         }
     };
@@ -461,48 +528,59 @@ fn impl_visit_seq_for_struct(
     field_idents: &[syn::Ident],
     field_types: &[&syn::Type],
     field_attrs: &[FieldAttr],
-    evaluate_descriptor: &proc_macro2::TokenStream,
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
 ) -> proc_macro2::TokenStream {
-    let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
-    for ((id, ty), attr) in field_idents.iter().zip(field_types.iter()).zip(field_attrs) {
-        let token = match attr.default {
-            true => {
-                quote! { unwrap_or_default!(#id, __seq.next_element::<Option<#ty>>()?.unwrap_or_default(), #ty) }
-            }
-            false => {
-                quote! { unwrap_or_none!(#id, __seq.next_element()?, #ty) }
-            }
-        };
-        field_impls.push(token);
-    }
+    let field_impls = impl_read_fields_from_seq(field_idents, field_types, field_attrs);
+    let (read_descriptor, check_no_remaining) = impl_read_descriptor_from_seq(evaluate_descriptor);
 
     quote! {
         fn visit_seq<_A>(self, mut __seq: _A) -> Result<Self::Value, _A::Error>
         where
             _A: serde_amqp::serde::de::SeqAccess<'de>,
         {
-            let __descriptor: serde_amqp::descriptor::Descriptor = match __seq.next_element()? {
-                Some(val) => val,
-                None => return Err(serde_amqp::serde::de::Error::custom("Expecting descriptor"))
-            };
-
-            #evaluate_descriptor
+            #read_descriptor
 
             // #( unwrap_or_none!(#field_idents, __seq, #field_types); )*
             #( #field_impls; )*
 
+            #check_no_remaining
+
             Ok( #ident{ #(#field_idents, )* } )
         }
     }
 }
 
+/// Returns the tokens that read each field from `__seq` in the order they are declared
+fn impl_read_fields_from_seq(
+    field_idents: &[syn::Ident],
+    field_types: &[&syn::Type],
+    field_attrs: &[FieldAttr],
+) -> Vec<proc_macro2::TokenStream> {
+    let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
+    for ((id, ty), attr) in field_idents.iter().zip(field_types.iter()).zip(field_attrs) {
+        let token = match (attr.flatten, attr.default) {
+            (true, _) => {
+                quote! { let #id: #ty = serde_amqp::__flatten::DeserializeFlattened::deserialize_flattened(&mut __seq)? }
+            }
+            (false, true) => {
+                quote! { unwrap_or_default!(#id, __seq.next_element::<Option<#ty>>()?.unwrap_or_default(), #ty) }
+            }
+            (false, false) => {
+                quote! { unwrap_or_none!(#id, __seq.next_element()?, #ty) }
+            }
+        };
+        field_impls.push(token);
+    }
+    field_impls
+}
+
 fn impl_visit_map(
     ident: &syn::Ident,
     field_idents: &Vec<syn::Ident>,
     field_names: &Vec<String>,
     field_types: &Vec<&syn::Type>,
     field_attrs: &Vec<FieldAttr>,
-    evaluate_descriptor: &proc_macro2::TokenStream,
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
 ) -> proc_macro2::TokenStream {
     let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
     for ((id, ty), attr) in field_idents.iter().zip(field_types.iter()).zip(field_attrs) {
@@ -516,13 +594,8 @@ fn impl_visit_map(
         };
         field_impls.push(token);
     }
-
-    quote! {
-        fn visit_map<_A>(self, mut __map: _A)-> Result<Self::Value, _A::Error>
-        where _A: serde_amqp::serde::de::MapAccess<'de>
-        {
-            #(let mut #field_idents: Option<#field_types> = None;)*
-
+    let read_descriptor = evaluate_descriptor.map(|evaluate_descriptor| {
+        quote! {
             // The first should always be the descriptor
             let __descriptor: serde_amqp::descriptor::Descriptor = match __map.next_key()? {
                 Some(val) => val,
@@ -530,6 +603,16 @@ fn impl_visit_map(
             };
 
             #evaluate_descriptor
+        }
+    });
+
+    quote! {
+        fn visit_map<_A>(self, mut __map: _A)-> Result<Self::Value, _A::Error>
+        where _A: serde_amqp::serde::de::MapAccess<'de>
+        {
+            #(let mut #field_idents: Option<#field_types> = None;)*
+
+            #read_descriptor
 
             while let Some(key) = __map.next_key::<Field>()? {
                 match key {
@@ -551,3 +634,31 @@ fn impl_visit_map(
         }
     }
 }
+
+/// Returns the tokens that read and evaluate the descriptor and, if there is no descriptor to
+/// identify the struct, the tokens that reject any remaining items in the list
+fn impl_read_descriptor_from_seq(
+    evaluate_descriptor: Option<&proc_macro2::TokenStream>,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    match evaluate_descriptor {
+        Some(evaluate_descriptor) => {
+            let read_descriptor = quote! {
+                let __descriptor: serde_amqp::descriptor::Descriptor = match __seq.next_element()? {
+                    Some(val) => val,
+                    None => return Err(serde_amqp::serde::de::Error::custom("Expecting descriptor"))
+                };
+
+                #evaluate_descriptor
+            };
+            (read_descriptor, quote! {})
+        }
+        None => {
+            let check_no_remaining = quote! {
+                if __seq.next_element::<serde_amqp::serde::de::IgnoredAny>()?.is_some() {
+                    return Err(serde_amqp::serde::de::Error::custom("Too many items"))
+                }
+            };
+            (quote! {}, check_no_remaining)
+        }
+    }
+}
@@ -3,6 +3,8 @@
 //!
 //! - [Change Log](https://github.com/minghuaw/fe2o3-amqp/blob/main/serde_amqp_derive/Changelog.md)
 //!
+//! The generated code requires `serde_amqp` 0.6.0 or later.
+//!
 //! # Usage
 //!
 //! The macro provides three types of encodings:
@@ -24,7 +26,8 @@
 //! If a field is not marked with `"mandatory"` in the specification, the field can be an `Option`.
 //! During serialization, the optional fields may be skipped completely or encoded as an AMQP1.0
 //! `null` primitive (`0x40`). During deserialization, an AMQP1.0 `null` primitive or an empty field
-//! will be decoded as a `None`.
+//! will be decoded as a `None`. Whether the trailing optional fields are skipped can be controlled
+//! with [`optional_encoding`](#optional_encoding).
 //!
 //! Fields with default values:
 //!
@@ -34,6 +37,10 @@
 //! AMQP1.0 `null` primitive (`0x40`). During deserialization, an AMQP1.0 `null` primitive or an
 //! empty field will be decoded as the default value of the type.
 //!
+//! The fields missing from the end of a shortened list are empty fields as well. A described list
+//! with no elements (eg. encoded as `list0`) is thus decoded with all the optional fields set to
+//! `None` and all the fields with default values set to their defaults.
+//!
 //! # Example
 //!
 //! The `"list"` encoding will encode the `Attach` struct as a described list (a descriptor followed
@@ -113,6 +120,93 @@
 //! )]
 //! pub struct ApplicationProperties(pub BTreeMap<String, SimpleValue>);
 //! ```
+//!
+//! ## `no_descriptor`
+//!
+//! Adding `no_descriptor` to the container attribute will have the struct encoded as a bare list
+//! (or a bare map with the `"map"` encoding) without the descriptor prefix. The deserialization
+//! will take either a bare list or a bare map, and the fields are still checked against the
+//! struct definition (ie. missing mandatory fields and extra items are rejected). `no_descriptor`
+//! is not supported with the `"basic"` encoding.
+//!
+//! ```rust,ignore
+//! #[derive(Debug, SerializeComposite, DeserializeComposite)]
+//! #[amqp_contract(encoding = "list", no_descriptor)]
+//! pub struct Point {
+//!     pub x: i32,
+//!     pub y: i32,
+//! }
+//! ```
+//!
+//! ## `optional_encoding`
+//!
+//! With the `"list"` encoding, a `None` field (or a field equal to its default value) that is
+//! followed by a non-null field is always encoded as a `null`. The container attribute
+//! `optional_encoding` controls the trailing ones:
+//!
+//! - `"skip"` (default): the trailing `None`/default fields are omitted and the list is shortened
+//! - `"null"`: the trailing `None`/default fields are encoded as `null` so that the list always
+//!   has one element per field
+//!
+//! `optional_encoding = "null"` is not supported with the `"map"` encoding.
+//!
+//! ```rust,ignore
+//! #[derive(Debug, SerializeComposite, DeserializeComposite)]
+//! #[amqp_contract(code = "0x0000_0000:0x0000_0001", encoding = "list", optional_encoding = "null")]
+//! pub struct Foo {
+//!     pub a: i32,
+//!     pub b: Option<String>,
+//! }
+//! ```
+//!
+//! ## `descriptor`
+//!
+//! The container attribute `descriptor` controls which form of the descriptor is serialized:
+//!
+//! - `"numeric"` (default): the `code` is serialized as a `ulong`. The `name` is used instead if
+//!   there is no `code`
+//! - `"symbolic"`: the `name` is serialized as a `symbol`
+//!
+//! Deserialization accepts both forms regardless of this attribute.
+//!
+//! ```rust,ignore
+//! #[derive(Debug, SerializeComposite, DeserializeComposite)]
+//! #[amqp_contract(
+//!     name = "amqp:header:list",
+//!     code = "0x0000_0000:0x0000_0070",
+//!     encoding = "list",
+//!     descriptor = "symbolic"
+//! )]
+//! pub struct Header {
+//!     pub durable: bool,
+//! }
+//! ```
+//!
+//! ## `flatten`
+//!
+//! Adding `flatten` to a field inlines the fields of the field's type into the list of the parent
+//! struct, in the position where the field is declared. The type of the field must be a `"list"`
+//! encoded struct with `no_descriptor`, and the parent struct must use the `"list"` encoding. A
+//! struct with flattened fields can only be deserialized from a list.
+//!
+//! ```rust,ignore
+//! #[derive(Debug, SerializeComposite, DeserializeComposite)]
+//! #[amqp_contract(encoding = "list", no_descriptor)]
+//! pub struct Base {
+//!     pub id: u32,
+//!     pub name: Option<String>,
+//! }
+//!
+//! // Encoded as a described list of `kind`, `id`, `name` and `extra`
+//! #[derive(Debug, SerializeComposite, DeserializeComposite)]
+//! #[amqp_contract(code = "0x0000_0000:0x0000_0001", encoding = "list")]
+//! pub struct Foo {
+//!     pub kind: u8,
+//!     #[amqp_contract(flatten)]
+//!     pub base: Base,
+//!     pub extra: Option<i32>,
+//! }
+//! ```
 
 use darling::{FromDeriveInput, FromMeta};
 use quote::quote;
@@ -130,9 +224,20 @@ enum EncodingType {
     Map,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, FromMeta)]
+enum OptionalEncoding {
+    Skip,
+    Null,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, FromMeta)]
+enum DescriptorForm {
+    Symbolic,
+    Numeric,
+}
+
 #[derive(Debug, Clone, FromDeriveInput)]
 #[darling(attributes(amqp_contract))]
-#[allow(dead_code)]
 struct DescribedAttr {
     #[darling(default)]
     pub name: Option<String>,
@@ -144,13 +249,19 @@ struct DescribedAttr {
     pub rename_all: String,
     #[darling(default)]
     pub no_descriptor: Option<()>,
+    #[darling(default)]
+    pub optional_encoding: Option<OptionalEncoding>,
+    #[darling(default)]
+    pub descriptor: Option<DescriptorForm>,
 }
 
-#[derive(Debug, darling::FromMeta, PartialEq)]
+#[derive(Debug, Default, darling::FromMeta, PartialEq)]
 struct FieldAttr {
     // default: syn::Lit
     #[darling(default)]
     default: bool,
+    #[darling(default)]
+    flatten: bool,
 }
 
 struct DescribedStructAttr {
@@ -158,6 +269,9 @@ struct DescribedStructAttr {
     code: Option<u64>,
     encoding: EncodingType,
     rename_field: String,
+    no_descriptor: bool,
+    optional_encoding: OptionalEncoding,
+    descriptor_form: DescriptorForm,
 }
 
 #[proc_macro_derive(SerializeComposite, attributes(amqp_contract))]
@@ -8,7 +8,7 @@ use crate::{
         macro_rules_serialize_if_some, parse_described_struct_attr, parse_named_field_attrs,
         where_serialize,
     },
-    DescribedStructAttr, EncodingType, FieldAttr,
+    DescribedStructAttr, DescriptorForm, EncodingType, OptionalEncoding,
 };
 
 pub(crate) fn expand_serialize(
@@ -32,23 +32,45 @@ fn expand_serialize_on_datastruct(
     data: &syn::DataStruct,
     ctx: &DeriveInput,
 ) -> Result<proc_macro2::TokenStream, syn::Error> {
-    let descriptor = match amqp_attr.code {
-        Some(code) => quote!(serde_amqp::descriptor::Descriptor::Code(#code)),
-        None => {
+    let descriptor = match (
+        amqp_attr.no_descriptor,
+        amqp_attr.descriptor_form,
+        amqp_attr.code,
+    ) {
+        (true, _, _) => {
+            if let EncodingType::Basic = amqp_attr.encoding {
+                panic!("Basic encoding is not supported with no_descriptor")
+            }
+            None
+        }
+        (false, DescriptorForm::Numeric, Some(code)) => {
+            Some(quote!(serde_amqp::descriptor::Descriptor::Code(#code)))
+        }
+        (false, _, _) => {
             let name = &amqp_attr.name[..];
-            quote!(serde_amqp::descriptor::Descriptor::Name(serde_amqp::primitives::Symbol::from(#name)))
+            Some(
+                quote!(serde_amqp::descriptor::Descriptor::Name(serde_amqp::primitives::Symbol::from_static(#name))),
+            )
         }
     };
+    let descriptor = descriptor.as_ref();
+
+    if let (EncodingType::Map, OptionalEncoding::Null) =
+        (&amqp_attr.encoding, amqp_attr.optional_encoding)
+    {
+        panic!("optional_encoding = \"null\" is not supported with the map encoding")
+    }
 
     match &data.fields {
         Fields::Named(fields) => {
             let token = match fields.named.len() {
-                0 => expand_serialize_unit_struct(ident, &descriptor, &amqp_attr.encoding),
+                0 => expand_serialize_unit_struct(ident, descriptor, &amqp_attr.encoding),
                 _ => expand_serialize_struct(
                     ident,
                     generics,
-                    &descriptor,
+                    descriptor,
                     &amqp_attr.encoding,
+                    amqp_attr.optional_encoding,
                     &amqp_attr.rename_field,
                     fields,
                     ctx,
@@ -58,12 +80,13 @@ fn expand_serialize_on_datastruct(
         }
         Fields::Unnamed(fields) => {
             let token = match fields.unnamed.len() {
-                0 => expand_serialize_unit_struct(ident, &descriptor, &amqp_attr.encoding),
+                0 => expand_serialize_unit_struct(ident, descriptor, &amqp_attr.encoding),
                 _ => expand_serialize_tuple_struct(
                     ident,
                     generics,
-                    &descriptor,
+                    descriptor,
                     &amqp_attr.encoding,
+                    amqp_attr.optional_encoding,
                     fields,
                 ),
             };
@@ -71,7 +94,7 @@ fn expand_serialize_on_datastruct(
         }
         Fields::Unit => Ok(expand_serialize_unit_struct(
             ident,
-            &descriptor,
+            descriptor,
             &amqp_attr.encoding,
         )),
     }
@@ -79,14 +102,29 @@ fn expand_serialize_on_datastruct(
 
 fn expand_serialize_unit_struct(
     ident: &syn::Ident,
-    descriptor: &proc_macro2::TokenStream,
+    descriptor: Option<&proc_macro2::TokenStream>,
     encoding: &EncodingType,
 ) -> proc_macro2::TokenStream {
     let struct_name = match encoding {
-        EncodingType::List => quote!(serde_amqp::__constants::DESCRIBED_LIST),
+        EncodingType::List => match descriptor {
+            Some(_) => quote!(serde_amqp::__constants::DESCRIBED_LIST),
+            None => quote!(stringify!(#ident)),
+        },
         EncodingType::Basic => panic!("Basic encoding on unit struct is not supported"),
         EncodingType::Map => panic!("Map encoding on unit struct is not supported"),
     };
+    let serialize_fields = match descriptor {
+        Some(descriptor) => quote! {
+            // len + 1 for compatibility with other serializer
+            let mut state = serializer.serialize_tuple_struct(#struct_name, 0 + 1)?;
+            // serialize descriptor
+            state.serialize_field(&#descriptor)?;
+            state.end()
+        },
+        None => quote! {
+            serializer.serialize_tuple_struct(#struct_name, 0)?.end()
+        },
+    };
     quote! {
         #[automatically_derived]
         impl serde_amqp::serde::ser::Serialize for #ident {
@@ -95,11 +133,7 @@ fn expand_serialize_unit_struct(
                 S: serde_amqp::serde::ser::Serializer,
             {
                 use serde_amqp::serde::ser::SerializeTupleStruct;
-                // len + 1 for compatibility with other serializer
-                let mut state = serializer.serialize_tuple_struct(#struct_name, 0 + 1)?;
-                // serialize descriptor
-                state.serialize_field(&#descriptor)?;
-                state.end()
+                #serialize_fields
             }
         }
     }
@@ -108,12 +142,17 @@ fn expand_serialize_unit_struct(
 fn expand_serialize_tuple_struct(
     ident: &syn::Ident,
     generics: &syn::Generics,
-    descriptor: &proc_macro2::TokenStream,
+    descriptor: Option<&proc_macro2::TokenStream>,
     encoding: &EncodingType,
+    optional_encoding: OptionalEncoding,
     fields: &syn::FieldsUnnamed,
 ) -> proc_macro2::TokenStream {
     let struct_name = match encoding {
-        EncodingType::List => quote!(serde_amqp::__constants::DESCRIBED_LIST),
+        EncodingType::List => match descriptor {
+            Some(_) => quote!(serde_amqp::__constants::DESCRIBED_LIST),
+            // A plain tuple struct is serialized as a list
+            None => quote!(stringify!(#ident)),
+        },
         EncodingType::Basic => {
             if fields.unnamed.len() == 1 {
                 // Basic encoding is allowed on newtype struct
@@ -131,8 +170,24 @@ fn expand_serialize_tuple_struct(
         .map(|(i, _)| syn::Index::from(i))
         .collect();
     let field_types: Vec<&syn::Type> = fields.unnamed.iter().map(|f| &f.ty).collect();
-    let len = field_indices.len();
+    let len = field_indices.len() + descriptor.map_or(0, |_| 1);
+    let serialize_descriptor = descriptor.map(|descriptor| {
+        quote! {
+            // serialize descriptor
+            // descriptor does not count towards number of element in list
+            // in serde_amqp serializer, this will be deducted
+            state.serialize_field(&#descriptor)?;
+        }
+    });
     let buffer_if_none = macro_rules_buffer_if_none_for_tuple_struct();
+    let serialize_trailing_nulls = match optional_encoding {
+        OptionalEncoding::Skip => quote! {},
+        OptionalEncoding::Null => quote! {
+            for _ in 0..null_count {
+                state.serialize_field(&())?; // `None` and `()` share the same encoding
+            }
+        },
+    };
     let where_clause = match generics.params.len() {
         0 => quote! {},
         _ => where_serialize(generics),
@@ -150,30 +205,55 @@ fn expand_serialize_tuple_struct(
             {
                 use serde_amqp::serde::ser::SerializeTupleStruct;
                 let mut null_count = 0u32;
-                // len + 1 for compatibility with other serializer
-                let mut state = serializer.serialize_tuple_struct(#struct_name, #len + 1)?;
-                // serialize descriptor
-                // descriptor does not count towards number of element in list
-                // in serde_amqp serializer, this will be deducted
-                state.serialize_field(&#descriptor)?;
+                // len includes the descriptor for compatibility with other serializer
+                let mut state = serializer.serialize_tuple_struct(#struct_name, #len)?;
+                #serialize_descriptor
                 // #( state.serialize_field(&self.#field_indices)?; )*
                 #( buffer_if_none_for_tuple!(state, null_count, &self.#field_indices, #field_types); )*
+                #serialize_trailing_nulls
                 state.end()
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn expand_serialize_struct(
     ident: &syn::Ident,
     generics: &syn::Generics,
-    descriptor: &proc_macro2::TokenStream,
+    descriptor: Option<&proc_macro2::TokenStream>,
     encoding: &EncodingType,
+    optional_encoding: OptionalEncoding,
     rename_all: &str,
     fields: &syn::FieldsNamed,
     ctx: &DeriveInput,
 ) -> proc_macro2::TokenStream {
-    let len = fields.named.len();
+    let field_attrs = parse_named_field_attrs(fields.named.iter());
+    let has_flatten = field_attrs.iter().any(|attr| attr.flatten);
+    if has_flatten && !matches!(encoding, EncodingType::List) {
+        panic!("flatten is only supported with the list encoding")
+    }
+    let field_types: Vec<&syn::Type> = fields.named.iter().map(|f| &f.ty).collect();
+    // A flattened field counts as many fields as it inlines into the list
+    let n_fields = field_attrs.iter().filter(|attr| !attr.flatten).count();
+    let flattened_types = field_types
+        .iter()
+        .zip(field_attrs.iter())
+        .filter(|(_, attr)| attr.flatten)
+        .map(|(ty, _)| ty);
+    let flattened_count = quote! { #n_fields #(+ <#flattened_types as serde_amqp::__flatten::SerializeFlattened>::FIELD_COUNT)* };
+    let len = match descriptor {
+        Some(_) => quote! { #flattened_count + 1 },
+        None => quote! { #flattened_count },
+    };
+    let serialize_descriptor = descriptor.map(|descriptor| {
+        quote! {
+            // serialize descriptor
+            // descriptor does not count towards number of element in list
+            // in serde_amqp serializer, this will be deducted
+            state.serialize_field(serde_amqp::__constants::DESCRIPTOR, &#descriptor)?;
+        }
+    });
     let struct_name = match encoding {
         EncodingType::Basic => {
             if fields.named.len() == 1 {
@@ -195,13 +275,11 @@ fn expand_serialize_struct(
         .iter()
         .map(|i| convert_to_case(rename_all, i.to_string(), ctx).unwrap())
         .collect();
-    let field_types: Vec<&syn::Type> = fields.named.iter().map(|f| &f.ty).collect();
-    let field_attrs = parse_named_field_attrs(fields.named.iter());
     let declarative_macro = match encoding {
         EncodingType::Basic | EncodingType::List => {
             let buffer_if_none = macro_rules_buffer_if_none();
 
-            let buffer_if_eq_default = match field_attrs.contains(&FieldAttr { default: true }) {
+            let buffer_if_eq_default = match field_attrs.iter().any(|attr| attr.default) {
                 true => macro_rules_buffer_if_eq_default(),
                 false => quote! {},
             };
@@ -221,6 +299,7 @@ fn expand_serialize_struct(
     };
 
     let mut field_impls: Vec<proc_macro2::TokenStream> = vec![];
+    let mut flattened_field_impls: Vec<proc_macro2::TokenStream> = vec![];
     match encoding {
         EncodingType::Basic | EncodingType::List => {
             // for ((id, name), ty) in field_idents
@@ -230,6 +309,17 @@ fn expand_serialize_struct(
                 .zip(field_types.iter())
                 .zip(field_attrs.iter())
             {
+                if attr.flatten {
+                    // `state` and `nulls` are already mutable references when the struct itself
+                    // is flattened into another struct
+                    field_impls.push(quote! {
+                        serde_amqp::__flatten::SerializeFlattened::serialize_flattened(&self.#id, &mut state, &mut nulls)?;
+                    });
+                    flattened_field_impls.push(quote! {
+                        serde_amqp::__flatten::SerializeFlattened::serialize_flattened(&self.#id, &mut *state, &mut *nulls)?;
+                    });
+                    continue;
+                }
                 let token = match attr.default {
                     true => quote! {
                         buffer_if_eq_default!(state, nulls, &self.#id, #name, #ty);
@@ -238,7 +328,8 @@ fn expand_serialize_struct(
                         buffer_if_none!(state, nulls, &self.#id, #name, #ty);
                     },
                 };
-                field_impls.push(token);
+                field_impls.push(token.clone());
+                flattened_field_impls.push(token);
             }
         }
         EncodingType::Map => {
@@ -261,14 +352,50 @@ fn expand_serialize_struct(
         }
     }
 
+    let serialize_trailing_nulls = match optional_encoding {
+        OptionalEncoding::Skip => quote! {},
+        OptionalEncoding::Null => quote! {
+            for field_name in nulls.drain(..) {
+                // name is not used in list encoding
+                state.serialize_field(field_name, &())?; // `None` and `()` share the same encoding
+            }
+        },
+    };
+
     let where_clause = match generics.params.len() {
         0 => quote! {},
         _ => where_serialize(generics),
     };
 
+    // A list without a descriptor can be flattened into another struct
+    let impl_serialize_flattened = match (encoding, descriptor) {
+        (EncodingType::List, None) => quote! {
+            #[automatically_derived]
+            impl #generics serde_amqp::__flatten::SerializeFlattened for #ident #generics #where_clause
+            {
+                const FIELD_COUNT: usize = #flattened_count;
+
+                fn serialize_flattened<_S>(
+                    &self,
+                    state: &mut _S,
+                    nulls: &mut Vec<&'static str>,
+                ) -> Result<(), _S::Error>
+                where
+                    _S: serde_amqp::serde::ser::SerializeStruct,
+                {
+                    #( #flattened_field_impls; )*
+                    Ok(())
+                }
+            }
+        },
+        _ => quote! {},
+    };
+
     quote! {
         #declarative_macro
 
+        #impl_serialize_flattened
+
         #[automatically_derived]
         impl #generics serde_amqp::serde::ser::Serialize for #ident #generics #where_clause
         {
@@ -279,15 +406,13 @@ fn expand_serialize_struct(
                 use serde_amqp::serde::ser::SerializeStruct;
                 // let mut null_count = 0u32;
                 let mut nulls: Vec<&str> = Vec::new();
-                // len + 1 for compatibility with other serializer
-                let mut state = serializer.serialize_struct(#struct_name, #len + 1)?;
-                // serialize descriptor
-                // descriptor does not count towards number of element in list
-                // in serde_amqp serializer, this will be deducted
-                state.serialize_field(serde_amqp::__constants::DESCRIPTOR, &#descriptor)?;
+                // len includes the descriptor for compatibility with other serializer
+                let mut state = serializer.serialize_struct(#struct_name, #len)?;
+                #serialize_descriptor
                 // #( state.serialize_field(#field_names, &self.#field_idents)?; )*
                 // #(buffer_if_none!(state, null_count, &self.#field_idents, #field_names, #field_types);) *
                 #( #field_impls; )*
+                #serialize_trailing_nulls
                 state.end()
             }
         }
@@ -5,7 +5,9 @@ use proc_macro2::Span;
 use quote::quote;
 use syn::{parse::Parser, DeriveInput, Field};
 
-use crate::{DescribedAttr, DescribedStructAttr, EncodingType, FieldAttr};
+use crate::{
+    DescribedAttr, DescribedStructAttr, DescriptorForm, EncodingType, FieldAttr, OptionalEncoding,
+};
 
 pub(crate) fn parse_described_struct_attr(input: &syn::DeriveInput) -> DescribedStructAttr {
     let attr = DescribedAttr::from_derive_input(input).unwrap();
@@ -14,11 +16,17 @@ pub(crate) fn parse_described_struct_attr(input: &syn::DeriveInput) -> Described
     let code = attr.code.map(parse_descriptor_code).transpose().unwrap();
     let encoding = attr.encoding.unwrap_or(EncodingType::List);
     let rename_field = attr.rename_all;
+    let no_descriptor = attr.no_descriptor.is_some();
+    let optional_encoding = attr.optional_encoding.unwrap_or(OptionalEncoding::Skip);
+    let descriptor_form = attr.descriptor.unwrap_or(DescriptorForm::Numeric);
     DescribedStructAttr {
         name,
         code,
         encoding,
         rename_field,
+        no_descriptor,
+        optional_encoding,
+        descriptor_form,
     }
 }
 
@@ -119,7 +127,7 @@ pub(crate) fn parse_named_field_attrs<'a>(
                 FieldAttr::from_meta(&item).ok()
             })
         })
-        .map(|o| o.unwrap_or(FieldAttr { default: false }))
+        .map(|o| o.unwrap_or_default())
         .collect()
 }
 